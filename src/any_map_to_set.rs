@@ -0,0 +1,371 @@
+use std::any::{Any, TypeId};
+use std::collections::{HashMap, HashSet};
+use std::borrow::Borrow;
+use std::cmp::Eq;
+use std::fmt;
+use std::hash::Hash;
+
+/// A map of types to sets of values of that type.
+///
+/// Each type `T` has its own `HashSet<T>`, keyed by `TypeId` and stored
+/// as a `Box<dyn Any>`, so one container can hold sets of many types.
+/// This suits registries such as plugins or extensions, where callers
+/// look up every registered value of a given type.
+///
+/// # Examples
+///
+/// ```
+/// use sixarm_collections::*;
+/// let mut a = AnyMapToSet::new();
+/// a.sub_insert::<u8>(1);
+/// a.sub_insert::<u8>(2);
+/// a.sub_insert::<&str>("alpha");
+/// assert_eq!(a.sub_len_of::<u8>(), 2);
+/// assert_eq!(a.sub_contains::<&str, _>(&"alpha"), true);
+/// assert_eq!(a.sub_iter::<u16>().count(), 0);
+/// ```
+#[derive(Default)]
+pub struct AnyMapToSet {
+    map: HashMap<TypeId, Box<dyn Any>>,
+}
+
+impl AnyMapToSet {
+
+    /// Create an empty map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a = AnyMapToSet::new();
+    /// assert_eq!(a.is_empty(), true);
+    /// ```
+    #[inline]
+    pub fn new() -> Self {
+        AnyMapToSet { map: HashMap::new() }
+    }
+
+    /// Return the number of types that have a set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a = AnyMapToSet::new();
+    /// a.sub_insert::<u8>(1);
+    /// a.sub_insert::<u8>(2);
+    /// a.sub_insert::<char>('a');
+    /// assert_eq!(a.len(), 2);
+    /// ```
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Return `true` if no type has a set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a = AnyMapToSet::new();
+    /// assert_eq!(a.is_empty(), true);
+    /// ```
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Remove every set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a = AnyMapToSet::new();
+    /// a.sub_insert::<u8>(1);
+    /// a.clear();
+    /// assert_eq!(a.is_empty(), true);
+    /// ```
+    #[inline]
+    pub fn clear(&mut self) {
+        self.map.clear()
+    }
+
+    /// Return `true` if a type has a set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a = AnyMapToSet::new();
+    /// a.sub_insert::<u8>(1);
+    /// assert_eq!(a.contains_type::<u8>(), true);
+    /// assert_eq!(a.contains_type::<u16>(), false);
+    /// ```
+    #[inline]
+    pub fn contains_type<T: Any>(&self) -> bool {
+        self.map.contains_key(&TypeId::of::<T>())
+    }
+
+    /// Return the set for a type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a = AnyMapToSet::new();
+    /// a.sub_insert::<u8>(1);
+    /// assert_eq!(a.get::<u8>().map(|set| set.len()), Some(1));
+    /// assert_eq!(a.get::<u16>(), None);
+    /// ```
+    #[inline]
+    pub fn get<T: Any>(&self) -> Option<&HashSet<T>> {
+        self.map.get(&TypeId::of::<T>())
+        .and_then(|set| set.downcast_ref::<HashSet<T>>())
+    }
+
+    /// Return the mutable set for a type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a = AnyMapToSet::new();
+    /// a.sub_insert::<u8>(1);
+    /// a.get_mut::<u8>().unwrap().insert(2);
+    /// assert_eq!(a.sub_len_of::<u8>(), 2);
+    /// ```
+    #[inline]
+    pub fn get_mut<T: Any>(&mut self) -> Option<&mut HashSet<T>> {
+        self.map.get_mut(&TypeId::of::<T>())
+        .and_then(|set| set.downcast_mut::<HashSet<T>>())
+    }
+
+    /// Return the mutable set for a type, and create an empty set if the type has none.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a = AnyMapToSet::new();
+    /// a.entry::<u8>().insert(1);
+    /// assert_eq!(a.sub_contains::<u8, _>(&1), true);
+    /// ```
+    #[inline]
+    pub fn entry<T: Any>(&mut self) -> &mut HashSet<T> {
+        self.map.entry(TypeId::of::<T>())
+        .or_insert_with(|| Box::new(HashSet::<T>::new()))
+        .downcast_mut::<HashSet<T>>()
+        .expect("set type matches its TypeId key")
+    }
+
+    /// Remove the set for a type, and return it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a = AnyMapToSet::new();
+    /// a.sub_insert::<u8>(1);
+    /// assert_eq!(a.remove_type::<u8>().map(|set| set.len()), Some(1));
+    /// assert_eq!(a.contains_type::<u8>(), false);
+    /// ```
+    #[inline]
+    pub fn remove_type<T: Any>(&mut self) -> Option<HashSet<T>> {
+        self.map.remove(&TypeId::of::<T>())
+        .and_then(|set| set.downcast::<HashSet<T>>().ok())
+        .map(|set| *set)
+    }
+
+    /// Return `true` if the set for a type contains a value.
+    ///
+    /// The value may be any borrowed form of the type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a = AnyMapToSet::new();
+    /// a.sub_insert::<String>(String::from("alpha"));
+    /// assert_eq!(a.sub_contains::<String, _>("alpha"), true);
+    /// assert_eq!(a.sub_contains::<String, _>("bravo"), false);
+    /// ```
+    #[inline]
+    pub fn sub_contains<T, R>(&self, value: &R) -> bool
+    where
+        T: Any + Borrow<R> + Hash + Eq,
+        R: Hash + Eq + ?Sized,
+    {
+        match self.get::<T>() {
+            Some(set) => set.contains(value),
+            None => false,
+        }
+    }
+
+    /// Add a value to the set for its type.
+    ///
+    /// Return whether the value is added in the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a = AnyMapToSet::new();
+    /// assert_eq!(a.sub_insert::<u8>(1), true);
+    /// assert_eq!(a.sub_insert::<u8>(1), false);
+    /// ```
+    #[inline]
+    pub fn sub_insert<T>(&mut self, value: T) -> bool
+    where
+        T: Any + Hash + Eq,
+    {
+        self.entry::<T>().insert(value)
+    }
+
+    /// Remove a value from the set for its type.
+    ///
+    /// Return whether the value was present in the set.
+    ///
+    /// The type stays in the collection even when its set becomes empty;
+    /// use `sub_remove_prune` to also remove the type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a = AnyMapToSet::new();
+    /// a.sub_insert::<u8>(1);
+    /// assert_eq!(a.sub_remove::<u8, _>(&1), true);
+    /// assert_eq!(a.contains_type::<u8>(), true);
+    /// ```
+    #[inline]
+    pub fn sub_remove<T, R>(&mut self, value: &R) -> bool
+    where
+        T: Any + Borrow<R> + Hash + Eq,
+        R: Hash + Eq + ?Sized,
+    {
+        match self.get_mut::<T>() {
+            Some(set) => set.remove(value),
+            None => false,
+        }
+    }
+
+    /// Remove a value from the set for its type, and prune the type if its set becomes empty.
+    ///
+    /// Return whether the value was present in the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a = AnyMapToSet::new();
+    /// a.sub_insert::<u8>(1);
+    /// assert_eq!(a.sub_remove_prune::<u8, _>(&1), true);
+    /// assert_eq!(a.contains_type::<u8>(), false);
+    /// ```
+    #[inline]
+    pub fn sub_remove_prune<T, R>(&mut self, value: &R) -> bool
+    where
+        T: Any + Borrow<R> + Hash + Eq,
+        R: Hash + Eq + ?Sized,
+    {
+        match self.get_mut::<T>() {
+            Some(set) => {
+                let removed = set.remove(value);
+                if set.is_empty() {
+                    self.map.remove(&TypeId::of::<T>());
+                }
+                removed
+            },
+            None => false,
+        }
+    }
+
+    /// Return an iterator over the values of a type, in arbitrary order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a = AnyMapToSet::new();
+    /// a.sub_insert::<u8>(1);
+    /// a.sub_insert::<char>('a');
+    /// assert_eq!(a.sub_iter::<u8>().collect::<Vec<&u8>>(), vec![&1]);
+    /// ```
+    #[inline]
+    pub fn sub_iter<T: Any>(&self) -> impl Iterator<Item = &T> {
+        self.get::<T>()
+        .into_iter()
+        .flatten()
+    }
+
+    /// Return the number of values of a type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a = AnyMapToSet::new();
+    /// a.sub_insert::<u8>(1);
+    /// a.sub_insert::<u8>(2);
+    /// assert_eq!(a.sub_len_of::<u8>(), 2);
+    /// assert_eq!(a.sub_len_of::<u16>(), 0);
+    /// ```
+    #[inline]
+    pub fn sub_len_of<T: Any>(&self) -> usize {
+        self.get::<T>().map_or(0, |set| set.len())
+    }
+
+}
+
+impl fmt::Debug for AnyMapToSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AnyMapToSet")
+        .field("types", &self.map.len())
+        .finish()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    struct Plugin(&'static str);
+
+    #[test]
+    /// Test that each type gets its own set, including types with the same shape.
+    fn test_sub_insert_x_types() {
+        let mut subject = AnyMapToSet::new();
+        assert_eq!(subject.sub_insert(Plugin("alpha")), true);
+        assert_eq!(subject.sub_insert(Plugin("bravo")), true);
+        assert_eq!(subject.sub_insert(Plugin("alpha")), false);
+        assert_eq!(subject.sub_insert::<&'static str>("alpha"), true);
+        assert_eq!(subject.len(), 2);
+        assert_eq!(subject.sub_len_of::<Plugin>(), 2);
+        assert_eq!(subject.sub_len_of::<&'static str>(), 1);
+        let mut actual: Vec<&Plugin> = subject.sub_iter::<Plugin>().collect();
+        actual.sort_by_key(|plugin| plugin.0);
+        assert_eq!(actual, vec![&Plugin("alpha"), &Plugin("bravo")]);
+    }
+
+    #[test]
+    /// Test `sub_remove`, `sub_remove_prune`, and `remove_type`.
+    fn test_sub_remove_x_prune() {
+        let mut subject = AnyMapToSet::new();
+        subject.sub_insert::<u8>(1);
+        subject.sub_insert::<u16>(1);
+        subject.sub_insert::<u32>(1);
+        assert_eq!(subject.sub_remove::<u8, _>(&1), true);
+        assert_eq!(subject.sub_remove::<u8, _>(&1), false);
+        assert_eq!(subject.contains_type::<u8>(), true);
+        assert_eq!(subject.sub_remove_prune::<u16, _>(&1), true);
+        assert_eq!(subject.contains_type::<u16>(), false);
+        assert_eq!(subject.sub_remove_prune::<u64, _>(&1), false);
+        assert_eq!(subject.remove_type::<u32>(), Some(vec![1].into_iter().collect()));
+        assert_eq!(subject.len(), 1);
+    }
+
+}
@@ -0,0 +1,490 @@
+use std::collections::HashMap;
+use std::borrow::Borrow;
+use std::cmp::Eq;
+use std::hash::Hash;
+use std::iter::FromIterator;
+use crate::hash_map_to_vec::HashMapToVec;
+
+/// The end of a key's chain of nodes.
+const NIL: usize = usize::MAX;
+
+/// One value in the arena, linked to the next value of the same key.
+#[derive(Debug, Clone)]
+struct Node<V> {
+    value: V,
+    next: usize,
+}
+
+/// The first node, last node, and node count of one key.
+#[derive(Debug, Clone, Copy)]
+struct Chain {
+    first: usize,
+    last: usize,
+    len: usize,
+}
+
+/// A multimap that stores every value in one arena owned by the map.
+///
+/// All values live in a single `Vec`, and each key holds a chain of
+/// indexes into it, so inserting a pair does not allocate per value, and
+/// dropping the map frees one buffer instead of one per key. This suits
+/// build-then-discard workloads such as a temporary index.
+///
+/// The trade-off is that there is no removal of single pairs or keys:
+/// use `clear` to reset the map while keeping its capacity. Like
+/// `HashMapToVec`, the map keeps duplicate pairs, and each key's values
+/// are in insertion order.
+///
+/// # Examples
+///
+/// ```
+/// use sixarm_collections::*;
+/// let mut a: ArenaMultiMap<&str, u8> = ArenaMultiMap::with_capacity(2, 3);
+/// a.insert("b", 1);
+/// a.insert("a", 2);
+/// a.insert("b", 3);
+/// let values: Vec<&u8> = a.values_of("b").collect();
+/// assert_eq!(values, vec![&1, &3]);
+/// a.clear();
+/// assert_eq!(a.is_empty(), true);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ArenaMultiMap<K, V> {
+    chains: HashMap<K, Chain>,
+    nodes: Vec<Node<V>>,
+}
+
+impl<K, V> ArenaMultiMap<K, V> {
+
+    /// Create an empty map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: ArenaMultiMap<u8, u8> = ArenaMultiMap::new();
+    /// assert_eq!(a.is_empty(), true);
+    /// ```
+    #[inline]
+    pub fn new() -> Self {
+        ArenaMultiMap { chains: HashMap::new(), nodes: Vec::new() }
+    }
+
+    /// Create an empty map with room for some keys and some values,
+    /// so a build of that size does not reallocate.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: ArenaMultiMap<u8, u8> = ArenaMultiMap::with_capacity(10, 100);
+    /// assert_eq!(a.capacity() >= 100, true);
+    /// ```
+    #[inline]
+    pub fn with_capacity(keys: usize, values: usize) -> Self {
+        ArenaMultiMap { chains: HashMap::with_capacity(keys), nodes: Vec::with_capacity(values) }
+    }
+
+    /// Return the number of pairs, including duplicates.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: ArenaMultiMap<u8, u8> = ArenaMultiMap::new();
+    /// a.insert(1, 2);
+    /// a.insert(1, 2);
+    /// assert_eq!(a.len(), 2);
+    /// ```
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Return `true` if the map has no pairs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: ArenaMultiMap<u8, u8> = ArenaMultiMap::new();
+    /// assert_eq!(a.is_empty(), true);
+    /// ```
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Return the number of distinct keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: ArenaMultiMap<u8, u8> = ArenaMultiMap::new();
+    /// a.insert(1, 2);
+    /// a.insert(1, 3);
+    /// assert_eq!(a.keys_len(), 1);
+    /// ```
+    #[inline]
+    pub fn keys_len(&self) -> usize {
+        self.chains.len()
+    }
+
+    /// Return the number of values the arena can hold without reallocating.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: ArenaMultiMap<u8, u8> = ArenaMultiMap::with_capacity(1, 8);
+    /// assert_eq!(a.capacity() >= 8, true);
+    /// ```
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.nodes.capacity()
+    }
+
+    /// Remove every pair, and keep the allocated capacity for the next build.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: ArenaMultiMap<u8, u8> = ArenaMultiMap::with_capacity(1, 8);
+    /// a.insert(1, 2);
+    /// a.clear();
+    /// assert_eq!(a.is_empty(), true);
+    /// assert_eq!(a.capacity() >= 8, true);
+    /// ```
+    #[inline]
+    pub fn clear(&mut self) {
+        self.chains.clear();
+        self.nodes.clear();
+    }
+
+    /// Return an iterator over the keys, in arbitrary order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: ArenaMultiMap<u8, u8> = ArenaMultiMap::new();
+    /// a.insert(1, 2);
+    /// a.insert(1, 3);
+    /// assert_eq!(a.keys().collect::<Vec<&u8>>(), vec![&1]);
+    /// ```
+    #[inline]
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.chains.keys()
+    }
+
+    /// Return an iterator over every value, in global insertion order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: ArenaMultiMap<u8, u8> = ArenaMultiMap::new();
+    /// a.insert(1, 2);
+    /// a.insert(4, 5);
+    /// a.insert(1, 3);
+    /// assert_eq!(a.values().collect::<Vec<&u8>>(), vec![&2, &5, &3]);
+    /// ```
+    #[inline]
+    pub fn values(&self) -> impl DoubleEndedIterator<Item = &V> + ExactSizeIterator {
+        self.nodes.iter().map(|node| &node.value)
+    }
+
+    /// Return an iterator over each pair, with keys in arbitrary order
+    /// and each key's values in insertion order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: ArenaMultiMap<u8, u8> = ArenaMultiMap::new();
+    /// a.insert(1, 2);
+    /// a.insert(1, 3);
+    /// let pairs: Vec<(&u8, &u8)> = a.iter().collect();
+    /// assert_eq!(pairs, vec![(&1, &2), (&1, &3)]);
+    /// ```
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.chains.iter()
+        .flat_map(move |(key, chain)| self.walk(chain.first).map(move |value| (key, value)))
+    }
+
+    /// Return an iterator that follows a chain of nodes from an index.
+    fn walk(&self, first: usize) -> impl Iterator<Item = &V> {
+        let mut index = first;
+        std::iter::from_fn(move || {
+            if index == NIL {
+                return None;
+            }
+            let node = &self.nodes[index];
+            index = node.next;
+            Some(&node.value)
+        })
+    }
+
+}
+
+impl<K, V> ArenaMultiMap<K, V>
+where
+    K: Hash + Eq,
+{
+
+    /// Append a pair.
+    ///
+    /// The value goes into the arena; the key is stored once, the first
+    /// time it is seen.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: ArenaMultiMap<u8, u8> = ArenaMultiMap::new();
+    /// a.insert(1, 2);
+    /// assert_eq!(a.len(), 1);
+    /// ```
+    #[inline]
+    pub fn insert(&mut self, key: K, value: V) {
+        let index = self.nodes.len();
+        self.nodes.push(Node { value, next: NIL });
+        match self.chains.get_mut(&key) {
+            Some(chain) => {
+                self.nodes[chain.last].next = index;
+                chain.last = index;
+                chain.len += 1;
+            },
+            None => {
+                self.chains.insert(key, Chain { first: index, last: index, len: 1 });
+            },
+        }
+    }
+
+    /// Return an iterator over the values of a key in insertion order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: ArenaMultiMap<u8, u8> = ArenaMultiMap::new();
+    /// a.insert(1, 3);
+    /// a.insert(4, 5);
+    /// a.insert(1, 2);
+    /// let values: Vec<&u8> = a.values_of(&1).collect();
+    /// assert_eq!(values, vec![&3, &2]);
+    /// ```
+    #[inline]
+    pub fn values_of<Q>(&self, key: &Q) -> impl Iterator<Item = &V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.walk(self.chains.get(key).map_or(NIL, |chain| chain.first))
+    }
+
+    /// Return the number of pairs for a key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: ArenaMultiMap<u8, u8> = ArenaMultiMap::new();
+    /// a.insert(1, 2);
+    /// a.insert(1, 2);
+    /// assert_eq!(a.len_of(&1), 2);
+    /// assert_eq!(a.len_of(&9), 0);
+    /// ```
+    #[inline]
+    pub fn len_of<Q>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.chains.get(key).map_or(0, |chain| chain.len)
+    }
+
+    /// Return `true` if the map has a key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: ArenaMultiMap<u8, u8> = ArenaMultiMap::new();
+    /// a.insert(1, 2);
+    /// assert_eq!(a.contains_key(&1), true);
+    /// ```
+    #[inline]
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.chains.contains_key(key)
+    }
+
+    /// Return `true` if the map has a pair, by scanning the key's values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: ArenaMultiMap<u8, u8> = ArenaMultiMap::new();
+    /// a.insert(1, 2);
+    /// assert_eq!(a.contains(&1, &2), true);
+    /// assert_eq!(a.contains(&1, &3), false);
+    /// ```
+    #[inline]
+    pub fn contains<Q>(&self, key: &Q, value: &V) -> bool
+    where
+        K: Borrow<Q>,
+        V: PartialEq,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.values_of(key).any(|x| x == value)
+    }
+
+    /// Consume the map and return a `HashMapToVec` with each key's values in insertion order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: ArenaMultiMap<u8, u8> = ArenaMultiMap::new();
+    /// a.insert(1, 3);
+    /// a.insert(1, 2);
+    /// let map = a.into_hash_map_to_vec();
+    /// assert_eq!(map[&1], vec![3, 2]);
+    /// ```
+    pub fn into_hash_map_to_vec(self) -> HashMapToVec<K, V> {
+        let nexts: Vec<usize> = self.nodes.iter().map(|node| node.next).collect();
+        let mut values: Vec<Option<V>> = self.nodes.into_iter().map(|node| Some(node.value)).collect();
+        self.chains.into_iter()
+        .map(|(key, chain)| {
+            let mut vec = Vec::with_capacity(chain.len);
+            let mut index = chain.first;
+            while index != NIL {
+                if let Some(value) = values[index].take() {
+                    vec.push(value);
+                }
+                index = nexts[index];
+            }
+            (key, vec)
+        })
+        .collect()
+    }
+
+}
+
+impl<K, V> Default for ArenaMultiMap<K, V> {
+    fn default() -> Self {
+        ArenaMultiMap::new()
+    }
+}
+
+impl<K, V> PartialEq for ArenaMultiMap<K, V>
+where
+    K: Hash + Eq,
+    V: PartialEq,
+{
+    /// Return `true` if both maps have the same keys, and each key has
+    /// the same values in the same order.
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len()
+        && self.keys_len() == other.keys_len()
+        && self.chains.keys().all(|key| self.values_of(key).eq(other.values_of(key)))
+    }
+}
+
+impl<K, V> Eq for ArenaMultiMap<K, V>
+where
+    K: Hash + Eq,
+    V: Eq,
+{}
+
+impl<K, V> FromIterator<(K, V)> for ArenaMultiMap<K, V>
+where
+    K: Hash + Eq,
+{
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let iter = iter.into_iter();
+        let mut map = ArenaMultiMap::with_capacity(0, iter.size_hint().0);
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K, V> Extend<(K, V)> for ArenaMultiMap<K, V>
+where
+    K: Hash + Eq,
+{
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.nodes.reserve(iter.size_hint().0);
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+
+    #[test]
+    /// Test `values_of` follows each key's chain through interleaved inserts.
+    fn test_values_of_x_interleaved() {
+        let subject: ArenaMultiMap<u8, char> = vec![(2, 'a'), (1, 'b'), (2, 'c'), (1, 'b'), (2, 'd')].into_iter().collect();
+        let actual: Vec<&char> = subject.values_of(&2).collect();
+        assert_eq!(actual, vec![&'a', &'c', &'d']);
+        let actual: Vec<&char> = subject.values_of(&1).collect();
+        assert_eq!(actual, vec![&'b', &'b']);
+        assert_eq!(subject.values_of(&9).count(), 0);
+        assert_eq!(subject.len(), 5);
+        assert_eq!(subject.keys_len(), 2);
+        assert_eq!(subject.len_of(&2), 3);
+        assert_eq!(subject.contains(&2, &'d'), true);
+        assert_eq!(subject.contains(&1, &'d'), false);
+        assert_eq!(subject.iter().count(), 5);
+    }
+
+    #[test]
+    /// Test `clear` empties the map and keeps the arena for the next build.
+    fn test_clear_x_reuse() {
+        let mut subject: ArenaMultiMap<u8, char> = ArenaMultiMap::with_capacity(2, 4);
+        subject.extend(vec![(1, 'a'), (2, 'b'), (1, 'c')]);
+        let capacity = subject.capacity();
+        subject.clear();
+        assert_eq!(subject.is_empty(), true);
+        assert_eq!(subject.keys_len(), 0);
+        assert_eq!(subject.capacity(), capacity);
+        subject.insert(3, 'd');
+        assert_eq!(subject.values_of(&3).collect::<Vec<&char>>(), vec![&'d']);
+        assert_eq!(subject.contains_key(&1), false);
+    }
+
+    #[test]
+    /// Test `into_hash_map_to_vec` keeps each key's order and duplicates.
+    fn test_into_hash_map_to_vec() {
+        let subject: ArenaMultiMap<u8, char> = vec![(2, 'a'), (1, 'b'), (2, 'c'), (2, 'a')].into_iter().collect();
+        let actual = subject.into_hash_map_to_vec();
+        assert_eq!(actual.len(), 2);
+        assert_eq!(actual[&2], vec!['a', 'c', 'a']);
+        assert_eq!(actual[&1], vec!['b']);
+    }
+
+    #[test]
+    /// Test `eq` compares each key's values in order, not the global order.
+    fn test_eq() {
+        let a: ArenaMultiMap<u8, char> = vec![(1, 'a'), (2, 'b'), (1, 'c')].into_iter().collect();
+        let b: ArenaMultiMap<u8, char> = vec![(2, 'b'), (1, 'a'), (1, 'c')].into_iter().collect();
+        let c: ArenaMultiMap<u8, char> = vec![(1, 'c'), (2, 'b'), (1, 'a')].into_iter().collect();
+        assert_eq!(a == b, true);
+        assert_eq!(a == c, false);
+    }
+
+}
@@ -0,0 +1,437 @@
+use std::borrow::Borrow;
+use std::cmp::Eq;
+use std::hash::Hash;
+use std::iter::FromIterator;
+use crate::hash_map_to_set::{HashMapToSet, HashMapToSetExt};
+
+/// A multimap that keeps a forward index `K → set<V>` and an inverse
+/// index `V → set<K>` in step.
+///
+/// Every change goes through both indexes, so `values_of` and `keys_of`
+/// always agree. Keys and values are pruned from an index when their
+/// set becomes empty.
+///
+/// # Examples
+///
+/// ```
+/// use sixarm_collections::*;
+/// let mut a: BiMultiMap<u8, char> = BiMultiMap::new();
+/// a.insert(1, 'a');
+/// a.insert(2, 'a');
+/// assert_eq!(a.values_of(&1).count(), 1);
+/// assert_eq!(a.keys_of(&'a').count(), 2);
+/// ```
+#[derive(Debug, Clone)]
+pub struct BiMultiMap<K, V> {
+    forward: HashMapToSet<K, V>,
+    inverse: HashMapToSet<V, K>,
+}
+
+impl<K, V> BiMultiMap<K, V> {
+
+    /// Create an empty map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: BiMultiMap<u8, char> = BiMultiMap::new();
+    /// assert_eq!(a.is_empty(), true);
+    /// ```
+    #[inline]
+    pub fn new() -> Self {
+        BiMultiMap { forward: HashMapToSet::new(), inverse: HashMapToSet::new() }
+    }
+
+    /// Return the number of key-value pairs in the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: BiMultiMap<u8, char> = BiMultiMap::new();
+    /// a.insert(1, 'a');
+    /// a.insert(1, 'b');
+    /// assert_eq!(a.len(), 2);
+    /// ```
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.forward.sub_len()
+    }
+
+    /// Return `true` if the map has no pairs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: BiMultiMap<u8, char> = BiMultiMap::new();
+    /// assert_eq!(a.is_empty(), true);
+    /// ```
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.forward.is_empty()
+    }
+
+    /// Remove every pair from the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: BiMultiMap<u8, char> = BiMultiMap::new();
+    /// a.insert(1, 'a');
+    /// a.clear();
+    /// assert_eq!(a.is_empty(), true);
+    /// ```
+    #[inline]
+    pub fn clear(&mut self) {
+        self.forward.clear();
+        self.inverse.clear();
+    }
+
+    /// Return the forward index of each key to its set of values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: BiMultiMap<u8, char> = BiMultiMap::new();
+    /// a.insert(1, 'a');
+    /// assert_eq!(a.forward().sub_contains(&1, &'a'), true);
+    /// ```
+    #[inline]
+    pub fn forward(&self) -> &HashMapToSet<K, V> {
+        &self.forward
+    }
+
+    /// Return the inverse index of each value to its set of keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: BiMultiMap<u8, char> = BiMultiMap::new();
+    /// a.insert(1, 'a');
+    /// assert_eq!(a.inverse().sub_contains(&'a', &1), true);
+    /// ```
+    #[inline]
+    pub fn inverse(&self) -> &HashMapToSet<V, K> {
+        &self.inverse
+    }
+
+    /// Return an iterator over every `(&K, &V)` pair in arbitrary order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: BiMultiMap<u8, char> = BiMultiMap::new();
+    /// a.insert(1, 'a');
+    /// let pairs: Vec<(&u8, &char)> = a.iter().collect();
+    /// assert_eq!(pairs, vec![(&1, &'a')]);
+    /// ```
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.forward.sub_iter()
+    }
+
+}
+
+impl<K, V> BiMultiMap<K, V>
+where
+    K: Hash + Eq,
+    V: Hash + Eq,
+{
+
+    /// Add a key-value pair to both indexes.
+    ///
+    /// Return whether the pair is newly added.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: BiMultiMap<u8, char> = BiMultiMap::new();
+    /// assert_eq!(a.insert(1, 'a'), true);
+    /// assert_eq!(a.insert(1, 'a'), false);
+    /// ```
+    #[inline]
+    pub fn insert(&mut self, key: K, value: V) -> bool
+    where
+        K: Clone,
+        V: Clone,
+    {
+        if self.forward.sub_contains(&key, &value) {
+            return false;
+        }
+        self.inverse.sub_insert(value.clone(), key.clone());
+        self.forward.sub_insert(key, value)
+    }
+
+    /// Remove a key-value pair from both indexes.
+    ///
+    /// Return whether the pair was present.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: BiMultiMap<u8, char> = BiMultiMap::new();
+    /// a.insert(1, 'a');
+    /// assert_eq!(a.remove(&1, &'a'), true);
+    /// assert_eq!(a.keys_of(&'a').count(), 0);
+    /// ```
+    #[inline]
+    pub fn remove<Q, R>(&mut self, key: &Q, value: &R) -> bool
+    where
+        K: Borrow<Q>,
+        V: Borrow<R>,
+        Q: Hash + Eq + ?Sized,
+        R: Hash + Eq + ?Sized,
+    {
+        if !self.forward.sub_remove_prune(key, value) {
+            return false;
+        }
+        self.inverse.sub_remove_prune(value, key);
+        true
+    }
+
+    /// Return `true` if the map contains a key-value pair.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: BiMultiMap<u8, char> = BiMultiMap::new();
+    /// a.insert(1, 'a');
+    /// assert_eq!(a.contains(&1, &'a'), true);
+    /// assert_eq!(a.contains(&1, &'b'), false);
+    /// ```
+    #[inline]
+    pub fn contains<Q, R>(&self, key: &Q, value: &R) -> bool
+    where
+        K: Borrow<Q>,
+        V: Borrow<R>,
+        Q: Hash + Eq + ?Sized,
+        R: Hash + Eq + ?Sized,
+    {
+        self.forward.sub_contains(key, value)
+    }
+
+    /// Return an iterator over the values of a key, in arbitrary order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: BiMultiMap<u8, char> = BiMultiMap::new();
+    /// a.insert(1, 'a');
+    /// let values: Vec<&char> = a.values_of(&1).collect();
+    /// assert_eq!(values, vec![&'a']);
+    /// ```
+    #[inline]
+    pub fn values_of<Q>(&self, key: &Q) -> impl Iterator<Item = &V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.forward.get(key).into_iter().flatten()
+    }
+
+    /// Return an iterator over the keys of a value, in arbitrary order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: BiMultiMap<u8, char> = BiMultiMap::new();
+    /// a.insert(1, 'a');
+    /// let keys: Vec<&u8> = a.keys_of(&'a').collect();
+    /// assert_eq!(keys, vec![&1]);
+    /// ```
+    #[inline]
+    pub fn keys_of<R>(&self, value: &R) -> impl Iterator<Item = &K>
+    where
+        V: Borrow<R>,
+        R: Hash + Eq + ?Sized,
+    {
+        self.inverse.get(value).into_iter().flatten()
+    }
+
+    /// Remove a key and all its pairs from both indexes.
+    ///
+    /// Return the number of pairs that were removed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: BiMultiMap<u8, char> = BiMultiMap::new();
+    /// a.insert(1, 'a');
+    /// a.insert(1, 'b');
+    /// assert_eq!(a.remove_key(&1), 2);
+    /// assert_eq!(a.is_empty(), true);
+    /// ```
+    #[inline]
+    pub fn remove_key<Q>(&mut self, key: &Q) -> usize
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        match self.forward.remove(key) {
+            Some(values) => {
+                for value in values.iter() {
+                    self.inverse.sub_remove_prune(value, key);
+                }
+                values.len()
+            },
+            None => 0,
+        }
+    }
+
+    /// Remove a value and all its pairs from both indexes.
+    ///
+    /// Return the number of pairs that were removed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: BiMultiMap<u8, char> = BiMultiMap::new();
+    /// a.insert(1, 'a');
+    /// a.insert(2, 'a');
+    /// assert_eq!(a.remove_value(&'a'), 2);
+    /// assert_eq!(a.is_empty(), true);
+    /// ```
+    #[inline]
+    pub fn remove_value<R>(&mut self, value: &R) -> usize
+    where
+        V: Borrow<R>,
+        R: Hash + Eq + ?Sized,
+    {
+        match self.inverse.remove(value) {
+            Some(keys) => {
+                for key in keys.iter() {
+                    self.forward.sub_remove_prune(key, value);
+                }
+                keys.len()
+            },
+            None => 0,
+        }
+    }
+
+}
+
+impl<K, V> Default for BiMultiMap<K, V> {
+    fn default() -> Self {
+        BiMultiMap::new()
+    }
+}
+
+impl<K, V> PartialEq for BiMultiMap<K, V>
+where
+    K: Hash + Eq,
+    V: Hash + Eq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.forward == other.forward
+    }
+}
+
+impl<K, V> Eq for BiMultiMap<K, V>
+where
+    K: Hash + Eq,
+    V: Hash + Eq,
+{}
+
+impl<K, V> FromIterator<(K, V)> for BiMultiMap<K, V>
+where
+    K: Clone + Hash + Eq,
+    V: Clone + Hash + Eq,
+{
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = BiMultiMap::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K, V> Extend<(K, V)> for BiMultiMap<K, V>
+where
+    K: Clone + Hash + Eq,
+    V: Clone + Hash + Eq,
+{
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+
+    /// Return `true` if the forward and inverse indexes hold the same pairs.
+    fn is_in_step(subject: &BiMultiMap<u8, char>) -> bool {
+        subject.forward().sub_len() == subject.inverse().sub_len()
+        && subject.forward().sub_iter().all(|(k, v)| subject.inverse().sub_contains(v, k))
+    }
+
+    #[test]
+    /// Test `insert` updates both indexes.
+    fn test_insert() {
+        let mut subject: BiMultiMap<u8, char> = BiMultiMap::new();
+        assert_eq!(subject.insert(1, 'a'), true);
+        assert_eq!(subject.insert(1, 'b'), true);
+        assert_eq!(subject.insert(2, 'a'), true);
+        assert_eq!(subject.insert(2, 'a'), false);
+        assert_eq!(subject.len(), 3);
+        let mut values: Vec<&char> = subject.values_of(&1).collect();
+        values.sort();
+        assert_eq!(values, vec![&'a', &'b']);
+        let mut keys: Vec<&u8> = subject.keys_of(&'a').collect();
+        keys.sort();
+        assert_eq!(keys, vec![&1, &2]);
+        assert_eq!(is_in_step(&subject), true);
+    }
+
+    #[test]
+    /// Test `remove` prunes empty sets from both indexes.
+    fn test_remove() {
+        let mut subject: BiMultiMap<u8, char> = vec![(1, 'a'), (1, 'b'), (2, 'a')].into_iter().collect();
+        assert_eq!(subject.remove(&1, &'a'), true);
+        assert_eq!(subject.remove(&1, &'a'), false);
+        assert_eq!(subject.remove(&9, &'z'), false);
+        assert_eq!(subject.remove(&1, &'b'), true);
+        assert_eq!(subject.forward().contains_key(&1), false);
+        assert_eq!(subject.inverse().contains_key(&'b'), false);
+        assert_eq!(subject.len(), 1);
+        assert_eq!(is_in_step(&subject), true);
+    }
+
+    #[test]
+    /// Test `remove_key` and `remove_value` remove every pair from both indexes.
+    fn test_remove_key_and_remove_value() {
+        let mut subject: BiMultiMap<u8, char> = vec![(1, 'a'), (1, 'b'), (2, 'a'), (3, 'c')].into_iter().collect();
+        assert_eq!(subject.remove_key(&1), 2);
+        assert_eq!(subject.remove_key(&1), 0);
+        assert_eq!(subject.inverse().contains_key(&'b'), false);
+        assert_eq!(is_in_step(&subject), true);
+        assert_eq!(subject.remove_value(&'a'), 1);
+        assert_eq!(subject.forward().contains_key(&2), false);
+        assert_eq!(subject.len(), 1);
+        assert_eq!(is_in_step(&subject), true);
+    }
+
+    #[test]
+    /// Test lookups with an absent key and an absent value.
+    fn test_values_of_and_keys_of_x_absent() {
+        let subject: BiMultiMap<u8, char> = BiMultiMap::new();
+        assert_eq!(subject.values_of(&1).count(), 0);
+        assert_eq!(subject.keys_of(&'a').count(), 0);
+    }
+
+}
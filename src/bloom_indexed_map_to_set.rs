@@ -0,0 +1,477 @@
+use std::collections::{HashMap, HashSet};
+use std::collections::hash_map::RandomState;
+use std::borrow::Borrow;
+use std::cmp::Eq;
+use std::hash::{BuildHasher, Hash, Hasher};
+use crate::hash_map_to_set::{HashMapToSet, HashMapToSetExt};
+
+/// A map of keys to sets of values, with a bloom filter over its pairs
+/// in front, so most negative `sub_contains` calls never touch the map.
+///
+/// The filter is a counting bloom filter: each slot is a small counter,
+/// so removing a pair decrements its slots and keeps the filter exact
+/// about what it may contain. A counter that reaches its maximum stays
+/// there, which can only cause extra false positives, never a false
+/// negative.
+///
+/// # Examples
+///
+/// ```
+/// use sixarm_collections::*;
+/// let mut a: BloomIndexedMapToSet<u32, u32> = BloomIndexedMapToSet::with_capacity(1000, 0.01);
+/// a.sub_insert(1, 2);
+/// assert_eq!(a.sub_contains(&1, &2), true);
+/// assert_eq!(a.sub_contains(&1, &3), false);
+/// ```
+#[derive(Debug, Clone)]
+pub struct BloomIndexedMapToSet<K, V> {
+    map: HashMapToSet<K, V>,
+    counters: Vec<u8>,
+    hashes: u32,
+    hasher: RandomState,
+}
+
+impl<K, V> BloomIndexedMapToSet<K, V> {
+
+    /// Create an empty map sized for 1024 pairs at a 1% false positive rate.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: BloomIndexedMapToSet<u8, u8> = BloomIndexedMapToSet::new();
+    /// assert_eq!(a.is_empty(), true);
+    /// ```
+    #[inline]
+    pub fn new() -> Self {
+        BloomIndexedMapToSet::with_capacity(1024, 0.01)
+    }
+
+    /// Create an empty map with a filter sized for a number of pairs and a false positive rate.
+    ///
+    /// The rate is clamped to between 0.000001 and 0.5. The filter does
+    /// not grow, so more pairs than the capacity raise the rate; use
+    /// `rebuild` to resize it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: BloomIndexedMapToSet<u8, u8> = BloomIndexedMapToSet::with_capacity(100, 0.001);
+    /// assert_eq!(a.bloom_len() >= 1437, true);
+    /// ```
+    pub fn with_capacity(pairs: usize, false_positive_rate: f64) -> Self {
+        let (slots, hashes) = bloom_size(pairs, false_positive_rate);
+        BloomIndexedMapToSet {
+            map: HashMap::new(),
+            counters: vec![0; slots],
+            hashes,
+            hasher: RandomState::new(),
+        }
+    }
+
+    /// Return the number of keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: BloomIndexedMapToSet<u8, u8> = BloomIndexedMapToSet::new();
+    /// a.sub_insert(1, 2);
+    /// a.sub_insert(1, 3);
+    /// assert_eq!(a.len(), 1);
+    /// ```
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Return `true` if the map has no keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: BloomIndexedMapToSet<u8, u8> = BloomIndexedMapToSet::new();
+    /// assert_eq!(a.is_empty(), true);
+    /// ```
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Return the total number of values across all keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: BloomIndexedMapToSet<u8, u8> = BloomIndexedMapToSet::new();
+    /// a.sub_insert(1, 2);
+    /// a.sub_insert(4, 5);
+    /// assert_eq!(a.sub_len(), 2);
+    /// ```
+    #[inline]
+    pub fn sub_len(&self) -> usize {
+        self.map.sub_len()
+    }
+
+    /// Return the number of slots in the filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: BloomIndexedMapToSet<u8, u8> = BloomIndexedMapToSet::with_capacity(0, 0.01);
+    /// assert_eq!(a.bloom_len() > 0, true);
+    /// ```
+    #[inline]
+    pub fn bloom_len(&self) -> usize {
+        self.counters.len()
+    }
+
+    /// Return a reference to the underlying map.
+    ///
+    /// The map is read-only here, so it stays consistent with the filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: BloomIndexedMapToSet<u8, u8> = BloomIndexedMapToSet::new();
+    /// a.sub_insert(1, 2);
+    /// assert_eq!(a.as_map().contains_key(&1), true);
+    /// ```
+    #[inline]
+    pub fn as_map(&self) -> &HashMapToSet<K, V> {
+        &self.map
+    }
+
+    /// Consume the wrapper and return the underlying map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: BloomIndexedMapToSet<u8, u8> = BloomIndexedMapToSet::new();
+    /// a.sub_insert(1, 2);
+    /// let b: HashMapToSet<u8, u8> = a.into_map();
+    /// assert_eq!(b.len(), 1);
+    /// ```
+    #[inline]
+    pub fn into_map(self) -> HashMapToSet<K, V> {
+        self.map
+    }
+
+    /// Remove every pair and reset the filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: BloomIndexedMapToSet<u8, u8> = BloomIndexedMapToSet::new();
+    /// a.sub_insert(1, 2);
+    /// a.clear();
+    /// assert_eq!(a.sub_might_contain(&1, &2), false);
+    /// ```
+    #[inline]
+    pub fn clear(&mut self) {
+        self.map.clear();
+        self.counters.iter_mut().for_each(|counter| *counter = 0);
+    }
+
+}
+
+impl<K, V> BloomIndexedMapToSet<K, V>
+where
+    K: Hash + Eq,
+    V: Hash + Eq,
+{
+
+    /// Return the filter slots of a pair, by double hashing.
+    ///
+    /// Borrowed forms hash the same as owned forms, so lookups with
+    /// borrowed keys and values reach the same slots.
+    fn slots<Q, R>(&self, key: &Q, value: &R) -> impl Iterator<Item = usize>
+    where
+        Q: Hash + ?Sized,
+        R: Hash + ?Sized,
+    {
+        let mut hasher = self.hasher.build_hasher();
+        key.hash(&mut hasher);
+        value.hash(&mut hasher);
+        let hash = hasher.finish();
+        let h1 = hash & 0xffff_ffff;
+        let h2 = (hash >> 32) | 1;
+        let len = self.counters.len() as u64;
+        (0..self.hashes as u64).map(move |i| (h1.wrapping_add(i.wrapping_mul(h2)) % len) as usize)
+    }
+
+    /// Increment the filter slots of a pair.
+    fn bloom_add<Q, R>(&mut self, key: &Q, value: &R)
+    where
+        Q: Hash + ?Sized,
+        R: Hash + ?Sized,
+    {
+        let slots: Vec<usize> = self.slots(key, value).collect();
+        for slot in slots {
+            self.counters[slot] = self.counters[slot].saturating_add(1);
+        }
+    }
+
+    /// Decrement the filter slots of a pair, leaving saturated slots alone.
+    fn bloom_sub<Q, R>(&mut self, key: &Q, value: &R)
+    where
+        Q: Hash + ?Sized,
+        R: Hash + ?Sized,
+    {
+        let slots: Vec<usize> = self.slots(key, value).collect();
+        for slot in slots {
+            if self.counters[slot] != u8::MAX {
+                self.counters[slot] -= 1;
+            }
+        }
+    }
+
+    /// Return `false` if the filter proves the pair is absent, else `true`.
+    ///
+    /// This never touches the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: BloomIndexedMapToSet<u8, u8> = BloomIndexedMapToSet::new();
+    /// a.sub_insert(1, 2);
+    /// assert_eq!(a.sub_might_contain(&1, &2), true);
+    /// ```
+    #[inline]
+    pub fn sub_might_contain<Q, R>(&self, key: &Q, value: &R) -> bool
+    where
+        K: Borrow<Q>,
+        V: Borrow<R>,
+        Q: Hash + Eq + ?Sized,
+        R: Hash + Eq + ?Sized,
+    {
+        self.slots(key, value).all(|slot| self.counters[slot] > 0)
+    }
+
+    /// Return `true` if a key's set has a value.
+    ///
+    /// The filter answers most negative lookups; the map is checked only
+    /// when the filter says the pair may be present.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: BloomIndexedMapToSet<String, String> = BloomIndexedMapToSet::new();
+    /// a.sub_insert(String::from("alpha"), String::from("bravo"));
+    /// assert_eq!(a.sub_contains("alpha", "bravo"), true);
+    /// assert_eq!(a.sub_contains("alpha", "charlie"), false);
+    /// ```
+    #[inline]
+    pub fn sub_contains<Q, R>(&self, key: &Q, value: &R) -> bool
+    where
+        K: Borrow<Q>,
+        V: Borrow<R>,
+        Q: Hash + Eq + ?Sized,
+        R: Hash + Eq + ?Sized,
+    {
+        self.sub_might_contain(key, value) && self.map.sub_contains(key, value)
+    }
+
+    /// Add a value to a key's set, and return `true` if the value was newly added.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: BloomIndexedMapToSet<u8, u8> = BloomIndexedMapToSet::new();
+    /// assert_eq!(a.sub_insert(1, 2), true);
+    /// assert_eq!(a.sub_insert(1, 2), false);
+    /// ```
+    pub fn sub_insert(&mut self, key: K, value: V) -> bool {
+        if self.sub_contains(&key, &value) {
+            return false;
+        }
+        self.bloom_add(&key, &value);
+        self.map.sub_insert(key, value)
+    }
+
+    /// Remove a value from a key's set, and return `true` if it was present.
+    ///
+    /// The key stays in the map even when its set becomes empty;
+    /// use `sub_remove_prune` to also remove the key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: BloomIndexedMapToSet<u8, u8> = BloomIndexedMapToSet::new();
+    /// a.sub_insert(1, 2);
+    /// assert_eq!(a.sub_remove(&1, &2), true);
+    /// assert_eq!(a.sub_contains(&1, &2), false);
+    /// ```
+    pub fn sub_remove<Q, R>(&mut self, key: &Q, value: &R) -> bool
+    where
+        K: Borrow<Q>,
+        V: Borrow<R>,
+        Q: Hash + Eq + ?Sized,
+        R: Hash + Eq + ?Sized,
+    {
+        let removed = self.map.sub_remove(key, value);
+        if removed {
+            self.bloom_sub(key, value);
+        }
+        removed
+    }
+
+    /// Remove a value from a key's set, and prune the key if its set becomes empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: BloomIndexedMapToSet<u8, u8> = BloomIndexedMapToSet::new();
+    /// a.sub_insert(1, 2);
+    /// assert_eq!(a.sub_remove_prune(&1, &2), true);
+    /// assert_eq!(a.len(), 0);
+    /// ```
+    pub fn sub_remove_prune<Q, R>(&mut self, key: &Q, value: &R) -> bool
+    where
+        K: Borrow<Q>,
+        V: Borrow<R>,
+        Q: Hash + Eq + ?Sized,
+        R: Hash + Eq + ?Sized,
+    {
+        let removed = self.sub_remove(key, value);
+        if self.map.get(key).is_some_and(|set| set.is_empty()) {
+            self.map.remove(key);
+        }
+        removed
+    }
+
+    /// Remove a key and return its set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: BloomIndexedMapToSet<u8, u8> = BloomIndexedMapToSet::new();
+    /// a.sub_insert(1, 2);
+    /// assert_eq!(a.remove(&1).map(|set| set.len()), Some(1));
+    /// assert_eq!(a.sub_might_contain(&1, &2), false);
+    /// ```
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<HashSet<V>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let (key, set) = self.map.remove_entry(key)?;
+        for value in set.iter() {
+            self.bloom_sub(&key, value);
+        }
+        Some(set)
+    }
+
+    /// Rebuild the filter for a new number of pairs and false positive rate.
+    ///
+    /// This also clears any saturated slots, so call it after heavy churn
+    /// or when the map has grown past the capacity it was sized for.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: BloomIndexedMapToSet<u8, u8> = BloomIndexedMapToSet::with_capacity(1, 0.5);
+    /// a.sub_insert(1, 2);
+    /// a.rebuild(1000, 0.01);
+    /// assert_eq!(a.sub_contains(&1, &2), true);
+    /// ```
+    pub fn rebuild(&mut self, pairs: usize, false_positive_rate: f64) {
+        let (slots, hashes) = bloom_size(pairs, false_positive_rate);
+        self.counters = vec![0; slots];
+        self.hashes = hashes;
+        let all: Vec<usize> = self.map.iter()
+        .flat_map(|(key, set)| set.iter().map(move |value| (key, value)))
+        .flat_map(|(key, value)| self.slots(key, value))
+        .collect();
+        for slot in all {
+            self.counters[slot] = self.counters[slot].saturating_add(1);
+        }
+    }
+
+}
+
+/// Return the number of slots and hashes for a bloom filter.
+fn bloom_size(pairs: usize, false_positive_rate: f64) -> (usize, u32) {
+    let pairs = pairs.max(1) as f64;
+    let rate = false_positive_rate.clamp(0.000001, 0.5);
+    let ln2 = std::f64::consts::LN_2;
+    let slots = (-(pairs * rate.ln()) / (ln2 * ln2)).ceil().max(8.0);
+    let hashes = ((slots / pairs) * ln2).round().clamp(1.0, 16.0);
+    (slots as usize, hashes as u32)
+}
+
+impl<K, V> Default for BloomIndexedMapToSet<K, V> {
+    fn default() -> Self {
+        BloomIndexedMapToSet::new()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+
+    #[test]
+    /// Test `sub_contains` has no false negatives and few false positives.
+    fn test_sub_contains_x_false_positive_rate() {
+        let mut subject: BloomIndexedMapToSet<u32, u32> = BloomIndexedMapToSet::with_capacity(1000, 0.01);
+        for i in 0..1000 {
+            subject.sub_insert(i % 100, i);
+        }
+        for i in 0..1000 {
+            assert_eq!(subject.sub_contains(&(i % 100), &i), true);
+        }
+        let false_positives = (1000..11000).filter(|i| subject.sub_might_contain(&(i % 100), i)).count();
+        assert_eq!(false_positives < 500, true);
+        assert_eq!((1000..11000).any(|i| subject.sub_contains(&(i % 100), &i)), false);
+    }
+
+    #[test]
+    /// Test removals keep the filter consistent with the map.
+    fn test_sub_remove_and_remove_x_filter() {
+        let mut subject: BloomIndexedMapToSet<u8, u8> = BloomIndexedMapToSet::new();
+        subject.sub_insert(1, 2);
+        subject.sub_insert(1, 3);
+        subject.sub_insert(4, 5);
+        assert_eq!(subject.sub_remove(&1, &2), true);
+        assert_eq!(subject.sub_remove(&1, &2), false);
+        assert_eq!(subject.sub_might_contain(&1, &2), false);
+        assert_eq!(subject.as_map().contains_key(&1), true);
+        assert_eq!(subject.sub_remove_prune(&1, &3), true);
+        assert_eq!(subject.len(), 1);
+        assert_eq!(subject.remove(&4).map(|set| set.len()), Some(1));
+        assert_eq!(subject.counters.iter().all(|counter| *counter == 0), true);
+    }
+
+    #[test]
+    /// Test saturated slots never cause a false negative.
+    fn test_saturation() {
+        let mut subject: BloomIndexedMapToSet<u32, u32> = BloomIndexedMapToSet::with_capacity(1, 0.5);
+        for i in 0..1000 {
+            subject.sub_insert(0, i);
+        }
+        for i in 0..999 {
+            subject.sub_remove(&0, &i);
+        }
+        assert_eq!(subject.sub_contains(&0, &999), true);
+        assert_eq!(subject.sub_len(), 1);
+        subject.rebuild(10, 0.01);
+        assert_eq!(subject.sub_contains(&0, &999), true);
+        assert_eq!(subject.sub_contains(&0, &1), false);
+    }
+
+}
@@ -0,0 +1,723 @@
+use std::collections::BTreeMap;
+use std::borrow::Borrow;
+use std::cmp::Ord;
+use std::iter::FromIterator;
+use std::ops::{Add, BitAnd, BitOr, RangeBounds, Sub};
+
+/// An ordered multiset of values, based on `BTreeMap<T, usize>` of each value to its count.
+///
+/// Each insert adds one occurrence, and each remove takes one away;
+/// a value is dropped from the bag when its count reaches zero.
+///
+/// # Examples
+///
+/// ```
+/// use sixarm_collections::*;
+/// let mut a: BTreeBag<u8> = BTreeBag::new();
+/// a.insert(1);
+/// a.insert(1);
+/// a.insert(2);
+/// assert_eq!(a.count(&1), 2);
+/// assert_eq!(a.len(), 3);
+/// assert_eq!(a.distinct_len(), 2);
+/// ```
+#[derive(Debug, Clone)]
+pub struct BTreeBag<T> {
+    map: BTreeMap<T, usize>,
+    len: usize,
+}
+
+impl<T> BTreeBag<T> {
+
+    /// Create an empty bag.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: BTreeBag<u8> = BTreeBag::new();
+    /// assert_eq!(a.is_empty(), true);
+    /// ```
+    #[inline]
+    pub fn new() -> Self {
+        BTreeBag { map: BTreeMap::new(), len: 0 }
+    }
+
+    /// Return the total number of occurrences in the bag.
+    ///
+    /// This counts duplicates, unlike `distinct_len`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: BTreeBag<u8> = BTreeBag::new();
+    /// a.insert(1);
+    /// a.insert(1);
+    /// assert_eq!(a.len(), 2);
+    /// ```
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Return the number of distinct values in the bag.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: BTreeBag<u8> = BTreeBag::new();
+    /// a.insert(1);
+    /// a.insert(1);
+    /// assert_eq!(a.distinct_len(), 1);
+    /// ```
+    #[inline]
+    pub fn distinct_len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Return `true` if the bag has no occurrences.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: BTreeBag<u8> = BTreeBag::new();
+    /// assert_eq!(a.is_empty(), true);
+    /// a.insert(1);
+    /// assert_eq!(a.is_empty(), false);
+    /// ```
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Remove every occurrence from the bag.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: BTreeBag<u8> = BTreeBag::new();
+    /// a.insert(1);
+    /// a.clear();
+    /// assert_eq!(a.is_empty(), true);
+    /// ```
+    #[inline]
+    pub fn clear(&mut self) {
+        self.map.clear();
+        self.len = 0;
+    }
+
+    /// Return an iterator over every occurrence in the bag.
+    ///
+    /// A value with count 3 is yielded three times, in value order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: BTreeBag<u8> = BTreeBag::new();
+    /// a.insert(1);
+    /// a.insert(1);
+    /// let values: Vec<&u8> = a.iter().collect();
+    /// assert_eq!(values, vec![&1, &1]);
+    /// ```
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.iter_with_counts()
+        .flat_map(|(value, count)| std::iter::repeat(value).take(count))
+    }
+
+    /// Return an iterator over every distinct value with its count, in value order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: BTreeBag<u8> = BTreeBag::new();
+    /// a.insert(1);
+    /// a.insert(1);
+    /// let pairs: Vec<(&u8, usize)> = a.iter_with_counts().collect();
+    /// assert_eq!(pairs, vec![(&1, 2)]);
+    /// ```
+    #[inline]
+    pub fn iter_with_counts(&self) -> impl Iterator<Item = (&T, usize)> {
+        self.map.iter().map(|(value, count)| (value, *count))
+    }
+
+}
+
+impl<T> BTreeBag<T>
+where
+    T: Ord,
+{
+
+    /// Add one occurrence of a value to the bag.
+    ///
+    /// Return the count of the value after adding.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: BTreeBag<u8> = BTreeBag::new();
+    /// assert_eq!(a.insert(1), 1);
+    /// assert_eq!(a.insert(1), 2);
+    /// ```
+    #[inline]
+    pub fn insert(&mut self, value: T) -> usize {
+        self.insert_many(value, 1)
+    }
+
+    /// Add many occurrences of a value to the bag.
+    ///
+    /// Return the count of the value after adding. Adding zero
+    /// occurrences leaves the bag unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: BTreeBag<u8> = BTreeBag::new();
+    /// assert_eq!(a.insert_many(1, 3), 3);
+    /// assert_eq!(a.len(), 3);
+    /// ```
+    #[inline]
+    pub fn insert_many(&mut self, value: T, n: usize) -> usize {
+        if n == 0 {
+            return self.count(&value);
+        }
+        let count = self.map.entry(value).or_insert(0);
+        *count += n;
+        self.len += n;
+        *count
+    }
+
+    /// Remove one occurrence of a value from the bag.
+    ///
+    /// Return whether the value was present.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: BTreeBag<u8> = BTreeBag::new();
+    /// a.insert(1);
+    /// assert_eq!(a.remove(&1), true);
+    /// assert_eq!(a.remove(&1), false);
+    /// ```
+    #[inline]
+    pub fn remove<R>(&mut self, value: &R) -> bool
+    where
+        T: Borrow<R>,
+        R: Ord + ?Sized,
+    {
+        match self.map.get_mut(value) {
+            Some(count) => {
+                *count -= 1;
+                if *count == 0 {
+                    self.map.remove(value);
+                }
+                self.len -= 1;
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Remove every occurrence of a value from the bag.
+    ///
+    /// Return the number of occurrences that were removed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: BTreeBag<u8> = BTreeBag::new();
+    /// a.insert_many(1, 3);
+    /// assert_eq!(a.remove_all(&1), 3);
+    /// assert_eq!(a.is_empty(), true);
+    /// ```
+    #[inline]
+    pub fn remove_all<R>(&mut self, value: &R) -> usize
+    where
+        T: Borrow<R>,
+        R: Ord + ?Sized,
+    {
+        let count = self.map.remove(value).unwrap_or(0);
+        self.len -= count;
+        count
+    }
+
+    /// Return the number of occurrences of a value.
+    ///
+    /// Return 0 if the value is absent.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: BTreeBag<u8> = BTreeBag::new();
+    /// a.insert(1);
+    /// assert_eq!(a.count(&1), 1);
+    /// assert_eq!(a.count(&2), 0);
+    /// ```
+    #[inline]
+    pub fn count<R>(&self, value: &R) -> usize
+    where
+        T: Borrow<R>,
+        R: Ord + ?Sized,
+    {
+        self.map.get(value).copied().unwrap_or(0)
+    }
+
+    /// Return `true` if the bag has at least one occurrence of a value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: BTreeBag<u8> = BTreeBag::new();
+    /// a.insert(1);
+    /// assert_eq!(a.contains(&1), true);
+    /// assert_eq!(a.contains(&2), false);
+    /// ```
+    #[inline]
+    pub fn contains<R>(&self, value: &R) -> bool
+    where
+        T: Borrow<R>,
+        R: Ord + ?Sized,
+    {
+        self.map.contains_key(value)
+    }
+
+    /// Return `true` if every value in this bag occurs at most as often in the other bag.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: BTreeBag<u8> = vec![1, 2].into_iter().collect();
+    /// let b: BTreeBag<u8> = vec![1, 1, 2].into_iter().collect();
+    /// assert_eq!(a.is_subbag(&b), true);
+    /// assert_eq!(b.is_subbag(&a), false);
+    /// ```
+    #[inline]
+    pub fn is_subbag(&self, other: &BTreeBag<T>) -> bool {
+        self.map.iter().all(|(value, count)| *count <= other.count(value))
+    }
+
+    /// Return the union, with each value at the larger of its two counts.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: BTreeBag<u8> = vec![1, 1, 2].into_iter().collect();
+    /// let b: BTreeBag<u8> = vec![1, 3].into_iter().collect();
+    /// let c = a.union(&b);
+    /// assert_eq!(c.count(&1), 2);
+    /// assert_eq!(c.len(), 4);
+    /// ```
+    #[inline]
+    pub fn union(&self, other: &BTreeBag<T>) -> BTreeBag<T>
+    where
+        T: Clone,
+    {
+        let mut bag = self.clone();
+        for (value, count) in other.iter_with_counts() {
+            let have = bag.count(value);
+            if count > have {
+                bag.insert_many(value.clone(), count - have);
+            }
+        }
+        bag
+    }
+
+    /// Return the intersection, with each value at the smaller of its two counts.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: BTreeBag<u8> = vec![1, 1, 2].into_iter().collect();
+    /// let b: BTreeBag<u8> = vec![1, 3].into_iter().collect();
+    /// let c = a.intersection(&b);
+    /// assert_eq!(c.count(&1), 1);
+    /// assert_eq!(c.len(), 1);
+    /// ```
+    #[inline]
+    pub fn intersection(&self, other: &BTreeBag<T>) -> BTreeBag<T>
+    where
+        T: Clone,
+    {
+        let mut bag = BTreeBag::new();
+        for (value, count) in self.iter_with_counts() {
+            bag.insert_many(value.clone(), count.min(other.count(value)));
+        }
+        bag
+    }
+
+    /// Return the difference, with each count reduced by the other count, stopping at zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: BTreeBag<u8> = vec![1, 1, 2].into_iter().collect();
+    /// let b: BTreeBag<u8> = vec![1, 3].into_iter().collect();
+    /// let c = a.difference(&b);
+    /// assert_eq!(c.count(&1), 1);
+    /// assert_eq!(c.count(&2), 1);
+    /// assert_eq!(c.len(), 2);
+    /// ```
+    #[inline]
+    pub fn difference(&self, other: &BTreeBag<T>) -> BTreeBag<T>
+    where
+        T: Clone,
+    {
+        let mut bag = BTreeBag::new();
+        for (value, count) in self.iter_with_counts() {
+            bag.insert_many(value.clone(), count.saturating_sub(other.count(value)));
+        }
+        bag
+    }
+
+    /// Return the sum, with each value at the total of its two counts.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: BTreeBag<u8> = vec![1, 1, 2].into_iter().collect();
+    /// let b: BTreeBag<u8> = vec![1, 3].into_iter().collect();
+    /// let c = a.sum(&b);
+    /// assert_eq!(c.count(&1), 3);
+    /// assert_eq!(c.len(), 5);
+    /// ```
+    #[inline]
+    pub fn sum(&self, other: &BTreeBag<T>) -> BTreeBag<T>
+    where
+        T: Clone,
+    {
+        let mut bag = self.clone();
+        for (value, count) in other.iter_with_counts() {
+            bag.insert_many(value.clone(), count);
+        }
+        bag
+    }
+
+    /// Return an iterator over each distinct value in a range with its count, in value order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: BTreeBag<u8> = vec![1, 3, 3, 5].into_iter().collect();
+    /// let pairs: Vec<(&u8, usize)> = a.range(2..=4).collect();
+    /// assert_eq!(pairs, vec![(&3, 2)]);
+    /// ```
+    #[inline]
+    pub fn range<R, B>(&self, range: B) -> impl Iterator<Item = (&T, usize)>
+    where
+        T: Borrow<R>,
+        R: Ord + ?Sized,
+        B: RangeBounds<R>,
+    {
+        self.map.range(range).map(|(value, count)| (value, *count))
+    }
+
+    /// Return the number of occurrences of values in a range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: BTreeBag<u8> = vec![1, 3, 3, 5].into_iter().collect();
+    /// assert_eq!(a.range_len(2..), 3);
+    /// ```
+    #[inline]
+    pub fn range_len<R, B>(&self, range: B) -> usize
+    where
+        T: Borrow<R>,
+        R: Ord + ?Sized,
+        B: RangeBounds<R>,
+    {
+        self.map.range(range).map(|(_, count)| count).sum()
+    }
+
+    /// Return the occurrence at a zero-based index in value order.
+    ///
+    /// Duplicates take one index per occurrence, so this is the value
+    /// that a sorted vector of every occurrence would hold at `n`.
+    /// Return `None` if `n` is at least `len`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: BTreeBag<u8> = vec![1, 3, 3, 5].into_iter().collect();
+    /// assert_eq!(a.nth(0), Some(&1));
+    /// assert_eq!(a.nth(2), Some(&3));
+    /// assert_eq!(a.nth(3), Some(&5));
+    /// assert_eq!(a.nth(4), None);
+    /// ```
+    #[inline]
+    pub fn nth(&self, n: usize) -> Option<&T> {
+        let mut seen = 0;
+        for (value, count) in self.map.iter() {
+            seen += count;
+            if n < seen {
+                return Some(value);
+            }
+        }
+        None
+    }
+
+    /// Return the smallest value in the bag.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: BTreeBag<u8> = vec![3, 1, 3].into_iter().collect();
+    /// assert_eq!(a.first(), Some(&1));
+    /// ```
+    #[inline]
+    pub fn first(&self) -> Option<&T> {
+        self.map.keys().next()
+    }
+
+    /// Return the largest value in the bag.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: BTreeBag<u8> = vec![3, 1, 3].into_iter().collect();
+    /// assert_eq!(a.last(), Some(&3));
+    /// ```
+    #[inline]
+    pub fn last(&self) -> Option<&T> {
+        self.map.keys().next_back()
+    }
+}
+
+impl<T> Default for BTreeBag<T> {
+    fn default() -> Self {
+        BTreeBag::new()
+    }
+}
+
+impl<T> PartialEq for BTreeBag<T>
+where
+    T: Ord,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.map == other.map
+    }
+}
+
+impl<T> Eq for BTreeBag<T>
+where
+    T: Ord,
+{}
+
+impl<T> FromIterator<T> for BTreeBag<T>
+where
+    T: Ord,
+{
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut bag = BTreeBag::new();
+        bag.extend(iter);
+        bag
+    }
+}
+
+impl<T> Extend<T> for BTreeBag<T>
+where
+    T: Ord,
+{
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.insert(value);
+        }
+    }
+}
+
+impl<T> BitOr<&BTreeBag<T>> for &BTreeBag<T>
+where
+    T: Clone + Ord,
+{
+    type Output = BTreeBag<T>;
+
+    /// Return the union, like `union`.
+    #[inline]
+    fn bitor(self, other: &BTreeBag<T>) -> BTreeBag<T> {
+        self.union(other)
+    }
+}
+
+impl<T> BitAnd<&BTreeBag<T>> for &BTreeBag<T>
+where
+    T: Clone + Ord,
+{
+    type Output = BTreeBag<T>;
+
+    /// Return the intersection, like `intersection`.
+    #[inline]
+    fn bitand(self, other: &BTreeBag<T>) -> BTreeBag<T> {
+        self.intersection(other)
+    }
+}
+
+impl<T> Sub<&BTreeBag<T>> for &BTreeBag<T>
+where
+    T: Clone + Ord,
+{
+    type Output = BTreeBag<T>;
+
+    /// Return the difference, like `difference`.
+    #[inline]
+    fn sub(self, other: &BTreeBag<T>) -> BTreeBag<T> {
+        self.difference(other)
+    }
+}
+
+impl<T> Add<&BTreeBag<T>> for &BTreeBag<T>
+where
+    T: Clone + Ord,
+{
+    type Output = BTreeBag<T>;
+
+    /// Return the sum, like `sum`.
+    #[inline]
+    fn add(self, other: &BTreeBag<T>) -> BTreeBag<T> {
+        self.sum(other)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+
+    #[test]
+    /// Test `insert`, `count`, `len`, and `distinct_len` with duplicate items.
+    fn test_insert_and_count() {
+        let mut subject: BTreeBag<u8> = BTreeBag::new();
+        let v1 = 1;
+        let v2 = 2;
+        let absent = 9;
+        assert_eq!(subject.insert(v1), 1);
+        assert_eq!(subject.insert(v1), 2);
+        assert_eq!(subject.insert(v2), 1);
+        assert_eq!(subject.count(&v1), 2);
+        assert_eq!(subject.count(&v2), 1);
+        assert_eq!(subject.count(&absent), 0);
+        assert_eq!(subject.len(), 3);
+        assert_eq!(subject.distinct_len(), 2);
+    }
+
+    #[test]
+    /// Test `insert_many` with zero and nonzero occurrences.
+    fn test_insert_many() {
+        let mut subject: BTreeBag<u8> = BTreeBag::new();
+        assert_eq!(subject.insert_many(1, 0), 0);
+        assert_eq!(subject.contains(&1), false);
+        assert_eq!(subject.insert_many(1, 3), 3);
+        assert_eq!(subject.len(), 3);
+    }
+
+    #[test]
+    /// Test `remove` and `remove_all` keep `len` in step and drop values at zero.
+    fn test_remove_and_remove_all() {
+        let mut subject: BTreeBag<u8> = BTreeBag::new();
+        let v1 = 1;
+        let v2 = 2;
+        subject.insert_many(v1, 2);
+        subject.insert_many(v2, 3);
+        assert_eq!(subject.remove(&v1), true);
+        assert_eq!(subject.remove(&v1), true);
+        assert_eq!(subject.remove(&v1), false);
+        assert_eq!(subject.contains(&v1), false);
+        assert_eq!(subject.distinct_len(), 1);
+        assert_eq!(subject.remove_all(&v2), 3);
+        assert_eq!(subject.remove_all(&v2), 0);
+        assert_eq!(subject.is_empty(), true);
+    }
+
+    #[test]
+    /// Test `count` and `remove` with borrowed forms of the value.
+    fn test_count_x_borrowed_forms() {
+        let mut subject: BTreeBag<String> = BTreeBag::new();
+        subject.insert(String::from("alpha"));
+        assert_eq!(subject.count("alpha"), 1);
+        assert_eq!(subject.remove("alpha"), true);
+    }
+
+    #[test]
+    /// Test `iter` and `iter_with_counts` with duplicate items.
+    fn test_iter_and_iter_with_counts() {
+        let subject: BTreeBag<u8> = vec![2, 1, 2].into_iter().collect();
+        let actual: Vec<&u8> = subject.iter().collect();
+        assert_eq!(actual, vec![&1, &2, &2]);
+        let actual: Vec<(&u8, usize)> = subject.iter_with_counts().collect();
+        assert_eq!(actual, vec![(&1, 1), (&2, 2)]);
+    }
+
+    #[test]
+    /// Test the set-algebra methods and operators agree.
+    fn test_set_algebra() {
+        let a: BTreeBag<u8> = vec![1, 1, 2].into_iter().collect();
+        let b: BTreeBag<u8> = vec![1, 3].into_iter().collect();
+        let union: BTreeBag<u8> = vec![1, 1, 2, 3].into_iter().collect();
+        let intersection: BTreeBag<u8> = vec![1].into_iter().collect();
+        let difference: BTreeBag<u8> = vec![1, 2].into_iter().collect();
+        let sum: BTreeBag<u8> = vec![1, 1, 1, 2, 3].into_iter().collect();
+        assert_eq!(a.union(&b), union);
+        assert_eq!(a.intersection(&b), intersection);
+        assert_eq!(a.difference(&b), difference);
+        assert_eq!(a.sum(&b), sum);
+        assert_eq!(&a | &b, union);
+        assert_eq!(&a & &b, intersection);
+        assert_eq!(&a - &b, difference);
+        assert_eq!(&a + &b, sum);
+        assert_eq!(intersection.is_subbag(&a), true);
+        assert_eq!(a.is_subbag(&intersection), false);
+    }
+
+    #[test]
+    /// Test `range` and `range_len` with inclusive, exclusive, and empty ranges.
+    fn test_range_and_range_len() {
+        let subject: BTreeBag<u8> = vec![1, 3, 3, 5, 7].into_iter().collect();
+        let actual: Vec<(&u8, usize)> = subject.range(2..=5).collect();
+        assert_eq!(actual, vec![(&3, 2), (&5, 1)]);
+        assert_eq!(subject.range_len(..5), 3);
+        assert_eq!(subject.range_len(8..), 0);
+    }
+
+    #[test]
+    /// Test `nth` counts duplicates, for percentile-style lookups.
+    fn test_nth() {
+        let subject: BTreeBag<u8> = vec![4, 1, 4, 4, 9].into_iter().collect();
+        let expect = vec![Some(&1), Some(&4), Some(&4), Some(&4), Some(&9), None];
+        let actual: Vec<Option<&u8>> = (0..6).map(|n| subject.nth(n)).collect();
+        assert_eq!(actual, expect);
+        let empty: BTreeBag<u8> = BTreeBag::new();
+        assert_eq!(empty.nth(0), None);
+    }
+
+    #[test]
+    /// Test `first` and `last` with some items and with an empty bag.
+    fn test_first_and_last() {
+        let mut subject: BTreeBag<u8> = BTreeBag::new();
+        assert_eq!(subject.first(), None);
+        assert_eq!(subject.last(), None);
+        subject.extend(vec![5, 2, 8, 2]);
+        assert_eq!(subject.first(), Some(&2));
+        assert_eq!(subject.last(), Some(&8));
+    }
+}
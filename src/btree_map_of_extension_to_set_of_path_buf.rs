@@ -0,0 +1,333 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::io;
+use std::path::{Path, PathBuf};
+use crate::path_scan::{self, PathErrors, SymlinkPolicy, WalkOptions};
+
+/// A map of file extensions to sets of paths.
+///
+/// Each key is the path's extension in lowercase, so "photo.JPG" and
+/// "photo.jpg" share a key, or `None` for a path without an extension.
+/// An extension that isn't valid Unicode is converted lossily.
+///
+/// The key comes from the path alone, so these helpers don't read
+/// file metadata, except to find the regular files in a directory.
+pub type BTreeMapOfExtensionToSetOfPathBuf = BTreeMap<Option<String>, BTreeSet<PathBuf>>;
+
+pub trait BTreeMapOfExtensionToSetOfPathBufExt {
+    fn sub_contains_path<P: AsRef<Path>>(&self, value: P) -> bool;
+    fn sub_insert_path(&mut self, value: PathBuf) -> bool;
+    fn sub_remove_path<P: AsRef<Path>>(&mut self, value: P) -> bool;
+    fn get_extension(&self, extension: &str) -> Option<&BTreeSet<PathBuf>>;
+    fn try_sub_insert_dir<P: AsRef<Path>>(&mut self, dir: P) -> io::Result<PathErrors>;
+    fn from_dir<P: AsRef<Path>>(dir: P) -> io::Result<(Self, PathErrors)> where Self: Sized;
+    fn sub_insert_tree<P: AsRef<Path>>(&mut self, root: P, options: &WalkOptions) -> io::Result<PathErrors>;
+    #[cfg(feature = "rayon")]
+    fn par_sub_insert_tree<P: AsRef<Path>>(&mut self, root: P, options: &WalkOptions) -> io::Result<PathErrors>;
+    fn prune_stale(&mut self) -> Vec<PathBuf>;
+}
+
+impl BTreeMapOfExtensionToSetOfPathBufExt for BTreeMapOfExtensionToSetOfPathBuf {
+
+    /// Return `true` if the collection contains a sub-key-value item.
+    ///
+    /// The path is borrowed, and may be any type that converts to a `&Path`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// use std::path::PathBuf;
+    ///
+    /// let mut a: BTreeMapOfExtensionToSetOfPathBuf = BTreeMapOfExtensionToSetOfPathBuf::new();
+    /// a.sub_insert_path(PathBuf::from("alpha.txt"));
+    /// assert_eq!(a.sub_contains_path("alpha.txt"), true);
+    /// assert_eq!(a.sub_contains_path("bravo.txt"), false);
+    /// ```
+    #[inline]
+    fn sub_contains_path<P: AsRef<Path>>(&self, value: P) -> bool {
+        let value = value.as_ref();
+        match self.get(&path_scan::extension_key(value)) {
+            Some(set) => set.contains(value),
+            None => false,
+        }
+    }
+
+    /// Add a sub-key-value item to the collection.
+    ///
+    /// Return whether the item is added in the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// use std::path::PathBuf;
+    ///
+    /// let mut a: BTreeMapOfExtensionToSetOfPathBuf = BTreeMapOfExtensionToSetOfPathBuf::new();
+    /// assert_eq!(a.sub_insert_path(PathBuf::from("alpha.TXT")), true);
+    /// assert_eq!(a.sub_insert_path(PathBuf::from("alpha.TXT")), false);
+    /// assert_eq!(a.get(&Some(String::from("txt"))).map(|set| set.len()), Some(1));
+    /// ```
+    #[inline]
+    fn sub_insert_path(&mut self, value: PathBuf) -> bool {
+        self.entry(path_scan::extension_key(&value))
+        .or_default()
+        .insert(value)
+    }
+
+    /// Remove a sub-key-value pair from the collection.
+    ///
+    /// Return whether the value was present in the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// use std::path::PathBuf;
+    ///
+    /// let mut a: BTreeMapOfExtensionToSetOfPathBuf = BTreeMapOfExtensionToSetOfPathBuf::new();
+    /// a.sub_insert_path(PathBuf::from("alpha.txt"));
+    /// assert_eq!(a.sub_remove_path("alpha.txt"), true);
+    /// assert_eq!(a.sub_contains_path("alpha.txt"), false);
+    /// ```
+    #[inline]
+    fn sub_remove_path<P: AsRef<Path>>(&mut self, value: P) -> bool {
+        let value = value.as_ref();
+        match self.get_mut(&path_scan::extension_key(value)) {
+            Some(set) => set.remove(value),
+            None => false,
+        }
+    }
+
+    /// Return the set of paths with an extension, in any case.
+    ///
+    /// The extension has no leading dot.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// use std::path::PathBuf;
+    ///
+    /// let mut a: BTreeMapOfExtensionToSetOfPathBuf = BTreeMapOfExtensionToSetOfPathBuf::new();
+    /// a.sub_insert_path(PathBuf::from("alpha.jpg"));
+    /// a.sub_insert_path(PathBuf::from("bravo.JPG"));
+    /// assert_eq!(a.get_extension("Jpg").map(|set| set.len()), Some(2));
+    /// assert_eq!(a.get_extension("png"), None);
+    /// ```
+    #[inline]
+    fn get_extension(&self, extension: &str) -> Option<&BTreeSet<PathBuf>> {
+        self.get(&Some(extension.to_lowercase()))
+    }
+
+    /// Add every regular file in a directory, not recursively.
+    ///
+    /// Return an error if the directory can't be read. Otherwise return
+    /// a report of each entry that can't be read, with its error; the
+    /// scan skips those entries and adds the rest.
+    ///
+    /// The scan skips symbolic links, like `sub_insert_tree` with the
+    /// default `WalkOptions`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    ///
+    /// let mut a: BTreeMapOfExtensionToSetOfPathBuf = BTreeMapOfExtensionToSetOfPathBuf::new();
+    /// # std::fs::create_dir_all("btree_extension_try_sub_insert_dir");
+    /// # std::fs::write("btree_extension_try_sub_insert_dir/alpha.txt", "alpha");
+    /// let errors = a.try_sub_insert_dir("btree_extension_try_sub_insert_dir")?;
+    /// assert_eq!(errors.is_empty(), true);
+    /// assert_eq!(a.sub_contains_path("btree_extension_try_sub_insert_dir/alpha.txt"), true);
+    /// # std::fs::remove_dir_all("btree_extension_try_sub_insert_dir");
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[inline]
+    fn try_sub_insert_dir<P: AsRef<Path>>(&mut self, dir: P) -> io::Result<PathErrors> {
+        path_scan::scan_dir(dir.as_ref(), SymlinkPolicy::Skip, |path, _| {
+            self.sub_insert_path(path);
+        })
+    }
+
+    /// Create a collection of every regular file in a directory, not recursively.
+    ///
+    /// Return an error if the directory can't be read. Otherwise return
+    /// the collection and a report of each entry that can't be read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    ///
+    /// # std::fs::create_dir_all("btree_extension_from_dir");
+    /// # std::fs::write("btree_extension_from_dir/alpha.txt", "alpha");
+    /// let (a, errors) = BTreeMapOfExtensionToSetOfPathBuf::from_dir("btree_extension_from_dir")?;
+    /// assert_eq!(errors.is_empty(), true);
+    /// assert_eq!(a.sub_contains_path("btree_extension_from_dir/alpha.txt"), true);
+    /// # std::fs::remove_dir_all("btree_extension_from_dir");
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[inline]
+    fn from_dir<P: AsRef<Path>>(dir: P) -> io::Result<(Self, PathErrors)> {
+        let mut map = Self::new();
+        let errors = map.try_sub_insert_dir(dir)?;
+        Ok((map, errors))
+    }
+
+    /// Add every regular file in a directory tree, walking it with options
+    /// for the max depth, symbolic links, and path predicates.
+    ///
+    /// Return an error if the root can't be read. Otherwise return a
+    /// report of each path below the root that can't be read, with its
+    /// error; the walk skips those paths and adds the rest.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    ///
+    /// let mut a: BTreeMapOfExtensionToSetOfPathBuf = BTreeMapOfExtensionToSetOfPathBuf::new();
+    /// # std::fs::create_dir_all("btree_extension_sub_insert_tree/one");
+    /// # std::fs::write("btree_extension_sub_insert_tree/alpha.txt", "alpha");
+    /// # std::fs::write("btree_extension_sub_insert_tree/one/bravo.txt", "bravo");
+    /// let errors = a.sub_insert_tree("btree_extension_sub_insert_tree", &WalkOptions::new())?;
+    /// assert_eq!(errors.is_empty(), true);
+    /// assert_eq!(a.get_extension("txt").map(|set| set.len()), Some(2));
+    /// # std::fs::remove_dir_all("btree_extension_sub_insert_tree");
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[inline]
+    fn sub_insert_tree<P: AsRef<Path>>(&mut self, root: P, options: &WalkOptions) -> io::Result<PathErrors> {
+        path_scan::walk_tree(root.as_ref(), options, |path, _| {
+            self.sub_insert_path(path);
+        })
+    }
+
+    /// Add every regular file in a directory tree, like `sub_insert_tree`,
+    /// and read the directories and file metadata of each depth on multiple threads.
+    ///
+    /// This method needs the `rayon` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    ///
+    /// let mut a: BTreeMapOfExtensionToSetOfPathBuf = BTreeMapOfExtensionToSetOfPathBuf::new();
+    /// # std::fs::create_dir_all("btree_extension_par_sub_insert_tree/one");
+    /// # std::fs::write("btree_extension_par_sub_insert_tree/one/bravo.txt", "bravo");
+    /// let errors = a.par_sub_insert_tree("btree_extension_par_sub_insert_tree", &WalkOptions::new())?;
+    /// assert_eq!(errors.is_empty(), true);
+    /// assert_eq!(a.sub_contains_path("btree_extension_par_sub_insert_tree/one/bravo.txt"), true);
+    /// # std::fs::remove_dir_all("btree_extension_par_sub_insert_tree");
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[cfg(feature = "rayon")]
+    #[inline]
+    fn par_sub_insert_tree<P: AsRef<Path>>(&mut self, root: P, options: &WalkOptions) -> io::Result<PathErrors> {
+        path_scan::par_walk_tree(root.as_ref(), options, |path, _| {
+            self.sub_insert_path(path);
+        })
+    }
+
+
+    /// Remove every path whose file no longer exists, and each extension
+    /// whose set becomes empty. Return the removed paths, in path order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// use std::path::PathBuf;
+    ///
+    /// let mut a: BTreeMapOfExtensionToSetOfPathBuf = BTreeMapOfExtensionToSetOfPathBuf::new();
+    /// # std::fs::write("btree_extension_prune_stale.txt", "alpha");
+    /// a.sub_insert_path(PathBuf::from("btree_extension_prune_stale.txt"));
+    /// # std::fs::remove_file("btree_extension_prune_stale.txt");
+    /// assert_eq!(a.prune_stale(), vec![PathBuf::from("btree_extension_prune_stale.txt")]);
+    /// assert_eq!(a.is_empty(), true);
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[inline]
+    fn prune_stale(&mut self) -> Vec<PathBuf> {
+        path_scan::prune_stale(self)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    /// Test `sub_insert_path` and `sub_contains_path`.
+    /// Must key each path by its lowercase extension, or `None`.
+    ///
+    fn test_sub_insert_path() {
+        let mut subject: BTreeMapOfExtensionToSetOfPathBuf = BTreeMapOfExtensionToSetOfPathBuf::new();
+        assert_eq!(subject.sub_insert_path(PathBuf::from("alpha.TXT")), true);
+        assert_eq!(subject.sub_insert_path(PathBuf::from("bravo.txt")), true);
+        assert_eq!(subject.sub_insert_path(PathBuf::from("charlie.tar.gz")), true);
+        assert_eq!(subject.sub_insert_path(PathBuf::from("Makefile")), true);
+        assert_eq!(subject.sub_insert_path(PathBuf::from(".profile")), true);
+        assert_eq!(subject.len(), 3);
+        assert_eq!(subject.get_extension("txt").map(|set| set.len()), Some(2));
+        assert_eq!(subject.get_extension("gz").map(|set| set.len()), Some(1));
+        assert_eq!(subject.get(&None).map(|set| set.len()), Some(2));
+        assert_eq!(subject.sub_contains_path("alpha.TXT"), true);
+        assert_eq!(subject.sub_contains_path("alpha.txt"), false);
+    }
+
+    #[test]
+    /// Test `sub_remove_path`.
+    /// Must remove only the exact path.
+    ///
+    fn test_sub_remove_path() {
+        let mut subject: BTreeMapOfExtensionToSetOfPathBuf = BTreeMapOfExtensionToSetOfPathBuf::new();
+        subject.sub_insert_path(PathBuf::from("alpha.txt"));
+        assert_eq!(subject.sub_remove_path("bravo.txt"), false);
+        assert_eq!(subject.sub_remove_path("alpha.md"), false);
+        assert_eq!(subject.sub_remove_path("alpha.txt"), true);
+        assert_eq!(subject.sub_contains_path("alpha.txt"), false);
+    }
+
+    #[test]
+    /// Test `sub_insert_tree`.
+    /// Must group the regular files by extension.
+    ///
+    fn test_sub_insert_tree() {
+        let dir = std::env::temp_dir().join(format!("btree_map_of_extension_to_set_of_path_buf_tree_{}", std::process::id()));
+        fs::create_dir_all(dir.join("one.d")).unwrap();
+        fs::write(dir.join("alpha.txt"), "alpha").unwrap();
+        fs::write(dir.join("one.d").join("bravo.TXT"), "bravo").unwrap();
+        fs::write(dir.join("one.d").join("charlie.md"), "charlie").unwrap();
+        let mut subject: BTreeMapOfExtensionToSetOfPathBuf = BTreeMapOfExtensionToSetOfPathBuf::new();
+        let errors = subject.sub_insert_tree(&dir, &WalkOptions::new()).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(errors.is_empty(), true);
+        assert_eq!(subject.len(), 2);
+        assert_eq!(subject.get_extension("txt"), Some(&vec![dir.join("alpha.txt"), dir.join("one.d").join("bravo.TXT")].into_iter().collect()));
+        assert_eq!(subject.get_extension("d"), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    /// Test `from_dir` with a symbolic link in the directory.
+    /// Must skip the link, like `sub_insert_tree` with the default options.
+    ///
+    fn test_from_dir_x_symlink() {
+        let dir = std::env::temp_dir().join(format!("btree_map_of_extension_to_set_of_path_buf_from_dir_symlink_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("alpha.txt"), "alpha").unwrap();
+        std::os::unix::fs::symlink("alpha.txt", dir.join("link")).unwrap();
+        let (subject, errors) = BTreeMapOfExtensionToSetOfPathBuf::from_dir(&dir).unwrap();
+        let mut tree = BTreeMapOfExtensionToSetOfPathBuf::new();
+        tree.sub_insert_tree(&dir, &WalkOptions::new().max_depth(1)).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(errors.is_empty(), true);
+        assert_eq!(subject.values().flatten().collect::<Vec<&PathBuf>>(), vec![&dir.join("alpha.txt")]);
+        assert_eq!(subject, tree);
+    }
+
+}
@@ -1,22 +1,49 @@
 use std::collections::{BTreeMap, BTreeSet};
 use std::fs;
-use std::path::PathBuf;
+use std::io;
+use std::ops::RangeBounds;
+use std::path::{Path, PathBuf};
+use crate::path_scan::{self, PathErrors, SymlinkPolicy, WalkOptions};
+use crate::range_bounds;
 
 pub type BTreeMapOfFileLenToSetOfPathBuf = BTreeMap<u64, BTreeSet<PathBuf>>;
 
 pub trait BTreeMapOfFileLenToSetOfPathBufExt {
-    fn sub_contains_path(&self, value: &PathBuf) -> bool;
+    #[deprecated(since = "1.2.0", note = "panics if the file metadata can't be read; use `try_sub_contains_path`")]
+    fn sub_contains_path<P: AsRef<Path>>(&self, value: P) -> bool;
+    #[deprecated(since = "1.2.0", note = "panics if the file metadata can't be read; use `try_sub_insert_path`")]
     fn sub_insert_path(&mut self, value: PathBuf) -> bool;
-    fn sub_remove_path(&mut self, value: PathBuf) -> bool;
+    #[deprecated(since = "1.2.0", note = "panics if the file metadata can't be read; use `try_sub_remove_path`")]
+    fn sub_remove_path<P: AsRef<Path>>(&mut self, value: P) -> bool;
+    fn duplicate_candidates(&self) -> impl Iterator<Item = (&u64, &BTreeSet<PathBuf>)>;
+    fn duplicate_candidates_by_savings(&self) -> Vec<(&u64, &BTreeSet<PathBuf>)>;
+    fn sub_range_len<R: RangeBounds<u64>>(&self, range: R) -> impl Iterator<Item = &PathBuf>;
+    fn sub_len_between<R: RangeBounds<u64>>(&self, range: R) -> u64;
+}
+
+pub trait BTreeMapOfFileLenToSetOfPathBufTryExt {
+    fn try_sub_contains_path<P: AsRef<Path>>(&self, value: P) -> io::Result<bool>;
+    fn try_sub_insert_path(&mut self, value: PathBuf) -> io::Result<bool>;
+    fn try_sub_insert_path_with(&mut self, value: PathBuf, options: &WalkOptions) -> io::Result<bool>;
+    fn try_sub_remove_path<P: AsRef<Path>>(&mut self, value: P) -> io::Result<bool>;
+    fn try_sub_contains_path_with<P: AsRef<Path>>(&self, value: P, options: &WalkOptions) -> io::Result<bool>;
+    fn try_sub_remove_path_with<P: AsRef<Path>>(&mut self, value: P, options: &WalkOptions) -> io::Result<bool>;
+    fn try_sub_insert_dir<P: AsRef<Path>>(&mut self, dir: P) -> io::Result<PathErrors>;
+    fn from_dir<P: AsRef<Path>>(dir: P) -> io::Result<(Self, PathErrors)> where Self: Sized;
+    fn sub_insert_tree<P: AsRef<Path>>(&mut self, root: P, options: &WalkOptions) -> io::Result<PathErrors>;
+    #[cfg(feature = "rayon")]
+    fn par_sub_insert_tree<P: AsRef<Path>>(&mut self, root: P, options: &WalkOptions) -> io::Result<PathErrors>;
+    fn prune_stale(&mut self) -> Vec<PathBuf>;
 }
 
 impl BTreeMapOfFileLenToSetOfPathBufExt for BTreeMapOfFileLenToSetOfPathBuf {
     
     /// Return `true` if the collection contains a sub-key-value item.
     ///
-    /// The value may be any borrowed form of the set's value type, but
-    /// [`Hash`] and [`Eq`] on the borrowed form *must* match those for
-    /// the value type.
+    /// Panic if the file metadata can't be read; prefer `try_sub_contains_path`.
+    ///
+    /// The path may be any type that converts to a `&Path`, such as
+    /// `&str`, `&Path`, or `&PathBuf`, so a lookup needs no owned path.
     ///
     /// # Examples
     ///
@@ -37,18 +64,16 @@ impl BTreeMapOfFileLenToSetOfPathBufExt for BTreeMapOfFileLenToSetOfPathBuf {
     /// # std::fs::remove_file("bravo.txt");
     /// ```
     #[inline]
-    fn sub_contains_path(&self, value: &PathBuf) -> bool {
-        let key = fs::metadata(&value).expect("metadata").len();
-        match self.get(&key) {
-            Some(set) => set.contains(value),
-            None => false,
-        }
+    fn sub_contains_path<P: AsRef<Path>>(&self, value: P) -> bool {
+        self.try_sub_contains_path(value).expect("metadata")
     }
         
     /// Add a sub-key-value item to the collection.
     ///
     /// Return whether the item is added in the set.
     ///
+    /// Panic if the file metadata can't be read; prefer `try_sub_insert_path`.
+    ///
     /// # Examples
     ///
     /// ```
@@ -65,19 +90,17 @@ impl BTreeMapOfFileLenToSetOfPathBufExt for BTreeMapOfFileLenToSetOfPathBuf {
     /// ```
     #[inline]
     fn sub_insert_path(&mut self, value: PathBuf) -> bool {
-        let key = fs::metadata(&value).expect("metadata").len();
-        self.entry(key)
-        .or_insert(BTreeSet::new())
-        .insert(value)
+        self.try_sub_insert_path(value).expect("metadata")
     }
 
     /// Remove a sub-key-value pair from the collection.
     ///
     /// Return whether the value was present in the set.
     ///
-    /// The value may be any borrowed form of the set's value type, but
-    /// [`Hash`] and [`Eq`] on the borrowed form *must* match those for
-    /// the value type.
+    /// Panic if the file metadata can't be read; prefer `try_sub_remove_path`.
+    ///
+    /// The path may be any type that converts to a `&Path`, such as
+    /// `&str`, `&Path`, or `&PathBuf`, so a lookup needs no owned path.
     ///
     /// # Examples
     ///
@@ -91,28 +114,477 @@ impl BTreeMapOfFileLenToSetOfPathBufExt for BTreeMapOfFileLenToSetOfPathBuf {
     /// let alpha = PathBuf::from("alpha.txt");
     /// a.sub_insert_path(alpha.clone());
     /// assert_eq!(a.sub_contains_path(&alpha), true);
-    /// a.sub_remove_path(alpha.clone());
+    /// a.sub_remove_path(&alpha);
     /// assert_eq!(a.sub_contains_path(&alpha), false);
     /// # std::fs::remove_file("alpha.txt");
     /// ```
     #[inline]
-    fn sub_remove_path(&mut self, value: PathBuf) -> bool {
-        let key = fs::metadata(&value).expect("metadata").len();
-        match self.get_mut(&key) {
-            Some(set) => set.remove(&value),
-            None => false,
+    fn sub_remove_path<P: AsRef<Path>>(&mut self, value: P) -> bool {
+        self.try_sub_remove_path(value).expect("metadata")
+    }
+
+    /// Return an iterator over the groups of paths that may be duplicates,
+    /// which are the sets with more than one path, with their file length.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// use std::path::PathBuf;
+    ///
+    /// let mut a: BTreeMapOfFileLenToSetOfPathBuf = BTreeMapOfFileLenToSetOfPathBuf::new();
+    /// a.entry(5).or_default().extend(vec![PathBuf::from("alpha.txt"), PathBuf::from("bravo.txt")]);
+    /// a.entry(7).or_default().insert(PathBuf::from("charlie.txt"));
+    /// let lens: Vec<&u64> = a.duplicate_candidates().map(|(len, _)| len).collect();
+    /// assert_eq!(lens, vec![&5]);
+    /// ```
+    #[inline]
+    fn duplicate_candidates(&self) -> impl Iterator<Item = (&u64, &BTreeSet<PathBuf>)> {
+        self.iter()
+        .filter(|(_, paths)| paths.len() > 1)
+    }
+
+    /// Return the groups of paths that may be duplicates, sorted by their
+    /// potential savings, largest first.
+    ///
+    /// The potential savings of a group is the bytes freed by keeping one
+    /// path and removing the rest, which is the file length times one less
+    /// than the number of paths. Ties sort by file length, largest first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// use std::path::PathBuf;
+    ///
+    /// let mut a: BTreeMapOfFileLenToSetOfPathBuf = BTreeMapOfFileLenToSetOfPathBuf::new();
+    /// a.entry(5).or_default().extend(vec![PathBuf::from("a"), PathBuf::from("b"), PathBuf::from("c")]);
+    /// a.entry(7).or_default().extend(vec![PathBuf::from("d"), PathBuf::from("e")]);
+    /// let lens: Vec<&u64> = a.duplicate_candidates_by_savings().into_iter().map(|(len, _)| len).collect();
+    /// assert_eq!(lens, vec![&5, &7]);
+    /// ```
+    #[inline]
+    fn duplicate_candidates_by_savings(&self) -> Vec<(&u64, &BTreeSet<PathBuf>)> {
+        let mut groups: Vec<(&u64, &BTreeSet<PathBuf>)> = self.duplicate_candidates().collect();
+        groups.sort_by(|a, b| {
+            path_scan::savings(*b.0, b.1.len()).cmp(&path_scan::savings(*a.0, a.1.len()))
+            .then(b.0.cmp(a.0))
+        });
+        groups
+    }
+
+    /// Return an iterator over the paths whose file length is in a range.
+    ///
+    /// The iterator yields paths in order of file length, then in path order.
+    /// An inverted range, such as `9..6`, yields nothing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// use std::path::PathBuf;
+    ///
+    /// let mut a: BTreeMapOfFileLenToSetOfPathBuf = BTreeMapOfFileLenToSetOfPathBuf::new();
+    /// a.entry(5).or_default().insert(PathBuf::from("alpha.txt"));
+    /// a.entry(7).or_default().insert(PathBuf::from("charlie.txt"));
+    /// a.entry(9).or_default().insert(PathBuf::from("echo.txt"));
+    /// let paths: Vec<&PathBuf> = a.sub_range_len(6..=9).collect();
+    /// assert_eq!(paths, vec![&PathBuf::from("charlie.txt"), &PathBuf::from("echo.txt")]);
+    /// ```
+    #[inline]
+    fn sub_range_len<R: RangeBounds<u64>>(&self, range: R) -> impl Iterator<Item = &PathBuf> {
+        (!range_bounds::is_inverted(&range)).then(|| self.range(range))
+        .into_iter()
+        .flatten()
+        .flat_map(|(_, paths)| paths.iter())
+    }
+
+    /// Return the total bytes of the files whose file length is in a range.
+    ///
+    /// Each path counts once, so the total is the sum of each file length
+    /// times its number of paths. The total saturates at `u64::MAX`.
+    /// An inverted range, such as `9..6`, totals 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// use std::path::PathBuf;
+    ///
+    /// let mut a: BTreeMapOfFileLenToSetOfPathBuf = BTreeMapOfFileLenToSetOfPathBuf::new();
+    /// a.entry(5).or_default().extend(vec![PathBuf::from("alpha.txt"), PathBuf::from("bravo.txt")]);
+    /// a.entry(7).or_default().insert(PathBuf::from("charlie.txt"));
+    /// assert_eq!(a.sub_len_between(..), 17);
+    /// assert_eq!(a.sub_len_between(6..), 7);
+    /// ```
+    #[inline]
+    fn sub_len_between<R: RangeBounds<u64>>(&self, range: R) -> u64 {
+        if range_bounds::is_inverted(&range) {
+            return 0;
         }
+        self.range(range)
+        .fold(0u64, |total, (len, paths)| total.saturating_add(len.saturating_mul(paths.len() as u64)))
+    }
+
+}
+
+impl BTreeMapOfFileLenToSetOfPathBufTryExt for BTreeMapOfFileLenToSetOfPathBuf {
+
+    /// Return `true` if the collection contains a sub-key-value item.
+    ///
+    /// Return an error if the file metadata can't be read, for example
+    /// because the file is missing or unreadable.
+    ///
+    /// The path may be any type that converts to a `&Path`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// use std::path::PathBuf;
+    ///
+    /// let mut a: BTreeMapOfFileLenToSetOfPathBuf = BTreeMapOfFileLenToSetOfPathBuf::new();
+    /// # std::fs::create_dir_all("btree_try_sub_contains_path");
+    /// # std::fs::write("btree_try_sub_contains_path/alpha.txt", "alpha");
+    /// let alpha = PathBuf::from("btree_try_sub_contains_path/alpha.txt");
+    /// a.try_sub_insert_path(alpha.clone())?;
+    /// assert_eq!(a.try_sub_contains_path(&alpha)?, true);
+    /// assert_eq!(a.try_sub_contains_path("btree_try_sub_contains_path/alpha.txt")?, true);
+    /// assert_eq!(a.try_sub_contains_path("missing.txt").is_err(), true);
+    /// # std::fs::remove_dir_all("btree_try_sub_contains_path");
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[inline]
+    fn try_sub_contains_path<P: AsRef<Path>>(&self, value: P) -> io::Result<bool> {
+        let value = value.as_ref();
+        let key = fs::metadata(value)?.len();
+        Ok(match self.get(&key) {
+            Some(set) => set.contains(value),
+            None => false,
+        })
+    }
+
+    /// Add a sub-key-value item to the collection.
+    ///
+    /// Return whether the item is added in the set, or an error if the
+    /// file metadata can't be read. On error the collection is unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// use std::path::PathBuf;
+    ///
+    /// let mut a: BTreeMapOfFileLenToSetOfPathBuf = BTreeMapOfFileLenToSetOfPathBuf::new();
+    /// # std::fs::create_dir_all("btree_try_sub_insert_path");
+    /// # std::fs::write("btree_try_sub_insert_path/alpha.txt", "alpha");
+    /// assert_eq!(a.try_sub_insert_path(PathBuf::from("btree_try_sub_insert_path/alpha.txt"))?, true);
+    /// assert_eq!(a.try_sub_insert_path(PathBuf::from("missing.txt")).is_err(), true);
+    /// # std::fs::remove_dir_all("btree_try_sub_insert_path");
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[inline]
+    fn try_sub_insert_path(&mut self, value: PathBuf) -> io::Result<bool> {
+        let key = fs::metadata(&value)?.len();
+        Ok(self.entry(key)
+        .or_default()
+        .insert(value))
+    }
+
+    /// Remove a sub-key-value pair from the collection.
+    ///
+    /// Return whether the value was present in the set, or an error if
+    /// the file metadata can't be read.
+    ///
+    /// The path is borrowed, and may be any type that converts to a `&Path`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// use std::path::PathBuf;
+    ///
+    /// let mut a: BTreeMapOfFileLenToSetOfPathBuf = BTreeMapOfFileLenToSetOfPathBuf::new();
+    /// # std::fs::create_dir_all("btree_try_sub_remove_path");
+    /// # std::fs::write("btree_try_sub_remove_path/alpha.txt", "alpha");
+    /// let alpha = PathBuf::from("btree_try_sub_remove_path/alpha.txt");
+    /// a.try_sub_insert_path(alpha.clone())?;
+    /// assert_eq!(a.try_sub_remove_path(&alpha)?, true);
+    /// assert_eq!(a.try_sub_contains_path(&alpha)?, false);
+    /// # std::fs::remove_dir_all("btree_try_sub_remove_path");
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[inline]
+    fn try_sub_remove_path<P: AsRef<Path>>(&mut self, value: P) -> io::Result<bool> {
+        let value = value.as_ref();
+        let key = fs::metadata(value)?.len();
+        Ok(match self.get_mut(&key) {
+            Some(set) => set.remove(value),
+            None => false,
+        })
+    }
+
+    /// Return `true` if the collection contains a sub-key-value item,
+    /// reading the file metadata the way the options' symbolic link
+    /// policy does.
+    ///
+    /// With `SymlinkPolicy::Record` this reads a link's own metadata, so
+    /// it finds a link that `try_sub_insert_path_with` or `sub_insert_tree` recorded. With another
+    /// policy this is like `try_sub_contains_path`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// use std::path::PathBuf;
+    ///
+    /// let mut a: BTreeMapOfFileLenToSetOfPathBuf = BTreeMapOfFileLenToSetOfPathBuf::new();
+    /// # std::fs::write("btree_file_len_contains_with.txt", "alpha");
+    /// let options = WalkOptions::new().symlinks(SymlinkPolicy::Record);
+    /// a.try_sub_insert_path_with(PathBuf::from("btree_file_len_contains_with.txt"), &options)?;
+    /// assert_eq!(a.try_sub_contains_path_with("btree_file_len_contains_with.txt", &options)?, true);
+    /// # std::fs::remove_file("btree_file_len_contains_with.txt");
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[inline]
+    fn try_sub_contains_path_with<P: AsRef<Path>>(&self, value: P, options: &WalkOptions) -> io::Result<bool> {
+        let value = value.as_ref();
+        let key = path_scan::lookup_metadata(value, options)?.len();
+        Ok(match self.get(&key) {
+            Some(set) => set.contains(value),
+            None => false,
+        })
+    }
+
+    /// Remove a sub-key-value pair from the collection, reading the file
+    /// metadata the way the options' symbolic link policy does, like
+    /// `try_sub_contains_path_with`.
+    ///
+    /// Return whether the value was present in the set, or an error if
+    /// the file metadata can't be read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// use std::path::PathBuf;
+    ///
+    /// let mut a: BTreeMapOfFileLenToSetOfPathBuf = BTreeMapOfFileLenToSetOfPathBuf::new();
+    /// # std::fs::write("btree_file_len_remove_with.txt", "alpha");
+    /// let options = WalkOptions::new().symlinks(SymlinkPolicy::Record);
+    /// a.try_sub_insert_path_with(PathBuf::from("btree_file_len_remove_with.txt"), &options)?;
+    /// assert_eq!(a.try_sub_remove_path_with("btree_file_len_remove_with.txt", &options)?, true);
+    /// assert_eq!(a.try_sub_contains_path_with("btree_file_len_remove_with.txt", &options)?, false);
+    /// # std::fs::remove_file("btree_file_len_remove_with.txt");
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[inline]
+    fn try_sub_remove_path_with<P: AsRef<Path>>(&mut self, value: P, options: &WalkOptions) -> io::Result<bool> {
+        let value = value.as_ref();
+        let key = path_scan::lookup_metadata(value, options)?.len();
+        Ok(match self.get_mut(&key) {
+            Some(set) => set.remove(value),
+            None => false,
+        })
+    }
+
+    /// Add a sub-key-value item to the collection if the options allow it.
+    ///
+    /// The options' symbolic link policy picks the metadata: `Follow`
+    /// keys a link by its target's length, `Skip` skips a link, and
+    /// `Record` keys a link by its own length. The predicates, the
+    /// hidden setting, the filters, and the min and max lengths apply
+    /// to the path, and the max depth doesn't apply.
+    ///
+    /// Return whether the item is added in the set, which is `false` if
+    /// the options skip it, or an error if the file metadata can't be read.
+    /// To find or remove a recorded link, use `try_sub_contains_path_with`
+    /// and `try_sub_remove_path_with` with the same options.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// use std::path::PathBuf;
+    ///
+    /// let mut a: BTreeMapOfFileLenToSetOfPathBuf = BTreeMapOfFileLenToSetOfPathBuf::new();
+    /// # std::fs::write("btree_try_sub_insert_path_with.txt", "alpha");
+    /// let options = WalkOptions::new().symlinks(SymlinkPolicy::Record);
+    /// assert_eq!(a.try_sub_insert_path_with(PathBuf::from("btree_try_sub_insert_path_with.txt"), &options)?, true);
+    /// let options = WalkOptions::new().exclude(|path| path.ends_with("btree_try_sub_insert_path_with.txt"));
+    /// a.clear();
+    /// assert_eq!(a.try_sub_insert_path_with(PathBuf::from("btree_try_sub_insert_path_with.txt"), &options)?, false);
+    /// # std::fs::remove_file("btree_try_sub_insert_path_with.txt");
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[inline]
+    fn try_sub_insert_path_with(&mut self, value: PathBuf, options: &WalkOptions) -> io::Result<bool> {
+        Ok(match path_scan::admit_path(&value, options)? {
+            Some(metadata) => {
+                self.entry(metadata.len())
+                .or_default()
+                .insert(value)
+            },
+            None => false,
+        })
+    }
+
+    /// Add every regular file in a directory, not recursively.
+    ///
+    /// Return an error if the directory can't be read. Otherwise return
+    /// a report of each entry that can't be read, with its error; the
+    /// scan skips those entries and adds the rest.
+    ///
+    /// The scan skips symbolic links, like `sub_insert_tree` with the
+    /// default `WalkOptions`. For another policy, use `sub_insert_tree`
+    /// with a max depth of 1.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    ///
+    /// let mut a: BTreeMapOfFileLenToSetOfPathBuf = BTreeMapOfFileLenToSetOfPathBuf::new();
+    /// # std::fs::create_dir_all("btree_try_sub_insert_dir");
+    /// # std::fs::write("btree_try_sub_insert_dir/alpha.txt", "alpha");
+    /// let errors = a.try_sub_insert_dir("btree_try_sub_insert_dir")?;
+    /// assert_eq!(errors.is_empty(), true);
+    /// assert_eq!(a.try_sub_contains_path("btree_try_sub_insert_dir/alpha.txt")?, true);
+    /// # std::fs::remove_dir_all("btree_try_sub_insert_dir");
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[inline]
+    fn try_sub_insert_dir<P: AsRef<Path>>(&mut self, dir: P) -> io::Result<PathErrors> {
+        path_scan::scan_dir(dir.as_ref(), SymlinkPolicy::Skip, |path, metadata| {
+            self.entry(metadata.len())
+            .or_default()
+            .insert(path);
+        })
+    }
+
+    /// Create a collection of every regular file in a directory, not recursively.
+    ///
+    /// Return an error if the directory can't be read. Otherwise return
+    /// the collection and a report of each entry that can't be read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    ///
+    /// # std::fs::create_dir_all("btree_from_dir");
+    /// # std::fs::write("btree_from_dir/alpha.txt", "alpha");
+    /// let (a, errors) = BTreeMapOfFileLenToSetOfPathBuf::from_dir("btree_from_dir")?;
+    /// assert_eq!(errors.is_empty(), true);
+    /// assert_eq!(a.try_sub_contains_path("btree_from_dir/alpha.txt")?, true);
+    /// # std::fs::remove_dir_all("btree_from_dir");
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[inline]
+    fn from_dir<P: AsRef<Path>>(dir: P) -> io::Result<(Self, PathErrors)> {
+        let mut map = Self::new();
+        let errors = map.try_sub_insert_dir(dir)?;
+        Ok((map, errors))
     }
 
+    /// Add every regular file in a directory tree, walking it with options
+    /// for the max depth, symbolic links, and path predicates.
+    ///
+    /// Return an error if the root can't be read. Otherwise return a
+    /// report of each path below the root that can't be read, with its
+    /// error; the walk skips those paths and adds the rest.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    ///
+    /// let mut a: BTreeMapOfFileLenToSetOfPathBuf = BTreeMapOfFileLenToSetOfPathBuf::new();
+    /// # std::fs::create_dir_all("btree_sub_insert_tree/one");
+    /// # std::fs::write("btree_sub_insert_tree/alpha.txt", "alpha");
+    /// # std::fs::write("btree_sub_insert_tree/one/bravo.txt", "bravo");
+    /// let options = WalkOptions::new().max_depth(8);
+    /// let errors = a.sub_insert_tree("btree_sub_insert_tree", &options)?;
+    /// assert_eq!(errors.is_empty(), true);
+    /// assert_eq!(a.try_sub_contains_path("btree_sub_insert_tree/one/bravo.txt")?, true);
+    /// # std::fs::remove_dir_all("btree_sub_insert_tree");
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[inline]
+    fn sub_insert_tree<P: AsRef<Path>>(&mut self, root: P, options: &WalkOptions) -> io::Result<PathErrors> {
+        path_scan::walk_tree(root.as_ref(), options, |path, metadata| {
+            self.entry(metadata.len())
+            .or_default()
+            .insert(path);
+        })
+    }
+
+    /// Add every regular file in a directory tree, like `sub_insert_tree`,
+    /// and read the directories and file metadata of each depth on multiple threads.
+    ///
+    /// This suits slow metadata reads, such as on a network filesystem.
+    /// The files go into the collection on the calling thread.
+    ///
+    /// This method needs the `rayon` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    ///
+    /// let mut a: BTreeMapOfFileLenToSetOfPathBuf = BTreeMapOfFileLenToSetOfPathBuf::new();
+    /// # std::fs::create_dir_all("btree_par_sub_insert_tree/one");
+    /// # std::fs::write("btree_par_sub_insert_tree/alpha.txt", "alpha");
+    /// # std::fs::write("btree_par_sub_insert_tree/one/bravo.txt", "bravo");
+    /// let errors = a.par_sub_insert_tree("btree_par_sub_insert_tree", &WalkOptions::new())?;
+    /// assert_eq!(errors.is_empty(), true);
+    /// assert_eq!(a.try_sub_contains_path("btree_par_sub_insert_tree/one/bravo.txt")?, true);
+    /// # std::fs::remove_dir_all("btree_par_sub_insert_tree");
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[cfg(feature = "rayon")]
+    #[inline]
+    fn par_sub_insert_tree<P: AsRef<Path>>(&mut self, root: P, options: &WalkOptions) -> io::Result<PathErrors> {
+        path_scan::par_walk_tree(root.as_ref(), options, |path, metadata| {
+            self.entry(metadata.len())
+            .or_default()
+            .insert(path);
+        })
+    }
+
+
+    /// Remove every path whose file no longer exists, and each length
+    /// whose set becomes empty. Return the removed paths, in path order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// use std::path::PathBuf;
+    ///
+    /// let mut a: BTreeMapOfFileLenToSetOfPathBuf = BTreeMapOfFileLenToSetOfPathBuf::new();
+    /// # std::fs::write("btree_file_len_prune_stale.txt", "alpha");
+    /// a.try_sub_insert_path(PathBuf::from("btree_file_len_prune_stale.txt"))?;
+    /// # std::fs::remove_file("btree_file_len_prune_stale.txt");
+    /// assert_eq!(a.prune_stale(), vec![PathBuf::from("btree_file_len_prune_stale.txt")]);
+    /// assert_eq!(a.is_empty(), true);
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[inline]
+    fn prune_stale(&mut self) -> Vec<PathBuf> {
+        path_scan::prune_stale(self)
+    }
 }
 
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
 mod tests {
     use super::*;
-    use sixarm_assert::*;
+    use crate::path_scan::SymlinkPolicy;
     use std::path::PathBuf;
 
     #[test]
+    #[allow(deprecated)]
     /// Test `sub_contains_path`.
     /// Must succeed.
     /// 
@@ -126,6 +598,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(deprecated)]
     /// Test `sub_insert_path`.
     /// Must succeed.
     /// 
@@ -139,6 +612,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(deprecated)]
     /// Test `sub_remove_path`.
     /// Must succeed.
     /// 
@@ -152,4 +626,259 @@ mod tests {
         assert!(subject.get(&len).unwrap().is_empty());
     }
 
+    #[test]
+    /// Test `try_sub_insert_path`, `try_sub_contains_path`, and `try_sub_remove_path`.
+    /// Must succeed.
+    /// 
+    fn test_try_sub_x_path() {
+        let mut subject: BTreeMapOfFileLenToSetOfPathBuf = BTreeMapOfFileLenToSetOfPathBuf::new();
+        let a: PathBuf = [env!("CARGO_MANIFEST_DIR"), "test", "hash_map_of_file_len_to_set_of_path_buf", "alpha.txt"].iter().collect::<PathBuf>();
+        let len = 5;
+        assert_eq!(subject.try_sub_insert_path(a.clone()).unwrap(), true);
+        assert_eq!(subject.try_sub_insert_path(a.clone()).unwrap(), false);
+        assert_eq!(subject.try_sub_contains_path(&a).unwrap(), true);
+        assert!(subject.get(&len).unwrap().contains(&a));
+        assert_eq!(subject.try_sub_remove_path(&a).unwrap(), true);
+        assert_eq!(subject.try_sub_contains_path(&a).unwrap(), false);
+    }
+
+    #[test]
+    /// Test the `try_` methods with a missing file.
+    /// Must return an error and leave the collection unchanged.
+    /// 
+    fn test_try_sub_x_path_x_missing() {
+        let mut subject: BTreeMapOfFileLenToSetOfPathBuf = BTreeMapOfFileLenToSetOfPathBuf::new();
+        let missing: PathBuf = [env!("CARGO_MANIFEST_DIR"), "test", "hash_map_of_file_len_to_set_of_path_buf", "missing.txt"].iter().collect::<PathBuf>();
+        let err = subject.try_sub_insert_path(missing.clone()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+        assert_eq!(subject.is_empty(), true);
+        assert_eq!(subject.try_sub_contains_path(&missing).is_err(), true);
+        assert_eq!(subject.try_sub_remove_path(missing).is_err(), true);
+    }
+
+    #[test]
+    /// Test the path methods with borrowed forms of the path.
+    /// Must succeed.
+    /// 
+    fn test_try_sub_x_path_x_borrowed_forms() {
+        let mut subject: BTreeMapOfFileLenToSetOfPathBuf = BTreeMapOfFileLenToSetOfPathBuf::new();
+        let a: PathBuf = [env!("CARGO_MANIFEST_DIR"), "test", "hash_map_of_file_len_to_set_of_path_buf", "alpha.txt"].iter().collect::<PathBuf>();
+        let s: &str = a.to_str().unwrap();
+        subject.try_sub_insert_path(a.clone()).unwrap();
+        assert_eq!(subject.try_sub_contains_path(a.as_path()).unwrap(), true);
+        assert_eq!(subject.try_sub_contains_path(s).unwrap(), true);
+        assert_eq!(subject.try_sub_remove_path(s).unwrap(), true);
+        assert_eq!(subject.try_sub_contains_path(&a).unwrap(), false);
+    }
+
+    #[test]
+    /// Test `from_dir` adds the files of a directory, and not of its subdirectories.
+    /// Must succeed.
+    /// 
+    fn test_from_dir() {
+        let dir = std::env::temp_dir().join(format!("btree_map_of_file_len_to_set_of_path_buf_from_dir_{}", std::process::id()));
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        fs::write(dir.join("alpha.txt"), "alpha").unwrap();
+        fs::write(dir.join("charlie.txt"), "charlie").unwrap();
+        fs::write(dir.join("sub").join("delta.txt"), "delta").unwrap();
+        let (subject, errors) = BTreeMapOfFileLenToSetOfPathBuf::from_dir(&dir).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(errors.is_empty(), true);
+        assert_eq!(subject.len(), 2);
+        assert_eq!(subject.get(&5).unwrap().len(), 1);
+        assert!(subject.get(&5).unwrap().contains(&dir.join("alpha.txt")));
+        assert!(subject.get(&7).unwrap().contains(&dir.join("charlie.txt")));
+    }
+
+    #[test]
+    /// Test `try_sub_insert_dir` with a missing directory.
+    /// Must return an error and leave the collection unchanged.
+    /// 
+    fn test_try_sub_insert_dir_x_missing() {
+        let mut subject: BTreeMapOfFileLenToSetOfPathBuf = BTreeMapOfFileLenToSetOfPathBuf::new();
+        let missing: PathBuf = [env!("CARGO_MANIFEST_DIR"), "test", "missing"].iter().collect::<PathBuf>();
+        let err = subject.try_sub_insert_dir(missing).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+        assert_eq!(subject.is_empty(), true);
+    }
+
+    #[test]
+    /// Test `sub_insert_tree` adds files in subdirectories, and honors the options.
+    /// Must succeed.
+    /// 
+    fn test_sub_insert_tree() {
+        let dir = std::env::temp_dir().join(format!("btree_map_of_file_len_to_set_of_path_buf_sub_insert_tree_{}", std::process::id()));
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        fs::write(dir.join("alpha.txt"), "alpha").unwrap();
+        fs::write(dir.join("sub").join("charlie.txt"), "charlie").unwrap();
+        let mut all: BTreeMapOfFileLenToSetOfPathBuf = BTreeMapOfFileLenToSetOfPathBuf::new();
+        let all_errors = all.sub_insert_tree(&dir, &WalkOptions::new()).unwrap();
+        let mut top: BTreeMapOfFileLenToSetOfPathBuf = BTreeMapOfFileLenToSetOfPathBuf::new();
+        let top_errors = top.sub_insert_tree(&dir, &WalkOptions::new().max_depth(1)).unwrap();
+        let mut excluded: BTreeMapOfFileLenToSetOfPathBuf = BTreeMapOfFileLenToSetOfPathBuf::new();
+        excluded.sub_insert_tree(&dir, &WalkOptions::new().exclude(|path| path.ends_with("alpha.txt"))).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(all_errors.is_empty() && top_errors.is_empty(), true);
+        assert!(all.get(&7).unwrap().contains(&dir.join("sub").join("charlie.txt")));
+        assert_eq!(all.len(), 2);
+        assert_eq!(top.len(), 1);
+        assert!(top.get(&5).unwrap().contains(&dir.join("alpha.txt")));
+        assert_eq!(excluded.len(), 1);
+        assert_eq!(excluded.contains_key(&5), false);
+    }
+
+    #[test]
+    /// Test `duplicate_candidates` and `duplicate_candidates_by_savings`.
+    /// Must succeed.
+    /// 
+    fn test_duplicate_candidates() {
+        let mut subject: BTreeMapOfFileLenToSetOfPathBuf = BTreeMapOfFileLenToSetOfPathBuf::new();
+        subject.entry(10).or_default().extend(vec![PathBuf::from("a"), PathBuf::from("b")]);
+        subject.entry(4).or_default().extend(vec![PathBuf::from("c"), PathBuf::from("d"), PathBuf::from("e"), PathBuf::from("f")]);
+        subject.entry(12).or_default().extend(vec![PathBuf::from("g"), PathBuf::from("h")]);
+        subject.entry(99).or_default().insert(PathBuf::from("i"));
+        subject.entry(0).or_default();
+        let mut lens: Vec<u64> = subject.duplicate_candidates().map(|(len, _)| *len).collect();
+        lens.sort();
+        assert_eq!(lens, vec![4, 10, 12]);
+        let lens: Vec<u64> = subject.duplicate_candidates_by_savings().into_iter().map(|(len, _)| *len).collect();
+        assert_eq!(lens, vec![12, 4, 10]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    /// Test `from_dir` with symbolic links in the directory.
+    /// Must skip the links, like `sub_insert_tree` with the default options.
+    ///
+    fn test_from_dir_x_symlinks() {
+        let dir = std::env::temp_dir().join(format!("btree_map_of_file_len_to_set_of_path_buf_from_dir_symlinks_{}", std::process::id()));
+        let outside = std::env::temp_dir().join(format!("btree_map_of_file_len_to_set_of_path_buf_from_dir_symlinks_outside_{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("sub")).unwrap();
+        std::fs::create_dir_all(&outside).unwrap();
+        std::fs::write(dir.join("alpha.txt"), "alpha").unwrap();
+        std::fs::write(outside.join("charlie.txt"), "charlie").unwrap();
+        std::os::unix::fs::symlink("alpha.txt", dir.join("link")).unwrap();
+        std::os::unix::fs::symlink(outside.join("charlie.txt"), dir.join("outside")).unwrap();
+        let (subject, errors) = BTreeMapOfFileLenToSetOfPathBuf::from_dir(&dir).unwrap();
+        let mut tree = BTreeMapOfFileLenToSetOfPathBuf::new();
+        tree.sub_insert_tree(&dir, &WalkOptions::new().max_depth(1)).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        std::fs::remove_dir_all(&outside).unwrap();
+        assert_eq!(errors.is_empty(), true);
+        assert_eq!(subject.len(), 1);
+        assert_eq!(subject.get(&5).unwrap().len(), 1);
+        assert!(subject.get(&5).unwrap().contains(&dir.join("alpha.txt")));
+        assert_eq!(subject, tree);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    /// Test `try_sub_insert_path_with` with each symbolic link policy.
+    /// Must key a link by its target's length, skip it, or key it by its own length.
+    ///
+    fn test_try_sub_insert_path_with_x_symlinks() {
+        let dir = std::env::temp_dir().join(format!("btree_map_of_file_len_to_set_of_path_buf_symlinks_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("alpha.txt"), "alpha").unwrap();
+        std::os::unix::fs::symlink("alpha.txt", dir.join("link")).unwrap();
+        let link = dir.join("link");
+        let mut follow: BTreeMapOfFileLenToSetOfPathBuf = BTreeMapOfFileLenToSetOfPathBuf::new();
+        let mut skip: BTreeMapOfFileLenToSetOfPathBuf = BTreeMapOfFileLenToSetOfPathBuf::new();
+        let mut record: BTreeMapOfFileLenToSetOfPathBuf = BTreeMapOfFileLenToSetOfPathBuf::new();
+        assert_eq!(follow.try_sub_insert_path_with(link.clone(), &WalkOptions::new().symlinks(SymlinkPolicy::Follow)).unwrap(), true);
+        assert_eq!(skip.try_sub_insert_path_with(link.clone(), &WalkOptions::new().symlinks(SymlinkPolicy::Skip)).unwrap(), false);
+        assert_eq!(record.try_sub_insert_path_with(link.clone(), &WalkOptions::new().symlinks(SymlinkPolicy::Record)).unwrap(), true);
+        assert_eq!(skip.try_sub_insert_path_with(dir.join("alpha.txt"), &WalkOptions::new()).unwrap(), true);
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(follow.get(&5).map(|set| set.contains(&link)), Some(true));
+        assert_eq!(skip.len(), 1);
+        // The link's own length is the length of its target path, "alpha.txt".
+        assert_eq!(record.get(&9).map(|set| set.contains(&link)), Some(true));
+    }
+
+    #[test]
+    /// Test `sub_range_len` and `sub_len_between`.
+    /// Must select the paths by file length bounds.
+    ///
+    fn test_sub_range_len() {
+        let mut subject: BTreeMapOfFileLenToSetOfPathBuf = BTreeMapOfFileLenToSetOfPathBuf::new();
+        subject.entry(0).or_default().insert(PathBuf::from("empty.txt"));
+        subject.entry(5).or_default().extend(vec![PathBuf::from("bravo.txt"), PathBuf::from("alpha.txt")]);
+        subject.entry(4096).or_default().insert(PathBuf::from("page.bin"));
+        subject.entry(u64::MAX).or_default().extend(vec![PathBuf::from("x"), PathBuf::from("y")]);
+        let small: Vec<&PathBuf> = subject.sub_range_len(1..4096).collect();
+        assert_eq!(small, vec![&PathBuf::from("alpha.txt"), &PathBuf::from("bravo.txt")]);
+        assert_eq!(subject.sub_range_len(..).count(), 6);
+        assert_eq!(subject.sub_range_len(6..4096).count(), 0);
+        assert_eq!(subject.sub_len_between(..=5), 10);
+        assert_eq!(subject.sub_len_between(1..=4096), 4106);
+        assert_eq!(subject.sub_len_between(4097..=4098), 0);
+        assert_eq!(subject.sub_len_between(..), u64::MAX);
+    }
+
+    #[test]
+    /// Test `sub_range_len` and `sub_len_between` with inverted ranges.
+    /// Must select nothing instead of panicking.
+    ///
+    fn test_sub_range_len_x_inverted() {
+        use std::ops::Bound;
+        let mut subject: BTreeMapOfFileLenToSetOfPathBuf = BTreeMapOfFileLenToSetOfPathBuf::new();
+        subject.entry(5).or_default().insert(PathBuf::from("alpha.txt"));
+        let (high, low): (u64, u64) = (9, 1);
+        assert_eq!(subject.sub_range_len(high..low).count(), 0);
+        assert_eq!(subject.sub_range_len(high..=low).count(), 0);
+        assert_eq!(subject.sub_range_len((Bound::Excluded(5), Bound::Excluded(5))).count(), 0);
+        assert_eq!(subject.sub_len_between(high..low), 0);
+        assert_eq!(subject.sub_len_between((Bound::Excluded(5), Bound::Excluded(5))), 0);
+    }
+
+    #[test]
+    /// Test `prune_stale`.
+    /// Must remove the deleted files, and the keys whose sets become empty.
+    ///
+    fn test_prune_stale() {
+        let dir = std::env::temp_dir().join(format!("btree_map_of_file_len_to_set_of_path_buf_prune_stale_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("alpha.txt"), "alpha").unwrap();
+        std::fs::write(dir.join("bravo.txt"), "bravo").unwrap();
+        std::fs::write(dir.join("charlie.txt"), "charlie").unwrap();
+        let (mut subject, _) = BTreeMapOfFileLenToSetOfPathBuf::from_dir(&dir).unwrap();
+        std::fs::remove_file(dir.join("bravo.txt")).unwrap();
+        std::fs::remove_file(dir.join("charlie.txt")).unwrap();
+        let removed = subject.prune_stale();
+        let again = subject.prune_stale();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(removed, vec![dir.join("bravo.txt"), dir.join("charlie.txt")]);
+        assert_eq!(again, Vec::<PathBuf>::new());
+        assert_eq!(subject.len(), 1);
+        assert_eq!(subject.get(&5).map(|set| set.len()), Some(1));
+        assert_eq!(subject.get(&7), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    /// Test `try_sub_contains_path_with` and `try_sub_remove_path_with` with a recorded link.
+    /// Must find and remove a link that a walk keyed by its own metadata.
+    ///
+    fn test_try_sub_x_path_with_x_record() {
+        let dir = std::env::temp_dir().join(format!("btree_map_of_file_len_to_set_of_path_buf_record_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("alpha.txt"), "alpha").unwrap();
+        std::os::unix::fs::symlink("alpha.txt", dir.join("link")).unwrap();
+        let link = dir.join("link");
+        let options = WalkOptions::new().symlinks(SymlinkPolicy::Record);
+        let mut subject: BTreeMapOfFileLenToSetOfPathBuf = BTreeMapOfFileLenToSetOfPathBuf::new();
+        subject.sub_insert_tree(&dir, &options).unwrap();
+        let mut inserted: BTreeMapOfFileLenToSetOfPathBuf = BTreeMapOfFileLenToSetOfPathBuf::new();
+        assert_eq!(inserted.try_sub_insert_path_with(link.clone(), &options).unwrap(), true);
+        assert_eq!(inserted.try_sub_contains_path_with(&link, &options).unwrap(), true);
+        assert_eq!(subject.try_sub_contains_path(&link).unwrap(), false);
+        assert_eq!(subject.try_sub_contains_path_with(&link, &options).unwrap(), true);
+        assert_eq!(subject.try_sub_contains_path_with(dir.join("alpha.txt"), &options).unwrap(), true);
+        assert_eq!(subject.try_sub_remove_path_with(&link, &options).unwrap(), true);
+        assert_eq!(subject.try_sub_contains_path_with(&link, &options).unwrap(), false);
+        assert_eq!(subject.try_sub_remove_path_with(&link, &options).unwrap(), false);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
 }
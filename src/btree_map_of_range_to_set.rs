@@ -0,0 +1,371 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::borrow::Borrow;
+use std::cmp::Ord;
+use std::error::Error;
+use std::fmt;
+use std::ops::{Bound, Range};
+
+/// A map of non-overlapping half-open ranges to sets of values.
+///
+/// Each range `start..end` holds the points `start <= point < end`.
+/// Ranges may touch but may not overlap, so a point is in at most one
+/// range, and a point query is one ordered lookup.
+///
+/// # Examples
+///
+/// ```
+/// use sixarm_collections::*;
+/// let mut a: BTreeMapOfRangeToSet<u64, &str> = BTreeMapOfRangeToSet::new();
+/// a.sub_insert(0..1024, "small").unwrap();
+/// a.sub_insert(1024..1048576, "medium").unwrap();
+/// let (range, set) = a.sub_get_at(&2048).unwrap();
+/// assert_eq!(range, &(1024..1048576));
+/// assert_eq!(set.contains("medium"), true);
+/// ```
+#[derive(Debug, Clone)]
+pub struct BTreeMapOfRangeToSet<T, V> {
+    map: BTreeMap<T, (Range<T>, BTreeSet<V>)>,
+}
+
+/// The reason that a range can't be inserted in a `BTreeMapOfRangeToSet`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RangeInsertError<T> {
+    /// The range has no points, because its start is not less than its end.
+    Empty,
+    /// The range overlaps this existing range, and is not equal to it.
+    Overlap(Range<T>),
+}
+
+impl<T: fmt::Debug> fmt::Display for RangeInsertError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RangeInsertError::Empty => write!(f, "range is empty"),
+            RangeInsertError::Overlap(range) => write!(f, "range overlaps existing range {:?}", range),
+        }
+    }
+}
+
+impl<T: fmt::Debug> Error for RangeInsertError<T> {}
+
+impl<T, V> BTreeMapOfRangeToSet<T, V> {
+
+    /// Create an empty map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: BTreeMapOfRangeToSet<u64, u8> = BTreeMapOfRangeToSet::new();
+    /// assert_eq!(a.is_empty(), true);
+    /// ```
+    #[inline]
+    pub fn new() -> Self {
+        BTreeMapOfRangeToSet { map: BTreeMap::new() }
+    }
+
+    /// Return the number of ranges in the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: BTreeMapOfRangeToSet<u64, u8> = BTreeMapOfRangeToSet::new();
+    /// a.sub_insert(0..10, 1).unwrap();
+    /// a.sub_insert(0..10, 2).unwrap();
+    /// assert_eq!(a.len(), 1);
+    /// ```
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Return `true` if the map has no ranges.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: BTreeMapOfRangeToSet<u64, u8> = BTreeMapOfRangeToSet::new();
+    /// assert_eq!(a.is_empty(), true);
+    /// ```
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Return the total number of values in every set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: BTreeMapOfRangeToSet<u64, u8> = BTreeMapOfRangeToSet::new();
+    /// a.sub_insert(0..10, 1).unwrap();
+    /// a.sub_insert(0..10, 2).unwrap();
+    /// assert_eq!(a.sub_len(), 2);
+    /// ```
+    #[inline]
+    pub fn sub_len(&self) -> usize {
+        self.map.values().map(|(_, set)| set.len()).sum()
+    }
+
+    /// Return an iterator over every range and its set, in range order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: BTreeMapOfRangeToSet<u64, u8> = BTreeMapOfRangeToSet::new();
+    /// a.sub_insert(5..10, 1).unwrap();
+    /// a.sub_insert(0..5, 2).unwrap();
+    /// let ranges: Vec<&std::ops::Range<u64>> = a.iter().map(|(range, _)| range).collect();
+    /// assert_eq!(ranges, vec![&(0..5), &(5..10)]);
+    /// ```
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = (&Range<T>, &BTreeSet<V>)> {
+        self.map.values().map(|(range, set)| (range, set))
+    }
+
+}
+
+impl<T, V> BTreeMapOfRangeToSet<T, V>
+where
+    T: Ord,
+    V: Ord,
+{
+
+    /// Add a value to the set for a range.
+    ///
+    /// Return whether the value is newly added in the set. A range that
+    /// equals an existing range shares its set; a range that overlaps an
+    /// existing range without equaling it is an error, as is an empty range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: BTreeMapOfRangeToSet<u64, u8> = BTreeMapOfRangeToSet::new();
+    /// assert_eq!(a.sub_insert(0..10, 1), Ok(true));
+    /// assert_eq!(a.sub_insert(0..10, 1), Ok(false));
+    /// assert_eq!(a.sub_insert(5..15, 2), Err(RangeInsertError::Overlap(0..10)));
+    /// assert_eq!(a.sub_insert(20..20, 2), Err(RangeInsertError::Empty));
+    /// ```
+    #[inline]
+    pub fn sub_insert(&mut self, range: Range<T>, value: V) -> Result<bool, RangeInsertError<T>>
+    where
+        T: Clone,
+    {
+        if range.start >= range.end {
+            return Err(RangeInsertError::Empty);
+        }
+        if let Some((existing, _)) = self.sub_overlapping(&range).next() {
+            if *existing != range {
+                return Err(RangeInsertError::Overlap(existing.clone()));
+            }
+        }
+        let (_, set) = self.map
+        .entry(range.start.clone())
+        .or_insert_with(|| (range, BTreeSet::new()));
+        Ok(set.insert(value))
+    }
+
+    /// Return the range that holds a point, and its set.
+    ///
+    /// Return `None` if no range holds the point.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: BTreeMapOfRangeToSet<u64, u8> = BTreeMapOfRangeToSet::new();
+    /// a.sub_insert(0..10, 1).unwrap();
+    /// assert_eq!(a.sub_get_at(&9).map(|(range, _)| range), Some(&(0..10)));
+    /// assert_eq!(a.sub_get_at(&10), None);
+    /// ```
+    #[inline]
+    pub fn sub_get_at<Q>(&self, point: &Q) -> Option<(&Range<T>, &BTreeSet<V>)>
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        match self.map.range::<Q, _>((Bound::Unbounded, Bound::Included(point))).next_back() {
+            Some((_, (range, set))) if range.end.borrow() > point => Some((range, set)),
+            _ => None,
+        }
+    }
+
+    /// Return an iterator over every range that overlaps a query range, and its set.
+    ///
+    /// The iterator yields ranges in order. An empty query range overlaps nothing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: BTreeMapOfRangeToSet<u64, u8> = BTreeMapOfRangeToSet::new();
+    /// a.sub_insert(0..10, 1).unwrap();
+    /// a.sub_insert(10..20, 2).unwrap();
+    /// a.sub_insert(30..40, 3).unwrap();
+    /// let ranges: Vec<&std::ops::Range<u64>> = a.sub_overlapping(&(5..30)).map(|(range, _)| range).collect();
+    /// assert_eq!(ranges, vec![&(0..10), &(10..20)]);
+    /// ```
+    #[inline]
+    pub fn sub_overlapping<'a>(&'a self, query: &'a Range<T>) -> impl Iterator<Item = (&'a Range<T>, &'a BTreeSet<V>)> {
+        let is_empty = query.start >= query.end;
+        let end = if is_empty { &query.start } else { &query.end };
+        let before = self.map
+        .range((Bound::Unbounded, Bound::Excluded(&query.start)))
+        .next_back()
+        .filter(move |(_, (range, _))| !is_empty && range.end > query.start);
+        let within = self.map
+        .range((Bound::Included(&query.start), Bound::Excluded(end)));
+        before.into_iter()
+        .chain(within)
+        .map(|(_, (range, set))| (range, set))
+    }
+
+    /// Remove a value from the set for a range.
+    ///
+    /// Return whether the value was present. The range stays in the
+    /// map even when its set becomes empty; use `remove_range` to
+    /// remove the range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: BTreeMapOfRangeToSet<u64, u8> = BTreeMapOfRangeToSet::new();
+    /// a.sub_insert(0..10, 1).unwrap();
+    /// assert_eq!(a.sub_remove(&(0..10), &1), true);
+    /// assert_eq!(a.sub_remove(&(0..10), &1), false);
+    /// ```
+    #[inline]
+    pub fn sub_remove<R>(&mut self, range: &Range<T>, value: &R) -> bool
+    where
+        V: Borrow<R>,
+        R: Ord + ?Sized,
+    {
+        match self.map.get_mut(&range.start) {
+            Some((existing, set)) if existing == range => set.remove(value),
+            _ => false,
+        }
+    }
+
+    /// Remove a range and return its set.
+    ///
+    /// Return `None` if the map has no range equal to `range`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: BTreeMapOfRangeToSet<u64, u8> = BTreeMapOfRangeToSet::new();
+    /// a.sub_insert(0..10, 1).unwrap();
+    /// assert_eq!(a.remove_range(&(0..5)), None);
+    /// assert_eq!(a.remove_range(&(0..10)).map(|set| set.len()), Some(1));
+    /// assert_eq!(a.is_empty(), true);
+    /// ```
+    #[inline]
+    pub fn remove_range(&mut self, range: &Range<T>) -> Option<BTreeSet<V>> {
+        match self.map.get(&range.start) {
+            Some((existing, _)) if existing == range => {
+                self.map.remove(&range.start).map(|(_, set)| set)
+            },
+            _ => None,
+        }
+    }
+
+}
+
+impl<T, V> Default for BTreeMapOfRangeToSet<T, V> {
+    fn default() -> Self {
+        BTreeMapOfRangeToSet::new()
+    }
+}
+
+impl<T, V> PartialEq for BTreeMapOfRangeToSet<T, V>
+where
+    T: PartialEq,
+    V: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.map == other.map
+    }
+}
+
+impl<T, V> Eq for BTreeMapOfRangeToSet<T, V>
+where
+    T: Eq,
+    V: Eq,
+{}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+
+    #[test]
+    /// Test `sub_insert` with equal, touching, overlapping, and empty ranges.
+    fn test_sub_insert() {
+        let mut subject: BTreeMapOfRangeToSet<u64, u8> = BTreeMapOfRangeToSet::new();
+        assert_eq!(subject.sub_insert(10..20, 1), Ok(true));
+        assert_eq!(subject.sub_insert(10..20, 2), Ok(true));
+        assert_eq!(subject.sub_insert(10..20, 2), Ok(false));
+        assert_eq!(subject.sub_insert(0..10, 3), Ok(true));
+        assert_eq!(subject.sub_insert(20..30, 4), Ok(true));
+        assert_eq!(subject.sub_insert(15..25, 5), Err(RangeInsertError::Overlap(10..20)));
+        assert_eq!(subject.sub_insert(5..25, 5), Err(RangeInsertError::Overlap(0..10)));
+        assert_eq!(subject.sub_insert(12..15, 5), Err(RangeInsertError::Overlap(10..20)));
+        assert_eq!(subject.sub_insert(10..30, 5), Err(RangeInsertError::Overlap(10..20)));
+        assert_eq!(subject.sub_insert(40..40, 5), Err(RangeInsertError::Empty));
+        assert_eq!(subject.len(), 3);
+        assert_eq!(subject.sub_len(), 4);
+    }
+
+    #[test]
+    /// Test `sub_get_at` at range starts, range ends, gaps, and outside every range.
+    fn test_sub_get_at() {
+        let mut subject: BTreeMapOfRangeToSet<u64, u8> = BTreeMapOfRangeToSet::new();
+        subject.sub_insert(10..20, 1).unwrap();
+        subject.sub_insert(30..40, 2).unwrap();
+        let actual: Vec<Option<&Range<u64>>> = [0, 10, 19, 20, 25, 30, 39, 40]
+        .iter()
+        .map(|point| subject.sub_get_at(point).map(|(range, _)| range))
+        .collect();
+        assert_eq!(actual, vec![None, Some(&(10..20)), Some(&(10..20)), None, None, Some(&(30..40)), Some(&(30..40)), None]);
+        assert_eq!(subject.sub_get_at(&15).unwrap().1.contains(&1), true);
+    }
+
+    #[test]
+    /// Test `sub_overlapping` with a range that starts inside, spans, touches, and is empty.
+    fn test_sub_overlapping() {
+        let mut subject: BTreeMapOfRangeToSet<u64, u8> = BTreeMapOfRangeToSet::new();
+        subject.sub_insert(0..10, 1).unwrap();
+        subject.sub_insert(10..20, 2).unwrap();
+        subject.sub_insert(30..40, 3).unwrap();
+        let ranges = |query: Range<u64>| -> Vec<Range<u64>> {
+            subject.sub_overlapping(&query).map(|(range, _)| range.clone()).collect()
+        };
+        assert_eq!(ranges(5..15), vec![0..10, 10..20]);
+        assert_eq!(ranges(0..100), vec![0..10, 10..20, 30..40]);
+        assert_eq!(ranges(20..30), vec![]);
+        assert_eq!(ranges(39..50), vec![30..40]);
+        assert_eq!(ranges(5..5), vec![]);
+        assert_eq!(ranges(Range { start: 15, end: 5 }), vec![]);
+    }
+
+    #[test]
+    /// Test `sub_remove` and `remove_range` need an equal range.
+    fn test_sub_remove_and_remove_range() {
+        let mut subject: BTreeMapOfRangeToSet<u64, u8> = BTreeMapOfRangeToSet::new();
+        subject.sub_insert(0..10, 1).unwrap();
+        subject.sub_insert(0..10, 2).unwrap();
+        assert_eq!(subject.sub_remove(&(0..5), &1), false);
+        assert_eq!(subject.sub_remove(&(0..10), &1), true);
+        assert_eq!(subject.sub_remove(&(0..10), &1), false);
+        assert_eq!(subject.remove_range(&(0..5)), None);
+        assert_eq!(subject.remove_range(&(0..10)), Some(vec![2].into_iter().collect()));
+        assert_eq!(subject.is_empty(), true);
+    }
+
+}
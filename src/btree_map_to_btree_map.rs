@@ -0,0 +1,502 @@
+use std::collections::BTreeMap;
+use std::borrow::Borrow;
+use std::cmp::Ord;
+use std::ops::RangeBounds;
+use crate::range_bounds;
+
+pub type BTreeMapToBTreeMap<K1, K2, V> = BTreeMap<K1, BTreeMap<K2, V>>;
+
+pub trait BTreeMapToBTreeMapExt<K1, K2, V> {
+
+    fn sub_insert(&mut self, key1: K1, key2: K2, value: V) -> Option<V>
+    where
+        K1: Ord,
+        K2: Ord;
+
+    fn sub_get<Q1, Q2>(&self, key1: &Q1, key2: &Q2) -> Option<&V>
+    where
+        K1: Borrow<Q1> + Ord,
+        K2: Borrow<Q2> + Ord,
+        Q1: Ord + ?Sized,
+        Q2: Ord + ?Sized;
+
+    fn sub_get_mut<Q1, Q2>(&mut self, key1: &Q1, key2: &Q2) -> Option<&mut V>
+    where
+        K1: Borrow<Q1> + Ord,
+        K2: Borrow<Q2> + Ord,
+        Q1: Ord + ?Sized,
+        Q2: Ord + ?Sized;
+
+    fn sub_contains<Q1, Q2>(&self, key1: &Q1, key2: &Q2) -> bool
+    where
+        K1: Borrow<Q1> + Ord,
+        K2: Borrow<Q2> + Ord,
+        Q1: Ord + ?Sized,
+        Q2: Ord + ?Sized;
+
+    fn sub_remove<Q1, Q2>(&mut self, key1: &Q1, key2: &Q2) -> Option<V>
+    where
+        K1: Borrow<Q1> + Ord,
+        K2: Borrow<Q2> + Ord,
+        Q1: Ord + ?Sized,
+        Q2: Ord + ?Sized;
+
+    fn sub_remove_prune<Q1, Q2>(&mut self, key1: &Q1, key2: &Q2) -> Option<V>
+    where
+        K1: Borrow<Q1> + Ord,
+        K2: Borrow<Q2> + Ord,
+        Q1: Ord + ?Sized,
+        Q2: Ord + ?Sized;
+
+    fn sub_len(&self) -> usize;
+
+    fn sub_len_of<Q1>(&self, key1: &Q1) -> usize
+    where
+        K1: Borrow<Q1> + Ord,
+        Q1: Ord + ?Sized;
+
+    fn sub_iter<'a>(&'a self) -> impl Iterator<Item = (&'a K1, &'a K2, &'a V)>
+    where
+        K1: 'a,
+        K2: 'a,
+        V: 'a;
+
+    fn into_sub_iter(self) -> impl Iterator<Item = (K1, K2, V)>
+    where
+        K1: Clone;
+
+    fn sub_range<'a, Q1, R1>(&'a self, range: R1) -> impl Iterator<Item = (&'a K1, &'a K2, &'a V)>
+    where
+        K1: Borrow<Q1> + Ord + 'a,
+        K2: 'a,
+        V: 'a,
+        Q1: Ord + ?Sized,
+        R1: RangeBounds<Q1>;
+
+    fn sub_range_within<'a, Q1, Q2, R2>(&'a self, key1: &Q1, range: R2) -> impl Iterator<Item = (&'a K2, &'a V)>
+    where
+        K1: Borrow<Q1> + Ord,
+        K2: Borrow<Q2> + Ord + 'a,
+        V: 'a,
+        Q1: Ord + ?Sized,
+        Q2: Ord + ?Sized,
+        R2: RangeBounds<Q2>;
+}
+
+impl<K1, K2, V> BTreeMapToBTreeMapExt<K1, K2, V> for BTreeMapToBTreeMap<K1, K2, V> {
+
+    /// Insert a value at an outer key and an inner key.
+    ///
+    /// Return the previous value at the two keys, if any.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: BTreeMapToBTreeMap<u8, u8, char> = BTreeMapToBTreeMap::new();
+    /// assert_eq!(a.sub_insert(1, 2, 'a'), None);
+    /// assert_eq!(a.sub_insert(1, 2, 'b'), Some('a'));
+    /// ```
+    #[inline]
+    fn sub_insert(&mut self, key1: K1, key2: K2, value: V) -> Option<V>
+    where
+        K1: Ord,
+        K2: Ord,
+    {
+        self.entry(key1)
+        .or_default()
+        .insert(key2, value)
+    }
+
+    /// Return a reference to the value at an outer key and an inner key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: BTreeMapToBTreeMap<u8, u8, char> = BTreeMapToBTreeMap::new();
+    /// a.sub_insert(1, 2, 'a');
+    /// assert_eq!(a.sub_get(&1, &2), Some(&'a'));
+    /// assert_eq!(a.sub_get(&1, &3), None);
+    /// ```
+    #[inline]
+    fn sub_get<Q1, Q2>(&self, key1: &Q1, key2: &Q2) -> Option<&V>
+    where
+        K1: Borrow<Q1> + Ord,
+        K2: Borrow<Q2> + Ord,
+        Q1: Ord + ?Sized,
+        Q2: Ord + ?Sized,
+    {
+        match self.get(key1) {
+            Some(map) => map.get(key2),
+            None => None,
+        }
+    }
+
+    /// Return a mutable reference to the value at an outer key and an inner key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: BTreeMapToBTreeMap<u8, u8, u8> = BTreeMapToBTreeMap::new();
+    /// a.sub_insert(1, 2, 3);
+    /// if let Some(value) = a.sub_get_mut(&1, &2) {
+    ///     *value += 1;
+    /// }
+    /// assert_eq!(a.sub_get(&1, &2), Some(&4));
+    /// ```
+    #[inline]
+    fn sub_get_mut<Q1, Q2>(&mut self, key1: &Q1, key2: &Q2) -> Option<&mut V>
+    where
+        K1: Borrow<Q1> + Ord,
+        K2: Borrow<Q2> + Ord,
+        Q1: Ord + ?Sized,
+        Q2: Ord + ?Sized,
+    {
+        match self.get_mut(key1) {
+            Some(map) => map.get_mut(key2),
+            None => None,
+        }
+    }
+
+    /// Return `true` if the collection has a value at an outer key and an inner key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: BTreeMapToBTreeMap<u8, u8, char> = BTreeMapToBTreeMap::new();
+    /// a.sub_insert(1, 2, 'a');
+    /// assert_eq!(a.sub_contains(&1, &2), true);
+    /// assert_eq!(a.sub_contains(&2, &1), false);
+    /// ```
+    #[inline]
+    fn sub_contains<Q1, Q2>(&self, key1: &Q1, key2: &Q2) -> bool
+    where
+        K1: Borrow<Q1> + Ord,
+        K2: Borrow<Q2> + Ord,
+        Q1: Ord + ?Sized,
+        Q2: Ord + ?Sized,
+    {
+        match self.get(key1) {
+            Some(map) => map.contains_key(key2),
+            None => false,
+        }
+    }
+
+    /// Remove the value at an outer key and an inner key, and return it.
+    ///
+    /// The outer key stays in the collection even when its inner map
+    /// becomes empty; use `sub_remove_prune` to also remove the outer key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: BTreeMapToBTreeMap<u8, u8, char> = BTreeMapToBTreeMap::new();
+    /// a.sub_insert(1, 2, 'a');
+    /// assert_eq!(a.sub_remove(&1, &2), Some('a'));
+    /// assert_eq!(a.contains_key(&1), true);
+    /// ```
+    #[inline]
+    fn sub_remove<Q1, Q2>(&mut self, key1: &Q1, key2: &Q2) -> Option<V>
+    where
+        K1: Borrow<Q1> + Ord,
+        K2: Borrow<Q2> + Ord,
+        Q1: Ord + ?Sized,
+        Q2: Ord + ?Sized,
+    {
+        match self.get_mut(key1) {
+            Some(map) => map.remove(key2),
+            None => None,
+        }
+    }
+
+    /// Remove the value at an outer key and an inner key, and prune the outer key if its inner map becomes empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: BTreeMapToBTreeMap<u8, u8, char> = BTreeMapToBTreeMap::new();
+    /// a.sub_insert(1, 2, 'a');
+    /// assert_eq!(a.sub_remove_prune(&1, &2), Some('a'));
+    /// assert_eq!(a.contains_key(&1), false);
+    /// ```
+    #[inline]
+    fn sub_remove_prune<Q1, Q2>(&mut self, key1: &Q1, key2: &Q2) -> Option<V>
+    where
+        K1: Borrow<Q1> + Ord,
+        K2: Borrow<Q2> + Ord,
+        Q1: Ord + ?Sized,
+        Q2: Ord + ?Sized,
+    {
+        match self.get_mut(key1) {
+            Some(map) => {
+                let removed = map.remove(key2);
+                if map.is_empty() {
+                    self.remove(key1);
+                }
+                removed
+            },
+            None => None,
+        }
+    }
+
+    /// Return the total number of values in every inner map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: BTreeMapToBTreeMap<u8, u8, char> = BTreeMapToBTreeMap::new();
+    /// a.sub_insert(1, 2, 'a');
+    /// a.sub_insert(1, 3, 'b');
+    /// a.sub_insert(4, 5, 'c');
+    /// assert_eq!(a.len(), 2);
+    /// assert_eq!(a.sub_len(), 3);
+    /// ```
+    #[inline]
+    fn sub_len(&self) -> usize {
+        self.values().map(|map| map.len()).sum()
+    }
+
+    /// Return the number of values in the inner map for an outer key.
+    ///
+    /// Return 0 if the outer key is absent.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: BTreeMapToBTreeMap<u8, u8, char> = BTreeMapToBTreeMap::new();
+    /// a.sub_insert(1, 2, 'a');
+    /// assert_eq!(a.sub_len_of(&1), 1);
+    /// assert_eq!(a.sub_len_of(&4), 0);
+    /// ```
+    #[inline]
+    fn sub_len_of<Q1>(&self, key1: &Q1) -> usize
+    where
+        K1: Borrow<Q1> + Ord,
+        Q1: Ord + ?Sized,
+    {
+        match self.get(key1) {
+            Some(map) => map.len(),
+            None => 0,
+        }
+    }
+
+    /// Return an iterator over every `(&K1, &K2, &V)` triple, in order of the outer key, then the inner key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: BTreeMapToBTreeMap<u8, u8, char> = BTreeMapToBTreeMap::new();
+    /// a.sub_insert(1, 2, 'a');
+    /// let triples: Vec<(&u8, &u8, &char)> = a.sub_iter().collect();
+    /// assert_eq!(triples, vec![(&1, &2, &'a')]);
+    /// ```
+    #[inline]
+    fn sub_iter<'a>(&'a self) -> impl Iterator<Item = (&'a K1, &'a K2, &'a V)>
+    where
+        K1: 'a,
+        K2: 'a,
+        V: 'a,
+    {
+        self.iter()
+        .flat_map(|(key1, map)| map.iter().map(move |(key2, value)| (key1, key2, value)))
+    }
+
+    /// Consume the collection and return an iterator over every `(K1, K2, V)` triple, in order of the outer key, then the inner key.
+    ///
+    /// Each outer key is cloned once per value in its inner map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: BTreeMapToBTreeMap<u8, u8, char> = BTreeMapToBTreeMap::new();
+    /// a.sub_insert(1, 2, 'a');
+    /// let triples: Vec<(u8, u8, char)> = a.into_sub_iter().collect();
+    /// assert_eq!(triples, vec![(1, 2, 'a')]);
+    /// ```
+    #[inline]
+    fn into_sub_iter(self) -> impl Iterator<Item = (K1, K2, V)>
+    where
+        K1: Clone,
+    {
+        self.into_iter()
+        .flat_map(|(key1, map)| map.into_iter().map(move |(key2, value)| (key1.clone(), key2, value)))
+    }
+
+    /// Return an iterator over every triple whose outer key is in a range.
+    ///
+    /// The iterator yields `(&K1, &K2, &V)` triples in order of the outer
+    /// key, then the inner key. An inverted range, such as `5..2`, yields
+    /// nothing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: BTreeMapToBTreeMap<u8, u8, char> = BTreeMapToBTreeMap::new();
+    /// a.sub_insert(1, 2, 'a');
+    /// a.sub_insert(3, 4, 'b');
+    /// a.sub_insert(6, 7, 'c');
+    /// let triples: Vec<(&u8, &u8, &char)> = a.sub_range(2..=5).collect();
+    /// assert_eq!(triples, vec![(&3, &4, &'b')]);
+    /// ```
+    #[inline]
+    fn sub_range<'a, Q1, R1>(&'a self, range: R1) -> impl Iterator<Item = (&'a K1, &'a K2, &'a V)>
+    where
+        K1: Borrow<Q1> + Ord + 'a,
+        K2: 'a,
+        V: 'a,
+        Q1: Ord + ?Sized,
+        R1: RangeBounds<Q1>,
+    {
+        (!range_bounds::is_inverted(&range)).then(|| self.range(range))
+        .into_iter()
+        .flatten()
+        .flat_map(|(key1, map)| map.iter().map(move |(key2, value)| (key1, key2, value)))
+    }
+
+    /// Return an iterator over the inner entries of one outer key whose inner key is in a range.
+    ///
+    /// The iterator yields `(&K2, &V)` pairs in inner key order.
+    /// If the outer key is absent or the range is inverted, then the
+    /// iterator is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: BTreeMapToBTreeMap<u8, u8, char> = BTreeMapToBTreeMap::new();
+    /// a.sub_insert(1, 2, 'a');
+    /// a.sub_insert(1, 5, 'b');
+    /// a.sub_insert(1, 9, 'c');
+    /// let pairs: Vec<(&u8, &char)> = a.sub_range_within(&1, 3..9).collect();
+    /// assert_eq!(pairs, vec![(&5, &'b')]);
+    /// ```
+    #[inline]
+    fn sub_range_within<'a, Q1, Q2, R2>(&'a self, key1: &Q1, range: R2) -> impl Iterator<Item = (&'a K2, &'a V)>
+    where
+        K1: Borrow<Q1> + Ord,
+        K2: Borrow<Q2> + Ord + 'a,
+        V: 'a,
+        Q1: Ord + ?Sized,
+        Q2: Ord + ?Sized,
+        R2: RangeBounds<Q2>,
+    {
+        self.get(key1)
+        .filter(|_| !range_bounds::is_inverted(&range))
+        .map(|map| map.range(range))
+        .into_iter()
+        .flatten()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+
+    #[test]
+    /// Test `sub_insert`, `sub_get`, and `sub_contains` with some items.
+    fn test_sub_insert_and_sub_get() {
+        let mut subject: BTreeMapToBTreeMap<u8, u8, char> = BTreeMapToBTreeMap::new();
+        let k1 = 1;
+        let k2 = 2;
+        let absent = 9;
+        assert_eq!(subject.sub_insert(k1, k2, 'a'), None);
+        assert_eq!(subject.sub_insert(k1, absent, 'b'), None);
+        assert_eq!(subject.sub_insert(k1, k2, 'c'), Some('a'));
+        assert_eq!(subject.sub_get(&k1, &k2), Some(&'c'));
+        assert_eq!(subject.sub_get(&k2, &k1), None);
+        assert_eq!(subject.sub_contains(&k1, &absent), true);
+        assert_eq!(subject.sub_contains(&absent, &k1), false);
+        assert_eq!(subject.sub_len(), 2);
+    }
+
+    #[test]
+    /// Test `sub_get_mut` with present and absent keys.
+    fn test_sub_get_mut() {
+        let mut subject: BTreeMapToBTreeMap<u8, u8, u8> = BTreeMapToBTreeMap::new();
+        subject.sub_insert(1, 2, 3);
+        *subject.sub_get_mut(&1, &2).unwrap() = 4;
+        assert_eq!(subject.sub_get(&1, &2), Some(&4));
+        assert_eq!(subject.sub_get_mut(&1, &9), None);
+        assert_eq!(subject.sub_get_mut(&9, &2), None);
+    }
+
+    #[test]
+    /// Test `sub_remove` keeps the outer key and `sub_remove_prune` removes it.
+    fn test_sub_remove_and_sub_remove_prune() {
+        let mut subject: BTreeMapToBTreeMap<u8, u8, char> = BTreeMapToBTreeMap::new();
+        subject.sub_insert(1, 2, 'a');
+        subject.sub_insert(3, 4, 'b');
+        assert_eq!(subject.sub_remove(&1, &2), Some('a'));
+        assert_eq!(subject.sub_remove(&1, &2), None);
+        assert_eq!(subject.contains_key(&1), true);
+        assert_eq!(subject.sub_remove_prune(&3, &4), Some('b'));
+        assert_eq!(subject.contains_key(&3), false);
+        assert_eq!(subject.sub_remove_prune(&9, &9), None);
+    }
+
+    #[test]
+    /// Test `sub_get` with borrowed forms of both keys.
+    fn test_sub_get_x_borrowed_forms() {
+        let mut subject: BTreeMapToBTreeMap<String, String, u8> = BTreeMapToBTreeMap::new();
+        subject.sub_insert(String::from("alpha"), String::from("bravo"), 1);
+        assert_eq!(subject.sub_get("alpha", "bravo"), Some(&1));
+        assert_eq!(subject.sub_remove_prune("alpha", "bravo"), Some(1));
+    }
+
+    #[test]
+    /// Test `sub_iter`, `into_sub_iter`, and `sub_len_of` with some items.
+    fn test_sub_iter_and_into_sub_iter() {
+        let mut subject: BTreeMapToBTreeMap<u8, u8, char> = BTreeMapToBTreeMap::new();
+        subject.sub_insert(4, 5, 'c');
+        subject.sub_insert(1, 3, 'b');
+        subject.sub_insert(1, 2, 'a');
+        assert_eq!(subject.sub_len_of(&1), 2);
+        let actual: Vec<(&u8, &u8, &char)> = subject.sub_iter().collect();
+        assert_eq!(actual, vec![(&1, &2, &'a'), (&1, &3, &'b'), (&4, &5, &'c')]);
+        let actual: Vec<(u8, u8, char)> = subject.into_sub_iter().collect();
+        assert_eq!(actual, vec![(1, 2, 'a'), (1, 3, 'b'), (4, 5, 'c')]);
+    }
+
+    #[test]
+    /// Test `sub_range` and `sub_range_within` with a day-and-timestamp layout.
+    fn test_sub_range_and_sub_range_within() {
+        let mut subject: BTreeMapToBTreeMap<u8, u16, char> = BTreeMapToBTreeMap::new();
+        subject.sub_insert(1, 900, 'a');
+        subject.sub_insert(2, 800, 'b');
+        subject.sub_insert(2, 1000, 'c');
+        subject.sub_insert(2, 1200, 'd');
+        subject.sub_insert(3, 700, 'e');
+        let actual: Vec<(&u8, &u16, &char)> = subject.sub_range(2..).collect();
+        assert_eq!(actual, vec![(&2, &800, &'b'), (&2, &1000, &'c'), (&2, &1200, &'d'), (&3, &700, &'e')]);
+        assert_eq!(subject.sub_range(4..).count(), 0);
+        let actual: Vec<(&u16, &char)> = subject.sub_range_within(&2, 900..=1200).collect();
+        assert_eq!(actual, vec![(&1000, &'c'), (&1200, &'d')]);
+        assert_eq!(subject.sub_range_within(&2, ..800).count(), 0);
+        assert_eq!(subject.sub_range_within(&9, ..).count(), 0);
+    }
+
+    #[test]
+    /// Test `sub_range` and `sub_range_within` yield nothing for inverted ranges.
+    fn test_sub_range_x_inverted() {
+        use std::ops::Bound;
+        let mut subject: BTreeMapToBTreeMap<u8, u16, char> = BTreeMapToBTreeMap::new();
+        subject.sub_insert(2, 800, 'a');
+        let (high, low): (u8, u8) = (3, 1);
+        assert_eq!(subject.sub_range(high..low).count(), 0);
+        assert_eq!(subject.sub_range((Bound::Excluded(2), Bound::Excluded(2))).count(), 0);
+        let (high, low): (u16, u16) = (900, 800);
+        assert_eq!(subject.sub_range_within(&2, high..=low).count(), 0);
+        assert_eq!(subject.sub_range_within(&2, (Bound::Excluded(800), Bound::Excluded(800))).count(), 0);
+    }
+}
@@ -0,0 +1,300 @@
+use std::collections::BTreeMap;
+use std::borrow::Borrow;
+use std::cmp::Ord;
+
+pub type BTreeMapToVec<K, V> = BTreeMap<K, Vec<V>>;
+
+pub trait BTreeMapToVecExt<K, V> {
+
+    fn sub_push(&mut self, key: K, value: V)
+    where
+        K: Ord;
+
+    fn sub_contains<Q>(&self, key: &Q, value: &V) -> bool
+    where
+        K: Borrow<Q> + Ord,
+        V: PartialEq,
+        Q: Ord + ?Sized;
+
+    fn sub_remove_first<Q>(&mut self, key: &Q, value: &V) -> bool
+    where
+        K: Borrow<Q> + Ord,
+        V: PartialEq,
+        Q: Ord + ?Sized;
+
+    fn sub_len(&self) -> usize;
+
+    fn sub_len_of<Q>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q> + Ord,
+        Q: Ord + ?Sized;
+
+    fn sub_iter<'a>(&'a self) -> impl Iterator<Item = (&'a K, &'a V)>
+    where
+        K: 'a,
+        V: 'a;
+
+    fn into_sub_iter(self) -> impl Iterator<Item = (K, V)>
+    where
+        K: Clone;
+
+}
+
+impl<K, V> BTreeMapToVecExt<K, V> for BTreeMapToVec<K, V> {
+
+    /// Add a sub-key-value item to the end of the vector for a key.
+    ///
+    /// The vector keeps insertion order and duplicate values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: BTreeMapToVec<u8, u8> = BTreeMapToVec::new();
+    /// a.sub_push(1, 2);
+    /// a.sub_push(1, 2);
+    /// assert_eq!(a.get(&1), Some(&vec![2, 2]));
+    /// ```
+    #[inline]
+    fn sub_push(&mut self, key: K, value: V)
+    where
+        K: Ord,
+    {
+        self.entry(key)
+        .or_default()
+        .push(value)
+    }
+
+    /// Return `true` if the collection contains a sub-key-value item.
+    ///
+    /// This scans the vector for the key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: BTreeMapToVec<u8, u8> = BTreeMapToVec::new();
+    /// a.sub_push(1, 2);
+    /// assert_eq!(a.sub_contains(&1, &2), true);
+    /// assert_eq!(a.sub_contains(&3, &4), false);
+    /// ```
+    #[inline]
+    fn sub_contains<Q>(&self, key: &Q, value: &V) -> bool
+    where
+        K: Borrow<Q> + Ord,
+        V: PartialEq,
+        Q: Ord + ?Sized,
+    {
+        match self.get(key) {
+            Some(vec) => vec.contains(value),
+            None => false,
+        }
+    }
+
+    /// Remove the first occurrence of a value from the vector for a key.
+    ///
+    /// Return whether the value was present in the vector. The remaining
+    /// values keep their order. The key stays in the collection even when
+    /// its vector becomes empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: BTreeMapToVec<u8, u8> = BTreeMapToVec::new();
+    /// a.sub_push(1, 2);
+    /// a.sub_push(1, 3);
+    /// a.sub_push(1, 2);
+    /// assert_eq!(a.sub_remove_first(&1, &2), true);
+    /// assert_eq!(a.get(&1), Some(&vec![3, 2]));
+    /// ```
+    #[inline]
+    fn sub_remove_first<Q>(&mut self, key: &Q, value: &V) -> bool
+    where
+        K: Borrow<Q> + Ord,
+        V: PartialEq,
+        Q: Ord + ?Sized,
+    {
+        match self.get_mut(key) {
+            Some(vec) => match vec.iter().position(|x| x == value) {
+                Some(index) => {
+                    vec.remove(index);
+                    true
+                },
+                None => false,
+            },
+            None => false,
+        }
+    }
+
+    /// Return the total number of sub-key-value items in the collection.
+    ///
+    /// This counts every value in every vector, including duplicates.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: BTreeMapToVec<u8, u8> = BTreeMapToVec::new();
+    /// a.sub_push(1, 2);
+    /// a.sub_push(1, 2);
+    /// a.sub_push(3, 4);
+    /// assert_eq!(a.sub_len(), 3);
+    /// ```
+    #[inline]
+    fn sub_len(&self) -> usize {
+        self.values().map(|vec| vec.len()).sum()
+    }
+
+    /// Return the number of values in the vector for a key.
+    ///
+    /// Return 0 if the key is absent.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: BTreeMapToVec<u8, u8> = BTreeMapToVec::new();
+    /// a.sub_push(1, 2);
+    /// a.sub_push(1, 2);
+    /// assert_eq!(a.sub_len_of(&1), 2);
+    /// assert_eq!(a.sub_len_of(&3), 0);
+    /// ```
+    #[inline]
+    fn sub_len_of<Q>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q> + Ord,
+        Q: Ord + ?Sized,
+    {
+        match self.get(key) {
+            Some(vec) => vec.len(),
+            None => 0,
+        }
+    }
+
+    /// Return an iterator over every sub-key-value pair in the collection.
+    ///
+    /// The iterator yields `(&K, &V)` pairs in key order, then insertion order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: BTreeMapToVec<u8, u8> = BTreeMapToVec::new();
+    /// a.sub_push(1, 3);
+    /// a.sub_push(1, 2);
+    /// let pairs: Vec<(&u8, &u8)> = a.sub_iter().collect();
+    /// assert_eq!(pairs, vec![(&1, &3), (&1, &2)]);
+    /// ```
+    #[inline]
+    fn sub_iter<'a>(&'a self) -> impl Iterator<Item = (&'a K, &'a V)>
+    where
+        K: 'a,
+        V: 'a,
+    {
+        self.iter()
+        .flat_map(|(key, vec)| vec.iter().map(move |value| (key, value)))
+    }
+
+    /// Consume the collection and return an iterator over every sub-key-value pair.
+    ///
+    /// The iterator yields owned `(K, V)` pairs in key order, then insertion order;
+    /// each key is cloned once per value in its vector.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: BTreeMapToVec<u8, u8> = BTreeMapToVec::new();
+    /// a.sub_push(1, 3);
+    /// a.sub_push(1, 2);
+    /// let pairs: Vec<(u8, u8)> = a.into_sub_iter().collect();
+    /// assert_eq!(pairs, vec![(1, 3), (1, 2)]);
+    /// ```
+    #[inline]
+    fn into_sub_iter(self) -> impl Iterator<Item = (K, V)>
+    where
+        K: Clone,
+    {
+        self.into_iter()
+        .flat_map(|(key, vec)| vec.into_iter().map(move |value| (key.clone(), value)))
+    }
+
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+
+    #[test]
+    /// Test `sub_push` keeps order and duplicates.
+    fn test_sub_push() {
+        let mut subject: BTreeMapToVec<u8, u8> = BTreeMapToVec::new();
+        let k1 = 1;
+        let k2 = 2;
+        subject.sub_push(k1, 3);
+        subject.sub_push(k1, 2);
+        subject.sub_push(k1, 3);
+        subject.sub_push(k2, 4);
+        assert_eq!(subject[&k1], vec![3, 2, 3]);
+        assert_eq!(subject[&k2], vec![4]);
+    }
+
+    #[test]
+    /// Test `sub_contains` with some items.
+    fn test_sub_contains() {
+        let mut subject: BTreeMapToVec<u8, u8> = BTreeMapToVec::new();
+        let k = 1;
+        let v = 2;
+        let absent = 3;
+        subject.sub_push(k, v);
+        assert_eq!(subject.sub_contains(&k, &v), true);
+        assert_eq!(subject.sub_contains(&k, &absent), false);
+        assert_eq!(subject.sub_contains(&absent, &v), false);
+    }
+
+    #[test]
+    /// Test `sub_remove_first` removes only the first occurrence.
+    fn test_sub_remove_first() {
+        let mut subject: BTreeMapToVec<u8, u8> = BTreeMapToVec::new();
+        let k = 1;
+        let absent = 9;
+        subject.sub_push(k, 2);
+        subject.sub_push(k, 3);
+        subject.sub_push(k, 2);
+        assert_eq!(subject.sub_remove_first(&k, &2), true);
+        assert_eq!(subject[&k], vec![3, 2]);
+        assert_eq!(subject.sub_remove_first(&k, &2), true);
+        assert_eq!(subject[&k], vec![3]);
+        assert_eq!(subject.sub_remove_first(&k, &2), false);
+        assert_eq!(subject.sub_remove_first(&absent, &2), false);
+    }
+
+    #[test]
+    /// Test `sub_len` and `sub_len_of` count duplicates.
+    fn test_sub_len_and_sub_len_of() {
+        let mut subject: BTreeMapToVec<u8, u8> = BTreeMapToVec::new();
+        subject.sub_push(1, 2);
+        subject.sub_push(1, 2);
+        subject.sub_push(3, 4);
+        assert_eq!(subject.sub_len(), 3);
+        assert_eq!(subject.sub_len_of(&1), 2);
+        assert_eq!(subject.sub_len_of(&9), 0);
+    }
+
+    #[test]
+    /// Test `sub_iter` and `into_sub_iter` with some items.
+    fn test_sub_iter_and_into_sub_iter() {
+        let mut subject: BTreeMapToVec<u8, u8> = BTreeMapToVec::new();
+        subject.sub_push(1, 3);
+        subject.sub_push(1, 2);
+        subject.sub_push(2, 4);
+        let actual: Vec<(u8, u8)> = subject.sub_iter().map(|(k, v)| (*k, *v)).collect();
+        let expect = actual.clone();
+        let actual: Vec<(u8, u8)> = subject.into_sub_iter().collect();
+        assert_eq!(actual, expect);
+        assert_eq!(actual.len(), 3);
+    }
+
+}
@@ -0,0 +1,509 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io;
+use std::path::{Path, PathBuf};
+use crate::file_compare::partition_identical;
+use crate::file_digest::{DigestAlgorithm, FileDigest};
+use crate::path_scan::{self, PathErrors, WalkOptions};
+
+/// The default number of leading bytes that the partial digest reads.
+const PARTIAL_HASH_LEN: u64 = 4096;
+
+/// A group of files that a `DuplicateFinder` found to be duplicates.
+///
+/// # Examples
+///
+/// ```
+/// use sixarm_collections::*;
+/// # let dir = std::path::PathBuf::from("duplicate_group");
+/// # std::fs::create_dir_all(&dir)?;
+/// # std::fs::write(dir.join("a1.txt"), "alpha")?;
+/// # std::fs::write(dir.join("a2.txt"), "alpha")?;
+/// let (groups, _) = DuplicateFinder::new().root("duplicate_group").find();
+/// assert_eq!(groups[0].file_len(), 5);
+/// assert_eq!(groups[0].paths().len(), 2);
+/// assert_eq!(groups[0].savings(), 5);
+/// # std::fs::remove_dir_all(&dir)?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateGroup {
+    file_len: u64,
+    digest: Option<FileDigest>,
+    paths: Vec<PathBuf>,
+}
+
+impl DuplicateGroup {
+
+    /// Return the length of each file in the group.
+    #[inline]
+    pub fn file_len(&self) -> u64 {
+        self.file_len
+    }
+
+    /// Return the full digest of the files, or `None` when the finder
+    /// skipped the full digest stage.
+    #[inline]
+    pub fn digest(&self) -> Option<&FileDigest> {
+        self.digest.as_ref()
+    }
+
+    /// Return the paths of the files, in sorted order.
+    #[inline]
+    pub fn paths(&self) -> &[PathBuf] {
+        &self.paths
+    }
+
+    /// Return the paths of the files, in sorted order.
+    #[inline]
+    pub fn into_paths(self) -> Vec<PathBuf> {
+        self.paths
+    }
+
+    /// Return the bytes freed by keeping one file of the group and
+    /// removing the rest.
+    #[inline]
+    pub fn savings(&self) -> u64 {
+        path_scan::savings(self.file_len, self.paths.len())
+    }
+
+}
+
+/// A builder that finds duplicate files under one or more roots.
+///
+/// The finder runs the classic pipeline, where each stage splits the
+/// groups of the stage before and drops any group of one:
+///
+/// 1. Walk the roots and group the regular files by length.
+/// 2. Group by a partial digest of the first bytes of each file.
+/// 3. Group by a full digest of each file.
+/// 4. Group by byte comparison, which is exact.
+///
+/// The default finder reads a partial digest of the first 4096 bytes,
+/// runs the full digest, and skips byte comparison. The partial digest
+/// stage is skipped for files no longer than its length, since the full
+/// digest covers the same bytes.
+///
+/// The walk options pick the files, including their min length and max
+/// length. The default walk options have a min length of 1, which skips
+/// empty files; options from `walk_options` replace them.
+///
+/// Hard links to the same file count once, since removing one frees no
+/// bytes: of the paths with the same device and inode, the finder keeps
+/// the first in sorted order, before any digest.
+///
+/// This collection needs the `blake3` feature or the `sha2` feature.
+///
+/// # Examples
+///
+/// ```
+/// use sixarm_collections::*;
+/// # let dir = std::path::PathBuf::from("duplicate_finder");
+/// # std::fs::create_dir_all(&dir)?;
+/// # std::fs::write(dir.join("a1.txt"), "alpha")?;
+/// # std::fs::write(dir.join("a2.txt"), "alpha")?;
+/// # std::fs::write(dir.join("b1.txt"), "bravo")?;
+/// let (groups, errors) = DuplicateFinder::new()
+/// .root("duplicate_finder")
+/// .walk_options(WalkOptions::new().max_depth(1))
+/// .byte_compare(true)
+/// .find();
+/// assert_eq!(errors.is_empty(), true);
+/// assert_eq!(groups.len(), 1);
+/// assert_eq!(groups[0].paths(), &[dir.join("a1.txt"), dir.join("a2.txt")]);
+/// # std::fs::remove_dir_all(&dir)?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct DuplicateFinder {
+    roots: Vec<PathBuf>,
+    walk_options: WalkOptions,
+    partial_hash_len: Option<u64>,
+    full_hash: bool,
+    byte_compare: bool,
+    algorithm: DigestAlgorithm,
+}
+
+impl Default for DuplicateFinder {
+    fn default() -> Self {
+        DuplicateFinder {
+            roots: Vec::new(),
+            walk_options: WalkOptions::new().min_len(1),
+            partial_hash_len: Some(PARTIAL_HASH_LEN),
+            full_hash: true,
+            byte_compare: false,
+            algorithm: DigestAlgorithm::default(),
+        }
+    }
+}
+
+impl DuplicateFinder {
+
+    /// Create the default finder, with no roots.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let finder = DuplicateFinder::new();
+    /// assert_eq!(finder.get_roots().is_empty(), true);
+    /// ```
+    #[inline]
+    pub fn new() -> Self {
+        DuplicateFinder::default()
+    }
+
+    /// Add a root directory to walk.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let finder = DuplicateFinder::new().root("alpha").root("bravo");
+    /// assert_eq!(finder.get_roots().len(), 2);
+    /// ```
+    #[inline]
+    pub fn root<P: Into<PathBuf>>(mut self, root: P) -> Self {
+        self.roots.push(root.into());
+        self
+    }
+
+    /// Add root directories to walk.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let finder = DuplicateFinder::new().roots(vec!["alpha", "bravo"]);
+    /// assert_eq!(finder.get_roots().len(), 2);
+    /// ```
+    #[inline]
+    pub fn roots<I, P>(mut self, roots: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<PathBuf>,
+    {
+        self.roots.extend(roots.into_iter().map(Into::into));
+        self
+    }
+
+    /// Set the options for walking each root.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let finder = DuplicateFinder::new().walk_options(WalkOptions::new().max_depth(1));
+    /// assert_eq!(finder.get_walk_options().get_max_depth(), Some(1));
+    /// ```
+    #[inline]
+    pub fn walk_options(mut self, options: WalkOptions) -> Self {
+        self.walk_options = options;
+        self
+    }
+
+    /// Set the number of leading bytes for the partial digest stage, or
+    /// `None` to skip the stage.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let finder = DuplicateFinder::new().partial_hash_len(None);
+    /// assert_eq!(finder.get_partial_hash_len(), None);
+    /// ```
+    #[inline]
+    pub fn partial_hash_len(mut self, len: Option<u64>) -> Self {
+        self.partial_hash_len = len;
+        self
+    }
+
+    /// Set whether to run the full digest stage.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let finder = DuplicateFinder::new().full_hash(false);
+    /// assert_eq!(finder.get_full_hash(), false);
+    /// ```
+    #[inline]
+    pub fn full_hash(mut self, full_hash: bool) -> Self {
+        self.full_hash = full_hash;
+        self
+    }
+
+    /// Set whether to run the byte comparison stage.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let finder = DuplicateFinder::new().byte_compare(true);
+    /// assert_eq!(finder.get_byte_compare(), true);
+    /// ```
+    #[inline]
+    pub fn byte_compare(mut self, byte_compare: bool) -> Self {
+        self.byte_compare = byte_compare;
+        self
+    }
+
+    /// Set the algorithm for the partial and full digest stages.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let finder = DuplicateFinder::new().algorithm(DigestAlgorithm::default());
+    /// assert_eq!(finder.get_algorithm(), DigestAlgorithm::default());
+    /// ```
+    #[inline]
+    pub fn algorithm(mut self, algorithm: DigestAlgorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    /// Return the root directories.
+    #[inline]
+    pub fn get_roots(&self) -> &[PathBuf] {
+        &self.roots
+    }
+
+    /// Return the options for walking each root.
+    #[inline]
+    pub fn get_walk_options(&self) -> &WalkOptions {
+        &self.walk_options
+    }
+
+    /// Return the number of leading bytes for the partial digest stage.
+    #[inline]
+    pub fn get_partial_hash_len(&self) -> Option<u64> {
+        self.partial_hash_len
+    }
+
+    /// Return whether the full digest stage runs.
+    #[inline]
+    pub fn get_full_hash(&self) -> bool {
+        self.full_hash
+    }
+
+    /// Return whether the byte comparison stage runs.
+    #[inline]
+    pub fn get_byte_compare(&self) -> bool {
+        self.byte_compare
+    }
+
+    /// Return the algorithm for the digest stages.
+    #[inline]
+    pub fn get_algorithm(&self) -> DigestAlgorithm {
+        self.algorithm
+    }
+
+    /// Run the pipeline and return the duplicate groups.
+    ///
+    /// The groups are sorted by savings, most first, then by file length,
+    /// longest first. Return the groups and a report of each path that
+    /// can't be read, including any root that can't be read; the finder
+    /// keeps going past them.
+    ///
+    /// With neither the full digest stage nor the byte comparison stage,
+    /// the groups are only candidates: their files share a length and a
+    /// partial digest.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let (groups, errors) = DuplicateFinder::new().root("missing").find();
+    /// assert_eq!(groups.is_empty(), true);
+    /// assert_eq!(errors.len(), 1);
+    /// ```
+    pub fn find(&self) -> (Vec<DuplicateGroup>, PathErrors) {
+        let mut errors = PathErrors::new();
+        let mut lens: HashMap<u64, BTreeMap<PathBuf, Option<(u64, u64)>>> = HashMap::new();
+        for root in &self.roots {
+            let walked = path_scan::walk_tree(root, &self.walk_options, |path, metadata| {
+                lens.entry(metadata.len()).or_default().insert(path, path_scan::file_id(metadata));
+            });
+            match walked {
+                Ok(root_errors) => errors.extend(root_errors),
+                Err(err) => errors.push((root.clone(), err)),
+            }
+        }
+        let mut groups = Vec::new();
+        for (file_len, paths) in lens {
+            let paths = fold_hard_links(paths);
+            if paths.len() < 2 {
+                continue;
+            }
+            let mut stage: Vec<(Option<FileDigest>, Vec<PathBuf>)> = vec![(None, paths)];
+            if let Some(partial_hash_len) = self.partial_hash_len {
+                if !self.full_hash || file_len > partial_hash_len {
+                    stage = stage.into_iter()
+                    .flat_map(|(_, paths)| group_by_digest(paths, &mut errors, |path| self.algorithm.digest_file_prefix(path, partial_hash_len)))
+                    .map(|(_, paths)| (None, paths))
+                    .collect();
+                }
+            }
+            if self.full_hash {
+                stage = stage.into_iter()
+                .flat_map(|(_, paths)| group_by_digest(paths, &mut errors, |path| self.algorithm.digest_file(path)))
+                .map(|(digest, paths)| (Some(digest), paths))
+                .collect();
+            }
+            if self.byte_compare {
+                stage = stage.into_iter()
+                .flat_map(|(digest, paths)| {
+                    let (partition, partition_errors) = partition_identical(paths);
+                    errors.extend(partition_errors);
+                    partition.into_iter().map(move |paths| (digest, paths))
+                })
+                .filter(|(_, paths)| paths.len() > 1)
+                .collect();
+            }
+            groups.extend(stage.into_iter().map(|(digest, paths)| DuplicateGroup { file_len, digest, paths }));
+        }
+        groups.sort_by(|a, b| {
+            b.savings().cmp(&a.savings())
+            .then(b.file_len.cmp(&a.file_len))
+            .then_with(|| a.paths.cmp(&b.paths))
+        });
+        (groups, errors)
+    }
+
+}
+
+/// Keep the first of the sorted paths to each file, by device and inode,
+/// so hard links count once.
+fn fold_hard_links(paths: BTreeMap<PathBuf, Option<(u64, u64)>>) -> Vec<PathBuf> {
+    let mut seen: HashSet<(u64, u64)> = HashSet::new();
+    paths.into_iter()
+    .filter(|(_, id)| id.map_or(true, |id| seen.insert(id)))
+    .map(|(path, _)| path)
+    .collect()
+}
+
+/// Group sorted paths by a digest, and drop any group of one.
+///
+/// Each group keeps the sorted order of its paths. Report each path
+/// whose digest fails, and leave it out.
+fn group_by_digest<F>(paths: Vec<PathBuf>, errors: &mut PathErrors, f: F) -> Vec<(FileDigest, Vec<PathBuf>)>
+where
+    F: Fn(&Path) -> io::Result<FileDigest>,
+{
+    let mut map: HashMap<FileDigest, Vec<PathBuf>> = HashMap::new();
+    for path in paths {
+        match f(&path) {
+            Ok(digest) => map.entry(digest).or_default().push(path),
+            Err(err) => errors.push((path, err)),
+        }
+    }
+    let mut groups: Vec<(FileDigest, Vec<PathBuf>)> = map.into_iter()
+    .filter(|(_, paths)| paths.len() > 1)
+    .collect();
+    groups.sort_by(|a, b| a.1.cmp(&b.1));
+    groups
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    /// Create a test tree under the temp dir and return its root.
+    ///
+    /// The files "a1", "sub/a2", and "other/a3" have the same content.
+    /// The files "b1" and "sub/b2" have the same length as the "a" files
+    /// and the same first bytes, but differ at the end. The files "e1"
+    /// and "e2" are empty.
+    fn tree(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("duplicate_finder_{}_{}", name, std::process::id()));
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        fs::create_dir_all(dir.join("other")).unwrap();
+        fs::write(dir.join("a1"), "alpha-alpha").unwrap();
+        fs::write(dir.join("sub").join("a2"), "alpha-alpha").unwrap();
+        fs::write(dir.join("other").join("a3"), "alpha-alpha").unwrap();
+        fs::write(dir.join("b1"), "alpha-bravo").unwrap();
+        fs::write(dir.join("sub").join("b2"), "alpha-bravo").unwrap();
+        fs::write(dir.join("c1"), "charlie").unwrap();
+        fs::write(dir.join("e1"), "").unwrap();
+        fs::write(dir.join("e2"), "").unwrap();
+        dir
+    }
+
+    #[test]
+    /// Test `find` with the default stages over two roots.
+    fn test_find() {
+        let dir = tree("find");
+        let (groups, errors) = DuplicateFinder::new()
+        .roots(vec![dir.join("sub"), dir.join("other")])
+        .root(dir.join("a1"))
+        .root(dir.join("missing"))
+        .root(&dir)
+        .walk_options(WalkOptions::new().min_len(1).exclude(|path| path.ends_with("b1")))
+        .find();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].0, dir.join("missing"));
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].file_len(), 11);
+        assert_eq!(groups[0].digest().is_some(), true);
+        assert_eq!(groups[0].paths(), &[dir.join("a1"), dir.join("other").join("a3"), dir.join("sub").join("a2")]);
+        assert_eq!(groups[0].savings(), 22);
+    }
+
+    #[test]
+    /// Test `find` with each digest stage alone, with byte comparison, and with empty files.
+    fn test_find_x_stages() {
+        let dir = tree("stages");
+        let finder = DuplicateFinder::new().root(&dir);
+        let (partial, _) = finder.full_hash(false).partial_hash_len(Some(6)).find();
+        let finder = DuplicateFinder::new().root(&dir);
+        let (full, _) = finder.partial_hash_len(None).find();
+        let finder = DuplicateFinder::new().root(&dir);
+        let (compared, _) = finder.partial_hash_len(Some(6)).full_hash(false).byte_compare(true).find();
+        let finder = DuplicateFinder::new().root(&dir);
+        let (empty, _) = finder.walk_options(WalkOptions::new()).find();
+        fs::remove_dir_all(&dir).unwrap();
+        // The partial digest can't tell the "a" files from the "b" files.
+        assert_eq!(partial.len(), 1);
+        assert_eq!(partial[0].paths().len(), 5);
+        assert_eq!(partial[0].digest(), None);
+        assert_eq!(full.len(), 2);
+        assert_eq!(full[0].paths().len(), 3);
+        assert_eq!(full[1].paths(), &[dir.join("b1"), dir.join("sub").join("b2")]);
+        assert_eq!(compared.iter().map(|group| group.paths().len()).collect::<Vec<_>>(), vec![3, 2]);
+        assert_eq!(compared[0].digest(), None);
+        assert_eq!(empty.len(), 3);
+        assert_eq!(empty[2].file_len(), 0);
+        assert_eq!(empty[2].savings(), 0);
+    }
+
+    #[test]
+    /// Test `find` applies the walk options' length thresholds.
+    fn test_find_x_len_thresholds() {
+        let dir = tree("len_thresholds");
+        let (short, _) = DuplicateFinder::new().root(&dir).walk_options(WalkOptions::new().max_len(10)).find();
+        let (long, _) = DuplicateFinder::new().root(&dir).walk_options(WalkOptions::new().min_len(11)).find();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(short.len(), 1);
+        assert_eq!(short[0].file_len(), 0);
+        assert_eq!(long.len(), 2);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    /// Test `find` counts hard links to the same file once.
+    fn test_find_x_hard_links() {
+        let dir = tree("hard_links");
+        fs::hard_link(dir.join("a1"), dir.join("a1-link")).unwrap();
+        fs::hard_link(dir.join("c1"), dir.join("c1-link")).unwrap();
+        let (groups, errors) = DuplicateFinder::new().root(&dir).find();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(errors.is_empty(), true);
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].paths(), &[dir.join("a1"), dir.join("other").join("a3"), dir.join("sub").join("a2")]);
+        assert_eq!(groups[0].savings(), 22);
+    }
+
+}
@@ -0,0 +1,225 @@
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use crate::path_scan::PathErrors;
+
+/// The size of each read when a comparison streams two files.
+const BUFFER_LEN: usize = 64 * 1024;
+
+/// Read as many bytes as fit in a buffer, stopping early only at the end.
+///
+/// Return the number of bytes read, which is less than the buffer length
+/// only at the end of the reader.
+fn read_full<R: Read>(reader: &mut R, buffer: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buffer.len() {
+        match reader.read(&mut buffer[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {},
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(filled)
+}
+
+/// Which of two compared inputs failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Side {
+    A,
+    B,
+}
+
+/// Return `true` if two readers yield the same bytes, and on error,
+/// return which reader failed.
+fn compare_readers<A: Read, B: Read>(mut a: A, mut b: B) -> Result<bool, (Side, io::Error)> {
+    let mut buffer_a = vec![0; BUFFER_LEN];
+    let mut buffer_b = vec![0; BUFFER_LEN];
+    loop {
+        let n_a = read_full(&mut a, &mut buffer_a).map_err(|err| (Side::A, err))?;
+        let n_b = read_full(&mut b, &mut buffer_b).map_err(|err| (Side::B, err))?;
+        if buffer_a[..n_a] != buffer_b[..n_b] {
+            return Ok(false);
+        }
+        if n_a < BUFFER_LEN {
+            return Ok(true);
+        }
+    }
+}
+
+/// Return `true` if two files have the same content, and on error,
+/// return which file failed.
+fn compare_files(a: &Path, b: &Path) -> Result<bool, (Side, io::Error)> {
+    let file_a = File::open(a).map_err(|err| (Side::A, err))?;
+    let file_b = File::open(b).map_err(|err| (Side::B, err))?;
+    let len_a = file_a.metadata().map_err(|err| (Side::A, err))?.len();
+    let len_b = file_b.metadata().map_err(|err| (Side::B, err))?.len();
+    if len_a != len_b {
+        return Ok(false);
+    }
+    compare_readers(file_a, file_b)
+}
+
+/// Return `true` if two readers yield the same bytes, streaming both in chunks.
+///
+/// The comparison stops at the first chunk that differs.
+///
+/// # Examples
+///
+/// ```
+/// use sixarm_collections::*;
+/// assert_eq!(readers_equal(&b"alpha"[..], &b"alpha"[..]).unwrap(), true);
+/// assert_eq!(readers_equal(&b"alpha"[..], &b"alphas"[..]).unwrap(), false);
+/// ```
+pub fn readers_equal<A: Read, B: Read>(a: A, b: B) -> io::Result<bool> {
+    compare_readers(a, b).map_err(|(_, err)| err)
+}
+
+/// Return `true` if two files have the same content, by byte comparison.
+///
+/// Files with different lengths differ without being read. Return an
+/// error if either file can't be read.
+///
+/// # Examples
+///
+/// ```
+/// use sixarm_collections::*;
+/// # std::fs::write("files_equal_alpha.txt", "alpha");
+/// # std::fs::write("files_equal_other.txt", "alpha");
+/// assert_eq!(files_equal("files_equal_alpha.txt", "files_equal_other.txt")?, true);
+/// # std::fs::remove_file("files_equal_alpha.txt");
+/// # std::fs::remove_file("files_equal_other.txt");
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub fn files_equal<P: AsRef<Path>, Q: AsRef<Path>>(a: P, b: Q) -> io::Result<bool> {
+    compare_files(a.as_ref(), b.as_ref()).map_err(|(_, err)| err)
+}
+
+/// Partition paths into groups of files with identical content, by byte comparison.
+///
+/// This is the final stage of duplicate detection, for a group of paths
+/// that already share a length and a digest: the comparison is exact, so
+/// a digest collision can't merge different files.
+///
+/// Each group starts with the first path that has its content, and the
+/// groups are in the order of their first paths. A path whose file has
+/// unique content is a group of one. Return the groups and a report of
+/// each path that can't be read; those paths are in no group. When the
+/// first path of a group fails during a comparison, the report names
+/// that path, and the next path in the group takes its place.
+///
+/// # Examples
+///
+/// ```
+/// use sixarm_collections::*;
+/// use std::path::PathBuf;
+/// # std::fs::write("partition_a1.txt", "alpha");
+/// # std::fs::write("partition_b1.txt", "bravo");
+/// # std::fs::write("partition_a2.txt", "alpha");
+/// let paths: Vec<PathBuf> = vec!["partition_a1.txt", "partition_b1.txt", "partition_a2.txt"].into_iter().map(PathBuf::from).collect();
+/// let (groups, errors) = partition_identical(paths);
+/// assert_eq!(errors.is_empty(), true);
+/// assert_eq!(groups, vec![
+///     vec![PathBuf::from("partition_a1.txt"), PathBuf::from("partition_a2.txt")],
+///     vec![PathBuf::from("partition_b1.txt")],
+/// ]);
+/// # std::fs::remove_file("partition_a1.txt");
+/// # std::fs::remove_file("partition_b1.txt");
+/// # std::fs::remove_file("partition_a2.txt");
+/// ```
+pub fn partition_identical<I>(paths: I) -> (Vec<Vec<PathBuf>>, PathErrors)
+where
+    I: IntoIterator<Item = PathBuf>,
+{
+    let mut groups: Vec<Vec<PathBuf>> = Vec::new();
+    let mut errors = PathErrors::new();
+    'paths: for path in paths {
+        if let Err(err) = File::open(&path) {
+            errors.push((path, err));
+            continue;
+        }
+        let mut i = 0;
+        while i < groups.len() {
+            match compare_files(&groups[i][0], &path) {
+                Ok(true) => {
+                    groups[i].push(path);
+                    continue 'paths;
+                },
+                Ok(false) => i += 1,
+                Err((Side::A, err)) => {
+                    errors.push((groups[i].remove(0), err));
+                    if groups[i].is_empty() {
+                        groups.remove(i);
+                    }
+                },
+                Err((Side::B, err)) => {
+                    errors.push((path, err));
+                    continue 'paths;
+                },
+            }
+        }
+        groups.push(vec![path]);
+    }
+    (groups, errors)
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    /// Test `readers_equal` at and across the buffer boundary.
+    fn test_readers_equal_x_boundary() {
+        let bytes: Vec<u8> = (0..BUFFER_LEN * 2).map(|i| (i % 251) as u8).collect();
+        assert_eq!(readers_equal(&bytes[..], &bytes[..]).unwrap(), true);
+        assert_eq!(readers_equal(&bytes[..BUFFER_LEN], &bytes[..BUFFER_LEN]).unwrap(), true);
+        assert_eq!(readers_equal(&bytes[..], &bytes[..BUFFER_LEN]).unwrap(), false);
+        let mut other = bytes.clone();
+        other[BUFFER_LEN + 1] ^= 1;
+        assert_eq!(readers_equal(&bytes[..], &other[..]).unwrap(), false);
+        assert_eq!(readers_equal(io::Read::chain(&bytes[..3], &bytes[3..]), &bytes[..]).unwrap(), true);
+        assert_eq!(readers_equal(&b""[..], &b""[..]).unwrap(), true);
+    }
+
+    #[test]
+    /// Test `partition_identical` splits same-length files by content, and reports missing files.
+    fn test_partition_identical() {
+        let dir = std::env::temp_dir().join(format!("file_compare_partition_identical_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("a1"), "alpha").unwrap();
+        fs::write(dir.join("b1"), "bravo").unwrap();
+        fs::write(dir.join("a2"), "alpha").unwrap();
+        fs::write(dir.join("b2"), "bravo").unwrap();
+        fs::write(dir.join("c1"), "charl").unwrap();
+        let names = ["a1", "b1", "missing", "a2", "b2", "c1"];
+        let (groups, errors) = partition_identical(names.iter().map(|name| dir.join(name)));
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(groups, vec![
+            vec![dir.join("a1"), dir.join("a2")],
+            vec![dir.join("b1"), dir.join("b2")],
+            vec![dir.join("c1")],
+        ]);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].0, dir.join("missing"));
+        assert_eq!(errors[0].1.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    /// Test `partition_identical` reports a first path that fails during a comparison against that path.
+    fn test_partition_identical_x_representative_error() {
+        let dir = std::env::temp_dir().join(format!("file_compare_partition_identical_x_representative_error_{}", std::process::id()));
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        let len = fs::metadata(dir.join("sub")).unwrap().len() as usize;
+        fs::write(dir.join("a1"), vec![b'a'; len]).unwrap();
+        fs::write(dir.join("a2"), vec![b'a'; len]).unwrap();
+        let (groups, errors) = partition_identical(vec![dir.join("sub"), dir.join("a1"), dir.join("a2")]);
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(groups, vec![vec![dir.join("a1"), dir.join("a2")]]);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].0, dir.join("sub"));
+    }
+
+}
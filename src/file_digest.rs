@@ -0,0 +1,193 @@
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+/// A 32-byte digest of a file's content.
+pub type FileDigest = [u8; 32];
+
+/// The size of each read when a digest streams a file.
+const BUFFER_LEN: usize = 64 * 1024;
+
+/// An algorithm that digests file content.
+///
+/// Each algorithm needs its feature: `blake3` for `Blake3`, and `sha2`
+/// for `Sha256`. The default is `Blake3` when its feature is on, because
+/// it is faster, and `Sha256` otherwise.
+///
+/// # Examples
+///
+/// ```
+/// use sixarm_collections::*;
+/// let algorithm = DigestAlgorithm::default();
+/// let a = algorithm.digest_reader(&b"alpha"[..]).unwrap();
+/// let b = algorithm.digest_reader(&b"bravo"[..]).unwrap();
+/// assert_ne!(a, b);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DigestAlgorithm {
+    /// BLAKE3, via the `blake3` crate.
+    #[cfg(feature = "blake3")]
+    Blake3,
+    /// SHA-256, via the `sha2` crate.
+    #[cfg(feature = "sha2")]
+    Sha256,
+}
+
+/// The running state of a digest.
+enum DigestState {
+    #[cfg(feature = "blake3")]
+    Blake3(Box<blake3::Hasher>),
+    #[cfg(feature = "sha2")]
+    Sha256(sha2::Sha256),
+}
+
+impl DigestState {
+
+    fn new(algorithm: DigestAlgorithm) -> Self {
+        match algorithm {
+            #[cfg(feature = "blake3")]
+            DigestAlgorithm::Blake3 => DigestState::Blake3(Box::new(blake3::Hasher::new())),
+            #[cfg(feature = "sha2")]
+            DigestAlgorithm::Sha256 => DigestState::Sha256(<sha2::Sha256 as sha2::Digest>::new()),
+        }
+    }
+
+    fn update(&mut self, bytes: &[u8]) {
+        match self {
+            #[cfg(feature = "blake3")]
+            DigestState::Blake3(hasher) => {
+                hasher.update(bytes);
+            },
+            #[cfg(feature = "sha2")]
+            DigestState::Sha256(hasher) => sha2::Digest::update(hasher, bytes),
+        }
+    }
+
+    fn finalize(self) -> FileDigest {
+        match self {
+            #[cfg(feature = "blake3")]
+            DigestState::Blake3(hasher) => *hasher.finalize().as_bytes(),
+            #[cfg(feature = "sha2")]
+            DigestState::Sha256(hasher) => sha2::Digest::finalize(hasher).into(),
+        }
+    }
+
+}
+
+impl DigestAlgorithm {
+
+    /// Return the digest of everything a reader yields, streaming it in chunks.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let algorithm = DigestAlgorithm::default();
+    /// let a = algorithm.digest_reader(&b"alpha"[..]).unwrap();
+    /// assert_eq!(a, algorithm.digest_reader(&b"alpha"[..]).unwrap());
+    /// ```
+    pub fn digest_reader<R: Read>(&self, mut reader: R) -> io::Result<FileDigest> {
+        let mut state = DigestState::new(*self);
+        let mut buffer = vec![0; BUFFER_LEN];
+        loop {
+            match reader.read(&mut buffer) {
+                Ok(0) => return Ok(state.finalize()),
+                Ok(n) => state.update(&buffer[..n]),
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {},
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    /// Return the digest of a file's content.
+    ///
+    /// Return an error if the file can't be opened or read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// # std::fs::write("digest_file.txt", "alpha");
+    /// let algorithm = DigestAlgorithm::default();
+    /// let a = algorithm.digest_file("digest_file.txt")?;
+    /// assert_eq!(a, algorithm.digest_reader(&b"alpha"[..])?);
+    /// assert_eq!(algorithm.digest_file("missing.txt").is_err(), true);
+    /// # std::fs::remove_file("digest_file.txt");
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[inline]
+    pub fn digest_file<P: AsRef<Path>>(&self, path: P) -> io::Result<FileDigest> {
+        self.digest_reader(File::open(path)?)
+    }
+
+    /// Return the digest of at most the first `len` bytes of a file.
+    ///
+    /// This is the cheap partial digest that duplicate detection uses to
+    /// split a group of same-length files before digesting them in full.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// # std::fs::write("digest_file_prefix.txt", "alpha");
+    /// let algorithm = DigestAlgorithm::default();
+    /// let a = algorithm.digest_file_prefix("digest_file_prefix.txt", 2)?;
+    /// assert_eq!(a, algorithm.digest_reader(&b"al"[..])?);
+    /// # std::fs::remove_file("digest_file_prefix.txt");
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[inline]
+    pub fn digest_file_prefix<P: AsRef<Path>>(&self, path: P, len: u64) -> io::Result<FileDigest> {
+        self.digest_reader(File::open(path)?.take(len))
+    }
+
+}
+
+impl Default for DigestAlgorithm {
+    #[cfg(feature = "blake3")]
+    fn default() -> Self {
+        DigestAlgorithm::Blake3
+    }
+    #[cfg(not(feature = "blake3"))]
+    fn default() -> Self {
+        DigestAlgorithm::Sha256
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Format a digest as lowercase hex.
+    fn hex(digest: FileDigest) -> String {
+        digest.iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+
+    #[cfg(feature = "blake3")]
+    #[test]
+    /// Test `Blake3` with a known digest.
+    fn test_blake3() {
+        let actual = DigestAlgorithm::Blake3.digest_reader(&b"abc"[..]).unwrap();
+        assert_eq!(hex(actual), "6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85");
+    }
+
+    #[cfg(feature = "sha2")]
+    #[test]
+    /// Test `Sha256` with a known digest.
+    fn test_sha256() {
+        let actual = DigestAlgorithm::Sha256.digest_reader(&b"abc"[..]).unwrap();
+        assert_eq!(hex(actual), "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+    }
+
+    #[test]
+    /// Test that a digest of more than one buffer of content matches across chunkings.
+    fn test_digest_reader_x_chunks() {
+        let algorithm = DigestAlgorithm::default();
+        let bytes: Vec<u8> = (0..BUFFER_LEN * 3 + 7).map(|i| (i % 251) as u8).collect();
+        let actual = algorithm.digest_reader(&bytes[..]).unwrap();
+        let expect = algorithm.digest_reader(io::Read::chain(&bytes[..10], &bytes[10..])).unwrap();
+        assert_eq!(actual, expect);
+        assert_ne!(actual, algorithm.digest_reader(&bytes[1..]).unwrap());
+    }
+
+}
@@ -0,0 +1,542 @@
+use std::collections::{HashMap, HashSet};
+use std::borrow::Borrow;
+use std::cmp::Eq;
+use std::hash::Hash;
+use std::iter::FromIterator;
+use crate::hash_map_to_set::{HashMapToSet, HashMapToSetExt};
+
+/// A mutable builder for a `FrozenMapToSet`.
+///
+/// The builder has the `sub_*` insert API of `HashMapToSet`; call `freeze`
+/// once the contents are complete to get the immutable lookup structure.
+///
+/// # Examples
+///
+/// ```
+/// use sixarm_collections::*;
+/// let mut builder: FrozenMapToSetBuilder<&str, u8> = FrozenMapToSetBuilder::new();
+/// builder.sub_insert("a", 1);
+/// builder.sub_insert_many("b", vec![2, 3]);
+/// let frozen = builder.freeze();
+/// assert_eq!(frozen.sub_contains("b", &3), true);
+/// ```
+#[derive(Debug, Clone)]
+pub struct FrozenMapToSetBuilder<K, V> {
+    map: HashMapToSet<K, V>,
+}
+
+/// An immutable map of keys to sets, optimized for lookups.
+///
+/// A frozen map is built once by `FrozenMapToSetBuilder::freeze` and then
+/// only read. Freezing drops any keys with empty sets and shrinks every
+/// capacity to fit. The map has no interior mutability, so it is `Send`
+/// and `Sync` whenever `K` and `V` are, and it can be shared across
+/// threads with `Arc`.
+///
+/// # Examples
+///
+/// ```
+/// use sixarm_collections::*;
+/// use std::sync::Arc;
+/// let frozen: FrozenMapToSet<&str, u8> = vec![("a", 1), ("a", 2)].into_iter().collect();
+/// let shared = Arc::new(frozen);
+/// let other = Arc::clone(&shared);
+/// let handle = std::thread::spawn(move || other.sub_len_of("a"));
+/// assert_eq!(handle.join().unwrap(), 2);
+/// ```
+#[derive(Debug, Clone)]
+pub struct FrozenMapToSet<K, V> {
+    map: HashMapToSet<K, V>,
+    sub_len: usize,
+}
+
+impl<K, V> FrozenMapToSetBuilder<K, V> {
+
+    /// Create an empty builder.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let builder: FrozenMapToSetBuilder<u8, u8> = FrozenMapToSetBuilder::new();
+    /// assert_eq!(builder.is_empty(), true);
+    /// ```
+    #[inline]
+    pub fn new() -> Self {
+        FrozenMapToSetBuilder { map: HashMap::new() }
+    }
+
+    /// Return the number of keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut builder: FrozenMapToSetBuilder<u8, u8> = FrozenMapToSetBuilder::new();
+    /// builder.sub_insert(1, 2);
+    /// builder.sub_insert(1, 3);
+    /// assert_eq!(builder.len(), 1);
+    /// ```
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Return `true` if the builder has no keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let builder: FrozenMapToSetBuilder<u8, u8> = FrozenMapToSetBuilder::new();
+    /// assert_eq!(builder.is_empty(), true);
+    /// ```
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Return the total number of values across all keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut builder: FrozenMapToSetBuilder<u8, u8> = FrozenMapToSetBuilder::new();
+    /// builder.sub_insert(1, 2);
+    /// builder.sub_insert(1, 3);
+    /// assert_eq!(builder.sub_len(), 2);
+    /// ```
+    #[inline]
+    pub fn sub_len(&self) -> usize {
+        self.map.values().map(|set| set.len()).sum()
+    }
+
+}
+
+impl<K, V> FrozenMapToSetBuilder<K, V>
+where
+    K: Hash + Eq,
+    V: Hash + Eq,
+{
+
+    /// Add a value to a key's set, and return `true` if the value was newly added.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut builder: FrozenMapToSetBuilder<u8, u8> = FrozenMapToSetBuilder::new();
+    /// assert_eq!(builder.sub_insert(1, 2), true);
+    /// assert_eq!(builder.sub_insert(1, 2), false);
+    /// ```
+    #[inline]
+    pub fn sub_insert(&mut self, key: K, value: V) -> bool {
+        self.map.sub_insert(key, value)
+    }
+
+    /// Add many values to a key's set, and return the number newly added.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut builder: FrozenMapToSetBuilder<u8, u8> = FrozenMapToSetBuilder::new();
+    /// assert_eq!(builder.sub_insert_many(1, vec![2, 3, 2]), 2);
+    /// ```
+    #[inline]
+    pub fn sub_insert_many<I>(&mut self, key: K, values: I) -> usize
+    where
+        I: IntoIterator<Item = V>,
+    {
+        self.map.sub_insert_many(key, values)
+    }
+
+    /// Remove a value from a key's set, and return `true` if it was present.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut builder: FrozenMapToSetBuilder<u8, u8> = FrozenMapToSetBuilder::new();
+    /// builder.sub_insert(1, 2);
+    /// assert_eq!(builder.sub_remove(&1, &2), true);
+    /// ```
+    #[inline]
+    pub fn sub_remove<Q, R>(&mut self, key: &Q, value: &R) -> bool
+    where
+        K: Borrow<Q>,
+        V: Borrow<R>,
+        Q: Hash + Eq + ?Sized,
+        R: Hash + Eq + ?Sized,
+    {
+        self.map.sub_remove(key, value)
+    }
+
+    /// Return `true` if a key's set has a value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut builder: FrozenMapToSetBuilder<u8, u8> = FrozenMapToSetBuilder::new();
+    /// builder.sub_insert(1, 2);
+    /// assert_eq!(builder.sub_contains(&1, &2), true);
+    /// ```
+    #[inline]
+    pub fn sub_contains<Q, R>(&self, key: &Q, value: &R) -> bool
+    where
+        K: Borrow<Q>,
+        V: Borrow<R>,
+        Q: Hash + Eq + ?Sized,
+        R: Hash + Eq + ?Sized,
+    {
+        self.map.sub_contains(key, value)
+    }
+
+    /// Consume the builder and return the immutable map.
+    ///
+    /// Keys with empty sets are dropped and all capacities are shrunk to fit.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut builder: FrozenMapToSetBuilder<u8, u8> = FrozenMapToSetBuilder::new();
+    /// builder.sub_insert(1, 2);
+    /// builder.sub_insert(3, 4);
+    /// builder.sub_remove(&3, &4);
+    /// let frozen = builder.freeze();
+    /// assert_eq!(frozen.len(), 1);
+    /// ```
+    #[inline]
+    pub fn freeze(self) -> FrozenMapToSet<K, V> {
+        FrozenMapToSet::from(self.map)
+    }
+
+}
+
+impl<K, V> FrozenMapToSet<K, V> {
+
+    /// Return the number of keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let frozen: FrozenMapToSet<u8, u8> = vec![(1, 2), (1, 3)].into_iter().collect();
+    /// assert_eq!(frozen.len(), 1);
+    /// ```
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Return `true` if the map has no keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let frozen: FrozenMapToSet<u8, u8> = FrozenMapToSetBuilder::new().freeze();
+    /// assert_eq!(frozen.is_empty(), true);
+    /// ```
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Return the total number of values across all keys.
+    ///
+    /// The total is computed once when the map is frozen.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let frozen: FrozenMapToSet<u8, u8> = vec![(1, 2), (1, 3), (4, 5)].into_iter().collect();
+    /// assert_eq!(frozen.sub_len(), 3);
+    /// ```
+    #[inline]
+    pub fn sub_len(&self) -> usize {
+        self.sub_len
+    }
+
+    /// Return an iterator over the keys, in arbitrary order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let frozen: FrozenMapToSet<u8, u8> = vec![(1, 2)].into_iter().collect();
+    /// assert_eq!(frozen.keys().collect::<Vec<&u8>>(), vec![&1]);
+    /// ```
+    #[inline]
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.map.keys()
+    }
+
+    /// Return an iterator over each key and its set, in arbitrary order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let frozen: FrozenMapToSet<u8, u8> = vec![(1, 2)].into_iter().collect();
+    /// assert_eq!(frozen.iter().count(), 1);
+    /// ```
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &HashSet<V>)> {
+        self.map.iter()
+    }
+
+    /// Return an iterator over every (key, value) pair, in arbitrary order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let frozen: FrozenMapToSet<u8, u8> = vec![(1, 2), (1, 3)].into_iter().collect();
+    /// assert_eq!(frozen.sub_iter().count(), 2);
+    /// ```
+    #[inline]
+    pub fn sub_iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.map.iter().flat_map(|(key, set)| set.iter().map(move |value| (key, value)))
+    }
+
+    /// Consume the map and return a builder with the same contents, to make a changed copy.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let frozen: FrozenMapToSet<u8, u8> = vec![(1, 2)].into_iter().collect();
+    /// let mut builder = frozen.thaw();
+    /// builder.sub_insert(1, 3);
+    /// assert_eq!(builder.freeze().sub_len(), 2);
+    /// ```
+    #[inline]
+    pub fn thaw(self) -> FrozenMapToSetBuilder<K, V> {
+        FrozenMapToSetBuilder { map: self.map }
+    }
+
+}
+
+impl<K, V> FrozenMapToSet<K, V>
+where
+    K: Hash + Eq,
+    V: Hash + Eq,
+{
+
+    /// Return a reference to a key's set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let frozen: FrozenMapToSet<u8, u8> = vec![(1, 2)].into_iter().collect();
+    /// assert_eq!(frozen.get(&1).map(|set| set.len()), Some(1));
+    /// assert_eq!(frozen.get(&9), None);
+    /// ```
+    #[inline]
+    pub fn get<Q>(&self, key: &Q) -> Option<&HashSet<V>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.get(key)
+    }
+
+    /// Return `true` if the map has a key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let frozen: FrozenMapToSet<u8, u8> = vec![(1, 2)].into_iter().collect();
+    /// assert_eq!(frozen.contains_key(&1), true);
+    /// ```
+    #[inline]
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.contains_key(key)
+    }
+
+    /// Return `true` if a key's set has a value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let frozen: FrozenMapToSet<u8, u8> = vec![(1, 2)].into_iter().collect();
+    /// assert_eq!(frozen.sub_contains(&1, &2), true);
+    /// assert_eq!(frozen.sub_contains(&1, &3), false);
+    /// ```
+    #[inline]
+    pub fn sub_contains<Q, R>(&self, key: &Q, value: &R) -> bool
+    where
+        K: Borrow<Q>,
+        V: Borrow<R>,
+        Q: Hash + Eq + ?Sized,
+        R: Hash + Eq + ?Sized,
+    {
+        self.map.sub_contains(key, value)
+    }
+
+    /// Return the number of values in a key's set, or 0 if the key is absent.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let frozen: FrozenMapToSet<u8, u8> = vec![(1, 2), (1, 3)].into_iter().collect();
+    /// assert_eq!(frozen.sub_len_of(&1), 2);
+    /// assert_eq!(frozen.sub_len_of(&9), 0);
+    /// ```
+    #[inline]
+    pub fn sub_len_of<Q>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.sub_len_of(key)
+    }
+
+}
+
+impl<K, V> Default for FrozenMapToSetBuilder<K, V> {
+    fn default() -> Self {
+        FrozenMapToSetBuilder::new()
+    }
+}
+
+impl<K, V> Default for FrozenMapToSet<K, V> {
+    fn default() -> Self {
+        FrozenMapToSet { map: HashMap::new(), sub_len: 0 }
+    }
+}
+
+impl<K, V> From<HashMapToSet<K, V>> for FrozenMapToSet<K, V>
+where
+    K: Hash + Eq,
+    V: Hash + Eq,
+{
+    /// Freeze an existing `HashMapToSet`, dropping empty sets and shrinking capacities.
+    fn from(mut map: HashMapToSet<K, V>) -> Self {
+        map.retain(|_, set| !set.is_empty());
+        map.shrink_to_fit();
+        let mut sub_len = 0;
+        for set in map.values_mut() {
+            set.shrink_to_fit();
+            sub_len += set.len();
+        }
+        FrozenMapToSet { map, sub_len }
+    }
+}
+
+impl<K, V> PartialEq for FrozenMapToSet<K, V>
+where
+    K: Hash + Eq,
+    V: Hash + Eq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.map == other.map
+    }
+}
+
+impl<K, V> Eq for FrozenMapToSet<K, V>
+where
+    K: Hash + Eq,
+    V: Hash + Eq,
+{}
+
+impl<K, V> FromIterator<(K, V)> for FrozenMapToSetBuilder<K, V>
+where
+    K: Hash + Eq,
+    V: Hash + Eq,
+{
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut builder = FrozenMapToSetBuilder::new();
+        builder.extend(iter);
+        builder
+    }
+}
+
+impl<K, V> FromIterator<(K, V)> for FrozenMapToSet<K, V>
+where
+    K: Hash + Eq,
+    V: Hash + Eq,
+{
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        FrozenMapToSetBuilder::from_iter(iter).freeze()
+    }
+}
+
+impl<K, V> Extend<(K, V)> for FrozenMapToSetBuilder<K, V>
+where
+    K: Hash + Eq,
+    V: Hash + Eq,
+{
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.sub_insert(key, value);
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+
+    #[test]
+    /// Test `freeze` keeps every pair and drops keys with empty sets.
+    fn test_freeze() {
+        let mut builder: FrozenMapToSetBuilder<u8, char> = FrozenMapToSetBuilder::new();
+        builder.sub_insert_many(1, vec!['a', 'b']);
+        builder.sub_insert(2, 'c');
+        builder.sub_remove(&2, &'c');
+        assert_eq!(builder.len(), 2);
+        let subject = builder.freeze();
+        assert_eq!(subject.len(), 1);
+        assert_eq!(subject.sub_len(), 2);
+        assert_eq!(subject.contains_key(&2), false);
+        assert_eq!(subject.sub_contains(&1, &'a'), true);
+        assert_eq!(subject.sub_contains(&1, &'z'), false);
+    }
+
+    #[test]
+    /// Test `from` a `HashMapToSet` equals freezing a builder with the same pairs.
+    fn test_from_hash_map_to_set() {
+        let mut map: HashMapToSet<u8, char> = HashMap::new();
+        map.sub_insert(1, 'a');
+        map.sub_insert(1, 'b');
+        let subject = FrozenMapToSet::from(map);
+        let expect: FrozenMapToSet<u8, char> = vec![(1, 'b'), (1, 'a')].into_iter().collect();
+        assert_eq!(subject, expect);
+    }
+
+    #[test]
+    /// Test `thaw` returns a builder with the same contents.
+    fn test_thaw() {
+        let subject: FrozenMapToSet<u8, char> = vec![(1, 'a')].into_iter().collect();
+        let mut builder = subject.thaw();
+        assert_eq!(builder.sub_contains(&1, &'a'), true);
+        builder.sub_insert(2, 'b');
+        let subject = builder.freeze();
+        let mut actual: Vec<(&u8, &char)> = subject.sub_iter().collect();
+        actual.sort();
+        assert_eq!(actual, vec![(&1, &'a'), (&2, &'b')]);
+    }
+
+    #[test]
+    /// Test the frozen map is `Send` and `Sync`.
+    fn test_send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<FrozenMapToSet<String, String>>();
+    }
+
+}
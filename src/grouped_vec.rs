@@ -0,0 +1,390 @@
+use std::collections::{HashMap, HashSet};
+use std::borrow::Borrow;
+use std::cmp::{Eq, Ord};
+use std::hash::Hash;
+use std::iter::FromIterator;
+use crate::hash_map_to_set::HashMapToSet;
+
+/// A grouped vector of keys to values, stored as `Vec<(K, Vec<V>)>` sorted by key.
+///
+/// This suits build-once, iterate-many workloads: the groups are
+/// contiguous, iteration walks memory in order, and a key lookup is a
+/// binary search. Each group keeps its values in the order they were
+/// given, including duplicates.
+///
+/// # Examples
+///
+/// ```
+/// use sixarm_collections::*;
+/// let a = GroupedVec::sort_and_group(vec![("b", 1), ("a", 2), ("b", 3)]);
+/// assert_eq!(a.get("b"), Some(&[1, 3][..]));
+/// let keys: Vec<&&str> = a.keys().collect();
+/// assert_eq!(keys, vec![&"a", &"b"]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GroupedVec<K, V> {
+    groups: Vec<(K, Vec<V>)>,
+}
+
+impl<K, V> GroupedVec<K, V> {
+
+    /// Create an empty grouped vector.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: GroupedVec<u8, u8> = GroupedVec::new();
+    /// assert_eq!(a.is_empty(), true);
+    /// ```
+    #[inline]
+    pub fn new() -> Self {
+        GroupedVec { groups: Vec::new() }
+    }
+
+    /// Return the number of groups, which is the number of distinct keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a = GroupedVec::sort_and_group(vec![(1, 2), (1, 3)]);
+    /// assert_eq!(a.len(), 1);
+    /// ```
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.groups.len()
+    }
+
+    /// Return `true` if there are no groups.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: GroupedVec<u8, u8> = GroupedVec::new();
+    /// assert_eq!(a.is_empty(), true);
+    /// ```
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty()
+    }
+
+    /// Return the total number of values across all groups.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a = GroupedVec::sort_and_group(vec![(1, 2), (1, 2), (3, 4)]);
+    /// assert_eq!(a.sub_len(), 3);
+    /// ```
+    #[inline]
+    pub fn sub_len(&self) -> usize {
+        self.groups.iter().map(|(_, values)| values.len()).sum()
+    }
+
+    /// Return the groups as a slice, sorted by key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a = GroupedVec::sort_and_group(vec![(3, 4), (1, 2)]);
+    /// assert_eq!(a.as_slice(), &[(1, vec![2]), (3, vec![4])]);
+    /// ```
+    #[inline]
+    pub fn as_slice(&self) -> &[(K, Vec<V>)] {
+        &self.groups
+    }
+
+    /// Consume the grouped vector and return its groups, sorted by key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a = GroupedVec::sort_and_group(vec![(3, 4), (1, 2)]);
+    /// assert_eq!(a.into_vec(), vec![(1, vec![2]), (3, vec![4])]);
+    /// ```
+    #[inline]
+    pub fn into_vec(self) -> Vec<(K, Vec<V>)> {
+        self.groups
+    }
+
+    /// Return an iterator over each key and its values, in key order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a = GroupedVec::sort_and_group(vec![(3, 4), (1, 2)]);
+    /// let groups: Vec<(&u8, &[u8])> = a.iter().collect();
+    /// assert_eq!(groups, vec![(&1, &[2][..]), (&3, &[4][..])]);
+    /// ```
+    #[inline]
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (&K, &[V])> + ExactSizeIterator {
+        self.groups.iter().map(|(key, values)| (key, values.as_slice()))
+    }
+
+    /// Return an iterator over the keys, in order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a = GroupedVec::sort_and_group(vec![(3, 4), (1, 2)]);
+    /// assert_eq!(a.keys().collect::<Vec<&u8>>(), vec![&1, &3]);
+    /// ```
+    #[inline]
+    pub fn keys(&self) -> impl DoubleEndedIterator<Item = &K> + ExactSizeIterator {
+        self.groups.iter().map(|(key, _)| key)
+    }
+
+    /// Return an iterator over every (key, value) pair, in key order then group order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a = GroupedVec::sort_and_group(vec![(3, 4), (1, 2), (1, 0)]);
+    /// let pairs: Vec<(&u8, &u8)> = a.sub_iter().collect();
+    /// assert_eq!(pairs, vec![(&1, &2), (&1, &0), (&3, &4)]);
+    /// ```
+    #[inline]
+    pub fn sub_iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.groups.iter()
+        .flat_map(|(key, values)| values.iter().map(move |value| (key, value)))
+    }
+
+}
+
+impl<K, V> GroupedVec<K, V>
+where
+    K: Ord,
+{
+
+    /// Sort pairs by key and group each key's values, keeping their given order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a = GroupedVec::sort_and_group(vec![(2, 'x'), (1, 'y'), (2, 'z')]);
+    /// assert_eq!(a.into_vec(), vec![(1, vec!['y']), (2, vec!['x', 'z'])]);
+    /// ```
+    pub fn sort_and_group(mut pairs: Vec<(K, V)>) -> Self {
+        pairs.sort_by(|a, b| a.0.cmp(&b.0));
+        let mut groups: Vec<(K, Vec<V>)> = Vec::new();
+        for (key, value) in pairs {
+            match groups.last_mut() {
+                Some((last, values)) if *last == key => values.push(value),
+                _ => groups.push((key, vec![value])),
+            }
+        }
+        GroupedVec { groups }
+    }
+
+    /// Binary search for a key, and return the index of its group.
+    fn position<Q>(&self, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.groups.binary_search_by(|(k, _)| k.borrow().cmp(key)).ok()
+    }
+
+    /// Return the values for a key, by binary search.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a = GroupedVec::sort_and_group(vec![(1, 2), (1, 3)]);
+    /// assert_eq!(a.get(&1), Some(&[2, 3][..]));
+    /// assert_eq!(a.get(&9), None);
+    /// ```
+    #[inline]
+    pub fn get<Q>(&self, key: &Q) -> Option<&[V]>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.position(key).map(|index| self.groups[index].1.as_slice())
+    }
+
+    /// Return `true` if there is a group for a key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a = GroupedVec::sort_and_group(vec![(1, 2)]);
+    /// assert_eq!(a.contains_key(&1), true);
+    /// ```
+    #[inline]
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.position(key).is_some()
+    }
+
+    /// Return `true` if a key's group has a value, by a linear scan of the group.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a = GroupedVec::sort_and_group(vec![(1, 2)]);
+    /// assert_eq!(a.sub_contains(&1, &2), true);
+    /// assert_eq!(a.sub_contains(&1, &3), false);
+    /// ```
+    #[inline]
+    pub fn sub_contains<Q>(&self, key: &Q, value: &V) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        V: PartialEq,
+    {
+        match self.get(key) {
+            Some(values) => values.contains(value),
+            None => false,
+        }
+    }
+
+    /// Return the number of values for a key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a = GroupedVec::sort_and_group(vec![(1, 2), (1, 2)]);
+    /// assert_eq!(a.sub_len_of(&1), 2);
+    /// assert_eq!(a.sub_len_of(&9), 0);
+    /// ```
+    #[inline]
+    pub fn sub_len_of<Q>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.get(key).map_or(0, |values| values.len())
+    }
+
+    /// Consume the grouped vector and return a `HashMapToSet`, dropping duplicate values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a = GroupedVec::sort_and_group(vec![(1, 2), (1, 2), (1, 3)]);
+    /// let b: HashMapToSet<u8, u8> = a.into_hash_map_to_set();
+    /// assert_eq!(b.sub_len(), 2);
+    /// ```
+    #[inline]
+    pub fn into_hash_map_to_set(self) -> HashMapToSet<K, V>
+    where
+        K: Hash,
+        V: Hash + Eq,
+    {
+        self.groups.into_iter()
+        .map(|(key, values)| (key, values.into_iter().collect::<HashSet<V>>()))
+        .collect()
+    }
+
+}
+
+impl<K, V> Default for GroupedVec<K, V> {
+    fn default() -> Self {
+        GroupedVec::new()
+    }
+}
+
+impl<K, V> From<HashMapToSet<K, V>> for GroupedVec<K, V>
+where
+    K: Ord,
+{
+    /// Convert a `HashMapToSet` into groups sorted by key.
+    ///
+    /// Each group's values are in the set's iteration order, which is arbitrary.
+    fn from(map: HashMapToSet<K, V>) -> Self {
+        let mut groups: Vec<(K, Vec<V>)> = map.into_iter()
+        .map(|(key, set)| (key, set.into_iter().collect()))
+        .collect();
+        groups.sort_by(|a, b| a.0.cmp(&b.0));
+        GroupedVec { groups }
+    }
+}
+
+impl<K, V> From<GroupedVec<K, V>> for HashMap<K, HashSet<V>>
+where
+    K: Ord + Hash,
+    V: Hash + Eq,
+{
+    fn from(grouped: GroupedVec<K, V>) -> Self {
+        grouped.into_hash_map_to_set()
+    }
+}
+
+impl<K, V> FromIterator<(K, V)> for GroupedVec<K, V>
+where
+    K: Ord,
+{
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        GroupedVec::sort_and_group(iter.into_iter().collect())
+    }
+}
+
+impl<K, V> IntoIterator for GroupedVec<K, V> {
+    type Item = (K, Vec<V>);
+    type IntoIter = std::vec::IntoIter<(K, Vec<V>)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.groups.into_iter()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+    use crate::hash_map_to_set::HashMapToSetExt;
+
+    #[test]
+    /// Test `sort_and_group` sorts keys, keeps value order within a group, and keeps duplicates.
+    fn test_sort_and_group() {
+        let subject = GroupedVec::sort_and_group(vec![(3, 'a'), (1, 'b'), (3, 'c'), (2, 'd'), (3, 'a')]);
+        assert_eq!(subject.len(), 3);
+        assert_eq!(subject.sub_len(), 5);
+        assert_eq!(subject.get(&3), Some(&['a', 'c', 'a'][..]));
+        assert_eq!(subject.keys().copied().collect::<Vec<u8>>(), vec![1, 2, 3]);
+        assert_eq!(GroupedVec::<u8, u8>::sort_and_group(vec![]).is_empty(), true);
+    }
+
+    #[test]
+    /// Test `get`, `contains_key`, and `sub_contains` with borrowed keys.
+    fn test_get_x_borrowed_forms() {
+        let subject: GroupedVec<String, u8> = vec![(String::from("b"), 1), (String::from("a"), 2)].into_iter().collect();
+        assert_eq!(subject.get("a"), Some(&[2][..]));
+        assert_eq!(subject.contains_key("c"), false);
+        assert_eq!(subject.sub_contains("b", &1), true);
+        assert_eq!(subject.sub_len_of("b"), 1);
+    }
+
+    #[test]
+    /// Test conversion to and from `HashMapToSet`.
+    fn test_hash_map_to_set_round_trip() {
+        let mut map: HashMapToSet<u8, char> = HashMap::new();
+        map.sub_insert(2, 'a');
+        map.sub_insert(1, 'b');
+        map.sub_insert(1, 'c');
+        let subject = GroupedVec::from(map.clone());
+        assert_eq!(subject.keys().copied().collect::<Vec<u8>>(), vec![1, 2]);
+        assert_eq!(subject.sub_len_of(&1), 2);
+        let actual: HashMapToSet<u8, char> = subject.into();
+        assert_eq!(actual, map);
+    }
+
+}
@@ -0,0 +1,599 @@
+use std::collections::HashMap;
+use std::borrow::Borrow;
+use std::cmp::Eq;
+use std::hash::Hash;
+use std::iter::FromIterator;
+use std::ops::{Add, BitAnd, BitOr, Sub};
+
+/// A multiset of values, based on `HashMap<T, usize>` of each value to its count.
+///
+/// Each insert adds one occurrence, and each remove takes one away;
+/// a value is dropped from the bag when its count reaches zero.
+///
+/// # Examples
+///
+/// ```
+/// use sixarm_collections::*;
+/// let mut a: HashBag<u8> = HashBag::new();
+/// a.insert(1);
+/// a.insert(1);
+/// a.insert(2);
+/// assert_eq!(a.count(&1), 2);
+/// assert_eq!(a.len(), 3);
+/// assert_eq!(a.distinct_len(), 2);
+/// ```
+#[derive(Debug, Clone)]
+pub struct HashBag<T> {
+    map: HashMap<T, usize>,
+    len: usize,
+}
+
+impl<T> HashBag<T> {
+
+    /// Create an empty bag.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: HashBag<u8> = HashBag::new();
+    /// assert_eq!(a.is_empty(), true);
+    /// ```
+    #[inline]
+    pub fn new() -> Self {
+        HashBag { map: HashMap::new(), len: 0 }
+    }
+
+    /// Return the total number of occurrences in the bag.
+    ///
+    /// This counts duplicates, unlike `distinct_len`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashBag<u8> = HashBag::new();
+    /// a.insert(1);
+    /// a.insert(1);
+    /// assert_eq!(a.len(), 2);
+    /// ```
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Return the number of distinct values in the bag.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashBag<u8> = HashBag::new();
+    /// a.insert(1);
+    /// a.insert(1);
+    /// assert_eq!(a.distinct_len(), 1);
+    /// ```
+    #[inline]
+    pub fn distinct_len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Return `true` if the bag has no occurrences.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashBag<u8> = HashBag::new();
+    /// assert_eq!(a.is_empty(), true);
+    /// a.insert(1);
+    /// assert_eq!(a.is_empty(), false);
+    /// ```
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Remove every occurrence from the bag.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashBag<u8> = HashBag::new();
+    /// a.insert(1);
+    /// a.clear();
+    /// assert_eq!(a.is_empty(), true);
+    /// ```
+    #[inline]
+    pub fn clear(&mut self) {
+        self.map.clear();
+        self.len = 0;
+    }
+
+    /// Return an iterator over every occurrence in the bag.
+    ///
+    /// A value with count 3 is yielded three times, in arbitrary order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashBag<u8> = HashBag::new();
+    /// a.insert(1);
+    /// a.insert(1);
+    /// let values: Vec<&u8> = a.iter().collect();
+    /// assert_eq!(values, vec![&1, &1]);
+    /// ```
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.iter_with_counts()
+        .flat_map(|(value, count)| std::iter::repeat(value).take(count))
+    }
+
+    /// Return an iterator over every distinct value with its count, in arbitrary order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashBag<u8> = HashBag::new();
+    /// a.insert(1);
+    /// a.insert(1);
+    /// let pairs: Vec<(&u8, usize)> = a.iter_with_counts().collect();
+    /// assert_eq!(pairs, vec![(&1, 2)]);
+    /// ```
+    #[inline]
+    pub fn iter_with_counts(&self) -> impl Iterator<Item = (&T, usize)> {
+        self.map.iter().map(|(value, count)| (value, *count))
+    }
+
+}
+
+impl<T> HashBag<T>
+where
+    T: Hash + Eq,
+{
+
+    /// Add one occurrence of a value to the bag.
+    ///
+    /// Return the count of the value after adding.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashBag<u8> = HashBag::new();
+    /// assert_eq!(a.insert(1), 1);
+    /// assert_eq!(a.insert(1), 2);
+    /// ```
+    #[inline]
+    pub fn insert(&mut self, value: T) -> usize {
+        self.insert_many(value, 1)
+    }
+
+    /// Add many occurrences of a value to the bag.
+    ///
+    /// Return the count of the value after adding. Adding zero
+    /// occurrences leaves the bag unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashBag<u8> = HashBag::new();
+    /// assert_eq!(a.insert_many(1, 3), 3);
+    /// assert_eq!(a.len(), 3);
+    /// ```
+    #[inline]
+    pub fn insert_many(&mut self, value: T, n: usize) -> usize {
+        if n == 0 {
+            return self.count(&value);
+        }
+        let count = self.map.entry(value).or_insert(0);
+        *count += n;
+        self.len += n;
+        *count
+    }
+
+    /// Remove one occurrence of a value from the bag.
+    ///
+    /// Return whether the value was present.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashBag<u8> = HashBag::new();
+    /// a.insert(1);
+    /// assert_eq!(a.remove(&1), true);
+    /// assert_eq!(a.remove(&1), false);
+    /// ```
+    #[inline]
+    pub fn remove<R>(&mut self, value: &R) -> bool
+    where
+        T: Borrow<R>,
+        R: Hash + Eq + ?Sized,
+    {
+        match self.map.get_mut(value) {
+            Some(count) => {
+                *count -= 1;
+                if *count == 0 {
+                    self.map.remove(value);
+                }
+                self.len -= 1;
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Remove every occurrence of a value from the bag.
+    ///
+    /// Return the number of occurrences that were removed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashBag<u8> = HashBag::new();
+    /// a.insert_many(1, 3);
+    /// assert_eq!(a.remove_all(&1), 3);
+    /// assert_eq!(a.is_empty(), true);
+    /// ```
+    #[inline]
+    pub fn remove_all<R>(&mut self, value: &R) -> usize
+    where
+        T: Borrow<R>,
+        R: Hash + Eq + ?Sized,
+    {
+        let count = self.map.remove(value).unwrap_or(0);
+        self.len -= count;
+        count
+    }
+
+    /// Return the number of occurrences of a value.
+    ///
+    /// Return 0 if the value is absent.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashBag<u8> = HashBag::new();
+    /// a.insert(1);
+    /// assert_eq!(a.count(&1), 1);
+    /// assert_eq!(a.count(&2), 0);
+    /// ```
+    #[inline]
+    pub fn count<R>(&self, value: &R) -> usize
+    where
+        T: Borrow<R>,
+        R: Hash + Eq + ?Sized,
+    {
+        self.map.get(value).copied().unwrap_or(0)
+    }
+
+    /// Return `true` if the bag has at least one occurrence of a value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashBag<u8> = HashBag::new();
+    /// a.insert(1);
+    /// assert_eq!(a.contains(&1), true);
+    /// assert_eq!(a.contains(&2), false);
+    /// ```
+    #[inline]
+    pub fn contains<R>(&self, value: &R) -> bool
+    where
+        T: Borrow<R>,
+        R: Hash + Eq + ?Sized,
+    {
+        self.map.contains_key(value)
+    }
+
+    /// Return `true` if every value in this bag occurs at most as often in the other bag.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: HashBag<u8> = vec![1, 2].into_iter().collect();
+    /// let b: HashBag<u8> = vec![1, 1, 2].into_iter().collect();
+    /// assert_eq!(a.is_subbag(&b), true);
+    /// assert_eq!(b.is_subbag(&a), false);
+    /// ```
+    #[inline]
+    pub fn is_subbag(&self, other: &HashBag<T>) -> bool {
+        self.map.iter().all(|(value, count)| *count <= other.count(value))
+    }
+
+    /// Return the union, with each value at the larger of its two counts.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: HashBag<u8> = vec![1, 1, 2].into_iter().collect();
+    /// let b: HashBag<u8> = vec![1, 3].into_iter().collect();
+    /// let c = a.union(&b);
+    /// assert_eq!(c.count(&1), 2);
+    /// assert_eq!(c.len(), 4);
+    /// ```
+    #[inline]
+    pub fn union(&self, other: &HashBag<T>) -> HashBag<T>
+    where
+        T: Clone,
+    {
+        let mut bag = self.clone();
+        for (value, count) in other.iter_with_counts() {
+            let have = bag.count(value);
+            if count > have {
+                bag.insert_many(value.clone(), count - have);
+            }
+        }
+        bag
+    }
+
+    /// Return the intersection, with each value at the smaller of its two counts.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: HashBag<u8> = vec![1, 1, 2].into_iter().collect();
+    /// let b: HashBag<u8> = vec![1, 3].into_iter().collect();
+    /// let c = a.intersection(&b);
+    /// assert_eq!(c.count(&1), 1);
+    /// assert_eq!(c.len(), 1);
+    /// ```
+    #[inline]
+    pub fn intersection(&self, other: &HashBag<T>) -> HashBag<T>
+    where
+        T: Clone,
+    {
+        let mut bag = HashBag::new();
+        for (value, count) in self.iter_with_counts() {
+            bag.insert_many(value.clone(), count.min(other.count(value)));
+        }
+        bag
+    }
+
+    /// Return the difference, with each count reduced by the other count, stopping at zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: HashBag<u8> = vec![1, 1, 2].into_iter().collect();
+    /// let b: HashBag<u8> = vec![1, 3].into_iter().collect();
+    /// let c = a.difference(&b);
+    /// assert_eq!(c.count(&1), 1);
+    /// assert_eq!(c.count(&2), 1);
+    /// assert_eq!(c.len(), 2);
+    /// ```
+    #[inline]
+    pub fn difference(&self, other: &HashBag<T>) -> HashBag<T>
+    where
+        T: Clone,
+    {
+        let mut bag = HashBag::new();
+        for (value, count) in self.iter_with_counts() {
+            bag.insert_many(value.clone(), count.saturating_sub(other.count(value)));
+        }
+        bag
+    }
+
+    /// Return the sum, with each value at the total of its two counts.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: HashBag<u8> = vec![1, 1, 2].into_iter().collect();
+    /// let b: HashBag<u8> = vec![1, 3].into_iter().collect();
+    /// let c = a.sum(&b);
+    /// assert_eq!(c.count(&1), 3);
+    /// assert_eq!(c.len(), 5);
+    /// ```
+    #[inline]
+    pub fn sum(&self, other: &HashBag<T>) -> HashBag<T>
+    where
+        T: Clone,
+    {
+        let mut bag = self.clone();
+        for (value, count) in other.iter_with_counts() {
+            bag.insert_many(value.clone(), count);
+        }
+        bag
+    }
+}
+
+impl<T> Default for HashBag<T> {
+    fn default() -> Self {
+        HashBag::new()
+    }
+}
+
+impl<T> PartialEq for HashBag<T>
+where
+    T: Hash + Eq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.map == other.map
+    }
+}
+
+impl<T> Eq for HashBag<T>
+where
+    T: Hash + Eq,
+{}
+
+impl<T> FromIterator<T> for HashBag<T>
+where
+    T: Hash + Eq,
+{
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut bag = HashBag::new();
+        bag.extend(iter);
+        bag
+    }
+}
+
+impl<T> Extend<T> for HashBag<T>
+where
+    T: Hash + Eq,
+{
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.insert(value);
+        }
+    }
+}
+
+impl<T> BitOr<&HashBag<T>> for &HashBag<T>
+where
+    T: Clone + Hash + Eq,
+{
+    type Output = HashBag<T>;
+
+    /// Return the union, like `union`.
+    #[inline]
+    fn bitor(self, other: &HashBag<T>) -> HashBag<T> {
+        self.union(other)
+    }
+}
+
+impl<T> BitAnd<&HashBag<T>> for &HashBag<T>
+where
+    T: Clone + Hash + Eq,
+{
+    type Output = HashBag<T>;
+
+    /// Return the intersection, like `intersection`.
+    #[inline]
+    fn bitand(self, other: &HashBag<T>) -> HashBag<T> {
+        self.intersection(other)
+    }
+}
+
+impl<T> Sub<&HashBag<T>> for &HashBag<T>
+where
+    T: Clone + Hash + Eq,
+{
+    type Output = HashBag<T>;
+
+    /// Return the difference, like `difference`.
+    #[inline]
+    fn sub(self, other: &HashBag<T>) -> HashBag<T> {
+        self.difference(other)
+    }
+}
+
+impl<T> Add<&HashBag<T>> for &HashBag<T>
+where
+    T: Clone + Hash + Eq,
+{
+    type Output = HashBag<T>;
+
+    /// Return the sum, like `sum`.
+    #[inline]
+    fn add(self, other: &HashBag<T>) -> HashBag<T> {
+        self.sum(other)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+
+    #[test]
+    /// Test `insert`, `count`, `len`, and `distinct_len` with duplicate items.
+    fn test_insert_and_count() {
+        let mut subject: HashBag<u8> = HashBag::new();
+        let v1 = 1;
+        let v2 = 2;
+        let absent = 9;
+        assert_eq!(subject.insert(v1), 1);
+        assert_eq!(subject.insert(v1), 2);
+        assert_eq!(subject.insert(v2), 1);
+        assert_eq!(subject.count(&v1), 2);
+        assert_eq!(subject.count(&v2), 1);
+        assert_eq!(subject.count(&absent), 0);
+        assert_eq!(subject.len(), 3);
+        assert_eq!(subject.distinct_len(), 2);
+    }
+
+    #[test]
+    /// Test `insert_many` with zero and nonzero occurrences.
+    fn test_insert_many() {
+        let mut subject: HashBag<u8> = HashBag::new();
+        assert_eq!(subject.insert_many(1, 0), 0);
+        assert_eq!(subject.contains(&1), false);
+        assert_eq!(subject.insert_many(1, 3), 3);
+        assert_eq!(subject.len(), 3);
+    }
+
+    #[test]
+    /// Test `remove` and `remove_all` keep `len` in step and drop values at zero.
+    fn test_remove_and_remove_all() {
+        let mut subject: HashBag<u8> = HashBag::new();
+        let v1 = 1;
+        let v2 = 2;
+        subject.insert_many(v1, 2);
+        subject.insert_many(v2, 3);
+        assert_eq!(subject.remove(&v1), true);
+        assert_eq!(subject.remove(&v1), true);
+        assert_eq!(subject.remove(&v1), false);
+        assert_eq!(subject.contains(&v1), false);
+        assert_eq!(subject.distinct_len(), 1);
+        assert_eq!(subject.remove_all(&v2), 3);
+        assert_eq!(subject.remove_all(&v2), 0);
+        assert_eq!(subject.is_empty(), true);
+    }
+
+    #[test]
+    /// Test `count` and `remove` with borrowed forms of the value.
+    fn test_count_x_borrowed_forms() {
+        let mut subject: HashBag<String> = HashBag::new();
+        subject.insert(String::from("alpha"));
+        assert_eq!(subject.count("alpha"), 1);
+        assert_eq!(subject.remove("alpha"), true);
+    }
+
+    #[test]
+    /// Test `iter` and `iter_with_counts` with duplicate items.
+    fn test_iter_and_iter_with_counts() {
+        let subject: HashBag<u8> = vec![2, 1, 2].into_iter().collect();
+        let mut actual: Vec<&u8> = subject.iter().collect();
+        actual.sort();
+        assert_eq!(actual, vec![&1, &2, &2]);
+        let mut actual: Vec<(&u8, usize)> = subject.iter_with_counts().collect();
+        actual.sort();
+        assert_eq!(actual, vec![(&1, 1), (&2, 2)]);
+    }
+
+    #[test]
+    /// Test the set-algebra methods and operators agree.
+    fn test_set_algebra() {
+        let a: HashBag<u8> = vec![1, 1, 2].into_iter().collect();
+        let b: HashBag<u8> = vec![1, 3].into_iter().collect();
+        let union: HashBag<u8> = vec![1, 1, 2, 3].into_iter().collect();
+        let intersection: HashBag<u8> = vec![1].into_iter().collect();
+        let difference: HashBag<u8> = vec![1, 2].into_iter().collect();
+        let sum: HashBag<u8> = vec![1, 1, 1, 2, 3].into_iter().collect();
+        assert_eq!(a.union(&b), union);
+        assert_eq!(a.intersection(&b), intersection);
+        assert_eq!(a.difference(&b), difference);
+        assert_eq!(a.sum(&b), sum);
+        assert_eq!(&a | &b, union);
+        assert_eq!(&a & &b, intersection);
+        assert_eq!(&a - &b, difference);
+        assert_eq!(&a + &b, sum);
+        assert_eq!(intersection.is_subbag(&a), true);
+        assert_eq!(a.is_subbag(&intersection), false);
+    }
+}
@@ -0,0 +1,415 @@
+use std::collections::HashMap;
+use std::borrow::Borrow;
+use std::cmp::Eq;
+use std::hash::Hash;
+use std::iter::FromIterator;
+
+/// A bijective map of unique left values to unique right values.
+///
+/// The map keeps a `HashMap<L, R>` and a `HashMap<R, L>` in step, so a
+/// lookup works in either direction, and each left value and each right
+/// value appears in at most one pair.
+///
+/// # Examples
+///
+/// ```
+/// use sixarm_collections::*;
+/// let mut a: HashBiMap<u8, char> = HashBiMap::new();
+/// a.insert(1, 'a');
+/// assert_eq!(a.get_by_left(&1), Some(&'a'));
+/// assert_eq!(a.get_by_right(&'a'), Some(&1));
+/// ```
+#[derive(Debug, Clone)]
+pub struct HashBiMap<L, R> {
+    left: HashMap<L, R>,
+    right: HashMap<R, L>,
+}
+
+impl<L, R> HashBiMap<L, R> {
+
+    /// Create an empty map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: HashBiMap<u8, char> = HashBiMap::new();
+    /// assert_eq!(a.is_empty(), true);
+    /// ```
+    #[inline]
+    pub fn new() -> Self {
+        HashBiMap { left: HashMap::new(), right: HashMap::new() }
+    }
+
+    /// Return the number of pairs in the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashBiMap<u8, char> = HashBiMap::new();
+    /// a.insert(1, 'a');
+    /// assert_eq!(a.len(), 1);
+    /// ```
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.left.len()
+    }
+
+    /// Return `true` if the map has no pairs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: HashBiMap<u8, char> = HashBiMap::new();
+    /// assert_eq!(a.is_empty(), true);
+    /// ```
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.left.is_empty()
+    }
+
+    /// Remove every pair from the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashBiMap<u8, char> = HashBiMap::new();
+    /// a.insert(1, 'a');
+    /// a.clear();
+    /// assert_eq!(a.is_empty(), true);
+    /// ```
+    #[inline]
+    pub fn clear(&mut self) {
+        self.left.clear();
+        self.right.clear();
+    }
+
+    /// Return an iterator over every `(&L, &R)` pair in arbitrary order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashBiMap<u8, char> = HashBiMap::new();
+    /// a.insert(1, 'a');
+    /// let pairs: Vec<(&u8, &char)> = a.iter().collect();
+    /// assert_eq!(pairs, vec![(&1, &'a')]);
+    /// ```
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = (&L, &R)> {
+        self.left.iter()
+    }
+
+}
+
+impl<L, R> HashBiMap<L, R>
+where
+    L: Hash + Eq,
+    R: Hash + Eq,
+{
+
+    /// Insert a pair, and remove any pairs that share its left or right value.
+    ///
+    /// Return the removed pairs, so the caller can see what was displaced.
+    /// Inserting a pair that is already present returns that pair.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashBiMap<u8, char> = HashBiMap::new();
+    /// a.insert(1, 'a');
+    /// a.insert(2, 'b');
+    /// let displaced = a.insert(1, 'b');
+    /// assert_eq!(displaced.len(), 2);
+    /// assert_eq!(a.len(), 1);
+    /// assert_eq!(a.get_by_left(&1), Some(&'b'));
+    /// ```
+    #[inline]
+    pub fn insert(&mut self, left: L, right: R) -> Vec<(L, R)>
+    where
+        L: Clone,
+        R: Clone,
+    {
+        let mut displaced = Vec::new();
+        if let Some(pair) = self.remove_by_left(&left) {
+            displaced.push(pair);
+        }
+        if let Some(pair) = self.remove_by_right(&right) {
+            displaced.push(pair);
+        }
+        self.left.insert(left.clone(), right.clone());
+        self.right.insert(right, left);
+        displaced
+    }
+
+    /// Insert a pair only if neither its left nor its right value is present.
+    ///
+    /// Return `Err` with the pair, unchanged, if either value is present.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashBiMap<u8, char> = HashBiMap::new();
+    /// assert_eq!(a.insert_no_overwrite(1, 'a'), Ok(()));
+    /// assert_eq!(a.insert_no_overwrite(1, 'b'), Err((1, 'b')));
+    /// assert_eq!(a.insert_no_overwrite(2, 'a'), Err((2, 'a')));
+    /// ```
+    #[inline]
+    pub fn insert_no_overwrite(&mut self, left: L, right: R) -> Result<(), (L, R)>
+    where
+        L: Clone,
+        R: Clone,
+    {
+        if self.left.contains_key(&left) || self.right.contains_key(&right) {
+            return Err((left, right));
+        }
+        self.left.insert(left.clone(), right.clone());
+        self.right.insert(right, left);
+        Ok(())
+    }
+
+    /// Return the right value paired with a left value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashBiMap<u8, char> = HashBiMap::new();
+    /// a.insert(1, 'a');
+    /// assert_eq!(a.get_by_left(&1), Some(&'a'));
+    /// assert_eq!(a.get_by_left(&2), None);
+    /// ```
+    #[inline]
+    pub fn get_by_left<Q>(&self, left: &Q) -> Option<&R>
+    where
+        L: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.left.get(left)
+    }
+
+    /// Return the left value paired with a right value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashBiMap<u8, char> = HashBiMap::new();
+    /// a.insert(1, 'a');
+    /// assert_eq!(a.get_by_right(&'a'), Some(&1));
+    /// assert_eq!(a.get_by_right(&'b'), None);
+    /// ```
+    #[inline]
+    pub fn get_by_right<Q>(&self, right: &Q) -> Option<&L>
+    where
+        R: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.right.get(right)
+    }
+
+    /// Return `true` if the map has a pair with this left value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashBiMap<u8, char> = HashBiMap::new();
+    /// a.insert(1, 'a');
+    /// assert_eq!(a.contains_left(&1), true);
+    /// ```
+    #[inline]
+    pub fn contains_left<Q>(&self, left: &Q) -> bool
+    where
+        L: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.left.contains_key(left)
+    }
+
+    /// Return `true` if the map has a pair with this right value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashBiMap<u8, char> = HashBiMap::new();
+    /// a.insert(1, 'a');
+    /// assert_eq!(a.contains_right(&'a'), true);
+    /// ```
+    #[inline]
+    pub fn contains_right<Q>(&self, right: &Q) -> bool
+    where
+        R: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.right.contains_key(right)
+    }
+
+    /// Remove the pair with this left value from both directions, and return it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashBiMap<u8, char> = HashBiMap::new();
+    /// a.insert(1, 'a');
+    /// assert_eq!(a.remove_by_left(&1), Some((1, 'a')));
+    /// assert_eq!(a.contains_right(&'a'), false);
+    /// ```
+    #[inline]
+    pub fn remove_by_left<Q>(&mut self, left: &Q) -> Option<(L, R)>
+    where
+        L: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let right = self.left.remove(left)?;
+        let left = self.right.remove(&right)?;
+        Some((left, right))
+    }
+
+    /// Remove the pair with this right value from both directions, and return it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashBiMap<u8, char> = HashBiMap::new();
+    /// a.insert(1, 'a');
+    /// assert_eq!(a.remove_by_right(&'a'), Some((1, 'a')));
+    /// assert_eq!(a.contains_left(&1), false);
+    /// ```
+    #[inline]
+    pub fn remove_by_right<Q>(&mut self, right: &Q) -> Option<(L, R)>
+    where
+        R: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let left = self.right.remove(right)?;
+        let right = self.left.remove(&left)?;
+        Some((left, right))
+    }
+
+}
+
+impl<L, R> Default for HashBiMap<L, R> {
+    fn default() -> Self {
+        HashBiMap::new()
+    }
+}
+
+impl<L, R> PartialEq for HashBiMap<L, R>
+where
+    L: Hash + Eq,
+    R: Hash + Eq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.left == other.left
+    }
+}
+
+impl<L, R> Eq for HashBiMap<L, R>
+where
+    L: Hash + Eq,
+    R: Hash + Eq,
+{}
+
+impl<L, R> FromIterator<(L, R)> for HashBiMap<L, R>
+where
+    L: Clone + Hash + Eq,
+    R: Clone + Hash + Eq,
+{
+    fn from_iter<I: IntoIterator<Item = (L, R)>>(iter: I) -> Self {
+        let mut map = HashBiMap::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<L, R> Extend<(L, R)> for HashBiMap<L, R>
+where
+    L: Clone + Hash + Eq,
+    R: Clone + Hash + Eq,
+{
+    fn extend<I: IntoIterator<Item = (L, R)>>(&mut self, iter: I) {
+        for (left, right) in iter {
+            self.insert(left, right);
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+
+    #[test]
+    /// Test `insert` with fresh pairs keeps both directions in step.
+    fn test_insert() {
+        let mut subject: HashBiMap<u8, char> = HashBiMap::new();
+        assert_eq!(subject.insert(1, 'a'), vec![]);
+        assert_eq!(subject.insert(2, 'b'), vec![]);
+        assert_eq!(subject.len(), 2);
+        assert_eq!(subject.get_by_left(&1), Some(&'a'));
+        assert_eq!(subject.get_by_left(&2), Some(&'b'));
+        assert_eq!(subject.get_by_right(&'a'), Some(&1));
+        assert_eq!(subject.get_by_right(&'b'), Some(&2));
+    }
+
+    #[test]
+    /// Test `insert` displaces pairs that share the left value, the right value, or both.
+    fn test_insert_x_displaced() {
+        let mut subject: HashBiMap<u8, char> = HashBiMap::new();
+        subject.insert(1, 'a');
+        subject.insert(2, 'b');
+        assert_eq!(subject.insert(1, 'c'), vec![(1, 'a')]);
+        assert_eq!(subject.contains_right(&'a'), false);
+        let mut displaced = subject.insert(2, 'c');
+        displaced.sort();
+        assert_eq!(displaced, vec![(1, 'c'), (2, 'b')]);
+        assert_eq!(subject.len(), 1);
+        assert_eq!(subject.get_by_right(&'c'), Some(&2));
+        assert_eq!(subject.insert(2, 'c'), vec![(2, 'c')]);
+        assert_eq!(subject.len(), 1);
+    }
+
+    #[test]
+    /// Test `insert_no_overwrite` leaves the map unchanged on a collision.
+    fn test_insert_no_overwrite() {
+        let mut subject: HashBiMap<u8, char> = HashBiMap::new();
+        assert_eq!(subject.insert_no_overwrite(1, 'a'), Ok(()));
+        assert_eq!(subject.insert_no_overwrite(1, 'b'), Err((1, 'b')));
+        assert_eq!(subject.insert_no_overwrite(2, 'a'), Err((2, 'a')));
+        assert_eq!(subject.len(), 1);
+        assert_eq!(subject.get_by_left(&1), Some(&'a'));
+    }
+
+    #[test]
+    /// Test `remove_by_left` and `remove_by_right` remove both directions.
+    fn test_remove_by_left_and_remove_by_right() {
+        let mut subject: HashBiMap<u8, char> = vec![(1, 'a'), (2, 'b')].into_iter().collect();
+        assert_eq!(subject.remove_by_left(&1), Some((1, 'a')));
+        assert_eq!(subject.remove_by_left(&1), None);
+        assert_eq!(subject.contains_right(&'a'), false);
+        assert_eq!(subject.remove_by_right(&'b'), Some((2, 'b')));
+        assert_eq!(subject.remove_by_right(&'b'), None);
+        assert_eq!(subject.contains_left(&2), false);
+        assert_eq!(subject.is_empty(), true);
+    }
+
+    #[test]
+    /// Test lookups with borrowed forms of the left and right values.
+    fn test_get_x_borrowed_forms() {
+        let mut subject: HashBiMap<String, String> = HashBiMap::new();
+        subject.insert(String::from("alpha"), String::from("bravo"));
+        assert_eq!(subject.get_by_left("alpha"), Some(&String::from("bravo")));
+        assert_eq!(subject.get_by_right("bravo"), Some(&String::from("alpha")));
+        assert_eq!(subject.remove_by_right("bravo"), Some((String::from("alpha"), String::from("bravo"))));
+    }
+
+}
@@ -0,0 +1,181 @@
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::cmp::Eq;
+use std::hash::Hash;
+
+/// A map of `Cow<str>` keys to sets of values, for building indexes from
+/// text without cloning keys that already exist.
+///
+/// A key is either borrowed from text that outlives the map, or owned.
+/// The insert methods look up the key as a `&str` first, and only make
+/// a key when it is new; `sub_insert_str` allocates a `String` only then.
+///
+/// A `HashMapOfCowStrToSet` is a `HashMapToSet`, so the `HashMapToSetExt`
+/// methods such as `sub_contains` and `sub_remove` also work, with
+/// lookups by `&str`.
+pub type HashMapOfCowStrToSet<'a, V> = HashMap<Cow<'a, str>, HashSet<V>>;
+
+pub trait HashMapOfCowStrToSetExt<'a, V> {
+
+    fn sub_insert_str(&mut self, key: &str, value: V) -> bool
+    where
+        V: Hash + Eq;
+
+    fn sub_insert_borrowed(&mut self, key: &'a str, value: V) -> bool
+    where
+        V: Hash + Eq;
+
+    fn sub_owned_key_count(&self) -> usize;
+
+    fn into_owned_keys(self) -> HashMapOfCowStrToSet<'static, V>;
+
+}
+
+impl<'a, V> HashMapOfCowStrToSetExt<'a, V> for HashMapOfCowStrToSet<'a, V> {
+
+    /// Add a sub-key-value item, copying the key into a new `String` only if the key is new.
+    ///
+    /// Use this when the key is borrowed from a short-lived buffer.
+    /// Return whether the item is added in the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapOfCowStrToSet<u8> = HashMapOfCowStrToSet::new();
+    /// let mut line = String::from("alpha");
+    /// assert_eq!(a.sub_insert_str(&line, 1), true);
+    /// line.clear();
+    /// line.push_str("alpha");
+    /// assert_eq!(a.sub_insert_str(&line, 2), true);
+    /// assert_eq!(a.sub_contains("alpha", &2), true);
+    /// ```
+    #[inline]
+    fn sub_insert_str(&mut self, key: &str, value: V) -> bool
+    where
+        V: Hash + Eq,
+    {
+        match self.get_mut(key) {
+            Some(set) => set.insert(value),
+            None => {
+                self.insert(Cow::Owned(key.to_owned()), HashSet::from([value]));
+                true
+            },
+        }
+    }
+
+    /// Add a sub-key-value item, borrowing the key if it is new, so this never allocates a key.
+    ///
+    /// Use this when the key is borrowed from text that outlives the map.
+    /// Return whether the item is added in the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let text = String::from("alpha bravo alpha");
+    /// let mut a: HashMapOfCowStrToSet<usize> = HashMapOfCowStrToSet::new();
+    /// for (i, word) in text.split(' ').enumerate() {
+    ///     a.sub_insert_borrowed(word, i);
+    /// }
+    /// assert_eq!(a.sub_len_of("alpha"), 2);
+    /// assert_eq!(a.sub_owned_key_count(), 0);
+    /// ```
+    #[inline]
+    fn sub_insert_borrowed(&mut self, key: &'a str, value: V) -> bool
+    where
+        V: Hash + Eq,
+    {
+        self.entry(Cow::Borrowed(key))
+        .or_default()
+        .insert(value)
+    }
+
+    /// Return the number of keys that own their text.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapOfCowStrToSet<u8> = HashMapOfCowStrToSet::new();
+    /// a.sub_insert_borrowed("alpha", 1);
+    /// a.sub_insert_str("bravo", 2);
+    /// assert_eq!(a.sub_owned_key_count(), 1);
+    /// ```
+    #[inline]
+    fn sub_owned_key_count(&self) -> usize {
+        self.keys().filter(|key| matches!(key, Cow::Owned(_))).count()
+    }
+
+    /// Consume the collection and return one whose keys all own their text.
+    ///
+    /// Only borrowed keys are copied; owned keys are moved.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let b: HashMapOfCowStrToSet<'static, u8> = {
+    ///     let text = String::from("alpha");
+    ///     let mut a: HashMapOfCowStrToSet<u8> = HashMapOfCowStrToSet::new();
+    ///     a.sub_insert_borrowed(&text, 1);
+    ///     a.into_owned_keys()
+    /// };
+    /// assert_eq!(b.sub_contains("alpha", &1), true);
+    /// ```
+    #[inline]
+    fn into_owned_keys(self) -> HashMapOfCowStrToSet<'static, V> {
+        self.into_iter()
+        .map(|(key, set)| (Cow::Owned(key.into_owned()), set))
+        .collect()
+    }
+
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+    use crate::hash_map_to_set::HashMapToSetExt;
+
+    #[test]
+    /// Test `sub_insert_str` allocates a key only when the key is new.
+    fn test_sub_insert_str() {
+        let mut subject: HashMapOfCowStrToSet<u8> = HashMapOfCowStrToSet::new();
+        subject.sub_insert_borrowed("alpha", 1);
+        assert_eq!(subject.sub_insert_str("alpha", 2), true);
+        assert_eq!(subject.sub_insert_str("alpha", 2), false);
+        assert_eq!(subject.sub_owned_key_count(), 0);
+        assert_eq!(subject.sub_insert_str("bravo", 3), true);
+        assert_eq!(subject.sub_owned_key_count(), 1);
+        assert_eq!(subject.sub_len(), 3);
+    }
+
+    #[test]
+    /// Test `sub_insert_borrowed` and `HashMapToSetExt` methods with `&str` lookups.
+    fn test_sub_insert_borrowed_x_hash_map_to_set_ext() {
+        let text = String::from("a b a c");
+        let mut subject: HashMapOfCowStrToSet<usize> = HashMapOfCowStrToSet::new();
+        for (i, word) in text.split(' ').enumerate() {
+            subject.sub_insert_borrowed(word, i);
+        }
+        assert_eq!(subject.len(), 3);
+        assert_eq!(subject.sub_contains("a", &2), true);
+        assert_eq!(subject.sub_remove_prune("c", &3), true);
+        assert_eq!(subject.contains_key("c"), false);
+    }
+
+    #[test]
+    /// Test `into_owned_keys` keeps every pair and owns every key.
+    fn test_into_owned_keys() {
+        let text = String::from("alpha");
+        let mut subject: HashMapOfCowStrToSet<u8> = HashMapOfCowStrToSet::new();
+        subject.sub_insert_borrowed(&text, 1);
+        subject.sub_insert_str("bravo", 2);
+        let actual = subject.into_owned_keys();
+        drop(text);
+        assert_eq!(actual.sub_owned_key_count(), 2);
+        assert_eq!(actual.sub_len(), 2);
+    }
+
+}
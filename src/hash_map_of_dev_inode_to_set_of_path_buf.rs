@@ -0,0 +1,414 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use crate::path_scan::{self, PathErrors, SymlinkPolicy, WalkOptions};
+
+/// A map of file identities to sets of paths, for finding hard links.
+///
+/// Each key is the pair of the device id and the inode number of a
+/// file, as in `st_dev` and `st_ino`, so the paths in one set are hard
+/// links to the same file, and take its space on disk once.
+///
+/// A symbolic link is not a hard link of its target, so the collection
+/// reads each path's own metadata, and skips symbolic links, except in
+/// a walk with `SymlinkPolicy::Record`, which keys a link by its own
+/// device id and inode number.
+///
+/// This collection is only on unix.
+pub type HashMapOfDevInodeToSetOfPathBuf = HashMap<(u64, u64), HashSet<PathBuf>>;
+
+/// Return the device id and the inode number of a file.
+#[inline]
+fn dev_inode(metadata: &fs::Metadata) -> (u64, u64) {
+    (metadata.dev(), metadata.ino())
+}
+
+/// Return the device id and the inode number of a path's own file, or
+/// `None` if the path is a symbolic link.
+fn own_dev_inode(path: &Path) -> io::Result<Option<(u64, u64)>> {
+    let metadata = fs::symlink_metadata(path)?;
+    Ok(if metadata.file_type().is_symlink() { None } else { Some(dev_inode(&metadata)) })
+}
+
+/// Return `true` if a walk reached a path through a symbolic link, so
+/// its metadata is the target's: a root, which a walk always follows,
+/// or any path in a walk that follows links.
+fn is_followed_link(path: &Path, root: &Path, options: &WalkOptions) -> bool {
+    let policy = options.get_symlinks();
+    policy != SymlinkPolicy::Record
+    && (policy == SymlinkPolicy::Follow || path == root)
+    && fs::symlink_metadata(path).is_ok_and(|metadata| metadata.file_type().is_symlink())
+}
+
+pub trait HashMapOfDevInodeToSetOfPathBufExt {
+    fn try_sub_contains_path<P: AsRef<Path>>(&self, value: P) -> io::Result<bool>;
+    fn try_sub_insert_path(&mut self, value: PathBuf) -> io::Result<bool>;
+    fn try_sub_remove_path<P: AsRef<Path>>(&mut self, value: P) -> io::Result<bool>;
+    fn hardlink_groups(&self) -> impl Iterator<Item = (&(u64, u64), &HashSet<PathBuf>)>;
+    fn try_sub_insert_dir<P: AsRef<Path>>(&mut self, dir: P) -> io::Result<PathErrors>;
+    fn from_dir<P: AsRef<Path>>(dir: P) -> io::Result<(Self, PathErrors)> where Self: Sized;
+    fn sub_insert_tree<P: AsRef<Path>>(&mut self, root: P, options: &WalkOptions) -> io::Result<PathErrors>;
+    #[cfg(feature = "rayon")]
+    fn par_sub_insert_tree<P: AsRef<Path>>(&mut self, root: P, options: &WalkOptions) -> io::Result<PathErrors>;
+    fn prune_stale(&mut self) -> Vec<PathBuf>;
+}
+
+impl HashMapOfDevInodeToSetOfPathBufExt for HashMapOfDevInodeToSetOfPathBuf {
+
+    /// Return `true` if the collection contains a sub-key-value item.
+    ///
+    /// Return an error if the file metadata can't be read. The path is
+    /// borrowed, and may be any type that converts to a `&Path`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// use std::path::PathBuf;
+    ///
+    /// let mut a: HashMapOfDevInodeToSetOfPathBuf = HashMapOfDevInodeToSetOfPathBuf::new();
+    /// # std::fs::write("dev_inode_contains.txt", "alpha");
+    /// a.try_sub_insert_path(PathBuf::from("dev_inode_contains.txt"))?;
+    /// assert_eq!(a.try_sub_contains_path("dev_inode_contains.txt")?, true);
+    /// assert_eq!(a.try_sub_contains_path("missing.txt").is_err(), true);
+    /// # std::fs::remove_file("dev_inode_contains.txt");
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[inline]
+    fn try_sub_contains_path<P: AsRef<Path>>(&self, value: P) -> io::Result<bool> {
+        let value = value.as_ref();
+        Ok(match own_dev_inode(value)?.and_then(|key| self.get(&key)) {
+            Some(set) => set.contains(value),
+            None => false,
+        })
+    }
+
+    /// Add a sub-key-value item to the collection.
+    ///
+    /// Return whether the item is added in the set, or an error if the
+    /// file metadata can't be read. On error the collection is unchanged.
+    /// A symbolic link is skipped, and returns `false`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// use std::path::PathBuf;
+    ///
+    /// let mut a: HashMapOfDevInodeToSetOfPathBuf = HashMapOfDevInodeToSetOfPathBuf::new();
+    /// # std::fs::write("dev_inode_insert.txt", "alpha");
+    /// assert_eq!(a.try_sub_insert_path(PathBuf::from("dev_inode_insert.txt"))?, true);
+    /// assert_eq!(a.try_sub_insert_path(PathBuf::from("missing.txt")).is_err(), true);
+    /// # std::fs::remove_file("dev_inode_insert.txt");
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[inline]
+    fn try_sub_insert_path(&mut self, value: PathBuf) -> io::Result<bool> {
+        Ok(match own_dev_inode(&value)? {
+            Some(key) => self.entry(key).or_default().insert(value),
+            None => false,
+        })
+    }
+
+    /// Remove a sub-key-value pair from the collection.
+    ///
+    /// Return whether the value was present in the set, or an error if
+    /// the file metadata can't be read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// use std::path::PathBuf;
+    ///
+    /// let mut a: HashMapOfDevInodeToSetOfPathBuf = HashMapOfDevInodeToSetOfPathBuf::new();
+    /// # std::fs::write("dev_inode_remove.txt", "alpha");
+    /// a.try_sub_insert_path(PathBuf::from("dev_inode_remove.txt"))?;
+    /// assert_eq!(a.try_sub_remove_path("dev_inode_remove.txt")?, true);
+    /// assert_eq!(a.try_sub_contains_path("dev_inode_remove.txt")?, false);
+    /// # std::fs::remove_file("dev_inode_remove.txt");
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[inline]
+    fn try_sub_remove_path<P: AsRef<Path>>(&mut self, value: P) -> io::Result<bool> {
+        let value = value.as_ref();
+        Ok(match own_dev_inode(value)?.and_then(|key| self.get_mut(&key)) {
+            Some(set) => set.remove(value),
+            None => false,
+        })
+    }
+
+    /// Return an iterator over the files that have more than one path,
+    /// each with its set of hard links.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// use std::path::PathBuf;
+    ///
+    /// let mut a: HashMapOfDevInodeToSetOfPathBuf = HashMapOfDevInodeToSetOfPathBuf::new();
+    /// # std::fs::write("hardlink_groups_alpha.txt", "alpha");
+    /// # std::fs::hard_link("hardlink_groups_alpha.txt", "hardlink_groups_bravo.txt");
+    /// a.try_sub_insert_path(PathBuf::from("hardlink_groups_alpha.txt"))?;
+    /// a.try_sub_insert_path(PathBuf::from("hardlink_groups_bravo.txt"))?;
+    /// assert_eq!(a.hardlink_groups().count(), 1);
+    /// # std::fs::remove_file("hardlink_groups_alpha.txt");
+    /// # std::fs::remove_file("hardlink_groups_bravo.txt");
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[inline]
+    fn hardlink_groups(&self) -> impl Iterator<Item = (&(u64, u64), &HashSet<PathBuf>)> {
+        self.iter()
+        .filter(|(_, paths)| paths.len() > 1)
+    }
+
+    /// Add every regular file in a directory, not recursively.
+    ///
+    /// Return an error if the directory can't be read. Otherwise return
+    /// a report of each entry that can't be read, with its error; the
+    /// scan skips those entries and adds the rest.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    ///
+    /// let mut a: HashMapOfDevInodeToSetOfPathBuf = HashMapOfDevInodeToSetOfPathBuf::new();
+    /// # std::fs::create_dir_all("dev_inode_try_sub_insert_dir");
+    /// # std::fs::write("dev_inode_try_sub_insert_dir/alpha.txt", "alpha");
+    /// let errors = a.try_sub_insert_dir("dev_inode_try_sub_insert_dir")?;
+    /// assert_eq!(errors.is_empty(), true);
+    /// assert_eq!(a.try_sub_contains_path("dev_inode_try_sub_insert_dir/alpha.txt")?, true);
+    /// # std::fs::remove_dir_all("dev_inode_try_sub_insert_dir");
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[inline]
+    fn try_sub_insert_dir<P: AsRef<Path>>(&mut self, dir: P) -> io::Result<PathErrors> {
+        path_scan::scan_dir(dir.as_ref(), SymlinkPolicy::Skip, |path, metadata| {
+            self.entry(dev_inode(metadata))
+            .or_default()
+            .insert(path);
+        })
+    }
+
+    /// Create a collection of every regular file in a directory, not recursively.
+    ///
+    /// Return an error if the directory can't be read. Otherwise return
+    /// the collection and a report of each entry that can't be read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    ///
+    /// # std::fs::create_dir_all("dev_inode_from_dir");
+    /// # std::fs::write("dev_inode_from_dir/alpha.txt", "alpha");
+    /// let (a, errors) = HashMapOfDevInodeToSetOfPathBuf::from_dir("dev_inode_from_dir")?;
+    /// assert_eq!(errors.is_empty(), true);
+    /// assert_eq!(a.try_sub_contains_path("dev_inode_from_dir/alpha.txt")?, true);
+    /// # std::fs::remove_dir_all("dev_inode_from_dir");
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[inline]
+    fn from_dir<P: AsRef<Path>>(dir: P) -> io::Result<(Self, PathErrors)> {
+        let mut map = Self::new();
+        let errors = map.try_sub_insert_dir(dir)?;
+        Ok((map, errors))
+    }
+
+    /// Add every regular file in a directory tree, walking it with options
+    /// for the max depth, symbolic links, and path predicates.
+    ///
+    /// Return an error if the root can't be read. Otherwise return a
+    /// report of each path below the root that can't be read, with its
+    /// error; the walk skips those paths and adds the rest.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    ///
+    /// let mut a: HashMapOfDevInodeToSetOfPathBuf = HashMapOfDevInodeToSetOfPathBuf::new();
+    /// # std::fs::create_dir_all("dev_inode_sub_insert_tree/one");
+    /// # std::fs::write("dev_inode_sub_insert_tree/alpha.txt", "alpha");
+    /// # std::fs::write("dev_inode_sub_insert_tree/one/bravo.txt", "bravo");
+    /// let errors = a.sub_insert_tree("dev_inode_sub_insert_tree", &WalkOptions::new())?;
+    /// assert_eq!(errors.is_empty(), true);
+    /// assert_eq!(a.try_sub_contains_path("dev_inode_sub_insert_tree/one/bravo.txt")?, true);
+    /// # std::fs::remove_dir_all("dev_inode_sub_insert_tree");
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[inline]
+    fn sub_insert_tree<P: AsRef<Path>>(&mut self, root: P, options: &WalkOptions) -> io::Result<PathErrors> {
+        let root = root.as_ref();
+        path_scan::walk_tree(root, options, |path, metadata| {
+            if is_followed_link(&path, root, options) {
+                return;
+            }
+            self.entry(dev_inode(metadata))
+            .or_default()
+            .insert(path);
+        })
+    }
+
+    /// Add every regular file in a directory tree, like `sub_insert_tree`,
+    /// and read the directories and file metadata of each depth on multiple threads.
+    ///
+    /// This method needs the `rayon` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    ///
+    /// let mut a: HashMapOfDevInodeToSetOfPathBuf = HashMapOfDevInodeToSetOfPathBuf::new();
+    /// # std::fs::create_dir_all("dev_inode_par_sub_insert_tree/one");
+    /// # std::fs::write("dev_inode_par_sub_insert_tree/one/bravo.txt", "bravo");
+    /// let errors = a.par_sub_insert_tree("dev_inode_par_sub_insert_tree", &WalkOptions::new())?;
+    /// assert_eq!(errors.is_empty(), true);
+    /// assert_eq!(a.try_sub_contains_path("dev_inode_par_sub_insert_tree/one/bravo.txt")?, true);
+    /// # std::fs::remove_dir_all("dev_inode_par_sub_insert_tree");
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[cfg(feature = "rayon")]
+    #[inline]
+    fn par_sub_insert_tree<P: AsRef<Path>>(&mut self, root: P, options: &WalkOptions) -> io::Result<PathErrors> {
+        let root = root.as_ref();
+        path_scan::par_walk_tree(root, options, |path, metadata| {
+            if is_followed_link(&path, root, options) {
+                return;
+            }
+            self.entry(dev_inode(metadata))
+            .or_default()
+            .insert(path);
+        })
+    }
+
+
+    /// Remove every path whose file no longer exists, and each file identity
+    /// whose set becomes empty. Return the removed paths, in path order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// use std::path::PathBuf;
+    ///
+    /// let mut a: HashMapOfDevInodeToSetOfPathBuf = HashMapOfDevInodeToSetOfPathBuf::new();
+    /// # std::fs::write("hash_dev_inode_prune_stale.txt", "alpha");
+    /// a.try_sub_insert_path(PathBuf::from("hash_dev_inode_prune_stale.txt"))?;
+    /// # std::fs::remove_file("hash_dev_inode_prune_stale.txt");
+    /// assert_eq!(a.prune_stale(), vec![PathBuf::from("hash_dev_inode_prune_stale.txt")]);
+    /// assert_eq!(a.is_empty(), true);
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[inline]
+    fn prune_stale(&mut self) -> Vec<PathBuf> {
+        path_scan::prune_stale(self)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+
+    #[test]
+    /// Test the `try_` methods.
+    /// Must key hard links to one file together, and other files apart.
+    ///
+    fn test_try_sub_x_path() {
+        let dir = std::env::temp_dir().join(format!("hash_map_of_dev_inode_to_set_of_path_buf_try_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("alpha.txt"), "alpha").unwrap();
+        fs::hard_link(dir.join("alpha.txt"), dir.join("link.txt")).unwrap();
+        fs::write(dir.join("bravo.txt"), "alpha").unwrap();
+        let mut subject: HashMapOfDevInodeToSetOfPathBuf = HashMapOfDevInodeToSetOfPathBuf::new();
+        assert_eq!(subject.try_sub_insert_path(dir.join("alpha.txt")).unwrap(), true);
+        assert_eq!(subject.try_sub_insert_path(dir.join("alpha.txt")).unwrap(), false);
+        assert_eq!(subject.try_sub_insert_path(dir.join("link.txt")).unwrap(), true);
+        assert_eq!(subject.try_sub_contains_path(dir.join("link.txt")).unwrap(), true);
+        assert_eq!(subject.try_sub_contains_path(dir.join("bravo.txt")).unwrap(), false);
+        assert_eq!(subject.len(), 1);
+        assert_eq!(subject.try_sub_remove_path(dir.join("link.txt")).unwrap(), true);
+        assert_eq!(subject.try_sub_remove_path(dir.join("link.txt")).unwrap(), false);
+        let err = subject.try_sub_insert_path(dir.join("missing.txt")).unwrap_err();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    /// Test `sub_insert_tree` and `hardlink_groups`.
+    /// Must group the hard links across directories.
+    ///
+    fn test_sub_insert_tree() {
+        let dir = std::env::temp_dir().join(format!("hash_map_of_dev_inode_to_set_of_path_buf_tree_{}", std::process::id()));
+        fs::create_dir_all(dir.join("one")).unwrap();
+        fs::write(dir.join("alpha.txt"), "alpha").unwrap();
+        fs::hard_link(dir.join("alpha.txt"), dir.join("one").join("alpha.txt")).unwrap();
+        fs::write(dir.join("one").join("bravo.txt"), "alpha").unwrap();
+        let mut subject: HashMapOfDevInodeToSetOfPathBuf = HashMapOfDevInodeToSetOfPathBuf::new();
+        let errors = subject.sub_insert_tree(&dir, &WalkOptions::new()).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(errors.is_empty(), true);
+        assert_eq!(subject.len(), 2);
+        let groups: Vec<&HashSet<PathBuf>> = subject.hardlink_groups().map(|(_, paths)| paths).collect();
+        assert_eq!(groups, vec![&vec![dir.join("alpha.txt"), dir.join("one").join("alpha.txt")].into_iter().collect::<HashSet<PathBuf>>()]);
+    }
+
+    #[test]
+    /// Test a symbolic link next to a hard link.
+    /// Must not key the symbolic link with its target, whatever the policy.
+    ///
+    fn test_x_symlink() {
+        let dir = std::env::temp_dir().join(format!("hash_map_of_dev_inode_to_set_of_path_buf_symlink_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("alpha.txt"), "alpha").unwrap();
+        fs::hard_link(dir.join("alpha.txt"), dir.join("hard.txt")).unwrap();
+        std::os::unix::fs::symlink(dir.join("alpha.txt"), dir.join("soft.txt")).unwrap();
+        let expect: HashSet<PathBuf> = vec![dir.join("alpha.txt"), dir.join("hard.txt")].into_iter().collect();
+        let mut subject: HashMapOfDevInodeToSetOfPathBuf = HashMapOfDevInodeToSetOfPathBuf::new();
+        assert_eq!(subject.try_sub_insert_path(dir.join("alpha.txt")).unwrap(), true);
+        assert_eq!(subject.try_sub_insert_path(dir.join("soft.txt")).unwrap(), false);
+        assert_eq!(subject.try_sub_contains_path(dir.join("soft.txt")).unwrap(), false);
+        assert_eq!(subject.try_sub_remove_path(dir.join("soft.txt")).unwrap(), false);
+        let (from_dir, _) = HashMapOfDevInodeToSetOfPathBuf::from_dir(&dir).unwrap();
+        let mut follow: HashMapOfDevInodeToSetOfPathBuf = HashMapOfDevInodeToSetOfPathBuf::new();
+        follow.sub_insert_tree(&dir, &WalkOptions::new().symlinks(SymlinkPolicy::Follow)).unwrap();
+        follow.sub_insert_tree(dir.join("soft.txt"), &WalkOptions::new()).unwrap();
+        let mut record: HashMapOfDevInodeToSetOfPathBuf = HashMapOfDevInodeToSetOfPathBuf::new();
+        record.sub_insert_tree(&dir, &WalkOptions::new().symlinks(SymlinkPolicy::Record)).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        for map in [&from_dir, &follow] {
+            assert_eq!(map.len(), 1);
+            assert_eq!(map.hardlink_groups().map(|(_, paths)| paths).collect::<Vec<_>>(), vec![&expect]);
+        }
+        assert_eq!(record.len(), 2);
+        assert_eq!(record.hardlink_groups().map(|(_, paths)| paths).collect::<Vec<_>>(), vec![&expect]);
+    }
+
+    #[test]
+    /// Test `prune_stale`.
+    /// Must keep a hard link whose other path is deleted, and remove the file identity when every path is deleted.
+    ///
+    fn test_prune_stale() {
+        let dir = std::env::temp_dir().join(format!("hash_map_of_dev_inode_to_set_of_path_buf_prune_stale_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("alpha.txt"), "alpha").unwrap();
+        fs::hard_link(dir.join("alpha.txt"), dir.join("link.txt")).unwrap();
+        fs::write(dir.join("bravo.txt"), "bravo").unwrap();
+        let (mut subject, _) = HashMapOfDevInodeToSetOfPathBuf::from_dir(&dir).unwrap();
+        fs::remove_file(dir.join("alpha.txt")).unwrap();
+        fs::remove_file(dir.join("bravo.txt")).unwrap();
+        let removed = subject.prune_stale();
+        let link = subject.try_sub_contains_path(dir.join("link.txt")).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(removed, vec![dir.join("alpha.txt"), dir.join("bravo.txt")]);
+        assert_eq!(subject.len(), 1);
+        assert_eq!(link, true);
+    }
+
+}
@@ -0,0 +1,283 @@
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::path::{Path, PathBuf};
+use crate::file_digest::{DigestAlgorithm, FileDigest};
+use crate::path_scan::{self, PathErrors};
+
+/// A map of file content digests to sets of paths.
+///
+/// This is the second stage of duplicate detection: files with the same
+/// length may differ, so this map groups a file length map's candidates
+/// by the digest of their content, which removes the false positives.
+///
+/// This collection needs the `blake3` feature or the `sha2` feature.
+pub type HashMapOfDigestToSetOfPathBuf = HashMap<FileDigest, HashSet<PathBuf>>;
+
+pub trait HashMapOfDigestToSetOfPathBufExt {
+    fn try_sub_contains_path<P: AsRef<Path>>(&self, value: P, algorithm: DigestAlgorithm) -> io::Result<bool>;
+    fn try_sub_insert_path(&mut self, value: PathBuf, algorithm: DigestAlgorithm) -> io::Result<bool>;
+    fn try_sub_remove_path<P: AsRef<Path>>(&mut self, value: P, algorithm: DigestAlgorithm) -> io::Result<bool>;
+    fn try_sub_insert_paths<I: IntoIterator<Item = PathBuf>>(&mut self, values: I, algorithm: DigestAlgorithm) -> PathErrors;
+    fn from_file_len_candidates<'a, I, S>(groups: I, algorithm: DigestAlgorithm) -> (Self, PathErrors)
+    where
+        Self: Sized,
+        I: IntoIterator<Item = (&'a u64, &'a S)>,
+        &'a S: IntoIterator<Item = &'a PathBuf>,
+        S: 'a;
+    fn duplicate_candidates(&self) -> impl Iterator<Item = (&FileDigest, &HashSet<PathBuf>)>;
+    fn prune_stale(&mut self) -> Vec<PathBuf>;
+}
+
+impl HashMapOfDigestToSetOfPathBufExt for HashMapOfDigestToSetOfPathBuf {
+
+    /// Return `true` if the collection contains a sub-key-value item.
+    ///
+    /// Return an error if the file can't be read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// use std::path::PathBuf;
+    ///
+    /// let mut a: HashMapOfDigestToSetOfPathBuf = HashMapOfDigestToSetOfPathBuf::new();
+    /// let algorithm = DigestAlgorithm::default();
+    /// # std::fs::write("digest_contains.txt", "alpha");
+    /// a.try_sub_insert_path(PathBuf::from("digest_contains.txt"), algorithm)?;
+    /// assert_eq!(a.try_sub_contains_path("digest_contains.txt", algorithm)?, true);
+    /// # std::fs::remove_file("digest_contains.txt");
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[inline]
+    fn try_sub_contains_path<P: AsRef<Path>>(&self, value: P, algorithm: DigestAlgorithm) -> io::Result<bool> {
+        let value = value.as_ref();
+        let key = algorithm.digest_file(value)?;
+        Ok(match self.get(&key) {
+            Some(set) => set.contains(value),
+            None => false,
+        })
+    }
+
+    /// Add a sub-key-value item to the collection, keyed by the digest of the file.
+    ///
+    /// Return whether the item is added in the set, or an error if the
+    /// file can't be read. On error the collection is unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// use std::path::PathBuf;
+    ///
+    /// let mut a: HashMapOfDigestToSetOfPathBuf = HashMapOfDigestToSetOfPathBuf::new();
+    /// let algorithm = DigestAlgorithm::default();
+    /// # std::fs::write("digest_insert_alpha.txt", "alpha");
+    /// # std::fs::write("digest_insert_bravo.txt", "alpha");
+    /// a.try_sub_insert_path(PathBuf::from("digest_insert_alpha.txt"), algorithm)?;
+    /// a.try_sub_insert_path(PathBuf::from("digest_insert_bravo.txt"), algorithm)?;
+    /// assert_eq!(a.len(), 1);
+    /// # std::fs::remove_file("digest_insert_alpha.txt");
+    /// # std::fs::remove_file("digest_insert_bravo.txt");
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[inline]
+    fn try_sub_insert_path(&mut self, value: PathBuf, algorithm: DigestAlgorithm) -> io::Result<bool> {
+        let key = algorithm.digest_file(&value)?;
+        Ok(self.entry(key)
+        .or_default()
+        .insert(value))
+    }
+
+    /// Remove a sub-key-value pair from the collection.
+    ///
+    /// Return whether the value was present in the set, or an error if
+    /// the file can't be read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// use std::path::PathBuf;
+    ///
+    /// let mut a: HashMapOfDigestToSetOfPathBuf = HashMapOfDigestToSetOfPathBuf::new();
+    /// let algorithm = DigestAlgorithm::default();
+    /// # std::fs::write("digest_remove.txt", "alpha");
+    /// a.try_sub_insert_path(PathBuf::from("digest_remove.txt"), algorithm)?;
+    /// assert_eq!(a.try_sub_remove_path("digest_remove.txt", algorithm)?, true);
+    /// assert_eq!(a.try_sub_contains_path("digest_remove.txt", algorithm)?, false);
+    /// # std::fs::remove_file("digest_remove.txt");
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[inline]
+    fn try_sub_remove_path<P: AsRef<Path>>(&mut self, value: P, algorithm: DigestAlgorithm) -> io::Result<bool> {
+        let value = value.as_ref();
+        let key = algorithm.digest_file(value)?;
+        Ok(match self.get_mut(&key) {
+            Some(set) => set.remove(value),
+            None => false,
+        })
+    }
+
+    /// Add many paths, each keyed by the digest of its file.
+    ///
+    /// Return a report of each path that can't be read, with its error;
+    /// those paths are skipped and the rest are added.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// use std::path::PathBuf;
+    ///
+    /// let mut a: HashMapOfDigestToSetOfPathBuf = HashMapOfDigestToSetOfPathBuf::new();
+    /// # std::fs::write("digest_insert_paths.txt", "alpha");
+    /// let errors = a.try_sub_insert_paths(vec![PathBuf::from("digest_insert_paths.txt"), PathBuf::from("missing.txt")], DigestAlgorithm::default());
+    /// assert_eq!(a.len(), 1);
+    /// assert_eq!(errors.len(), 1);
+    /// # std::fs::remove_file("digest_insert_paths.txt");
+    /// ```
+    #[inline]
+    fn try_sub_insert_paths<I: IntoIterator<Item = PathBuf>>(&mut self, values: I, algorithm: DigestAlgorithm) -> PathErrors {
+        let mut errors = PathErrors::new();
+        for value in values {
+            match algorithm.digest_file(&value) {
+                Ok(key) => {
+                    self.entry(key)
+                    .or_default()
+                    .insert(value);
+                },
+                Err(err) => errors.push((value, err)),
+            }
+        }
+        errors
+    }
+
+    /// Create a collection by refining the candidate groups of a file length map into digest groups.
+    ///
+    /// The groups are `(length, paths)` pairs, such as the output of a
+    /// file length map's `duplicate_candidates`. Return the collection
+    /// and a report of each path that can't be read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// use std::path::PathBuf;
+    ///
+    /// # std::fs::write("refine_alpha.txt", "alpha");
+    /// # std::fs::write("refine_bravo.txt", "bravo");
+    /// # std::fs::write("refine_other.txt", "alpha");
+    /// let mut lens: HashMapOfFileLenToSetOfPathBuf = HashMapOfFileLenToSetOfPathBuf::new();
+    /// lens.try_sub_insert_path(PathBuf::from("refine_alpha.txt"))?;
+    /// lens.try_sub_insert_path(PathBuf::from("refine_bravo.txt"))?;
+    /// lens.try_sub_insert_path(PathBuf::from("refine_other.txt"))?;
+    /// let (digests, errors) = HashMapOfDigestToSetOfPathBuf::from_file_len_candidates(lens.duplicate_candidates(), DigestAlgorithm::default());
+    /// assert_eq!(errors.is_empty(), true);
+    /// assert_eq!(digests.duplicate_candidates().count(), 1);
+    /// # std::fs::remove_file("refine_alpha.txt");
+    /// # std::fs::remove_file("refine_bravo.txt");
+    /// # std::fs::remove_file("refine_other.txt");
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    fn from_file_len_candidates<'a, I, S>(groups: I, algorithm: DigestAlgorithm) -> (Self, PathErrors)
+    where
+        I: IntoIterator<Item = (&'a u64, &'a S)>,
+        &'a S: IntoIterator<Item = &'a PathBuf>,
+        S: 'a,
+    {
+        let mut map = HashMapOfDigestToSetOfPathBuf::new();
+        let errors = map.try_sub_insert_paths(groups.into_iter().flat_map(|(_, paths)| paths).cloned(), algorithm);
+        (map, errors)
+    }
+
+    /// Return an iterator over the groups of paths that are duplicates
+    /// by digest, which are the sets with more than one path.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// use std::path::PathBuf;
+    ///
+    /// let mut a: HashMapOfDigestToSetOfPathBuf = HashMapOfDigestToSetOfPathBuf::new();
+    /// a.entry([1; 32]).or_default().extend(vec![PathBuf::from("alpha.txt"), PathBuf::from("bravo.txt")]);
+    /// a.entry([2; 32]).or_default().insert(PathBuf::from("charlie.txt"));
+    /// assert_eq!(a.duplicate_candidates().count(), 1);
+    /// ```
+    #[inline]
+    fn duplicate_candidates(&self) -> impl Iterator<Item = (&FileDigest, &HashSet<PathBuf>)> {
+        self.iter()
+        .filter(|(_, paths)| paths.len() > 1)
+    }
+
+
+    /// Remove every path whose file no longer exists, and each digest
+    /// whose set becomes empty. Return the removed paths, in path order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// use std::path::PathBuf;
+    ///
+    /// let mut a: HashMapOfDigestToSetOfPathBuf = HashMapOfDigestToSetOfPathBuf::new();
+    /// # std::fs::write("hash_digest_prune_stale.txt", "alpha");
+    /// a.try_sub_insert_path(PathBuf::from("hash_digest_prune_stale.txt"), DigestAlgorithm::default())?;
+    /// # std::fs::remove_file("hash_digest_prune_stale.txt");
+    /// assert_eq!(a.prune_stale(), vec![PathBuf::from("hash_digest_prune_stale.txt")]);
+    /// assert_eq!(a.is_empty(), true);
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[inline]
+    fn prune_stale(&mut self) -> Vec<PathBuf> {
+        path_scan::prune_stale(self)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+    use crate::hash_map_of_file_len_to_set_of_path_buf::{HashMapOfFileLenToSetOfPathBuf, HashMapOfFileLenToSetOfPathBufExt, HashMapOfFileLenToSetOfPathBufTryExt};
+    use std::fs;
+
+    #[test]
+    /// Test `from_file_len_candidates` splits a same-length group by content.
+    /// Must succeed.
+    ///
+    fn test_from_file_len_candidates() {
+        let dir = std::env::temp_dir().join(format!("hash_map_of_digest_to_set_of_path_buf_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("a1.txt"), "alpha").unwrap();
+        fs::write(dir.join("a2.txt"), "alpha").unwrap();
+        fs::write(dir.join("b1.txt"), "bravo").unwrap();
+        fs::write(dir.join("c1.txt"), "charlie").unwrap();
+        let (lens, _) = HashMapOfFileLenToSetOfPathBuf::from_dir(&dir).unwrap();
+        let algorithm = DigestAlgorithm::default();
+        let (subject, errors) = HashMapOfDigestToSetOfPathBuf::from_file_len_candidates(lens.duplicate_candidates(), algorithm);
+        let alpha = algorithm.digest_file(dir.join("a1.txt")).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(errors.is_empty(), true);
+        assert_eq!(subject.len(), 2);
+        assert_eq!(subject.get(&alpha).map(|set| set.len()), Some(2));
+        let groups: Vec<&HashSet<PathBuf>> = subject.duplicate_candidates().map(|(_, paths)| paths).collect();
+        assert_eq!(groups.len(), 1);
+        assert!(groups[0].contains(&dir.join("a1.txt")) && groups[0].contains(&dir.join("a2.txt")));
+    }
+
+    #[test]
+    /// Test the `try_` methods with a missing file.
+    /// Must return an error and leave the collection unchanged.
+    ///
+    fn test_try_sub_x_path_x_missing() {
+        let mut subject: HashMapOfDigestToSetOfPathBuf = HashMapOfDigestToSetOfPathBuf::new();
+        let missing: PathBuf = [env!("CARGO_MANIFEST_DIR"), "test", "missing.txt"].iter().collect::<PathBuf>();
+        let algorithm = DigestAlgorithm::default();
+        let err = subject.try_sub_insert_path(missing.clone(), algorithm).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+        assert_eq!(subject.is_empty(), true);
+        assert_eq!(subject.try_sub_contains_path(&missing, algorithm).is_err(), true);
+        assert_eq!(subject.try_sub_remove_path(&missing, algorithm).is_err(), true);
+    }
+
+}
@@ -0,0 +1,365 @@
+use std::collections::{HashMap, HashSet};
+use std::ffi::OsString;
+use std::io;
+use std::path::{Path, PathBuf};
+use crate::path_scan::{self, PathErrors, SymlinkPolicy, WalkOptions};
+
+/// A map of file names to sets of paths.
+///
+/// Each key is the final component of a path, so "one/alpha.txt" and
+/// "two/alpha.txt" share the key "alpha.txt". A path without a final
+/// component, such as "/" or "..", is not added.
+///
+/// The key comes from the path alone, so these helpers don't read
+/// file metadata, except to find the regular files in a directory.
+pub type HashMapOfFileNameToSetOfPathBuf = HashMap<OsString, HashSet<PathBuf>>;
+
+pub trait HashMapOfFileNameToSetOfPathBufExt {
+    fn sub_contains_path<P: AsRef<Path>>(&self, value: P) -> bool;
+    fn sub_insert_path(&mut self, value: PathBuf) -> bool;
+    fn sub_remove_path<P: AsRef<Path>>(&mut self, value: P) -> bool;
+    fn duplicate_names(&self) -> impl Iterator<Item = (&OsString, &HashSet<PathBuf>)>;
+    fn try_sub_insert_dir<P: AsRef<Path>>(&mut self, dir: P) -> io::Result<PathErrors>;
+    fn from_dir<P: AsRef<Path>>(dir: P) -> io::Result<(Self, PathErrors)> where Self: Sized;
+    fn sub_insert_tree<P: AsRef<Path>>(&mut self, root: P, options: &WalkOptions) -> io::Result<PathErrors>;
+    #[cfg(feature = "rayon")]
+    fn par_sub_insert_tree<P: AsRef<Path>>(&mut self, root: P, options: &WalkOptions) -> io::Result<PathErrors>;
+    fn prune_stale(&mut self) -> Vec<PathBuf>;
+}
+
+impl HashMapOfFileNameToSetOfPathBufExt for HashMapOfFileNameToSetOfPathBuf {
+
+    /// Return `true` if the collection contains a sub-key-value item.
+    ///
+    /// The path is borrowed, and may be any type that converts to a `&Path`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// use std::path::PathBuf;
+    ///
+    /// let mut a: HashMapOfFileNameToSetOfPathBuf = HashMapOfFileNameToSetOfPathBuf::new();
+    /// a.sub_insert_path(PathBuf::from("one/alpha.txt"));
+    /// assert_eq!(a.sub_contains_path("one/alpha.txt"), true);
+    /// assert_eq!(a.sub_contains_path("two/alpha.txt"), false);
+    /// ```
+    #[inline]
+    fn sub_contains_path<P: AsRef<Path>>(&self, value: P) -> bool {
+        let value = value.as_ref();
+        match value.file_name().and_then(|name| self.get(name)) {
+            Some(set) => set.contains(value),
+            None => false,
+        }
+    }
+
+    /// Add a sub-key-value item to the collection.
+    ///
+    /// Return whether the item is added in the set. A path without a
+    /// final component is not added.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// use std::ffi::OsStr;
+    /// use std::path::PathBuf;
+    ///
+    /// let mut a: HashMapOfFileNameToSetOfPathBuf = HashMapOfFileNameToSetOfPathBuf::new();
+    /// assert_eq!(a.sub_insert_path(PathBuf::from("one/alpha.txt")), true);
+    /// assert_eq!(a.sub_insert_path(PathBuf::from("two/alpha.txt")), true);
+    /// assert_eq!(a.sub_insert_path(PathBuf::from("..")), false);
+    /// assert_eq!(a.get(OsStr::new("alpha.txt")).map(|set| set.len()), Some(2));
+    /// ```
+    #[inline]
+    fn sub_insert_path(&mut self, value: PathBuf) -> bool {
+        let key = match value.file_name() {
+            Some(name) => name.to_os_string(),
+            None => return false,
+        };
+        self.entry(key)
+        .or_default()
+        .insert(value)
+    }
+
+    /// Remove a sub-key-value pair from the collection.
+    ///
+    /// Return whether the value was present in the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// use std::path::PathBuf;
+    ///
+    /// let mut a: HashMapOfFileNameToSetOfPathBuf = HashMapOfFileNameToSetOfPathBuf::new();
+    /// a.sub_insert_path(PathBuf::from("one/alpha.txt"));
+    /// assert_eq!(a.sub_remove_path("one/alpha.txt"), true);
+    /// assert_eq!(a.sub_contains_path("one/alpha.txt"), false);
+    /// ```
+    #[inline]
+    fn sub_remove_path<P: AsRef<Path>>(&mut self, value: P) -> bool {
+        let value = value.as_ref();
+        match value.file_name().and_then(|name| self.get_mut(name)) {
+            Some(set) => set.remove(value),
+            None => false,
+        }
+    }
+
+    /// Return an iterator over the file names that more than one path
+    /// bears, each with its set of paths.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// use std::path::PathBuf;
+    ///
+    /// let mut a: HashMapOfFileNameToSetOfPathBuf = HashMapOfFileNameToSetOfPathBuf::new();
+    /// a.sub_insert_path(PathBuf::from("one/alpha.txt"));
+    /// a.sub_insert_path(PathBuf::from("two/alpha.txt"));
+    /// a.sub_insert_path(PathBuf::from("one/bravo.txt"));
+    /// assert_eq!(a.duplicate_names().count(), 1);
+    /// ```
+    #[inline]
+    fn duplicate_names(&self) -> impl Iterator<Item = (&OsString, &HashSet<PathBuf>)> {
+        self.iter()
+        .filter(|(_, paths)| paths.len() > 1)
+    }
+
+    /// Add every regular file in a directory, not recursively.
+    ///
+    /// Return an error if the directory can't be read. Otherwise return
+    /// a report of each entry that can't be read, with its error; the
+    /// scan skips those entries and adds the rest.
+    ///
+    /// The scan skips symbolic links, like `sub_insert_tree` with the
+    /// default `WalkOptions`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    ///
+    /// let mut a: HashMapOfFileNameToSetOfPathBuf = HashMapOfFileNameToSetOfPathBuf::new();
+    /// # std::fs::create_dir_all("file_name_try_sub_insert_dir");
+    /// # std::fs::write("file_name_try_sub_insert_dir/alpha.txt", "alpha");
+    /// let errors = a.try_sub_insert_dir("file_name_try_sub_insert_dir")?;
+    /// assert_eq!(errors.is_empty(), true);
+    /// assert_eq!(a.sub_contains_path("file_name_try_sub_insert_dir/alpha.txt"), true);
+    /// # std::fs::remove_dir_all("file_name_try_sub_insert_dir");
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[inline]
+    fn try_sub_insert_dir<P: AsRef<Path>>(&mut self, dir: P) -> io::Result<PathErrors> {
+        path_scan::scan_dir(dir.as_ref(), SymlinkPolicy::Skip, |path, _| {
+            self.sub_insert_path(path);
+        })
+    }
+
+    /// Create a collection of every regular file in a directory, not recursively.
+    ///
+    /// Return an error if the directory can't be read. Otherwise return
+    /// the collection and a report of each entry that can't be read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    ///
+    /// # std::fs::create_dir_all("file_name_from_dir");
+    /// # std::fs::write("file_name_from_dir/alpha.txt", "alpha");
+    /// let (a, errors) = HashMapOfFileNameToSetOfPathBuf::from_dir("file_name_from_dir")?;
+    /// assert_eq!(errors.is_empty(), true);
+    /// assert_eq!(a.sub_contains_path("file_name_from_dir/alpha.txt"), true);
+    /// # std::fs::remove_dir_all("file_name_from_dir");
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[inline]
+    fn from_dir<P: AsRef<Path>>(dir: P) -> io::Result<(Self, PathErrors)> {
+        let mut map = Self::new();
+        let errors = map.try_sub_insert_dir(dir)?;
+        Ok((map, errors))
+    }
+
+    /// Add every regular file in a directory tree, walking it with options
+    /// for the max depth, symbolic links, and path predicates.
+    ///
+    /// Return an error if the root can't be read. Otherwise return a
+    /// report of each path below the root that can't be read, with its
+    /// error; the walk skips those paths and adds the rest.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    ///
+    /// let mut a: HashMapOfFileNameToSetOfPathBuf = HashMapOfFileNameToSetOfPathBuf::new();
+    /// # std::fs::create_dir_all("file_name_sub_insert_tree/one");
+    /// # std::fs::write("file_name_sub_insert_tree/alpha.txt", "alpha");
+    /// # std::fs::write("file_name_sub_insert_tree/one/alpha.txt", "alpha");
+    /// let errors = a.sub_insert_tree("file_name_sub_insert_tree", &WalkOptions::new())?;
+    /// assert_eq!(errors.is_empty(), true);
+    /// assert_eq!(a.duplicate_names().count(), 1);
+    /// # std::fs::remove_dir_all("file_name_sub_insert_tree");
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[inline]
+    fn sub_insert_tree<P: AsRef<Path>>(&mut self, root: P, options: &WalkOptions) -> io::Result<PathErrors> {
+        path_scan::walk_tree(root.as_ref(), options, |path, _| {
+            self.sub_insert_path(path);
+        })
+    }
+
+    /// Add every regular file in a directory tree, like `sub_insert_tree`,
+    /// and read the directories and file metadata of each depth on multiple threads.
+    ///
+    /// This method needs the `rayon` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    ///
+    /// let mut a: HashMapOfFileNameToSetOfPathBuf = HashMapOfFileNameToSetOfPathBuf::new();
+    /// # std::fs::create_dir_all("file_name_par_sub_insert_tree/one");
+    /// # std::fs::write("file_name_par_sub_insert_tree/one/bravo.txt", "bravo");
+    /// let errors = a.par_sub_insert_tree("file_name_par_sub_insert_tree", &WalkOptions::new())?;
+    /// assert_eq!(errors.is_empty(), true);
+    /// assert_eq!(a.sub_contains_path("file_name_par_sub_insert_tree/one/bravo.txt"), true);
+    /// # std::fs::remove_dir_all("file_name_par_sub_insert_tree");
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[cfg(feature = "rayon")]
+    #[inline]
+    fn par_sub_insert_tree<P: AsRef<Path>>(&mut self, root: P, options: &WalkOptions) -> io::Result<PathErrors> {
+        path_scan::par_walk_tree(root.as_ref(), options, |path, _| {
+            self.sub_insert_path(path);
+        })
+    }
+
+
+    /// Remove every path whose file no longer exists, and each file name
+    /// whose set becomes empty. Return the removed paths, in path order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// use std::path::PathBuf;
+    ///
+    /// let mut a: HashMapOfFileNameToSetOfPathBuf = HashMapOfFileNameToSetOfPathBuf::new();
+    /// # std::fs::write("hash_file_name_prune_stale.txt", "alpha");
+    /// a.sub_insert_path(PathBuf::from("hash_file_name_prune_stale.txt"));
+    /// # std::fs::remove_file("hash_file_name_prune_stale.txt");
+    /// assert_eq!(a.prune_stale(), vec![PathBuf::from("hash_file_name_prune_stale.txt")]);
+    /// assert_eq!(a.is_empty(), true);
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[inline]
+    fn prune_stale(&mut self) -> Vec<PathBuf> {
+        path_scan::prune_stale(self)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+    use std::ffi::OsStr;
+    use std::fs;
+
+    #[test]
+    /// Test `sub_insert_path` and `sub_contains_path`.
+    /// Must key each path by its file name.
+    ///
+    fn test_sub_insert_path() {
+        let mut subject: HashMapOfFileNameToSetOfPathBuf = HashMapOfFileNameToSetOfPathBuf::new();
+        assert_eq!(subject.sub_insert_path(PathBuf::from("one/alpha.txt")), true);
+        assert_eq!(subject.sub_insert_path(PathBuf::from("two/alpha.txt")), true);
+        assert_eq!(subject.sub_insert_path(PathBuf::from("two/alpha.txt")), false);
+        assert_eq!(subject.sub_insert_path(PathBuf::from("alpha.TXT")), true);
+        assert_eq!(subject.sub_insert_path(PathBuf::from("/")), false);
+        assert_eq!(subject.len(), 2);
+        assert_eq!(subject.get(OsStr::new("alpha.txt")).map(|set| set.len()), Some(2));
+        assert_eq!(subject.sub_contains_path("one/alpha.txt"), true);
+        assert_eq!(subject.sub_contains_path("three/alpha.txt"), false);
+        assert_eq!(subject.sub_contains_path("/"), false);
+    }
+
+    #[test]
+    /// Test `sub_remove_path`.
+    /// Must remove only the exact path.
+    ///
+    fn test_sub_remove_path() {
+        let mut subject: HashMapOfFileNameToSetOfPathBuf = HashMapOfFileNameToSetOfPathBuf::new();
+        subject.sub_insert_path(PathBuf::from("one/alpha.txt"));
+        assert_eq!(subject.sub_remove_path("two/alpha.txt"), false);
+        assert_eq!(subject.sub_remove_path(".."), false);
+        assert_eq!(subject.sub_remove_path("one/alpha.txt"), true);
+        assert_eq!(subject.sub_contains_path("one/alpha.txt"), false);
+    }
+
+    #[test]
+    /// Test `sub_insert_tree` and `duplicate_names`.
+    /// Must find the file names in more than one directory.
+    ///
+    fn test_sub_insert_tree() {
+        let dir = std::env::temp_dir().join(format!("hash_map_of_file_name_to_set_of_path_buf_tree_{}", std::process::id()));
+        fs::create_dir_all(dir.join("one").join("two")).unwrap();
+        fs::write(dir.join("alpha.txt"), "alpha").unwrap();
+        fs::write(dir.join("one").join("alpha.txt"), "alpha").unwrap();
+        fs::write(dir.join("one").join("two").join("alpha.txt"), "other").unwrap();
+        fs::write(dir.join("one").join("bravo.txt"), "bravo").unwrap();
+        let mut subject: HashMapOfFileNameToSetOfPathBuf = HashMapOfFileNameToSetOfPathBuf::new();
+        let errors = subject.sub_insert_tree(&dir, &WalkOptions::new()).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(errors.is_empty(), true);
+        assert_eq!(subject.len(), 2);
+        let duplicates: Vec<(&OsString, &HashSet<PathBuf>)> = subject.duplicate_names().collect();
+        assert_eq!(duplicates.len(), 1);
+        assert_eq!(duplicates[0].0, "alpha.txt");
+        assert_eq!(duplicates[0].1.len(), 3);
+    }
+
+    #[test]
+    /// Test `prune_stale`.
+    /// Must remove the deleted files, and the file names whose sets become empty.
+    ///
+    fn test_prune_stale() {
+        let dir = std::env::temp_dir().join(format!("hash_map_of_file_name_to_set_of_path_buf_prune_stale_{}", std::process::id()));
+        fs::create_dir_all(dir.join("one")).unwrap();
+        fs::write(dir.join("alpha.txt"), "alpha").unwrap();
+        fs::write(dir.join("one").join("alpha.txt"), "alpha").unwrap();
+        fs::write(dir.join("bravo.txt"), "bravo").unwrap();
+        let mut subject = HashMapOfFileNameToSetOfPathBuf::new();
+        subject.sub_insert_tree(&dir, &WalkOptions::new()).unwrap();
+        fs::remove_file(dir.join("one").join("alpha.txt")).unwrap();
+        fs::remove_file(dir.join("bravo.txt")).unwrap();
+        let removed = subject.prune_stale();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(removed, vec![dir.join("bravo.txt"), dir.join("one").join("alpha.txt")]);
+        assert_eq!(subject.len(), 1);
+        assert_eq!(subject[OsStr::new("alpha.txt")], vec![dir.join("alpha.txt")].into_iter().collect());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    /// Test `from_dir` with a symbolic link in the directory.
+    /// Must skip the link, like `sub_insert_tree` with the default options.
+    ///
+    fn test_from_dir_x_symlink() {
+        let dir = std::env::temp_dir().join(format!("hash_map_of_file_name_to_set_of_path_buf_from_dir_symlink_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("alpha.txt"), "alpha").unwrap();
+        std::os::unix::fs::symlink("alpha.txt", dir.join("link")).unwrap();
+        let (subject, errors) = HashMapOfFileNameToSetOfPathBuf::from_dir(&dir).unwrap();
+        let mut tree = HashMapOfFileNameToSetOfPathBuf::new();
+        tree.sub_insert_tree(&dir, &WalkOptions::new().max_depth(1)).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(errors.is_empty(), true);
+        assert_eq!(subject.values().flatten().collect::<Vec<&PathBuf>>(), vec![&dir.join("alpha.txt")]);
+        assert_eq!(subject, tree);
+    }
+
+}
@@ -0,0 +1,460 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use crate::path_scan::{self, PathErrors, SymlinkPolicy, WalkOptions};
+
+/// A map of file modification times to sets of paths.
+///
+/// Each key is a modification time in whole seconds since the Unix
+/// epoch, so the files that one batch touched tend to share a key.
+pub type HashMapOfMtimeToSetOfPathBuf = HashMap<i64, HashSet<PathBuf>>;
+
+pub trait HashMapOfMtimeToSetOfPathBufExt {
+    fn try_sub_contains_path<P: AsRef<Path>>(&self, value: P) -> io::Result<bool>;
+    fn try_sub_insert_path(&mut self, value: PathBuf) -> io::Result<bool>;
+    fn try_sub_remove_path<P: AsRef<Path>>(&mut self, value: P) -> io::Result<bool>;
+    fn try_sub_contains_path_with<P: AsRef<Path>>(&self, value: P, options: &WalkOptions) -> io::Result<bool>;
+    fn try_sub_remove_path_with<P: AsRef<Path>>(&mut self, value: P, options: &WalkOptions) -> io::Result<bool>;
+    fn try_sub_insert_dir<P: AsRef<Path>>(&mut self, dir: P) -> io::Result<PathErrors>;
+    fn from_dir<P: AsRef<Path>>(dir: P) -> io::Result<(Self, PathErrors)> where Self: Sized;
+    fn sub_insert_tree<P: AsRef<Path>>(&mut self, root: P, options: &WalkOptions) -> io::Result<PathErrors>;
+    #[cfg(feature = "rayon")]
+    fn par_sub_insert_tree<P: AsRef<Path>>(&mut self, root: P, options: &WalkOptions) -> io::Result<PathErrors>;
+    fn prune_stale(&mut self) -> Vec<PathBuf>;
+}
+
+impl HashMapOfMtimeToSetOfPathBufExt for HashMapOfMtimeToSetOfPathBuf {
+
+    /// Return `true` if the collection contains a sub-key-value item.
+    ///
+    /// Return an error if the file metadata can't be read. The path is
+    /// borrowed, and may be any type that converts to a `&Path`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// use std::path::PathBuf;
+    ///
+    /// let mut a: HashMapOfMtimeToSetOfPathBuf = HashMapOfMtimeToSetOfPathBuf::new();
+    /// # std::fs::write("mtime_contains.txt", "alpha");
+    /// a.try_sub_insert_path(PathBuf::from("mtime_contains.txt"))?;
+    /// assert_eq!(a.try_sub_contains_path("mtime_contains.txt")?, true);
+    /// assert_eq!(a.try_sub_contains_path("missing.txt").is_err(), true);
+    /// # std::fs::remove_file("mtime_contains.txt");
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[inline]
+    fn try_sub_contains_path<P: AsRef<Path>>(&self, value: P) -> io::Result<bool> {
+        let value = value.as_ref();
+        let key = path_scan::mtime_secs(&fs::metadata(value)?)?;
+        Ok(match self.get(&key) {
+            Some(set) => set.contains(value),
+            None => false,
+        })
+    }
+
+    /// Add a sub-key-value item to the collection.
+    ///
+    /// Return whether the item is added in the set, or an error if the
+    /// file metadata can't be read. On error the collection is unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// use std::path::PathBuf;
+    ///
+    /// let mut a: HashMapOfMtimeToSetOfPathBuf = HashMapOfMtimeToSetOfPathBuf::new();
+    /// # std::fs::write("mtime_insert.txt", "alpha");
+    /// assert_eq!(a.try_sub_insert_path(PathBuf::from("mtime_insert.txt"))?, true);
+    /// assert_eq!(a.try_sub_insert_path(PathBuf::from("missing.txt")).is_err(), true);
+    /// # std::fs::remove_file("mtime_insert.txt");
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[inline]
+    fn try_sub_insert_path(&mut self, value: PathBuf) -> io::Result<bool> {
+        let key = path_scan::mtime_secs(&fs::metadata(&value)?)?;
+        Ok(self.entry(key)
+        .or_default()
+        .insert(value))
+    }
+
+    /// Remove a sub-key-value pair from the collection.
+    ///
+    /// Return whether the value was present in the set, or an error if
+    /// the file metadata can't be read.
+    ///
+    /// The file must still have the modification time that it had when
+    /// it was added; otherwise the removal looks in the wrong set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// use std::path::PathBuf;
+    ///
+    /// let mut a: HashMapOfMtimeToSetOfPathBuf = HashMapOfMtimeToSetOfPathBuf::new();
+    /// # std::fs::write("mtime_remove.txt", "alpha");
+    /// a.try_sub_insert_path(PathBuf::from("mtime_remove.txt"))?;
+    /// assert_eq!(a.try_sub_remove_path("mtime_remove.txt")?, true);
+    /// assert_eq!(a.try_sub_contains_path("mtime_remove.txt")?, false);
+    /// # std::fs::remove_file("mtime_remove.txt");
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[inline]
+    fn try_sub_remove_path<P: AsRef<Path>>(&mut self, value: P) -> io::Result<bool> {
+        let value = value.as_ref();
+        let key = path_scan::mtime_secs(&fs::metadata(value)?)?;
+        Ok(match self.get_mut(&key) {
+            Some(set) => set.remove(value),
+            None => false,
+        })
+    }
+
+    /// Return `true` if the collection contains a sub-key-value item,
+    /// reading the file metadata the way the options' symbolic link
+    /// policy does.
+    ///
+    /// With `SymlinkPolicy::Record` this reads a link's own metadata, so
+    /// it finds a link that `sub_insert_tree` recorded. With another
+    /// policy this is like `try_sub_contains_path`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// use std::path::PathBuf;
+    ///
+    /// let mut a: HashMapOfMtimeToSetOfPathBuf = HashMapOfMtimeToSetOfPathBuf::new();
+    /// # std::fs::write("hash_mtime_contains_with.txt", "alpha");
+    /// let options = WalkOptions::new().symlinks(SymlinkPolicy::Record);
+    /// a.try_sub_insert_path(PathBuf::from("hash_mtime_contains_with.txt"))?;
+    /// assert_eq!(a.try_sub_contains_path_with("hash_mtime_contains_with.txt", &options)?, true);
+    /// # std::fs::remove_file("hash_mtime_contains_with.txt");
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[inline]
+    fn try_sub_contains_path_with<P: AsRef<Path>>(&self, value: P, options: &WalkOptions) -> io::Result<bool> {
+        let value = value.as_ref();
+        let key = path_scan::mtime_secs(&path_scan::lookup_metadata(value, options)?)?;
+        Ok(match self.get(&key) {
+            Some(set) => set.contains(value),
+            None => false,
+        })
+    }
+
+    /// Remove a sub-key-value pair from the collection, reading the file
+    /// metadata the way the options' symbolic link policy does, like
+    /// `try_sub_contains_path_with`.
+    ///
+    /// Return whether the value was present in the set, or an error if
+    /// the file metadata can't be read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// use std::path::PathBuf;
+    ///
+    /// let mut a: HashMapOfMtimeToSetOfPathBuf = HashMapOfMtimeToSetOfPathBuf::new();
+    /// # std::fs::write("hash_mtime_remove_with.txt", "alpha");
+    /// let options = WalkOptions::new().symlinks(SymlinkPolicy::Record);
+    /// a.try_sub_insert_path(PathBuf::from("hash_mtime_remove_with.txt"))?;
+    /// assert_eq!(a.try_sub_remove_path_with("hash_mtime_remove_with.txt", &options)?, true);
+    /// assert_eq!(a.try_sub_contains_path_with("hash_mtime_remove_with.txt", &options)?, false);
+    /// # std::fs::remove_file("hash_mtime_remove_with.txt");
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[inline]
+    fn try_sub_remove_path_with<P: AsRef<Path>>(&mut self, value: P, options: &WalkOptions) -> io::Result<bool> {
+        let value = value.as_ref();
+        let key = path_scan::mtime_secs(&path_scan::lookup_metadata(value, options)?)?;
+        Ok(match self.get_mut(&key) {
+            Some(set) => set.remove(value),
+            None => false,
+        })
+    }
+
+    /// Add every regular file in a directory, not recursively.
+    ///
+    /// Return an error if the directory can't be read. Otherwise return
+    /// a report of each entry that can't be read, with its error; the
+    /// scan skips those entries and adds the rest.
+    ///
+    /// The scan skips symbolic links, like `sub_insert_tree` with the
+    /// default `WalkOptions`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    ///
+    /// let mut a: HashMapOfMtimeToSetOfPathBuf = HashMapOfMtimeToSetOfPathBuf::new();
+    /// # std::fs::create_dir_all("mtime_try_sub_insert_dir");
+    /// # std::fs::write("mtime_try_sub_insert_dir/alpha.txt", "alpha");
+    /// let errors = a.try_sub_insert_dir("mtime_try_sub_insert_dir")?;
+    /// assert_eq!(errors.is_empty(), true);
+    /// assert_eq!(a.try_sub_contains_path("mtime_try_sub_insert_dir/alpha.txt")?, true);
+    /// # std::fs::remove_dir_all("mtime_try_sub_insert_dir");
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[inline]
+    fn try_sub_insert_dir<P: AsRef<Path>>(&mut self, dir: P) -> io::Result<PathErrors> {
+        let mut failed = PathErrors::new();
+        let mut errors = path_scan::scan_dir(dir.as_ref(), SymlinkPolicy::Skip, |path, metadata| {
+            match path_scan::mtime_secs(metadata) {
+                Ok(key) => {
+                    self.entry(key)
+                    .or_default()
+                    .insert(path);
+                },
+                Err(err) => failed.push((path, err)),
+            }
+        })?;
+        errors.extend(failed);
+        Ok(errors)
+    }
+
+    /// Create a collection of every regular file in a directory, not recursively.
+    ///
+    /// Return an error if the directory can't be read. Otherwise return
+    /// the collection and a report of each entry that can't be read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    ///
+    /// # std::fs::create_dir_all("mtime_from_dir");
+    /// # std::fs::write("mtime_from_dir/alpha.txt", "alpha");
+    /// let (a, errors) = HashMapOfMtimeToSetOfPathBuf::from_dir("mtime_from_dir")?;
+    /// assert_eq!(errors.is_empty(), true);
+    /// assert_eq!(a.try_sub_contains_path("mtime_from_dir/alpha.txt")?, true);
+    /// # std::fs::remove_dir_all("mtime_from_dir");
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[inline]
+    fn from_dir<P: AsRef<Path>>(dir: P) -> io::Result<(Self, PathErrors)> {
+        let mut map = Self::new();
+        let errors = map.try_sub_insert_dir(dir)?;
+        Ok((map, errors))
+    }
+
+    /// Add every regular file in a directory tree, walking it with options
+    /// for the max depth, symbolic links, and path predicates.
+    ///
+    /// Return an error if the root can't be read. Otherwise return a
+    /// report of each path below the root that can't be read, with its
+    /// error; the walk skips those paths and adds the rest.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    ///
+    /// let mut a: HashMapOfMtimeToSetOfPathBuf = HashMapOfMtimeToSetOfPathBuf::new();
+    /// # std::fs::create_dir_all("mtime_sub_insert_tree/one");
+    /// # std::fs::write("mtime_sub_insert_tree/alpha.txt", "alpha");
+    /// # std::fs::write("mtime_sub_insert_tree/one/bravo.txt", "bravo");
+    /// let errors = a.sub_insert_tree("mtime_sub_insert_tree", &WalkOptions::new())?;
+    /// assert_eq!(errors.is_empty(), true);
+    /// assert_eq!(a.try_sub_contains_path("mtime_sub_insert_tree/one/bravo.txt")?, true);
+    /// # std::fs::remove_dir_all("mtime_sub_insert_tree");
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[inline]
+    fn sub_insert_tree<P: AsRef<Path>>(&mut self, root: P, options: &WalkOptions) -> io::Result<PathErrors> {
+        let mut failed = PathErrors::new();
+        let mut errors = path_scan::walk_tree(root.as_ref(), options, |path, metadata| {
+            match path_scan::mtime_secs(metadata) {
+                Ok(key) => {
+                    self.entry(key)
+                    .or_default()
+                    .insert(path);
+                },
+                Err(err) => failed.push((path, err)),
+            }
+        })?;
+        errors.extend(failed);
+        Ok(errors)
+    }
+
+    /// Add every regular file in a directory tree, like `sub_insert_tree`,
+    /// and read the directories and file metadata of each depth on multiple threads.
+    ///
+    /// This method needs the `rayon` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    ///
+    /// let mut a: HashMapOfMtimeToSetOfPathBuf = HashMapOfMtimeToSetOfPathBuf::new();
+    /// # std::fs::create_dir_all("mtime_par_sub_insert_tree/one");
+    /// # std::fs::write("mtime_par_sub_insert_tree/one/bravo.txt", "bravo");
+    /// let errors = a.par_sub_insert_tree("mtime_par_sub_insert_tree", &WalkOptions::new())?;
+    /// assert_eq!(errors.is_empty(), true);
+    /// assert_eq!(a.try_sub_contains_path("mtime_par_sub_insert_tree/one/bravo.txt")?, true);
+    /// # std::fs::remove_dir_all("mtime_par_sub_insert_tree");
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[cfg(feature = "rayon")]
+    #[inline]
+    fn par_sub_insert_tree<P: AsRef<Path>>(&mut self, root: P, options: &WalkOptions) -> io::Result<PathErrors> {
+        let mut failed = PathErrors::new();
+        let mut errors = path_scan::par_walk_tree(root.as_ref(), options, |path, metadata| {
+            match path_scan::mtime_secs(metadata) {
+                Ok(key) => {
+                    self.entry(key)
+                    .or_default()
+                    .insert(path);
+                },
+                Err(err) => failed.push((path, err)),
+            }
+        })?;
+        errors.extend(failed);
+        Ok(errors)
+    }
+
+
+    /// Remove every path whose file no longer exists, and each modification time
+    /// whose set becomes empty. Return the removed paths, in path order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// use std::path::PathBuf;
+    ///
+    /// let mut a: HashMapOfMtimeToSetOfPathBuf = HashMapOfMtimeToSetOfPathBuf::new();
+    /// # std::fs::write("hash_mtime_prune_stale.txt", "alpha");
+    /// a.try_sub_insert_path(PathBuf::from("hash_mtime_prune_stale.txt"))?;
+    /// # std::fs::remove_file("hash_mtime_prune_stale.txt");
+    /// assert_eq!(a.prune_stale(), vec![PathBuf::from("hash_mtime_prune_stale.txt")]);
+    /// assert_eq!(a.is_empty(), true);
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[inline]
+    fn prune_stale(&mut self) -> Vec<PathBuf> {
+        path_scan::prune_stale(self)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    /// Create a file in a test directory with a modification time.
+    fn touch(path: &Path, secs: u64) {
+        let file = fs::File::create(path).unwrap();
+        file.set_modified(UNIX_EPOCH + Duration::from_secs(secs)).unwrap();
+    }
+
+    #[test]
+    /// Test the `try_` methods.
+    /// Must key each path by its modification time.
+    ///
+    fn test_try_sub_x_path() {
+        let dir = std::env::temp_dir().join(format!("hash_map_of_mtime_to_set_of_path_buf_try_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        touch(&dir.join("alpha.txt"), 1_000);
+        touch(&dir.join("bravo.txt"), 2_000);
+        let mut subject: HashMapOfMtimeToSetOfPathBuf = HashMapOfMtimeToSetOfPathBuf::new();
+        assert_eq!(subject.try_sub_insert_path(dir.join("alpha.txt")).unwrap(), true);
+        assert_eq!(subject.try_sub_insert_path(dir.join("alpha.txt")).unwrap(), false);
+        assert_eq!(subject.try_sub_contains_path(dir.join("alpha.txt")).unwrap(), true);
+        assert_eq!(subject.try_sub_contains_path(dir.join("bravo.txt")).unwrap(), false);
+        assert_eq!(subject.get(&1_000).map(|set| set.len()), Some(1));
+        assert_eq!(subject.try_sub_remove_path(dir.join("alpha.txt")).unwrap(), true);
+        assert_eq!(subject.try_sub_remove_path(dir.join("alpha.txt")).unwrap(), false);
+        let err = subject.try_sub_insert_path(dir.join("missing.txt")).unwrap_err();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    /// Test `sub_insert_tree`.
+    /// Must group the files by modification time.
+    ///
+    fn test_sub_insert_tree() {
+        let dir = std::env::temp_dir().join(format!("hash_map_of_mtime_to_set_of_path_buf_tree_{}", std::process::id()));
+        fs::create_dir_all(dir.join("one")).unwrap();
+        touch(&dir.join("alpha.txt"), 1_000);
+        touch(&dir.join("one").join("bravo.txt"), 1_000);
+        touch(&dir.join("one").join("charlie.txt"), 2_000);
+        let mut subject: HashMapOfMtimeToSetOfPathBuf = HashMapOfMtimeToSetOfPathBuf::new();
+        let errors = subject.sub_insert_tree(&dir, &WalkOptions::new()).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(errors.is_empty(), true);
+        assert_eq!(subject.len(), 2);
+        assert_eq!(subject[&1_000], vec![dir.join("alpha.txt"), dir.join("one").join("bravo.txt")].into_iter().collect());
+        assert_eq!(subject[&2_000], vec![dir.join("one").join("charlie.txt")].into_iter().collect());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    /// Test `try_sub_contains_path_with` and `try_sub_remove_path_with` with a recorded link.
+    /// Must find and remove a link that a walk keyed by its own metadata.
+    ///
+    fn test_try_sub_x_path_with_x_record() {
+        let dir = std::env::temp_dir().join(format!("hash_map_of_mtime_to_set_of_path_buf_record_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        touch(&dir.join("alpha.txt"), 1_000);
+        std::os::unix::fs::symlink("alpha.txt", dir.join("link")).unwrap();
+        let link = dir.join("link");
+        let options = WalkOptions::new().symlinks(SymlinkPolicy::Record);
+        let mut subject: HashMapOfMtimeToSetOfPathBuf = HashMapOfMtimeToSetOfPathBuf::new();
+        subject.sub_insert_tree(&dir, &options).unwrap();
+        assert_eq!(subject.try_sub_contains_path(&link).unwrap(), false);
+        assert_eq!(subject.try_sub_contains_path_with(&link, &options).unwrap(), true);
+        assert_eq!(subject.try_sub_contains_path_with(dir.join("alpha.txt"), &options).unwrap(), true);
+        assert_eq!(subject.try_sub_remove_path_with(&link, &options).unwrap(), true);
+        assert_eq!(subject.try_sub_contains_path_with(&link, &options).unwrap(), false);
+        assert_eq!(subject.try_sub_remove_path_with(&link, &options).unwrap(), false);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    /// Test `prune_stale`.
+    /// Must remove the deleted files, and the modification times whose sets become empty.
+    ///
+    fn test_prune_stale() {
+        let dir = std::env::temp_dir().join(format!("hash_map_of_mtime_to_set_of_path_buf_prune_stale_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        touch(&dir.join("alpha.txt"), 1_000);
+        touch(&dir.join("bravo.txt"), 1_000);
+        touch(&dir.join("charlie.txt"), 2_000);
+        let (mut subject, _) = HashMapOfMtimeToSetOfPathBuf::from_dir(&dir).unwrap();
+        fs::remove_file(dir.join("bravo.txt")).unwrap();
+        fs::remove_file(dir.join("charlie.txt")).unwrap();
+        let removed = subject.prune_stale();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(removed, vec![dir.join("bravo.txt"), dir.join("charlie.txt")]);
+        assert_eq!(subject.len(), 1);
+        assert_eq!(subject[&1_000], vec![dir.join("alpha.txt")].into_iter().collect());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    /// Test `from_dir` with a symbolic link in the directory.
+    /// Must skip the link, like `sub_insert_tree` with the default options.
+    ///
+    fn test_from_dir_x_symlink() {
+        let dir = std::env::temp_dir().join(format!("hash_map_of_mtime_to_set_of_path_buf_from_dir_symlink_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("alpha.txt"), "alpha").unwrap();
+        std::os::unix::fs::symlink("alpha.txt", dir.join("link")).unwrap();
+        let (subject, errors) = HashMapOfMtimeToSetOfPathBuf::from_dir(&dir).unwrap();
+        let mut tree = HashMapOfMtimeToSetOfPathBuf::new();
+        tree.sub_insert_tree(&dir, &WalkOptions::new().max_depth(1)).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(errors.is_empty(), true);
+        assert_eq!(subject.values().flatten().collect::<Vec<&PathBuf>>(), vec![&dir.join("alpha.txt")]);
+        assert_eq!(subject, tree);
+    }
+
+}
@@ -0,0 +1,549 @@
+use std::collections::{HashMap, HashSet};
+use std::borrow::Borrow;
+use std::cmp::Eq;
+use std::hash::Hash;
+use std::sync::Arc;
+
+/// A map of keys to shared sets, so many keys can point to one allocation.
+///
+/// Mutation is copy-on-write: changing a set that other keys share clones
+/// it first, via `Arc::make_mut`, so the other keys keep the old set.
+/// Read-only and no-op calls never clone.
+pub type HashMapToArcSet<K, V> = HashMap<K, Arc<HashSet<V>>>;
+
+pub trait HashMapToArcSetExt<K, V> {
+
+    fn sub_contains<Q, R>(&self, key: &Q, value: &R) -> bool
+    where
+        K: Borrow<Q> + Hash + Eq,
+        V: Borrow<R> + Hash + Eq,
+        Q: Hash + Eq + ?Sized,
+        R: Hash + Eq + ?Sized;
+
+    fn sub_insert(&mut self, key: K, value: V) -> bool
+    where
+        K: Hash + Eq,
+        V: Hash + Eq + Clone;
+
+    fn sub_remove<Q, R>(&mut self, key: &Q, value: &R) -> bool
+    where
+        K: Borrow<Q> + Hash + Eq,
+        V: Borrow<R> + Hash + Eq + Clone,
+        Q: Hash + Eq + ?Sized,
+        R: Hash + Eq + ?Sized;
+
+    fn sub_remove_prune<Q, R>(&mut self, key: &Q, value: &R) -> bool
+    where
+        K: Borrow<Q> + Hash + Eq,
+        V: Borrow<R> + Hash + Eq + Clone,
+        Q: Hash + Eq + ?Sized,
+        R: Hash + Eq + ?Sized;
+
+    fn sub_insert_many<I>(&mut self, key: K, values: I) -> usize
+    where
+        I: IntoIterator<Item = V>,
+        K: Hash + Eq,
+        V: Hash + Eq + Clone;
+
+    fn sub_iter<'a>(&'a self) -> impl Iterator<Item = (&'a K, &'a V)>
+    where
+        K: 'a,
+        V: 'a;
+
+    fn into_sub_iter(self) -> impl Iterator<Item = (K, V)>
+    where
+        K: Clone,
+        V: Clone;
+
+    fn sub_len(&self) -> usize;
+
+    fn sub_len_of<Q>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q> + Hash + Eq,
+        Q: Hash + Eq + ?Sized;
+
+    fn sub_is_shared<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q> + Hash + Eq,
+        Q: Hash + Eq + ?Sized;
+
+    fn sub_share<Q>(&mut self, from_key: &Q, to_key: K) -> bool
+    where
+        K: Borrow<Q> + Hash + Eq,
+        Q: Hash + Eq + ?Sized;
+
+    fn sub_make_mut<Q>(&mut self, key: &Q) -> Option<&mut HashSet<V>>
+    where
+        K: Borrow<Q> + Hash + Eq,
+        V: Clone,
+        Q: Hash + Eq + ?Sized;
+
+    fn sub_intern(&mut self) -> usize
+    where
+        V: Hash + Eq;
+
+}
+
+impl<K, V> HashMapToArcSetExt<K, V> for HashMapToArcSet<K, V> {
+
+    /// Return `true` if the collection contains a sub-key-value item.
+    ///
+    /// The key and value may be any borrowed form of the map's key type
+    /// and the set's value type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToArcSet<u8, u8> = HashMapToArcSet::new();
+    /// a.sub_insert(1, 2);
+    /// assert_eq!(a.sub_contains(&1, &2), true);
+    /// assert_eq!(a.sub_contains(&3, &4), false);
+    /// ```
+    #[inline]
+    fn sub_contains<Q, R>(&self, key: &Q, value: &R) -> bool
+    where
+        K: Borrow<Q> + Hash + Eq,
+        V: Borrow<R> + Hash + Eq,
+        Q: Hash + Eq + ?Sized,
+        R: Hash + Eq + ?Sized,
+    {
+        match self.get(key) {
+            Some(set) => set.contains(value),
+            None => false,
+        }
+    }
+
+    /// Add a sub-key-value item to the collection.
+    ///
+    /// Return whether the item is added in the set. If the key's set is
+    /// shared and the value is new, the set is cloned before the insert.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToArcSet<u8, u8> = HashMapToArcSet::new();
+    /// a.sub_insert(1, 2);
+    /// assert_eq!(a.sub_contains(&1, &2), true);
+    /// ```
+    #[inline]
+    fn sub_insert(&mut self, key: K, value: V) -> bool
+    where
+        K: Hash + Eq,
+        V: Hash + Eq + Clone,
+    {
+        let set = self.entry(key).or_default();
+        if set.contains(&value) {
+            return false;
+        }
+        Arc::make_mut(set).insert(value)
+    }
+
+    /// Remove a sub-key-value pair from the collection.
+    ///
+    /// Return whether the value was present in the set. If the key's set
+    /// is shared and has the value, the set is cloned before the remove.
+    ///
+    /// The key stays in the collection even when its set becomes empty;
+    /// use `sub_remove_prune` to also remove the key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToArcSet<u8, u8> = HashMapToArcSet::new();
+    /// a.sub_insert(1, 2);
+    /// a.sub_remove(&1, &2);
+    /// assert_eq!(a.sub_contains(&1, &2), false);
+    /// assert_eq!(a.contains_key(&1), true);
+    /// ```
+    #[inline]
+    fn sub_remove<Q, R>(&mut self, key: &Q, value: &R) -> bool
+    where
+        K: Borrow<Q> + Hash + Eq,
+        V: Borrow<R> + Hash + Eq + Clone,
+        Q: Hash + Eq + ?Sized,
+        R: Hash + Eq + ?Sized,
+    {
+        match self.get_mut(key) {
+            Some(set) if set.contains(value) => Arc::make_mut(set).remove(value),
+            _ => false,
+        }
+    }
+
+    /// Remove a sub-key-value pair from the collection, and prune the key if its set becomes empty.
+    ///
+    /// Return whether the value was present in the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToArcSet<u8, u8> = HashMapToArcSet::new();
+    /// a.sub_insert(1, 2);
+    /// a.sub_remove_prune(&1, &2);
+    /// assert_eq!(a.contains_key(&1), false);
+    /// ```
+    #[inline]
+    fn sub_remove_prune<Q, R>(&mut self, key: &Q, value: &R) -> bool
+    where
+        K: Borrow<Q> + Hash + Eq,
+        V: Borrow<R> + Hash + Eq + Clone,
+        Q: Hash + Eq + ?Sized,
+        R: Hash + Eq + ?Sized,
+    {
+        let removed = self.sub_remove(key, value);
+        if removed && self.get(key).is_some_and(|set| set.is_empty()) {
+            self.remove(key);
+        }
+        removed
+    }
+
+    /// Add many values to the set for one key.
+    ///
+    /// Return the number of values that are newly added in the set. A
+    /// shared set is cloned at most once, and only if a value is new.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToArcSet<u8, u8> = HashMapToArcSet::new();
+    /// assert_eq!(a.sub_insert_many(1, vec![2, 3, 2]), 2);
+    /// assert_eq!(a.sub_len_of(&1), 2);
+    /// ```
+    #[inline]
+    fn sub_insert_many<I>(&mut self, key: K, values: I) -> usize
+    where
+        I: IntoIterator<Item = V>,
+        K: Hash + Eq,
+        V: Hash + Eq + Clone,
+    {
+        let set = self.entry(key).or_default();
+        let added: Vec<V> = values.into_iter().filter(|value| !set.contains(value)).collect();
+        if added.is_empty() {
+            return 0;
+        }
+        let set = Arc::make_mut(set);
+        let len = set.len();
+        set.extend(added);
+        set.len() - len
+    }
+
+    /// Return an iterator over every sub-key-value pair in the collection.
+    ///
+    /// The iterator yields `(&K, &V)` pairs in arbitrary order. A set that
+    /// several keys share is visited once per key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToArcSet<u8, u8> = HashMapToArcSet::new();
+    /// a.sub_insert(1, 2);
+    /// a.sub_insert(1, 3);
+    /// a.sub_insert(4, 5);
+    /// assert_eq!(a.sub_iter().count(), 3);
+    /// ```
+    #[inline]
+    fn sub_iter<'a>(&'a self) -> impl Iterator<Item = (&'a K, &'a V)>
+    where
+        K: 'a,
+        V: 'a,
+    {
+        self.iter()
+        .flat_map(|(key, set)| set.iter().map(move |value| (key, value)))
+    }
+
+    /// Consume the collection and return an iterator over every sub-key-value pair.
+    ///
+    /// Each key is cloned once per value in its set. Values are moved out
+    /// of sets that are not shared, and cloned out of sets that are.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToArcSet<u8, u8> = HashMapToArcSet::new();
+    /// a.sub_insert(1, 2);
+    /// a.sub_insert(1, 3);
+    /// let mut pairs: Vec<(u8, u8)> = a.into_sub_iter().collect();
+    /// pairs.sort();
+    /// assert_eq!(pairs, vec![(1, 2), (1, 3)]);
+    /// ```
+    #[inline]
+    fn into_sub_iter(self) -> impl Iterator<Item = (K, V)>
+    where
+        K: Clone,
+        V: Clone,
+    {
+        self.into_iter()
+        .flat_map(|(key, set)| Arc::try_unwrap(set).unwrap_or_else(|set| (*set).clone()).into_iter().map(move |value| (key.clone(), value)))
+    }
+
+    /// Return the total number of sub-key-value items in the collection.
+    ///
+    /// This counts every value in every set, unlike `len` which counts
+    /// keys. A set that several keys share is counted once per key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToArcSet<u8, u8> = HashMapToArcSet::new();
+    /// a.sub_insert(1, 2);
+    /// a.sub_insert(1, 3);
+    /// a.sub_insert(4, 5);
+    /// assert_eq!(a.sub_len(), 3);
+    /// ```
+    #[inline]
+    fn sub_len(&self) -> usize {
+        self.values().map(|set| set.len()).sum()
+    }
+
+    /// Return the number of values in the set for a key.
+    ///
+    /// Return 0 if the key is absent or if its set is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToArcSet<u8, u8> = HashMapToArcSet::new();
+    /// a.sub_insert(1, 2);
+    /// assert_eq!(a.sub_len_of(&1), 1);
+    /// assert_eq!(a.sub_len_of(&4), 0);
+    /// ```
+    #[inline]
+    fn sub_len_of<Q>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q> + Hash + Eq,
+        Q: Hash + Eq + ?Sized,
+    {
+        match self.get(key) {
+            Some(set) => set.len(),
+            None => 0,
+        }
+    }
+
+    /// Return `true` if the set for a key is shared with another key or another owner.
+    ///
+    /// Return `false` if the key is absent.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToArcSet<u8, u8> = HashMapToArcSet::new();
+    /// a.sub_insert(1, 2);
+    /// assert_eq!(a.sub_is_shared(&1), false);
+    /// a.sub_share(&1, 3);
+    /// assert_eq!(a.sub_is_shared(&1), true);
+    /// ```
+    #[inline]
+    fn sub_is_shared<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q> + Hash + Eq,
+        Q: Hash + Eq + ?Sized,
+    {
+        match self.get(key) {
+            Some(set) => Arc::strong_count(set) > 1,
+            None => false,
+        }
+    }
+
+    /// Point another key at the same set as an existing key, without cloning the set.
+    ///
+    /// Return `true` if the existing key is present. Any previous set of
+    /// the other key is replaced.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToArcSet<&str, &str> = HashMapToArcSet::new();
+    /// a.sub_insert_many("admin", vec!["read", "write"]);
+    /// assert_eq!(a.sub_share("admin", "alice"), true);
+    /// assert_eq!(a.sub_contains("alice", "write"), true);
+    /// ```
+    #[inline]
+    fn sub_share<Q>(&mut self, from_key: &Q, to_key: K) -> bool
+    where
+        K: Borrow<Q> + Hash + Eq,
+        Q: Hash + Eq + ?Sized,
+    {
+        match self.get(from_key) {
+            Some(set) => {
+                let set = Arc::clone(set);
+                self.insert(to_key, set);
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Return a mutable reference to the set for a key, cloning it first if it is shared.
+    ///
+    /// Use this for several edits to one key's set with a single clone.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToArcSet<u8, u8> = HashMapToArcSet::new();
+    /// a.sub_insert(1, 2);
+    /// a.sub_share(&1, 3);
+    /// a.sub_make_mut(&3).unwrap().extend(vec![4, 5]);
+    /// assert_eq!(a.sub_len_of(&1), 1);
+    /// assert_eq!(a.sub_len_of(&3), 3);
+    /// ```
+    #[inline]
+    fn sub_make_mut<Q>(&mut self, key: &Q) -> Option<&mut HashSet<V>>
+    where
+        K: Borrow<Q> + Hash + Eq,
+        V: Clone,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get_mut(key).map(Arc::make_mut)
+    }
+
+    /// Make keys with equal sets share one allocation.
+    ///
+    /// Return the number of keys whose set was replaced by a shared one.
+    /// This compares each set with every distinct set seen so far, so it
+    /// suits maps with a small number of distinct sets.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToArcSet<u8, u8> = HashMapToArcSet::new();
+    /// a.sub_insert_many(1, vec![2, 3]);
+    /// a.sub_insert_many(4, vec![3, 2]);
+    /// assert_eq!(a.sub_intern(), 1);
+    /// assert_eq!(a.sub_is_shared(&1), true);
+    /// ```
+    #[inline]
+    fn sub_intern(&mut self) -> usize
+    where
+        V: Hash + Eq,
+    {
+        let mut distinct: Vec<Arc<HashSet<V>>> = Vec::new();
+        let mut count = 0;
+        for set in self.values_mut() {
+            match distinct.iter().find(|x| x.len() == set.len() && (Arc::ptr_eq(x, set) || x.as_ref() == set.as_ref())) {
+                Some(x) => {
+                    if !Arc::ptr_eq(x, set) {
+                        *set = Arc::clone(x);
+                        count += 1;
+                    }
+                },
+                None => distinct.push(Arc::clone(set)),
+            }
+        }
+        count
+    }
+
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+
+    #[test]
+    /// Test `sub_contains` with some items.
+    fn test_sub_contains() {
+        let mut subject: HashMapToArcSet<u8, u8> = HashMapToArcSet::new();
+        let k = 1;
+        let v = 2;
+        let absent = 3;
+        assert_eq!(subject.sub_insert(k, v), true);
+        assert_eq!(subject.sub_contains(&k, &v), true);
+        assert_eq!(subject.sub_contains(&k, &absent), false);
+        assert_eq!(subject.sub_contains(&absent, &v), false);
+    }
+
+    #[test]
+    /// Test `sub_insert` on a shared set clones it and leaves the other key unchanged.
+    fn test_sub_insert_x_copy_on_write() {
+        let mut subject: HashMapToArcSet<u8, u8> = HashMapToArcSet::new();
+        let k1 = 1;
+        let k2 = 2;
+        subject.sub_insert(k1, 10);
+        subject.sub_share(&k1, k2);
+        assert_eq!(Arc::ptr_eq(&subject[&k1], &subject[&k2]), true);
+        assert_eq!(subject.sub_insert(k2, 10), false);
+        assert_eq!(Arc::ptr_eq(&subject[&k1], &subject[&k2]), true);
+        assert_eq!(subject.sub_insert(k2, 20), true);
+        assert_eq!(Arc::ptr_eq(&subject[&k1], &subject[&k2]), false);
+        assert_eq!(subject.sub_contains(&k1, &20), false);
+        assert_eq!(subject.sub_is_shared(&k1), false);
+    }
+
+    #[test]
+    /// Test `sub_remove` and `sub_remove_prune` only clone a shared set when the value is present.
+    fn test_sub_remove_and_sub_remove_prune() {
+        let mut subject: HashMapToArcSet<u8, u8> = HashMapToArcSet::new();
+        let k1 = 1;
+        let k2 = 2;
+        let v = 3;
+        let absent = 9;
+        subject.sub_insert(k1, v);
+        subject.sub_share(&k1, k2);
+        assert_eq!(subject.sub_remove(&k1, &absent), false);
+        assert_eq!(subject.sub_is_shared(&k1), true);
+        assert_eq!(subject.sub_remove(&k1, &v), true);
+        assert_eq!(subject.contains_key(&k1), true);
+        assert_eq!(subject.sub_contains(&k2, &v), true);
+        assert_eq!(subject.sub_remove_prune(&k2, &v), true);
+        assert_eq!(subject.contains_key(&k2), false);
+        assert_eq!(subject.sub_remove_prune(&absent, &v), false);
+    }
+
+    #[test]
+    /// Test `sub_insert_many` does not clone a shared set when every value is already present.
+    fn test_sub_insert_many_x_shared() {
+        let mut subject: HashMapToArcSet<u8, u8> = HashMapToArcSet::new();
+        subject.sub_insert_many(1, vec![2, 3]);
+        subject.sub_share(&1, 4);
+        assert_eq!(subject.sub_insert_many(4, vec![3, 2]), 0);
+        assert_eq!(subject.sub_is_shared(&4), true);
+        assert_eq!(subject.sub_insert_many(4, vec![3, 5]), 1);
+        assert_eq!(subject.sub_is_shared(&4), false);
+        assert_eq!(subject.sub_len_of(&1), 2);
+    }
+
+    #[test]
+    /// Test `sub_iter`, `into_sub_iter`, `sub_len`, and `sub_len_of` with a shared set.
+    fn test_sub_iter_and_sub_len() {
+        let mut subject: HashMapToArcSet<u8, u8> = HashMapToArcSet::new();
+        subject.sub_insert_many(1, vec![3, 2]);
+        subject.sub_share(&1, 4);
+        assert_eq!(subject.sub_len(), 4);
+        assert_eq!(subject.sub_len_of(&4), 2);
+        let mut actual: Vec<(u8, u8)> = subject.sub_iter().map(|(k, v)| (*k, *v)).collect();
+        actual.sort();
+        assert_eq!(actual, vec![(1, 2), (1, 3), (4, 2), (4, 3)]);
+        let mut actual: Vec<(u8, u8)> = subject.into_sub_iter().collect();
+        actual.sort();
+        assert_eq!(actual, vec![(1, 2), (1, 3), (4, 2), (4, 3)]);
+    }
+
+    #[test]
+    /// Test `sub_intern` shares equal sets and leaves distinct sets alone.
+    fn test_sub_intern() {
+        let mut subject: HashMapToArcSet<u8, u8> = HashMapToArcSet::new();
+        subject.sub_insert_many(1, vec![2, 3]);
+        subject.sub_insert_many(4, vec![3, 2]);
+        subject.sub_insert_many(5, vec![3, 2]);
+        subject.sub_insert_many(6, vec![7]);
+        assert_eq!(subject.sub_intern(), 2);
+        assert_eq!(subject.sub_intern(), 0);
+        assert_eq!(Arc::ptr_eq(&subject[&1], &subject[&4]), true);
+        assert_eq!(Arc::ptr_eq(&subject[&4], &subject[&5]), true);
+        assert_eq!(subject.sub_is_shared(&6), false);
+    }
+
+}
@@ -0,0 +1,422 @@
+use std::collections::HashMap;
+use std::borrow::Borrow;
+use std::cmp::Eq;
+use std::hash::Hash;
+
+pub type HashMapToBag<K, V> = HashMap<K, HashMap<V, usize>>;
+
+pub trait HashMapToBagExt<K, V> {
+
+    fn sub_add(&mut self, key: K, value: V) -> usize
+    where
+        K: Hash + Eq,
+        V: Hash + Eq;
+
+    fn sub_set_count(&mut self, key: K, value: V, count: usize) -> Option<usize>
+    where
+        K: Hash + Eq,
+        V: Hash + Eq;
+
+    fn sub_remove_one<Q, R>(&mut self, key: &Q, value: &R) -> bool
+    where
+        K: Borrow<Q> + Hash + Eq,
+        V: Borrow<R> + Hash + Eq,
+        Q: Hash + Eq + ?Sized,
+        R: Hash + Eq + ?Sized;
+
+    fn sub_count<Q, R>(&self, key: &Q, value: &R) -> usize
+    where
+        K: Borrow<Q> + Hash + Eq,
+        V: Borrow<R> + Hash + Eq,
+        Q: Hash + Eq + ?Sized,
+        R: Hash + Eq + ?Sized;
+
+    fn sub_contains<Q, R>(&self, key: &Q, value: &R) -> bool
+    where
+        K: Borrow<Q> + Hash + Eq,
+        V: Borrow<R> + Hash + Eq,
+        Q: Hash + Eq + ?Sized,
+        R: Hash + Eq + ?Sized;
+
+    fn sub_len(&self) -> usize;
+
+    fn sub_len_of<Q>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q> + Hash + Eq,
+        Q: Hash + Eq + ?Sized;
+
+    fn sub_iter<'a>(&'a self) -> impl Iterator<Item = (&'a K, &'a V)>
+    where
+        K: 'a,
+        V: 'a;
+
+    fn sub_iter_with_counts<'a>(&'a self) -> impl Iterator<Item = (&'a K, &'a V, usize)>
+    where
+        K: 'a,
+        V: 'a;
+
+}
+
+impl<K, V> HashMapToBagExt<K, V> for HashMapToBag<K, V> {
+
+    /// Add one occurrence of a sub-key-value item to the collection.
+    ///
+    /// Return the count of the value for the key after adding.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToBag<u8, u8> = HashMapToBag::new();
+    /// assert_eq!(a.sub_add(1, 2), 1);
+    /// assert_eq!(a.sub_add(1, 2), 2);
+    /// ```
+    #[inline]
+    fn sub_add(&mut self, key: K, value: V) -> usize
+    where
+        K: Hash + Eq,
+        V: Hash + Eq,
+    {
+        let count = self.entry(key)
+        .or_default()
+        .entry(value)
+        .or_insert(0);
+        *count += 1;
+        *count
+    }
+
+    /// Set the number of occurrences of a sub-key-value item.
+    ///
+    /// Return the previous count, if any, like `HashMap::insert`. A count
+    /// of 0 removes the value, and removes the key when its bag becomes
+    /// empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToBag<u8, u8> = HashMapToBag::new();
+    /// assert_eq!(a.sub_set_count(1, 2, 3), None);
+    /// assert_eq!(a.sub_set_count(1, 2, 5), Some(3));
+    /// assert_eq!(a.sub_set_count(1, 2, 0), Some(5));
+    /// assert_eq!(a.contains_key(&1), false);
+    /// ```
+    #[inline]
+    fn sub_set_count(&mut self, key: K, value: V, count: usize) -> Option<usize>
+    where
+        K: Hash + Eq,
+        V: Hash + Eq,
+    {
+        if count == 0 {
+            let bag = self.get_mut(&key)?;
+            let previous = bag.remove(&value);
+            if bag.is_empty() {
+                self.remove(&key);
+            }
+            previous
+        } else {
+            self.entry(key)
+            .or_default()
+            .insert(value, count)
+        }
+    }
+
+    /// Remove one occurrence of a sub-key-value item from the collection.
+    ///
+    /// Return whether the value was present for the key. When the count
+    /// reaches zero the value is removed from the bag, and the key is
+    /// removed from the collection when its bag becomes empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToBag<u8, u8> = HashMapToBag::new();
+    /// a.sub_add(1, 2);
+    /// a.sub_add(1, 2);
+    /// assert_eq!(a.sub_remove_one(&1, &2), true);
+    /// assert_eq!(a.sub_count(&1, &2), 1);
+    /// assert_eq!(a.sub_remove_one(&1, &2), true);
+    /// assert_eq!(a.sub_remove_one(&1, &2), false);
+    /// assert_eq!(a.is_empty(), true);
+    /// ```
+    #[inline]
+    fn sub_remove_one<Q, R>(&mut self, key: &Q, value: &R) -> bool
+    where
+        K: Borrow<Q> + Hash + Eq,
+        V: Borrow<R> + Hash + Eq,
+        Q: Hash + Eq + ?Sized,
+        R: Hash + Eq + ?Sized,
+    {
+        let bag = match self.get_mut(key) {
+            Some(bag) => bag,
+            None => return false,
+        };
+        match bag.get_mut(value) {
+            Some(count) => {
+                *count -= 1;
+                if *count == 0 {
+                    bag.remove(value);
+                    if bag.is_empty() {
+                        self.remove(key);
+                    }
+                }
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Return the number of occurrences of a sub-key-value item.
+    ///
+    /// Return 0 if the key is absent or if the value is absent for the key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToBag<u8, u8> = HashMapToBag::new();
+    /// a.sub_add(1, 2);
+    /// a.sub_add(1, 2);
+    /// assert_eq!(a.sub_count(&1, &2), 2);
+    /// assert_eq!(a.sub_count(&1, &3), 0);
+    /// ```
+    #[inline]
+    fn sub_count<Q, R>(&self, key: &Q, value: &R) -> usize
+    where
+        K: Borrow<Q> + Hash + Eq,
+        V: Borrow<R> + Hash + Eq,
+        Q: Hash + Eq + ?Sized,
+        R: Hash + Eq + ?Sized,
+    {
+        match self.get(key) {
+            Some(bag) => bag.get(value).copied().unwrap_or(0),
+            None => 0,
+        }
+    }
+
+    /// Return `true` if the collection contains at least one occurrence of a sub-key-value item.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToBag<u8, u8> = HashMapToBag::new();
+    /// a.sub_add(1, 2);
+    /// assert_eq!(a.sub_contains(&1, &2), true);
+    /// assert_eq!(a.sub_contains(&1, &3), false);
+    /// ```
+    #[inline]
+    fn sub_contains<Q, R>(&self, key: &Q, value: &R) -> bool
+    where
+        K: Borrow<Q> + Hash + Eq,
+        V: Borrow<R> + Hash + Eq,
+        Q: Hash + Eq + ?Sized,
+        R: Hash + Eq + ?Sized,
+    {
+        self.sub_count(key, value) > 0
+    }
+
+    /// Return the total number of occurrences in the collection.
+    ///
+    /// This sums every count in every bag, so duplicates are counted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToBag<u8, u8> = HashMapToBag::new();
+    /// a.sub_add(1, 2);
+    /// a.sub_add(1, 2);
+    /// a.sub_add(3, 4);
+    /// assert_eq!(a.sub_len(), 3);
+    /// ```
+    #[inline]
+    fn sub_len(&self) -> usize {
+        self.values().flat_map(|bag| bag.values()).sum()
+    }
+
+    /// Return the total number of occurrences in the bag for a key.
+    ///
+    /// Return 0 if the key is absent or if its bag is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToBag<u8, u8> = HashMapToBag::new();
+    /// a.sub_add(1, 2);
+    /// a.sub_add(1, 2);
+    /// a.sub_add(1, 3);
+    /// assert_eq!(a.sub_len_of(&1), 3);
+    /// assert_eq!(a.sub_len_of(&4), 0);
+    /// ```
+    #[inline]
+    fn sub_len_of<Q>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q> + Hash + Eq,
+        Q: Hash + Eq + ?Sized,
+    {
+        match self.get(key) {
+            Some(bag) => bag.values().sum(),
+            None => 0,
+        }
+    }
+
+    /// Return an iterator over every occurrence in the collection.
+    ///
+    /// The iterator yields one `(&K, &V)` pair per occurrence, so a value
+    /// with count 3 is yielded three times, in arbitrary order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToBag<u8, u8> = HashMapToBag::new();
+    /// a.sub_add(1, 2);
+    /// a.sub_add(1, 2);
+    /// let pairs: Vec<(&u8, &u8)> = a.sub_iter().collect();
+    /// assert_eq!(pairs, vec![(&1, &2), (&1, &2)]);
+    /// ```
+    #[inline]
+    fn sub_iter<'a>(&'a self) -> impl Iterator<Item = (&'a K, &'a V)>
+    where
+        K: 'a,
+        V: 'a,
+    {
+        self.sub_iter_with_counts()
+        .flat_map(|(key, value, count)| std::iter::repeat((key, value)).take(count))
+    }
+
+    /// Return an iterator over every distinct sub-key-value item with its count.
+    ///
+    /// The iterator yields `(&K, &V, usize)` triples in arbitrary order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToBag<u8, u8> = HashMapToBag::new();
+    /// a.sub_add(1, 2);
+    /// a.sub_add(1, 2);
+    /// let triples: Vec<(&u8, &u8, usize)> = a.sub_iter_with_counts().collect();
+    /// assert_eq!(triples, vec![(&1, &2, 2)]);
+    /// ```
+    #[inline]
+    fn sub_iter_with_counts<'a>(&'a self) -> impl Iterator<Item = (&'a K, &'a V, usize)>
+    where
+        K: 'a,
+        V: 'a,
+    {
+        self.iter()
+        .flat_map(|(key, bag)| bag.iter().map(move |(value, count)| (key, value, *count)))
+    }
+
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+
+    #[test]
+    /// Test `sub_add` and `sub_count` with duplicate items.
+    fn test_sub_add_and_sub_count() {
+        let mut subject: HashMapToBag<u8, u8> = HashMapToBag::new();
+        let k1 = 1;
+        let k2 = 2;
+        let v1 = 3;
+        let v2 = 4;
+        let absent = 9;
+        assert_eq!(subject.sub_add(k1, v1), 1);
+        assert_eq!(subject.sub_add(k1, v1), 2);
+        assert_eq!(subject.sub_add(k1, v2), 1);
+        assert_eq!(subject.sub_add(k2, v1), 1);
+        assert_eq!(subject.sub_count(&k1, &v1), 2);
+        assert_eq!(subject.sub_count(&k1, &v2), 1);
+        assert_eq!(subject.sub_count(&k2, &v1), 1);
+        assert_eq!(subject.sub_count(&k2, &v2), 0);
+        assert_eq!(subject.sub_count(&absent, &v1), 0);
+    }
+
+    #[test]
+    /// Test `sub_remove_one` decrements, then removes the value, then removes the key.
+    fn test_sub_remove_one() {
+        let mut subject: HashMapToBag<u8, u8> = HashMapToBag::new();
+        let k = 1;
+        let v = 2;
+        let absent = 9;
+        subject.sub_add(k, v);
+        subject.sub_add(k, v);
+        assert_eq!(subject.sub_remove_one(&k, &v), true);
+        assert_eq!(subject.sub_count(&k, &v), 1);
+        assert_eq!(subject.sub_remove_one(&k, &v), true);
+        assert_eq!(subject.sub_contains(&k, &v), false);
+        assert_eq!(subject.contains_key(&k), false);
+        assert_eq!(subject.len(), 0);
+        assert_eq!(subject.sub_remove_one(&k, &v), false);
+        assert_eq!(subject.sub_remove_one(&absent, &v), false);
+    }
+
+    #[test]
+    /// Test `sub_remove_one` keeps a key while its bag holds another value.
+    fn test_sub_remove_one_x_len() {
+        let mut subject: HashMapToBag<u8, u8> = HashMapToBag::new();
+        subject.sub_add(1, 2);
+        subject.sub_add(1, 3);
+        subject.sub_add(4, 5);
+        assert_eq!(subject.sub_remove_one(&1, &2), true);
+        assert_eq!(subject.len(), 2);
+        assert_eq!(subject.sub_remove_one(&1, &3), true);
+        assert_eq!(subject.len(), 1);
+        assert_eq!(subject.sub_remove_one(&4, &5), true);
+        assert_eq!(subject.len(), 0);
+    }
+
+    #[test]
+    /// Test `sub_set_count` with present and absent values, and with a count of 0.
+    fn test_sub_set_count() {
+        let mut subject: HashMapToBag<u8, u8> = HashMapToBag::new();
+        assert_eq!(subject.sub_set_count(1, 2, 0), None);
+        assert_eq!(subject.contains_key(&1), false);
+        assert_eq!(subject.sub_set_count(1, 2, 3), None);
+        assert_eq!(subject.sub_set_count(1, 4, 1), None);
+        assert_eq!(subject.sub_set_count(1, 2, 5), Some(3));
+        assert_eq!(subject.sub_count(&1, &2), 5);
+        assert_eq!(subject.sub_set_count(1, 9, 0), None);
+        assert_eq!(subject.sub_set_count(1, 2, 0), Some(5));
+        assert_eq!(subject.sub_contains(&1, &2), false);
+        assert_eq!(subject.len(), 1);
+        assert_eq!(subject.sub_set_count(1, 4, 0), Some(1));
+        assert_eq!(subject.len(), 0);
+    }
+
+    #[test]
+    /// Test `sub_count` with borrowed forms of the key and value.
+    fn test_sub_count_x_borrowed_forms() {
+        let mut subject: HashMapToBag<String, String> = HashMapToBag::new();
+        subject.sub_add(String::from("alpha"), String::from("bravo"));
+        assert_eq!(subject.sub_count("alpha", "bravo"), 1);
+        assert_eq!(subject.sub_remove_one("alpha", "bravo"), true);
+    }
+
+    #[test]
+    /// Test `sub_len`, `sub_len_of`, `sub_iter`, and `sub_iter_with_counts` count duplicates.
+    fn test_sub_len_and_sub_iter() {
+        let mut subject: HashMapToBag<u8, u8> = HashMapToBag::new();
+        subject.sub_add(1, 2);
+        subject.sub_add(1, 2);
+        subject.sub_add(1, 3);
+        subject.sub_add(4, 5);
+        assert_eq!(subject.sub_len(), 4);
+        assert_eq!(subject.sub_len_of(&1), 3);
+        assert_eq!(subject.sub_len_of(&9), 0);
+        let mut actual: Vec<(&u8, &u8)> = subject.sub_iter().collect();
+        actual.sort();
+        assert_eq!(actual, vec![(&1, &2), (&1, &2), (&1, &3), (&4, &5)]);
+        let mut actual: Vec<(&u8, &u8, usize)> = subject.sub_iter_with_counts().collect();
+        actual.sort();
+        assert_eq!(actual, vec![(&1, &2, 2), (&1, &3, 1), (&4, &5, 1)]);
+    }
+
+}
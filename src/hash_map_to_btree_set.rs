@@ -0,0 +1,447 @@
+use std::collections::{BTreeSet, HashMap};
+use std::borrow::Borrow;
+use std::cmp::{Eq, Ord};
+use std::hash::Hash;
+
+pub type HashMapToBTreeSet<K, V> = HashMap<K, BTreeSet<V>>;
+
+pub trait HashMapToBTreeSetExt<K, V> {
+
+    fn sub_contains<Q, R>(&self, key: &Q, value: &R) -> bool
+    where
+        K: Borrow<Q> + Hash + Eq,
+        V: Borrow<R> + Ord,
+        Q: Hash + Eq + ?Sized,
+        R: Ord + ?Sized;
+
+    fn sub_insert(&mut self, key: K, value: V) -> bool
+    where
+        K: Hash + Eq,
+        V: Ord;
+
+    fn sub_remove<Q, R>(&mut self, key: &Q, value: &R) -> bool
+    where
+        K: Borrow<Q> + Hash + Eq,
+        V: Borrow<R> + Ord,
+        Q: Hash + Eq + ?Sized,
+        R: Ord + ?Sized;
+
+    fn sub_remove_prune<Q, R>(&mut self, key: &Q, value: &R) -> bool
+    where
+        K: Borrow<Q> + Hash + Eq,
+        V: Borrow<R> + Ord,
+        Q: Hash + Eq + ?Sized,
+        R: Ord + ?Sized;
+
+    fn sub_insert_many<I>(&mut self, key: K, values: I) -> usize
+    where
+        I: IntoIterator<Item = V>,
+        K: Hash + Eq,
+        V: Ord;
+
+    fn sub_iter<'a>(&'a self) -> impl Iterator<Item = (&'a K, &'a V)>
+    where
+        K: 'a,
+        V: 'a;
+
+    fn into_sub_iter(self) -> impl Iterator<Item = (K, V)>
+    where
+        K: Clone;
+
+    fn sub_len(&self) -> usize;
+
+    fn sub_len_of<Q>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q> + Hash + Eq,
+        Q: Hash + Eq + ?Sized;
+
+    fn sub_first_value<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q> + Hash + Eq,
+        V: Ord,
+        Q: Hash + Eq + ?Sized;
+
+    fn sub_last_value<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q> + Hash + Eq,
+        V: Ord,
+        Q: Hash + Eq + ?Sized;
+
+}
+
+impl<K, V> HashMapToBTreeSetExt<K, V> for HashMapToBTreeSet<K, V> {
+
+    /// Return `true` if the collection contains a sub-key-value item.
+    ///
+    /// The key and value may be any borrowed form of the map's key type
+    /// and the set's value type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToBTreeSet<u8, u8> = HashMapToBTreeSet::new();
+    /// a.sub_insert(1, 2);
+    /// assert_eq!(a.sub_contains(&1, &2), true);
+    /// assert_eq!(a.sub_contains(&3, &4), false);
+    /// ```
+    #[inline]
+    fn sub_contains<Q, R>(&self, key: &Q, value: &R) -> bool
+    where
+        K: Borrow<Q> + Hash + Eq,
+        V: Borrow<R> + Ord,
+        Q: Hash + Eq + ?Sized,
+        R: Ord + ?Sized,
+    {
+        match self.get(key) {
+            Some(set) => set.contains(value),
+            None => false,
+        }
+    }
+
+    /// Add a sub-key-value item to the collection.
+    ///
+    /// Return whether the item is added in the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToBTreeSet<u8, u8> = HashMapToBTreeSet::new();
+    /// a.sub_insert(1, 2);
+    /// assert_eq!(a.sub_contains(&1, &2), true);
+    /// ```
+    #[inline]
+    fn sub_insert(&mut self, key: K, value: V) -> bool
+    where
+        K: Hash + Eq,
+        V: Ord,
+    {
+        self.entry(key)
+        .or_default()
+        .insert(value)
+    }
+
+    /// Remove a sub-key-value pair from the collection.
+    ///
+    /// Return whether the value was present in the set.
+    ///
+    /// The key stays in the collection even when its set becomes empty;
+    /// use `sub_remove_prune` to also remove the key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToBTreeSet<u8, u8> = HashMapToBTreeSet::new();
+    /// a.sub_insert(1, 2);
+    /// a.sub_remove(&1, &2);
+    /// assert_eq!(a.sub_contains(&1, &2), false);
+    /// assert_eq!(a.contains_key(&1), true);
+    /// ```
+    #[inline]
+    fn sub_remove<Q, R>(&mut self, key: &Q, value: &R) -> bool
+    where
+        K: Borrow<Q> + Hash + Eq,
+        V: Borrow<R> + Ord,
+        Q: Hash + Eq + ?Sized,
+        R: Ord + ?Sized,
+    {
+        match self.get_mut(key) {
+            Some(set) => set.remove(value),
+            None => false,
+        }
+    }
+
+    /// Remove a sub-key-value pair from the collection, and prune the key if its set becomes empty.
+    ///
+    /// Return whether the value was present in the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToBTreeSet<u8, u8> = HashMapToBTreeSet::new();
+    /// a.sub_insert(1, 2);
+    /// a.sub_remove_prune(&1, &2);
+    /// assert_eq!(a.contains_key(&1), false);
+    /// ```
+    #[inline]
+    fn sub_remove_prune<Q, R>(&mut self, key: &Q, value: &R) -> bool
+    where
+        K: Borrow<Q> + Hash + Eq,
+        V: Borrow<R> + Ord,
+        Q: Hash + Eq + ?Sized,
+        R: Ord + ?Sized,
+    {
+        match self.get_mut(key) {
+            Some(set) => {
+                let removed = set.remove(value);
+                if set.is_empty() {
+                    self.remove(key);
+                }
+                removed
+            },
+            None => false,
+        }
+    }
+
+    /// Add many values to the set for one key.
+    ///
+    /// Return the number of values that are newly added in the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToBTreeSet<u8, u8> = HashMapToBTreeSet::new();
+    /// assert_eq!(a.sub_insert_many(1, vec![2, 3, 2]), 2);
+    /// assert_eq!(a.sub_len_of(&1), 2);
+    /// ```
+    #[inline]
+    fn sub_insert_many<I>(&mut self, key: K, values: I) -> usize
+    where
+        I: IntoIterator<Item = V>,
+        K: Hash + Eq,
+        V: Ord,
+    {
+        let set = self.entry(key).or_default();
+        let len = set.len();
+        set.extend(values);
+        set.len() - len
+    }
+
+    /// Return an iterator over every sub-key-value pair in the collection.
+    ///
+    /// The iterator yields `(&K, &V)` pairs with keys in arbitrary order
+    /// and each key's values in sorted order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToBTreeSet<u8, u8> = HashMapToBTreeSet::new();
+    /// a.sub_insert(1, 2);
+    /// a.sub_insert(1, 3);
+    /// a.sub_insert(4, 5);
+    /// assert_eq!(a.sub_iter().count(), 3);
+    /// ```
+    #[inline]
+    fn sub_iter<'a>(&'a self) -> impl Iterator<Item = (&'a K, &'a V)>
+    where
+        K: 'a,
+        V: 'a,
+    {
+        self.iter()
+        .flat_map(|(key, set)| set.iter().map(move |value| (key, value)))
+    }
+
+    /// Consume the collection and return an iterator over every sub-key-value pair.
+    ///
+    /// Each key is cloned once per value in its set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToBTreeSet<u8, u8> = HashMapToBTreeSet::new();
+    /// a.sub_insert(1, 2);
+    /// a.sub_insert(1, 3);
+    /// let mut pairs: Vec<(u8, u8)> = a.into_sub_iter().collect();
+    /// pairs.sort();
+    /// assert_eq!(pairs, vec![(1, 2), (1, 3)]);
+    /// ```
+    #[inline]
+    fn into_sub_iter(self) -> impl Iterator<Item = (K, V)>
+    where
+        K: Clone,
+    {
+        self.into_iter()
+        .flat_map(|(key, set)| set.into_iter().map(move |value| (key.clone(), value)))
+    }
+
+    /// Return the total number of sub-key-value items in the collection.
+    ///
+    /// This counts every value in every set, unlike `len` which counts keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToBTreeSet<u8, u8> = HashMapToBTreeSet::new();
+    /// a.sub_insert(1, 2);
+    /// a.sub_insert(1, 3);
+    /// a.sub_insert(4, 5);
+    /// assert_eq!(a.sub_len(), 3);
+    /// ```
+    #[inline]
+    fn sub_len(&self) -> usize {
+        self.values().map(|set| set.len()).sum()
+    }
+
+    /// Return the number of values in the set for a key.
+    ///
+    /// Return 0 if the key is absent or if its set is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToBTreeSet<u8, u8> = HashMapToBTreeSet::new();
+    /// a.sub_insert(1, 2);
+    /// assert_eq!(a.sub_len_of(&1), 1);
+    /// assert_eq!(a.sub_len_of(&4), 0);
+    /// ```
+    #[inline]
+    fn sub_len_of<Q>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q> + Hash + Eq,
+        Q: Hash + Eq + ?Sized,
+    {
+        match self.get(key) {
+            Some(set) => set.len(),
+            None => 0,
+        }
+    }
+
+    /// Return the smallest value in the set for a key.
+    ///
+    /// Return `None` if the key is absent or if its set is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToBTreeSet<u8, u8> = HashMapToBTreeSet::new();
+    /// a.sub_insert(1, 3);
+    /// a.sub_insert(1, 2);
+    /// assert_eq!(a.sub_first_value(&1), Some(&2));
+    /// assert_eq!(a.sub_first_value(&4), None);
+    /// ```
+    #[inline]
+    fn sub_first_value<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q> + Hash + Eq,
+        V: Ord,
+        Q: Hash + Eq + ?Sized,
+    {
+        match self.get(key) {
+            Some(set) => set.first(),
+            None => None,
+        }
+    }
+
+    /// Return the largest value in the set for a key.
+    ///
+    /// Return `None` if the key is absent or if its set is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToBTreeSet<u8, u8> = HashMapToBTreeSet::new();
+    /// a.sub_insert(1, 3);
+    /// a.sub_insert(1, 2);
+    /// assert_eq!(a.sub_last_value(&1), Some(&3));
+    /// assert_eq!(a.sub_last_value(&4), None);
+    /// ```
+    #[inline]
+    fn sub_last_value<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q> + Hash + Eq,
+        V: Ord,
+        Q: Hash + Eq + ?Sized,
+    {
+        match self.get(key) {
+            Some(set) => set.last(),
+            None => None,
+        }
+    }
+
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+
+    #[test]
+    /// Test `sub_contains` with some items.
+    fn test_sub_contains() {
+        let mut subject: HashMapToBTreeSet<u8, u8> = HashMapToBTreeSet::new();
+        let k = 1;
+        let v = 2;
+        let absent = 3;
+        assert_eq!(subject.sub_insert(k, v), true);
+        assert_eq!(subject.sub_contains(&k, &v), true);
+        assert_eq!(subject.sub_contains(&k, &absent), false);
+        assert_eq!(subject.sub_contains(&absent, &v), false);
+    }
+
+    #[test]
+    /// Test `sub_contains` with borrowed forms of the key and value.
+    fn test_sub_contains_x_borrowed_forms() {
+        let mut subject: HashMapToBTreeSet<String, String> = HashMapToBTreeSet::new();
+        subject.sub_insert(String::from("alpha"), String::from("bravo"));
+        assert_eq!(subject.sub_contains("alpha", "bravo"), true);
+        assert_eq!(subject.sub_contains("alpha", "charlie"), false);
+    }
+
+    #[test]
+    /// Test `sub_insert` with a duplicate item.
+    fn test_sub_insert() {
+        let mut subject: HashMapToBTreeSet<u8, u8> = HashMapToBTreeSet::new();
+        let k = 1;
+        let v = 2;
+        assert_eq!(subject.sub_insert(k, v), true);
+        assert_eq!(subject.sub_insert(k, v), false);
+        assert_eq!(subject.sub_len_of(&k), 1);
+    }
+
+    #[test]
+    /// Test `sub_remove` and `sub_remove_prune` with some items.
+    fn test_sub_remove_and_sub_remove_prune() {
+        let mut subject: HashMapToBTreeSet<u8, u8> = HashMapToBTreeSet::new();
+        let k1 = 1;
+        let k2 = 2;
+        let v = 3;
+        let absent = 9;
+        subject.sub_insert(k1, v);
+        subject.sub_insert(k2, v);
+        assert_eq!(subject.sub_remove(&k1, &v), true);
+        assert_eq!(subject.sub_remove(&k1, &v), false);
+        assert_eq!(subject.contains_key(&k1), true);
+        assert_eq!(subject.sub_remove_prune(&k2, &v), true);
+        assert_eq!(subject.contains_key(&k2), false);
+        assert_eq!(subject.sub_remove_prune(&absent, &v), false);
+    }
+
+    #[test]
+    /// Test `sub_iter`, `into_sub_iter`, `sub_len`, and `sub_len_of` with some items.
+    fn test_sub_iter_and_sub_len() {
+        let mut subject: HashMapToBTreeSet<u8, u8> = HashMapToBTreeSet::new();
+        subject.sub_insert_many(1, vec![3, 2]);
+        subject.sub_insert(4, 5);
+        assert_eq!(subject.sub_len(), 3);
+        assert_eq!(subject.sub_len_of(&1), 2);
+        let mut actual: Vec<(u8, u8)> = subject.sub_iter().map(|(k, v)| (*k, *v)).collect();
+        actual.sort();
+        assert_eq!(actual, vec![(1, 2), (1, 3), (4, 5)]);
+        let mut actual: Vec<(u8, u8)> = subject.into_sub_iter().collect();
+        actual.sort();
+        assert_eq!(actual, vec![(1, 2), (1, 3), (4, 5)]);
+    }
+
+    #[test]
+    /// Test that each key's values iterate in sorted order.
+    fn test_values_iterate_in_sorted_order() {
+        let mut subject: HashMapToBTreeSet<u8, u8> = HashMapToBTreeSet::new();
+        let k = 1;
+        subject.sub_insert_many(k, vec![5, 3, 4, 1]);
+        let actual: Vec<&u8> = subject[&k].iter().collect();
+        assert_eq!(actual, vec![&1, &3, &4, &5]);
+        assert_eq!(subject.sub_first_value(&k), Some(&1));
+        assert_eq!(subject.sub_last_value(&k), Some(&5));
+    }
+
+}
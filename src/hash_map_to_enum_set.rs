@@ -0,0 +1,402 @@
+use std::collections::HashMap;
+use std::borrow::Borrow;
+use std::cmp::Eq;
+use std::hash::Hash;
+use enumset::{EnumSet, EnumSetType};
+
+/// A map of keys to sets of fieldless enum values, where each set is a
+/// bit set stored in a single integer.
+///
+/// Values are passed and returned by value, because enum set values are
+/// `Copy` and a bit set has no value to reference.
+///
+/// This collection needs the `enumset` feature.
+pub type HashMapToEnumSet<K, V> = HashMap<K, EnumSet<V>>;
+
+pub trait HashMapToEnumSetExt<K, V> {
+
+    fn sub_contains<Q>(&self, key: &Q, value: V) -> bool
+    where
+        K: Borrow<Q> + Hash + Eq,
+        V: EnumSetType,
+        Q: Hash + Eq + ?Sized;
+
+    fn sub_insert(&mut self, key: K, value: V) -> bool
+    where
+        K: Hash + Eq,
+        V: EnumSetType;
+
+    fn sub_remove<Q>(&mut self, key: &Q, value: V) -> bool
+    where
+        K: Borrow<Q> + Hash + Eq,
+        V: EnumSetType,
+        Q: Hash + Eq + ?Sized;
+
+    fn sub_remove_prune<Q>(&mut self, key: &Q, value: V) -> bool
+    where
+        K: Borrow<Q> + Hash + Eq,
+        V: EnumSetType,
+        Q: Hash + Eq + ?Sized;
+
+    fn sub_insert_many<I>(&mut self, key: K, values: I) -> usize
+    where
+        I: IntoIterator<Item = V>,
+        K: Hash + Eq,
+        V: EnumSetType;
+
+    fn sub_iter<'a>(&'a self) -> impl Iterator<Item = (&'a K, V)>
+    where
+        K: 'a,
+        V: EnumSetType + 'a;
+
+    fn into_sub_iter(self) -> impl Iterator<Item = (K, V)>
+    where
+        K: Clone,
+        V: EnumSetType;
+
+    fn sub_len(&self) -> usize
+    where
+        V: EnumSetType;
+
+    fn sub_len_of<Q>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q> + Hash + Eq,
+        V: EnumSetType,
+        Q: Hash + Eq + ?Sized;
+
+    fn sub_get<Q>(&self, key: &Q) -> EnumSet<V>
+    where
+        K: Borrow<Q> + Hash + Eq,
+        V: EnumSetType,
+        Q: Hash + Eq + ?Sized;
+
+}
+
+impl<K, V> HashMapToEnumSetExt<K, V> for HashMapToEnumSet<K, V>
+where
+    V: EnumSetType,
+{
+
+    /// Return `true` if the collection contains a sub-key-value item.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// use enumset::EnumSetType;
+    /// #[derive(EnumSetType, Debug)]
+    /// enum Flag { Read, Write }
+    /// let mut a: HashMapToEnumSet<u8, Flag> = HashMapToEnumSet::new();
+    /// a.sub_insert(1, Flag::Read);
+    /// assert_eq!(a.sub_contains(&1, Flag::Read), true);
+    /// assert_eq!(a.sub_contains(&1, Flag::Write), false);
+    /// ```
+    #[inline]
+    fn sub_contains<Q>(&self, key: &Q, value: V) -> bool
+    where
+        K: Borrow<Q> + Hash + Eq,
+        Q: Hash + Eq + ?Sized,
+    {
+        match self.get(key) {
+            Some(set) => set.contains(value),
+            None => false,
+        }
+    }
+
+    /// Add a sub-key-value item to the collection.
+    ///
+    /// Return whether the item is added in the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// use enumset::EnumSetType;
+    /// #[derive(EnumSetType, Debug)]
+    /// enum Flag { Read, Write }
+    /// let mut a: HashMapToEnumSet<u8, Flag> = HashMapToEnumSet::new();
+    /// assert_eq!(a.sub_insert(1, Flag::Read), true);
+    /// assert_eq!(a.sub_insert(1, Flag::Read), false);
+    /// ```
+    #[inline]
+    fn sub_insert(&mut self, key: K, value: V) -> bool
+    where
+        K: Hash + Eq,
+    {
+        self.entry(key)
+        .or_default()
+        .insert(value)
+    }
+
+    /// Remove a sub-key-value pair from the collection.
+    ///
+    /// Return whether the value was present in the set.
+    ///
+    /// The key stays in the collection even when its set becomes empty;
+    /// use `sub_remove_prune` to also remove the key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// use enumset::EnumSetType;
+    /// #[derive(EnumSetType, Debug)]
+    /// enum Flag { Read, Write }
+    /// let mut a: HashMapToEnumSet<u8, Flag> = HashMapToEnumSet::new();
+    /// a.sub_insert(1, Flag::Read);
+    /// a.sub_remove(&1, Flag::Read);
+    /// assert_eq!(a.sub_contains(&1, Flag::Read), false);
+    /// assert_eq!(a.contains_key(&1), true);
+    /// ```
+    #[inline]
+    fn sub_remove<Q>(&mut self, key: &Q, value: V) -> bool
+    where
+        K: Borrow<Q> + Hash + Eq,
+        Q: Hash + Eq + ?Sized,
+    {
+        match self.get_mut(key) {
+            Some(set) => set.remove(value),
+            None => false,
+        }
+    }
+
+    /// Remove a sub-key-value pair from the collection, and prune the key if its set becomes empty.
+    ///
+    /// Return whether the value was present in the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// use enumset::EnumSetType;
+    /// #[derive(EnumSetType, Debug)]
+    /// enum Flag { Read, Write }
+    /// let mut a: HashMapToEnumSet<u8, Flag> = HashMapToEnumSet::new();
+    /// a.sub_insert(1, Flag::Read);
+    /// a.sub_remove_prune(&1, Flag::Read);
+    /// assert_eq!(a.contains_key(&1), false);
+    /// ```
+    #[inline]
+    fn sub_remove_prune<Q>(&mut self, key: &Q, value: V) -> bool
+    where
+        K: Borrow<Q> + Hash + Eq,
+        Q: Hash + Eq + ?Sized,
+    {
+        match self.get_mut(key) {
+            Some(set) => {
+                let removed = set.remove(value);
+                if set.is_empty() {
+                    self.remove(key);
+                }
+                removed
+            },
+            None => false,
+        }
+    }
+
+    /// Add many values to the set for one key.
+    ///
+    /// Return the number of values that are newly added in the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// use enumset::EnumSetType;
+    /// #[derive(EnumSetType, Debug)]
+    /// enum Flag { Read, Write }
+    /// let mut a: HashMapToEnumSet<u8, Flag> = HashMapToEnumSet::new();
+    /// assert_eq!(a.sub_insert_many(1, vec![Flag::Read, Flag::Write, Flag::Read]), 2);
+    /// ```
+    #[inline]
+    fn sub_insert_many<I>(&mut self, key: K, values: I) -> usize
+    where
+        I: IntoIterator<Item = V>,
+        K: Hash + Eq,
+    {
+        let set = self.entry(key).or_default();
+        let len = set.len();
+        set.extend(values);
+        set.len() - len
+    }
+
+    /// Return an iterator over every sub-key-value pair in the collection.
+    ///
+    /// The iterator yields `(&K, V)` pairs with keys in arbitrary order
+    /// and each key's values in enum declaration order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// use enumset::EnumSetType;
+    /// #[derive(EnumSetType, Debug)]
+    /// enum Flag { Read, Write }
+    /// let mut a: HashMapToEnumSet<u8, Flag> = HashMapToEnumSet::new();
+    /// a.sub_insert(1, Flag::Write);
+    /// a.sub_insert(1, Flag::Read);
+    /// assert_eq!(a.sub_iter().collect::<Vec<_>>(), vec![(&1, Flag::Read), (&1, Flag::Write)]);
+    /// ```
+    #[inline]
+    fn sub_iter<'a>(&'a self) -> impl Iterator<Item = (&'a K, V)>
+    where
+        K: 'a,
+        V: 'a,
+    {
+        self.iter()
+        .flat_map(|(key, set)| set.iter().map(move |value| (key, value)))
+    }
+
+    /// Consume the collection and return an iterator over every sub-key-value pair.
+    ///
+    /// Each key is cloned once per value in its set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// use enumset::EnumSetType;
+    /// #[derive(EnumSetType, Debug)]
+    /// enum Flag { Read, Write }
+    /// let mut a: HashMapToEnumSet<u8, Flag> = HashMapToEnumSet::new();
+    /// a.sub_insert(1, Flag::Read);
+    /// assert_eq!(a.into_sub_iter().collect::<Vec<_>>(), vec![(1, Flag::Read)]);
+    /// ```
+    #[inline]
+    fn into_sub_iter(self) -> impl Iterator<Item = (K, V)>
+    where
+        K: Clone,
+    {
+        self.into_iter()
+        .flat_map(|(key, set)| set.into_iter().map(move |value| (key.clone(), value)))
+    }
+
+    /// Return the total number of sub-key-value items in the collection.
+    ///
+    /// This counts every value in every set, unlike `len` which counts keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// use enumset::EnumSetType;
+    /// #[derive(EnumSetType, Debug)]
+    /// enum Flag { Read, Write }
+    /// let mut a: HashMapToEnumSet<u8, Flag> = HashMapToEnumSet::new();
+    /// a.sub_insert(1, Flag::Read);
+    /// a.sub_insert(1, Flag::Write);
+    /// a.sub_insert(2, Flag::Read);
+    /// assert_eq!(a.sub_len(), 3);
+    /// ```
+    #[inline]
+    fn sub_len(&self) -> usize {
+        self.values().map(|set| set.len()).sum()
+    }
+
+    /// Return the number of values in the set for a key.
+    ///
+    /// Return 0 if the key is absent or if its set is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// use enumset::EnumSetType;
+    /// #[derive(EnumSetType, Debug)]
+    /// enum Flag { Read, Write }
+    /// let mut a: HashMapToEnumSet<u8, Flag> = HashMapToEnumSet::new();
+    /// a.sub_insert(1, Flag::Read);
+    /// assert_eq!(a.sub_len_of(&1), 1);
+    /// assert_eq!(a.sub_len_of(&2), 0);
+    /// ```
+    #[inline]
+    fn sub_len_of<Q>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q> + Hash + Eq,
+        Q: Hash + Eq + ?Sized,
+    {
+        match self.get(key) {
+            Some(set) => set.len(),
+            None => 0,
+        }
+    }
+
+    /// Return a copy of the set for a key.
+    ///
+    /// Return the empty set if the key is absent.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// use enumset::{EnumSet, EnumSetType};
+    /// #[derive(EnumSetType, Debug)]
+    /// enum Flag { Read, Write }
+    /// let mut a: HashMapToEnumSet<u8, Flag> = HashMapToEnumSet::new();
+    /// a.sub_insert(1, Flag::Read);
+    /// assert_eq!(a.sub_get(&1), Flag::Read);
+    /// assert_eq!(a.sub_get(&2), EnumSet::empty());
+    /// ```
+    #[inline]
+    fn sub_get<Q>(&self, key: &Q) -> EnumSet<V>
+    where
+        K: Borrow<Q> + Hash + Eq,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get(key).copied().unwrap_or_default()
+    }
+
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+
+    #[derive(EnumSetType, Debug)]
+    enum Flag { A, B, C }
+
+    #[test]
+    /// Test `sub_contains` with some items.
+    fn test_sub_contains() {
+        let mut subject: HashMapToEnumSet<u8, Flag> = HashMapToEnumSet::new();
+        let k = 1;
+        let absent = 9;
+        assert_eq!(subject.sub_insert(k, Flag::A), true);
+        assert_eq!(subject.sub_contains(&k, Flag::A), true);
+        assert_eq!(subject.sub_contains(&k, Flag::B), false);
+        assert_eq!(subject.sub_contains(&absent, Flag::A), false);
+    }
+
+    #[test]
+    /// Test `sub_remove` and `sub_remove_prune` with some items.
+    fn test_sub_remove_and_sub_remove_prune() {
+        let mut subject: HashMapToEnumSet<u8, Flag> = HashMapToEnumSet::new();
+        let k1 = 1;
+        let k2 = 2;
+        let absent = 9;
+        subject.sub_insert(k1, Flag::A);
+        subject.sub_insert(k2, Flag::A);
+        assert_eq!(subject.sub_remove(&k1, Flag::A), true);
+        assert_eq!(subject.sub_remove(&k1, Flag::A), false);
+        assert_eq!(subject.contains_key(&k1), true);
+        assert_eq!(subject.sub_remove_prune(&k2, Flag::A), true);
+        assert_eq!(subject.contains_key(&k2), false);
+        assert_eq!(subject.sub_remove_prune(&absent, Flag::A), false);
+    }
+
+    #[test]
+    /// Test `sub_iter`, `into_sub_iter`, `sub_len`, and `sub_get` with some items.
+    fn test_sub_iter_and_sub_len() {
+        let mut subject: HashMapToEnumSet<u8, Flag> = HashMapToEnumSet::new();
+        assert_eq!(subject.sub_insert_many(1, vec![Flag::C, Flag::A]), 2);
+        subject.sub_insert(4, Flag::B);
+        assert_eq!(subject.sub_len(), 3);
+        assert_eq!(subject.sub_get(&1), Flag::A | Flag::C);
+        let mut actual: Vec<(u8, Flag)> = subject.sub_iter().map(|(k, v)| (*k, v)).collect();
+        actual.sort_by_key(|(k, v)| (*k, *v as u8));
+        assert_eq!(actual, vec![(1, Flag::A), (1, Flag::C), (4, Flag::B)]);
+        assert_eq!(subject.into_sub_iter().count(), 3);
+    }
+
+}
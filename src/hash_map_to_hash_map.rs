@@ -0,0 +1,407 @@
+use std::collections::HashMap;
+use std::borrow::Borrow;
+use std::cmp::Eq;
+use std::hash::Hash;
+
+/// A map of outer keys to inner maps of inner keys to values.
+///
+/// The counting and iterating methods name entries, such as
+/// `sub_entries_len` and `sub_entries`, so they don't collide with
+/// `HashMapToBagExt`, whose `HashMapToBag<K, V>` is also a
+/// `HashMap<K, HashMap<V, usize>>`.
+pub type HashMapToHashMap<K1, K2, V> = HashMap<K1, HashMap<K2, V>>;
+
+pub trait HashMapToHashMapExt<K1, K2, V> {
+
+    fn sub_insert(&mut self, key1: K1, key2: K2, value: V) -> Option<V>
+    where
+        K1: Hash + Eq,
+        K2: Hash + Eq;
+
+    fn sub_get<Q1, Q2>(&self, key1: &Q1, key2: &Q2) -> Option<&V>
+    where
+        K1: Borrow<Q1> + Hash + Eq,
+        K2: Borrow<Q2> + Hash + Eq,
+        Q1: Hash + Eq + ?Sized,
+        Q2: Hash + Eq + ?Sized;
+
+    fn sub_get_mut<Q1, Q2>(&mut self, key1: &Q1, key2: &Q2) -> Option<&mut V>
+    where
+        K1: Borrow<Q1> + Hash + Eq,
+        K2: Borrow<Q2> + Hash + Eq,
+        Q1: Hash + Eq + ?Sized,
+        Q2: Hash + Eq + ?Sized;
+
+    fn sub_contains_key<Q1, Q2>(&self, key1: &Q1, key2: &Q2) -> bool
+    where
+        K1: Borrow<Q1> + Hash + Eq,
+        K2: Borrow<Q2> + Hash + Eq,
+        Q1: Hash + Eq + ?Sized,
+        Q2: Hash + Eq + ?Sized;
+
+    fn sub_remove<Q1, Q2>(&mut self, key1: &Q1, key2: &Q2) -> Option<V>
+    where
+        K1: Borrow<Q1> + Hash + Eq,
+        K2: Borrow<Q2> + Hash + Eq,
+        Q1: Hash + Eq + ?Sized,
+        Q2: Hash + Eq + ?Sized;
+
+    fn sub_remove_prune<Q1, Q2>(&mut self, key1: &Q1, key2: &Q2) -> Option<V>
+    where
+        K1: Borrow<Q1> + Hash + Eq,
+        K2: Borrow<Q2> + Hash + Eq,
+        Q1: Hash + Eq + ?Sized,
+        Q2: Hash + Eq + ?Sized;
+
+    fn sub_entries_len(&self) -> usize;
+
+    fn sub_entries_len_of<Q1>(&self, key1: &Q1) -> usize
+    where
+        K1: Borrow<Q1> + Hash + Eq,
+        Q1: Hash + Eq + ?Sized;
+
+    fn sub_entries<'a>(&'a self) -> impl Iterator<Item = (&'a K1, &'a K2, &'a V)>
+    where
+        K1: 'a,
+        K2: 'a,
+        V: 'a;
+
+    fn into_sub_entries(self) -> impl Iterator<Item = (K1, K2, V)>
+    where
+        K1: Clone;
+}
+
+impl<K1, K2, V> HashMapToHashMapExt<K1, K2, V> for HashMapToHashMap<K1, K2, V> {
+
+    /// Insert a value at an outer key and an inner key.
+    ///
+    /// Return the previous value at the two keys, if any.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToHashMap<u8, u8, char> = HashMapToHashMap::new();
+    /// assert_eq!(a.sub_insert(1, 2, 'a'), None);
+    /// assert_eq!(a.sub_insert(1, 2, 'b'), Some('a'));
+    /// ```
+    #[inline]
+    fn sub_insert(&mut self, key1: K1, key2: K2, value: V) -> Option<V>
+    where
+        K1: Hash + Eq,
+        K2: Hash + Eq,
+    {
+        self.entry(key1)
+        .or_default()
+        .insert(key2, value)
+    }
+
+    /// Return a reference to the value at an outer key and an inner key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToHashMap<u8, u8, char> = HashMapToHashMap::new();
+    /// a.sub_insert(1, 2, 'a');
+    /// assert_eq!(a.sub_get(&1, &2), Some(&'a'));
+    /// assert_eq!(a.sub_get(&1, &3), None);
+    /// ```
+    #[inline]
+    fn sub_get<Q1, Q2>(&self, key1: &Q1, key2: &Q2) -> Option<&V>
+    where
+        K1: Borrow<Q1> + Hash + Eq,
+        K2: Borrow<Q2> + Hash + Eq,
+        Q1: Hash + Eq + ?Sized,
+        Q2: Hash + Eq + ?Sized,
+    {
+        match self.get(key1) {
+            Some(map) => map.get(key2),
+            None => None,
+        }
+    }
+
+    /// Return a mutable reference to the value at an outer key and an inner key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToHashMap<u8, u8, u8> = HashMapToHashMap::new();
+    /// a.sub_insert(1, 2, 3);
+    /// if let Some(value) = a.sub_get_mut(&1, &2) {
+    ///     *value += 1;
+    /// }
+    /// assert_eq!(a.sub_get(&1, &2), Some(&4));
+    /// ```
+    #[inline]
+    fn sub_get_mut<Q1, Q2>(&mut self, key1: &Q1, key2: &Q2) -> Option<&mut V>
+    where
+        K1: Borrow<Q1> + Hash + Eq,
+        K2: Borrow<Q2> + Hash + Eq,
+        Q1: Hash + Eq + ?Sized,
+        Q2: Hash + Eq + ?Sized,
+    {
+        match self.get_mut(key1) {
+            Some(map) => map.get_mut(key2),
+            None => None,
+        }
+    }
+
+    /// Return `true` if the collection has a value at an outer key and an inner key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToHashMap<u8, u8, char> = HashMapToHashMap::new();
+    /// a.sub_insert(1, 2, 'a');
+    /// assert_eq!(a.sub_contains_key(&1, &2), true);
+    /// assert_eq!(a.sub_contains_key(&2, &1), false);
+    /// ```
+    #[inline]
+    fn sub_contains_key<Q1, Q2>(&self, key1: &Q1, key2: &Q2) -> bool
+    where
+        K1: Borrow<Q1> + Hash + Eq,
+        K2: Borrow<Q2> + Hash + Eq,
+        Q1: Hash + Eq + ?Sized,
+        Q2: Hash + Eq + ?Sized,
+    {
+        match self.get(key1) {
+            Some(map) => map.contains_key(key2),
+            None => false,
+        }
+    }
+
+    /// Remove the value at an outer key and an inner key, and return it.
+    ///
+    /// The outer key stays in the collection even when its inner map
+    /// becomes empty; use `sub_remove_prune` to also remove the outer key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToHashMap<u8, u8, char> = HashMapToHashMap::new();
+    /// a.sub_insert(1, 2, 'a');
+    /// assert_eq!(a.sub_remove(&1, &2), Some('a'));
+    /// assert_eq!(a.contains_key(&1), true);
+    /// ```
+    #[inline]
+    fn sub_remove<Q1, Q2>(&mut self, key1: &Q1, key2: &Q2) -> Option<V>
+    where
+        K1: Borrow<Q1> + Hash + Eq,
+        K2: Borrow<Q2> + Hash + Eq,
+        Q1: Hash + Eq + ?Sized,
+        Q2: Hash + Eq + ?Sized,
+    {
+        match self.get_mut(key1) {
+            Some(map) => map.remove(key2),
+            None => None,
+        }
+    }
+
+    /// Remove the value at an outer key and an inner key, and prune the outer key if its inner map becomes empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToHashMap<u8, u8, char> = HashMapToHashMap::new();
+    /// a.sub_insert(1, 2, 'a');
+    /// assert_eq!(a.sub_remove_prune(&1, &2), Some('a'));
+    /// assert_eq!(a.contains_key(&1), false);
+    /// ```
+    #[inline]
+    fn sub_remove_prune<Q1, Q2>(&mut self, key1: &Q1, key2: &Q2) -> Option<V>
+    where
+        K1: Borrow<Q1> + Hash + Eq,
+        K2: Borrow<Q2> + Hash + Eq,
+        Q1: Hash + Eq + ?Sized,
+        Q2: Hash + Eq + ?Sized,
+    {
+        match self.get_mut(key1) {
+            Some(map) => {
+                let removed = map.remove(key2);
+                if map.is_empty() {
+                    self.remove(key1);
+                }
+                removed
+            },
+            None => None,
+        }
+    }
+
+    /// Return the total number of values in every inner map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToHashMap<u8, u8, char> = HashMapToHashMap::new();
+    /// a.sub_insert(1, 2, 'a');
+    /// a.sub_insert(1, 3, 'b');
+    /// a.sub_insert(4, 5, 'c');
+    /// assert_eq!(a.len(), 2);
+    /// assert_eq!(a.sub_entries_len(), 3);
+    /// ```
+    #[inline]
+    fn sub_entries_len(&self) -> usize {
+        self.values().map(|map| map.len()).sum()
+    }
+
+    /// Return the number of values in the inner map for an outer key.
+    ///
+    /// Return 0 if the outer key is absent.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToHashMap<u8, u8, char> = HashMapToHashMap::new();
+    /// a.sub_insert(1, 2, 'a');
+    /// assert_eq!(a.sub_entries_len_of(&1), 1);
+    /// assert_eq!(a.sub_entries_len_of(&4), 0);
+    /// ```
+    #[inline]
+    fn sub_entries_len_of<Q1>(&self, key1: &Q1) -> usize
+    where
+        K1: Borrow<Q1> + Hash + Eq,
+        Q1: Hash + Eq + ?Sized,
+    {
+        match self.get(key1) {
+            Some(map) => map.len(),
+            None => 0,
+        }
+    }
+
+    /// Return an iterator over every `(&K1, &K2, &V)` triple, in arbitrary order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToHashMap<u8, u8, char> = HashMapToHashMap::new();
+    /// a.sub_insert(1, 2, 'a');
+    /// let triples: Vec<(&u8, &u8, &char)> = a.sub_entries().collect();
+    /// assert_eq!(triples, vec![(&1, &2, &'a')]);
+    /// ```
+    #[inline]
+    fn sub_entries<'a>(&'a self) -> impl Iterator<Item = (&'a K1, &'a K2, &'a V)>
+    where
+        K1: 'a,
+        K2: 'a,
+        V: 'a,
+    {
+        self.iter()
+        .flat_map(|(key1, map)| map.iter().map(move |(key2, value)| (key1, key2, value)))
+    }
+
+    /// Consume the collection and return an iterator over every `(K1, K2, V)` triple, in arbitrary order.
+    ///
+    /// Each outer key is cloned once per value in its inner map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToHashMap<u8, u8, char> = HashMapToHashMap::new();
+    /// a.sub_insert(1, 2, 'a');
+    /// let triples: Vec<(u8, u8, char)> = a.into_sub_entries().collect();
+    /// assert_eq!(triples, vec![(1, 2, 'a')]);
+    /// ```
+    #[inline]
+    fn into_sub_entries(self) -> impl Iterator<Item = (K1, K2, V)>
+    where
+        K1: Clone,
+    {
+        self.into_iter()
+        .flat_map(|(key1, map)| map.into_iter().map(move |(key2, value)| (key1.clone(), key2, value)))
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+
+    #[test]
+    /// Test `sub_insert`, `sub_get`, and `sub_contains_key` with some items.
+    fn test_sub_insert_and_sub_get() {
+        let mut subject: HashMapToHashMap<u8, u8, char> = HashMapToHashMap::new();
+        let k1 = 1;
+        let k2 = 2;
+        let absent = 9;
+        assert_eq!(subject.sub_insert(k1, k2, 'a'), None);
+        assert_eq!(subject.sub_insert(k1, absent, 'b'), None);
+        assert_eq!(subject.sub_insert(k1, k2, 'c'), Some('a'));
+        assert_eq!(subject.sub_get(&k1, &k2), Some(&'c'));
+        assert_eq!(subject.sub_get(&k2, &k1), None);
+        assert_eq!(subject.sub_contains_key(&k1, &absent), true);
+        assert_eq!(subject.sub_contains_key(&absent, &k1), false);
+        assert_eq!(subject.sub_entries_len(), 2);
+    }
+
+    #[test]
+    /// Test `sub_get_mut` with present and absent keys.
+    fn test_sub_get_mut() {
+        let mut subject: HashMapToHashMap<u8, u8, u8> = HashMapToHashMap::new();
+        subject.sub_insert(1, 2, 3);
+        *subject.sub_get_mut(&1, &2).unwrap() = 4;
+        assert_eq!(subject.sub_get(&1, &2), Some(&4));
+        assert_eq!(subject.sub_get_mut(&1, &9), None);
+        assert_eq!(subject.sub_get_mut(&9, &2), None);
+    }
+
+    #[test]
+    /// Test `sub_remove` keeps the outer key and `sub_remove_prune` removes it.
+    fn test_sub_remove_and_sub_remove_prune() {
+        let mut subject: HashMapToHashMap<u8, u8, char> = HashMapToHashMap::new();
+        subject.sub_insert(1, 2, 'a');
+        subject.sub_insert(3, 4, 'b');
+        assert_eq!(subject.sub_remove(&1, &2), Some('a'));
+        assert_eq!(subject.sub_remove(&1, &2), None);
+        assert_eq!(subject.contains_key(&1), true);
+        assert_eq!(subject.sub_remove_prune(&3, &4), Some('b'));
+        assert_eq!(subject.contains_key(&3), false);
+        assert_eq!(subject.sub_remove_prune(&9, &9), None);
+    }
+
+    #[test]
+    /// Test `sub_get` with borrowed forms of both keys.
+    fn test_sub_get_x_borrowed_forms() {
+        let mut subject: HashMapToHashMap<String, String, u8> = HashMapToHashMap::new();
+        subject.sub_insert(String::from("alpha"), String::from("bravo"), 1);
+        assert_eq!(subject.sub_get("alpha", "bravo"), Some(&1));
+        assert_eq!(subject.sub_remove_prune("alpha", "bravo"), Some(1));
+    }
+
+    #[test]
+    /// Test `sub_entries`, `into_sub_entries`, and `sub_entries_len_of` with some items.
+    fn test_sub_iter_and_into_sub_iter() {
+        let mut subject: HashMapToHashMap<u8, u8, char> = HashMapToHashMap::new();
+        subject.sub_insert(4, 5, 'c');
+        subject.sub_insert(1, 3, 'b');
+        subject.sub_insert(1, 2, 'a');
+        assert_eq!(subject.sub_entries_len_of(&1), 2);
+        let mut actual: Vec<(&u8, &u8, &char)> = subject.sub_entries().collect();
+        actual.sort();
+        assert_eq!(actual, vec![(&1, &2, &'a'), (&1, &3, &'b'), (&4, &5, &'c')]);
+        let mut actual: Vec<(u8, u8, char)> = subject.into_sub_entries().collect();
+        actual.sort();
+        assert_eq!(actual, vec![(1, 2, 'a'), (1, 3, 'b'), (4, 5, 'c')]);
+    }
+
+    #[test]
+    /// Test the two-level methods and `HashMapToBagExt` are both usable on a bag.
+    fn test_x_hash_map_to_bag() {
+        use crate::hash_map_to_bag::{HashMapToBag, HashMapToBagExt};
+        let mut subject: HashMapToBag<u8, u8> = HashMapToBag::new();
+        subject.sub_add(1, 2);
+        subject.sub_add(1, 2);
+        assert_eq!(subject.sub_len(), 2);
+        assert_eq!(subject.sub_entries_len(), 1);
+        assert_eq!(subject.sub_contains(&1, &2), true);
+        assert_eq!(subject.sub_contains_key(&1, &2), true);
+    }
+}
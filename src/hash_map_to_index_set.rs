@@ -0,0 +1,454 @@
+use indexmap::IndexSet;
+use std::borrow::Borrow;
+use std::cmp::Eq;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// A map of keys to sets of values, where each key's values keep their
+/// insertion order, so the first-seen value comes first.
+///
+/// Removal uses `shift_remove`, so the remaining values keep their
+/// relative order.
+///
+/// This collection needs the `indexmap` feature.
+pub type HashMapToIndexSet<K, V> = HashMap<K, IndexSet<V>>;
+
+pub trait HashMapToIndexSetExt<K, V> {
+
+    fn sub_contains<Q, R>(&self, key: &Q, value: &R) -> bool
+    where
+        K: Borrow<Q> + Hash + Eq,
+        V: Borrow<R> + Hash + Eq,
+        Q: Hash + Eq + ?Sized,
+        R: Hash + Eq + ?Sized;
+
+    fn sub_insert(&mut self, key: K, value: V) -> bool
+    where
+        K: Hash + Eq,
+        V: Hash + Eq;
+
+    fn sub_remove<Q, R>(&mut self, key: &Q, value: &R) -> bool
+    where
+        K: Borrow<Q> + Hash + Eq,
+        V: Borrow<R> + Hash + Eq,
+        Q: Hash + Eq + ?Sized,
+        R: Hash + Eq + ?Sized;
+
+    fn sub_remove_prune<Q, R>(&mut self, key: &Q, value: &R) -> bool
+    where
+        K: Borrow<Q> + Hash + Eq,
+        V: Borrow<R> + Hash + Eq,
+        Q: Hash + Eq + ?Sized,
+        R: Hash + Eq + ?Sized;
+
+    fn sub_insert_many<I>(&mut self, key: K, values: I) -> usize
+    where
+        I: IntoIterator<Item = V>,
+        K: Hash + Eq,
+        V: Hash + Eq;
+
+    fn sub_iter<'a>(&'a self) -> impl Iterator<Item = (&'a K, &'a V)>
+    where
+        K: 'a,
+        V: 'a;
+
+    fn into_sub_iter(self) -> impl Iterator<Item = (K, V)>
+    where
+        K: Clone;
+
+    fn sub_len(&self) -> usize;
+
+    fn sub_len_of<Q>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q> + Hash + Eq,
+        Q: Hash + Eq + ?Sized;
+
+    fn sub_get_index<Q>(&self, key: &Q, index: usize) -> Option<&V>
+    where
+        K: Borrow<Q> + Hash + Eq,
+        Q: Hash + Eq + ?Sized;
+
+    fn sub_get_index_of<Q, R>(&self, key: &Q, value: &R) -> Option<usize>
+    where
+        K: Borrow<Q> + Hash + Eq,
+        V: Borrow<R> + Hash + Eq,
+        Q: Hash + Eq + ?Sized,
+        R: Hash + Eq + ?Sized;
+
+}
+
+impl<K, V> HashMapToIndexSetExt<K, V> for HashMapToIndexSet<K, V> {
+
+    /// Return `true` if the collection contains a sub-key-value item.
+    ///
+    /// The key and value may be any borrowed form of the map's key type
+    /// and the set's value type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToIndexSet<u8, u8> = HashMapToIndexSet::new();
+    /// a.sub_insert(1, 2);
+    /// assert_eq!(a.sub_contains(&1, &2), true);
+    /// assert_eq!(a.sub_contains(&3, &4), false);
+    /// ```
+    #[inline]
+    fn sub_contains<Q, R>(&self, key: &Q, value: &R) -> bool
+    where
+        K: Borrow<Q> + Hash + Eq,
+        V: Borrow<R> + Hash + Eq,
+        Q: Hash + Eq + ?Sized,
+        R: Hash + Eq + ?Sized,
+    {
+        match self.get(key) {
+            Some(set) => set.contains(value),
+            None => false,
+        }
+    }
+
+    /// Add a sub-key-value item to the collection.
+    ///
+    /// Return whether the item is added in the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToIndexSet<u8, u8> = HashMapToIndexSet::new();
+    /// a.sub_insert(1, 2);
+    /// assert_eq!(a.sub_contains(&1, &2), true);
+    /// ```
+    #[inline]
+    fn sub_insert(&mut self, key: K, value: V) -> bool
+    where
+        K: Hash + Eq,
+        V: Hash + Eq,
+    {
+        self.entry(key)
+        .or_default()
+        .insert(value)
+    }
+
+    /// Remove a sub-key-value pair from the collection.
+    ///
+    /// Return whether the value was present in the set.
+    ///
+    /// The key stays in the collection even when its set becomes empty;
+    /// use `sub_remove_prune` to also remove the key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToIndexSet<u8, u8> = HashMapToIndexSet::new();
+    /// a.sub_insert(1, 2);
+    /// a.sub_remove(&1, &2);
+    /// assert_eq!(a.sub_contains(&1, &2), false);
+    /// assert_eq!(a.contains_key(&1), true);
+    /// ```
+    #[inline]
+    fn sub_remove<Q, R>(&mut self, key: &Q, value: &R) -> bool
+    where
+        K: Borrow<Q> + Hash + Eq,
+        V: Borrow<R> + Hash + Eq,
+        Q: Hash + Eq + ?Sized,
+        R: Hash + Eq + ?Sized,
+    {
+        match self.get_mut(key) {
+            Some(set) => set.shift_remove(value),
+            None => false,
+        }
+    }
+
+    /// Remove a sub-key-value pair from the collection, and prune the key if its set becomes empty.
+    ///
+    /// Return whether the value was present in the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToIndexSet<u8, u8> = HashMapToIndexSet::new();
+    /// a.sub_insert(1, 2);
+    /// a.sub_remove_prune(&1, &2);
+    /// assert_eq!(a.contains_key(&1), false);
+    /// ```
+    #[inline]
+    fn sub_remove_prune<Q, R>(&mut self, key: &Q, value: &R) -> bool
+    where
+        K: Borrow<Q> + Hash + Eq,
+        V: Borrow<R> + Hash + Eq,
+        Q: Hash + Eq + ?Sized,
+        R: Hash + Eq + ?Sized,
+    {
+        match self.get_mut(key) {
+            Some(set) => {
+                let removed = set.shift_remove(value);
+                if set.is_empty() {
+                    self.remove(key);
+                }
+                removed
+            },
+            None => false,
+        }
+    }
+
+    /// Add many values to the set for one key.
+    ///
+    /// Return the number of values that are newly added in the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToIndexSet<u8, u8> = HashMapToIndexSet::new();
+    /// assert_eq!(a.sub_insert_many(1, vec![2, 3, 2]), 2);
+    /// assert_eq!(a.sub_len_of(&1), 2);
+    /// ```
+    #[inline]
+    fn sub_insert_many<I>(&mut self, key: K, values: I) -> usize
+    where
+        I: IntoIterator<Item = V>,
+        K: Hash + Eq,
+        V: Hash + Eq,
+    {
+        let set = self.entry(key).or_default();
+        let len = set.len();
+        set.extend(values);
+        set.len() - len
+    }
+
+    /// Return an iterator over every sub-key-value pair in the collection.
+    ///
+    /// The iterator yields `(&K, &V)` pairs with keys in arbitrary order
+    /// and each key's values in insertion order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToIndexSet<u8, u8> = HashMapToIndexSet::new();
+    /// a.sub_insert(1, 2);
+    /// a.sub_insert(1, 3);
+    /// a.sub_insert(4, 5);
+    /// assert_eq!(a.sub_iter().count(), 3);
+    /// ```
+    #[inline]
+    fn sub_iter<'a>(&'a self) -> impl Iterator<Item = (&'a K, &'a V)>
+    where
+        K: 'a,
+        V: 'a,
+    {
+        self.iter()
+        .flat_map(|(key, set)| set.iter().map(move |value| (key, value)))
+    }
+
+    /// Consume the collection and return an iterator over every sub-key-value pair.
+    ///
+    /// Each key is cloned once per value in its set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToIndexSet<u8, u8> = HashMapToIndexSet::new();
+    /// a.sub_insert(1, 2);
+    /// a.sub_insert(1, 3);
+    /// let mut pairs: Vec<(u8, u8)> = a.into_sub_iter().collect();
+    /// pairs.sort();
+    /// assert_eq!(pairs, vec![(1, 2), (1, 3)]);
+    /// ```
+    #[inline]
+    fn into_sub_iter(self) -> impl Iterator<Item = (K, V)>
+    where
+        K: Clone,
+    {
+        self.into_iter()
+        .flat_map(|(key, set)| set.into_iter().map(move |value| (key.clone(), value)))
+    }
+
+    /// Return the total number of sub-key-value items in the collection.
+    ///
+    /// This counts every value in every set, unlike `len` which counts keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToIndexSet<u8, u8> = HashMapToIndexSet::new();
+    /// a.sub_insert(1, 2);
+    /// a.sub_insert(1, 3);
+    /// a.sub_insert(4, 5);
+    /// assert_eq!(a.sub_len(), 3);
+    /// ```
+    #[inline]
+    fn sub_len(&self) -> usize {
+        self.values().map(|set| set.len()).sum()
+    }
+
+    /// Return the number of values in the set for a key.
+    ///
+    /// Return 0 if the key is absent or if its set is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToIndexSet<u8, u8> = HashMapToIndexSet::new();
+    /// a.sub_insert(1, 2);
+    /// assert_eq!(a.sub_len_of(&1), 1);
+    /// assert_eq!(a.sub_len_of(&4), 0);
+    /// ```
+    #[inline]
+    fn sub_len_of<Q>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q> + Hash + Eq,
+        Q: Hash + Eq + ?Sized,
+    {
+        match self.get(key) {
+            Some(set) => set.len(),
+            None => 0,
+        }
+    }
+
+    /// Return the value at an index in the set for a key, in insertion order.
+    ///
+    /// Return `None` if the key is absent or if the index is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToIndexSet<u8, u8> = HashMapToIndexSet::new();
+    /// a.sub_insert(1, 3);
+    /// a.sub_insert(1, 2);
+    /// a.sub_insert(1, 3);
+    /// assert_eq!(a.sub_get_index(&1, 0), Some(&3));
+    /// assert_eq!(a.sub_get_index(&1, 1), Some(&2));
+    /// assert_eq!(a.sub_get_index(&1, 2), None);
+    /// ```
+    #[inline]
+    fn sub_get_index<Q>(&self, key: &Q, index: usize) -> Option<&V>
+    where
+        K: Borrow<Q> + Hash + Eq,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get(key).and_then(|set| set.get_index(index))
+    }
+
+    /// Return the index of a value in the set for a key, in insertion order.
+    ///
+    /// Return `None` if the key is absent or if the value is absent.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToIndexSet<u8, u8> = HashMapToIndexSet::new();
+    /// a.sub_insert(1, 3);
+    /// a.sub_insert(1, 2);
+    /// assert_eq!(a.sub_get_index_of(&1, &2), Some(1));
+    /// assert_eq!(a.sub_get_index_of(&1, &9), None);
+    /// ```
+    #[inline]
+    fn sub_get_index_of<Q, R>(&self, key: &Q, value: &R) -> Option<usize>
+    where
+        K: Borrow<Q> + Hash + Eq,
+        V: Borrow<R> + Hash + Eq,
+        Q: Hash + Eq + ?Sized,
+        R: Hash + Eq + ?Sized,
+    {
+        self.get(key).and_then(|set| set.get_index_of(value))
+    }
+
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+
+    #[test]
+    /// Test `sub_contains` with some items.
+    fn test_sub_contains() {
+        let mut subject: HashMapToIndexSet<u8, u8> = HashMapToIndexSet::new();
+        let k = 1;
+        let v = 2;
+        let absent = 3;
+        assert_eq!(subject.sub_insert(k, v), true);
+        assert_eq!(subject.sub_contains(&k, &v), true);
+        assert_eq!(subject.sub_contains(&k, &absent), false);
+        assert_eq!(subject.sub_contains(&absent, &v), false);
+    }
+
+    #[test]
+    /// Test `sub_contains` with borrowed forms of the key and value.
+    fn test_sub_contains_x_borrowed_forms() {
+        let mut subject: HashMapToIndexSet<String, String> = HashMapToIndexSet::new();
+        subject.sub_insert(String::from("alpha"), String::from("bravo"));
+        assert_eq!(subject.sub_contains("alpha", "bravo"), true);
+        assert_eq!(subject.sub_contains("alpha", "charlie"), false);
+    }
+
+    #[test]
+    /// Test `sub_insert` with a duplicate item.
+    fn test_sub_insert() {
+        let mut subject: HashMapToIndexSet<u8, u8> = HashMapToIndexSet::new();
+        let k = 1;
+        let v = 2;
+        assert_eq!(subject.sub_insert(k, v), true);
+        assert_eq!(subject.sub_insert(k, v), false);
+        assert_eq!(subject.sub_len_of(&k), 1);
+    }
+
+    #[test]
+    /// Test `sub_remove` and `sub_remove_prune` with some items.
+    fn test_sub_remove_and_sub_remove_prune() {
+        let mut subject: HashMapToIndexSet<u8, u8> = HashMapToIndexSet::new();
+        let k1 = 1;
+        let k2 = 2;
+        let v = 3;
+        let absent = 9;
+        subject.sub_insert(k1, v);
+        subject.sub_insert(k2, v);
+        assert_eq!(subject.sub_remove(&k1, &v), true);
+        assert_eq!(subject.sub_remove(&k1, &v), false);
+        assert_eq!(subject.contains_key(&k1), true);
+        assert_eq!(subject.sub_remove_prune(&k2, &v), true);
+        assert_eq!(subject.contains_key(&k2), false);
+        assert_eq!(subject.sub_remove_prune(&absent, &v), false);
+    }
+
+    #[test]
+    /// Test `sub_iter`, `into_sub_iter`, `sub_len`, and `sub_len_of` with some items.
+    fn test_sub_iter_and_sub_len() {
+        let mut subject: HashMapToIndexSet<u8, u8> = HashMapToIndexSet::new();
+        subject.sub_insert_many(1, vec![3, 2]);
+        subject.sub_insert(4, 5);
+        assert_eq!(subject.sub_len(), 3);
+        assert_eq!(subject.sub_len_of(&1), 2);
+        let mut actual: Vec<(u8, u8)> = subject.sub_iter().map(|(k, v)| (*k, *v)).collect();
+        actual.sort();
+        assert_eq!(actual, vec![(1, 2), (1, 3), (4, 5)]);
+        let mut actual: Vec<(u8, u8)> = subject.into_sub_iter().collect();
+        actual.sort();
+        assert_eq!(actual, vec![(1, 2), (1, 3), (4, 5)]);
+    }
+
+    #[test]
+    /// Test that each key's values keep first-seen order, including after removal.
+    fn test_values_keep_insertion_order() {
+        let mut subject: HashMapToIndexSet<u8, u8> = HashMapToIndexSet::new();
+        let k = 1;
+        subject.sub_insert_many(k, vec![9, 3, 7, 3, 5]);
+        let actual: Vec<&u8> = subject[&k].iter().collect();
+        assert_eq!(actual, vec![&9, &3, &7, &5]);
+        subject.sub_remove(&k, &3);
+        assert_eq!(subject.sub_get_index(&k, 1), Some(&7));
+        subject.sub_insert(k, 3);
+        assert_eq!(subject.sub_get_index_of(&k, &3), Some(3));
+        assert_eq!(subject.sub_get_index(&9, 0), None);
+    }
+
+}
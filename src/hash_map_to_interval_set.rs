@@ -0,0 +1,323 @@
+use std::collections::HashMap;
+use std::borrow::Borrow;
+use std::cmp::{Eq, Ord};
+use std::hash::Hash;
+use std::ops::Range;
+use crate::interval_set::IntervalSet;
+
+/// A map of keys to sets of points, where each set stores its points as
+/// coalesced half-open ranges.
+///
+/// This suits tracking spans per key, such as the byte ranges seen per
+/// file, where a set of individual offsets would be too large.
+pub type HashMapToIntervalSet<K, T> = HashMap<K, IntervalSet<T>>;
+
+pub trait HashMapToIntervalSetExt<K, T> {
+
+    fn sub_contains<Q>(&self, key: &Q, point: &T) -> bool
+    where
+        K: Borrow<Q> + Hash + Eq,
+        Q: Hash + Eq + ?Sized;
+
+    fn sub_contains_range<Q>(&self, key: &Q, range: &Range<T>) -> bool
+    where
+        K: Borrow<Q> + Hash + Eq,
+        Q: Hash + Eq + ?Sized;
+
+    fn sub_insert_range(&mut self, key: K, range: Range<T>) -> bool
+    where
+        K: Hash + Eq;
+
+    fn sub_remove_range<Q>(&mut self, key: &Q, range: &Range<T>) -> bool
+    where
+        K: Borrow<Q> + Hash + Eq,
+        Q: Hash + Eq + ?Sized;
+
+    fn sub_remove_range_prune<Q>(&mut self, key: &Q, range: &Range<T>) -> bool
+    where
+        K: Borrow<Q> + Hash + Eq,
+        Q: Hash + Eq + ?Sized;
+
+    fn sub_gaps<Q>(&self, key: &Q, range: &Range<T>) -> Vec<Range<T>>
+    where
+        K: Borrow<Q> + Hash + Eq,
+        Q: Hash + Eq + ?Sized;
+
+    fn sub_iter<'a>(&'a self) -> impl Iterator<Item = (&'a K, Range<T>)>
+    where
+        K: 'a,
+        T: 'a;
+
+    fn sub_len(&self) -> usize;
+
+    fn sub_len_of<Q>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q> + Hash + Eq,
+        Q: Hash + Eq + ?Sized;
+
+}
+
+impl<K, T> HashMapToIntervalSetExt<K, T> for HashMapToIntervalSet<K, T>
+where
+    T: Ord + Copy,
+{
+
+    /// Return `true` if the set for a key contains a point.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToIntervalSet<&str, u64> = HashMapToIntervalSet::new();
+    /// a.sub_insert_range("file", 10..20);
+    /// assert_eq!(a.sub_contains("file", &15), true);
+    /// assert_eq!(a.sub_contains("file", &20), false);
+    /// assert_eq!(a.sub_contains("other", &15), false);
+    /// ```
+    #[inline]
+    fn sub_contains<Q>(&self, key: &Q, point: &T) -> bool
+    where
+        K: Borrow<Q> + Hash + Eq,
+        Q: Hash + Eq + ?Sized,
+    {
+        match self.get(key) {
+            Some(set) => set.contains(point),
+            None => false,
+        }
+    }
+
+    /// Return `true` if the set for a key contains every point of a range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToIntervalSet<&str, u64> = HashMapToIntervalSet::new();
+    /// a.sub_insert_range("file", 10..20);
+    /// a.sub_insert_range("file", 20..30);
+    /// assert_eq!(a.sub_contains_range("file", &(15..25)), true);
+    /// assert_eq!(a.sub_contains_range("file", &(25..35)), false);
+    /// ```
+    #[inline]
+    fn sub_contains_range<Q>(&self, key: &Q, range: &Range<T>) -> bool
+    where
+        K: Borrow<Q> + Hash + Eq,
+        Q: Hash + Eq + ?Sized,
+    {
+        match self.get(key) {
+            Some(set) => set.contains_range(range),
+            None => range.start >= range.end,
+        }
+    }
+
+    /// Add the points of a range to the set for a key, merging it with
+    /// every range that it overlaps or touches.
+    ///
+    /// Return `true` if any point is newly added.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToIntervalSet<&str, u64> = HashMapToIntervalSet::new();
+    /// assert_eq!(a.sub_insert_range("file", 10..20), true);
+    /// assert_eq!(a.sub_insert_range("file", 12..18), false);
+    /// assert_eq!(a.sub_insert_range("file", 20..30), true);
+    /// assert_eq!(a.sub_len_of("file"), 1);
+    /// ```
+    #[inline]
+    fn sub_insert_range(&mut self, key: K, range: Range<T>) -> bool
+    where
+        K: Hash + Eq,
+    {
+        self.entry(key)
+        .or_default()
+        .insert(range)
+    }
+
+    /// Remove the points of a range from the set for a key.
+    ///
+    /// Return `true` if any point is removed.
+    ///
+    /// The key stays in the collection even when its set becomes empty;
+    /// use `sub_remove_range_prune` to also remove the key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToIntervalSet<&str, u64> = HashMapToIntervalSet::new();
+    /// a.sub_insert_range("file", 10..20);
+    /// assert_eq!(a.sub_remove_range("file", &(12..14)), true);
+    /// assert_eq!(a.sub_len_of("file"), 2);
+    /// ```
+    #[inline]
+    fn sub_remove_range<Q>(&mut self, key: &Q, range: &Range<T>) -> bool
+    where
+        K: Borrow<Q> + Hash + Eq,
+        Q: Hash + Eq + ?Sized,
+    {
+        match self.get_mut(key) {
+            Some(set) => set.remove(range),
+            None => false,
+        }
+    }
+
+    /// Remove the points of a range from the set for a key, and prune
+    /// the key if its set becomes empty.
+    ///
+    /// Return `true` if any point is removed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToIntervalSet<&str, u64> = HashMapToIntervalSet::new();
+    /// a.sub_insert_range("file", 10..20);
+    /// assert_eq!(a.sub_remove_range_prune("file", &(0..100)), true);
+    /// assert_eq!(a.contains_key("file"), false);
+    /// ```
+    #[inline]
+    fn sub_remove_range_prune<Q>(&mut self, key: &Q, range: &Range<T>) -> bool
+    where
+        K: Borrow<Q> + Hash + Eq,
+        Q: Hash + Eq + ?Sized,
+    {
+        match self.get_mut(key) {
+            Some(set) => {
+                let removed = set.remove(range);
+                if set.is_empty() {
+                    self.remove(key);
+                }
+                removed
+            },
+            None => false,
+        }
+    }
+
+    /// Return the ranges of a query range that the set for a key does not contain, in order.
+    ///
+    /// An absent key has no points, so the whole query range is one gap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToIntervalSet<&str, u64> = HashMapToIntervalSet::new();
+    /// a.sub_insert_range("file", 10..20);
+    /// assert_eq!(a.sub_gaps("file", &(0..30)), vec![0..10, 20..30]);
+    /// assert_eq!(a.sub_gaps("other", &(0..30)), vec![0..30]);
+    /// ```
+    #[inline]
+    fn sub_gaps<Q>(&self, key: &Q, range: &Range<T>) -> Vec<Range<T>>
+    where
+        K: Borrow<Q> + Hash + Eq,
+        Q: Hash + Eq + ?Sized,
+    {
+        match self.get(key) {
+            Some(set) => set.gaps(range),
+            None if range.start < range.end => vec![range.clone()],
+            None => Vec::new(),
+        }
+    }
+
+    /// Return an iterator over each key and each of its ranges.
+    ///
+    /// The iterator yields keys in arbitrary order, and each key's ranges in order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToIntervalSet<&str, u64> = HashMapToIntervalSet::new();
+    /// a.sub_insert_range("file", 30..40);
+    /// a.sub_insert_range("file", 10..20);
+    /// let pairs: Vec<(&&str, std::ops::Range<u64>)> = a.sub_iter().collect();
+    /// assert_eq!(pairs, vec![(&"file", 10..20), (&"file", 30..40)]);
+    /// ```
+    #[inline]
+    fn sub_iter<'a>(&'a self) -> impl Iterator<Item = (&'a K, Range<T>)>
+    where
+        K: 'a,
+        T: 'a,
+    {
+        self.iter()
+        .flat_map(|(key, set)| set.iter().map(move |range| (key, range)))
+    }
+
+    /// Return the number of ranges across all keys, after coalescing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToIntervalSet<&str, u64> = HashMapToIntervalSet::new();
+    /// a.sub_insert_range("a", 0..10);
+    /// a.sub_insert_range("a", 20..30);
+    /// a.sub_insert_range("b", 0..10);
+    /// assert_eq!(a.sub_len(), 3);
+    /// ```
+    #[inline]
+    fn sub_len(&self) -> usize {
+        self.values().map(|set| set.len()).sum()
+    }
+
+    /// Return the number of ranges for a key, after coalescing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToIntervalSet<&str, u64> = HashMapToIntervalSet::new();
+    /// a.sub_insert_range("a", 0..10);
+    /// a.sub_insert_range("a", 5..15);
+    /// assert_eq!(a.sub_len_of("a"), 1);
+    /// assert_eq!(a.sub_len_of("b"), 0);
+    /// ```
+    #[inline]
+    fn sub_len_of<Q>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q> + Hash + Eq,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get(key).map_or(0, |set| set.len())
+    }
+
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+
+    #[test]
+    /// Test byte ranges per file coalesce as they arrive out of order.
+    fn test_sub_insert_range_x_out_of_order() {
+        let mut subject: HashMapToIntervalSet<String, u64> = HashMapToIntervalSet::new();
+        let k = String::from("alpha");
+        subject.sub_insert_range(k.clone(), 4096..8192);
+        subject.sub_insert_range(k.clone(), 0..1024);
+        subject.sub_insert_range(k.clone(), 1024..4096);
+        subject.sub_insert_range(String::from("bravo"), 0..1);
+        assert_eq!(subject.sub_len_of("alpha"), 1);
+        assert_eq!(subject.sub_contains_range("alpha", &(0..8192)), true);
+        assert_eq!(subject.sub_contains("alpha", &8192), false);
+        assert_eq!(subject.sub_len(), 2);
+    }
+
+    #[test]
+    /// Test `sub_remove_range`, `sub_remove_range_prune`, and `sub_gaps`.
+    fn test_sub_remove_range_x_sub_gaps() {
+        let mut subject: HashMapToIntervalSet<u8, u64> = HashMapToIntervalSet::new();
+        subject.sub_insert_range(1, 0..100);
+        subject.sub_insert_range(2, 0..10);
+        assert_eq!(subject.sub_remove_range(&1, &(40..60)), true);
+        assert_eq!(subject.sub_gaps(&1, &(0..100)), vec![40..60]);
+        assert_eq!(subject.sub_remove_range(&9, &(0..1)), false);
+        assert_eq!(subject.sub_remove_range_prune(&2, &(0..10)), true);
+        assert_eq!(subject.contains_key(&2), false);
+        assert_eq!(subject.sub_gaps(&2, &(5..5)), vec![]);
+        assert_eq!(subject.sub_contains_range(&2, &(5..5)), true);
+    }
+
+}
@@ -1,22 +1,322 @@
 use std::collections::{HashMap, HashSet};
+use std::collections::hash_map::{Entry, VacantEntry};
+use std::borrow::Borrow;
 use std::cmp::Eq;
-use std::hash::Hash;
+use std::hash::{Hash, Hasher};
+use std::ops::{BitAnd, BitOr, BitXor, Deref, DerefMut, Sub};
+use std::collections::hash_map::DefaultHasher;
+use crate::merge_strategy::MergeStrategy;
+use crate::top_entry::top_by_count;
+#[cfg(feature = "rand")]
+use rand::{Rng, seq::IteratorRandom};
 
 pub type HashMapToSet<K, V> = HashMap<K, HashSet<V>>;
 
 pub trait HashMapToSetExt<K, V> {
 
-    fn sub_contains(&self, key: &K, value: &V) -> bool
+    fn sub_contains<Q, R>(&self, key: &Q, value: &R) -> bool
     where
-        K: Hash + Eq,
-        V: Hash + Eq;
+        K: Borrow<Q> + Hash + Eq,
+        V: Borrow<R> + Hash + Eq,
+        Q: Hash + Eq + ?Sized,
+        R: Hash + Eq + ?Sized;
 
     fn sub_insert(&mut self, key: K, value: V) -> bool
     where
         K: Hash + Eq,
         V: Hash + Eq;
   
-    fn sub_remove(&mut self, key: &K, value: &V) -> bool
+    fn sub_remove<Q, R>(&mut self, key: &Q, value: &R) -> bool
+    where
+        K: Borrow<Q> + Hash + Eq,
+        V: Borrow<R> + Hash + Eq,
+        Q: Hash + Eq + ?Sized,
+        R: Hash + Eq + ?Sized;
+
+    fn sub_iter<'a>(&'a self) -> impl Iterator<Item = (&'a K, &'a V)>
+    where
+        K: 'a,
+        V: 'a;
+
+    fn into_sub_iter(self) -> impl Iterator<Item = (K, V)>
+    where
+        K: Clone;
+
+    fn sub_len(&self) -> usize;
+
+    fn sub_len_of<Q>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q> + Hash + Eq,
+        Q: Hash + Eq + ?Sized;
+
+    fn sub_key_is_populated<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q> + Hash + Eq,
+        Q: Hash + Eq + ?Sized;
+
+    fn sub_remove_prune<Q, R>(&mut self, key: &Q, value: &R) -> bool
+    where
+        K: Borrow<Q> + Hash + Eq,
+        V: Borrow<R> + Hash + Eq,
+        Q: Hash + Eq + ?Sized,
+        R: Hash + Eq + ?Sized;
+
+    fn sub_extend<I>(&mut self, iter: I) -> usize
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Hash + Eq,
+        V: Hash + Eq;
+
+    fn sub_entry(&mut self, key: K) -> HashMapToSetEntry<'_, K, V>
+    where
+        K: Hash + Eq,
+        V: Hash + Eq;
+
+    fn sub_insert_many<I>(&mut self, key: K, values: I) -> usize
+    where
+        I: IntoIterator<Item = V>,
+        K: Hash + Eq,
+        V: Hash + Eq;
+
+    fn sub_take<Q, R>(&mut self, key: &Q, value: &R) -> Option<V>
+    where
+        K: Borrow<Q> + Hash + Eq,
+        V: Borrow<R> + Hash + Eq,
+        Q: Hash + Eq + ?Sized,
+        R: Hash + Eq + ?Sized;
+
+    fn sub_move<Q, R>(&mut self, from_key: &Q, to_key: K, value: &R) -> bool
+    where
+        K: Borrow<Q> + Hash + Eq,
+        V: Borrow<R> + Hash + Eq,
+        Q: Hash + Eq + ?Sized,
+        R: Hash + Eq + ?Sized;
+
+    fn sub_drain(&mut self) -> impl Iterator<Item = (K, V)>
+    where
+        K: Clone;
+
+    fn sub_drain_key<Q>(&mut self, key: &Q) -> impl Iterator<Item = V>
+    where
+        K: Borrow<Q> + Hash + Eq,
+        Q: Hash + Eq + ?Sized;
+
+    fn sub_keys_with_value<'a, R>(&'a self, value: &'a R) -> impl Iterator<Item = &'a K>
+    where
+        K: 'a,
+        V: Borrow<R> + Hash + Eq + 'a,
+        R: Hash + Eq + ?Sized;
+
+    fn sub_values<'a>(&'a self) -> impl Iterator<Item = &'a V>
+    where
+        V: 'a;
+
+    fn sub_values_distinct<'a>(&'a self) -> impl Iterator<Item = &'a V>
+    where
+        V: Hash + Eq + 'a;
+
+    fn sub_counts(&self) -> HashMap<K, usize>
+    where
+        K: Clone + Hash + Eq;
+
+    fn sub_merge(&mut self, other: HashMapToSet<K, V>, strategy: MergeStrategy)
+    where
+        K: Hash + Eq,
+        V: Hash + Eq;
+
+    fn sub_union(&self, other: &HashMapToSet<K, V>) -> HashMapToSet<K, V>
+    where
+        K: Clone + Hash + Eq,
+        V: Clone + Hash + Eq;
+
+    fn sub_intersection(&self, other: &HashMapToSet<K, V>) -> HashMapToSet<K, V>
+    where
+        K: Clone + Hash + Eq,
+        V: Clone + Hash + Eq;
+
+    fn sub_difference(&self, other: &HashMapToSet<K, V>) -> HashMapToSet<K, V>
+    where
+        K: Clone + Hash + Eq,
+        V: Clone + Hash + Eq;
+
+    fn sub_symmetric_difference(&self, other: &HashMapToSet<K, V>) -> HashMapToSet<K, V>
+    where
+        K: Clone + Hash + Eq,
+        V: Clone + Hash + Eq;
+
+    fn is_sub_multimap(&self, other: &HashMapToSet<K, V>) -> bool
+    where
+        K: Hash + Eq,
+        V: Hash + Eq;
+
+    fn is_super_multimap(&self, other: &HashMapToSet<K, V>) -> bool
+    where
+        K: Hash + Eq,
+        V: Hash + Eq;
+
+    fn invert(&self) -> HashMapToSet<V, K>
+    where
+        K: Clone + Hash + Eq,
+        V: Clone + Hash + Eq;
+
+    fn into_invert(self) -> HashMapToSet<V, K>
+    where
+        K: Clone + Hash + Eq,
+        V: Hash + Eq;
+
+    fn prune_empty(&mut self) -> usize
+    where
+        K: Hash + Eq;
+
+    fn sub_replace(&mut self, key: K, set: HashSet<V>) -> Option<HashSet<V>>
+    where
+        K: Hash + Eq;
+
+    fn from_pairs<I>(iter: I) -> Self
+    where
+        Self: Sized,
+        I: IntoIterator<Item = (K, V)>,
+        K: Hash + Eq,
+        V: Hash + Eq;
+
+    fn sub_extender(&mut self) -> HashMapToSetExtender<'_, K, V>;
+
+    fn from_grouping<I, F>(items: I, key_fn: F) -> Self
+    where
+        Self: Sized,
+        I: IntoIterator<Item = V>,
+        F: FnMut(&V) -> K,
+        K: Hash + Eq,
+        V: Hash + Eq;
+
+    fn into_pairs(self) -> Vec<(K, V)>
+    where
+        K: Clone;
+
+    fn into_groups(self) -> Vec<(K, HashSet<V>)>;
+
+    fn sub_pop<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q> + Hash + Eq,
+        V: Clone + Hash + Eq,
+        Q: Hash + Eq + ?Sized;
+
+    fn top_keys_by_count(&self, n: usize) -> Vec<(&K, usize)>;
+
+    fn sub_get_or_insert_with<F>(&mut self, key: K, factory: F) -> &mut HashSet<V>
+    where
+        F: FnOnce() -> HashSet<V>,
+        K: Hash + Eq;
+
+    fn sub_get<Q>(&self, key: &Q) -> HashMapToSetView<'_, V>
+    where
+        K: Borrow<Q> + Hash + Eq,
+        Q: Hash + Eq + ?Sized;
+
+    fn sub_overlap_count<Q>(&self, key_a: &Q, key_b: &Q) -> usize
+    where
+        K: Borrow<Q> + Hash + Eq,
+        V: Hash + Eq,
+        Q: Hash + Eq + ?Sized;
+
+    fn sub_similarity<Q>(&self, key_a: &Q, key_b: &Q) -> f64
+    where
+        K: Borrow<Q> + Hash + Eq,
+        V: Hash + Eq,
+        Q: Hash + Eq + ?Sized;
+
+    fn sub_contains_all<'a, Q, R, I>(&self, key: &Q, values: I) -> bool
+    where
+        K: Borrow<Q> + Hash + Eq,
+        V: Borrow<R> + Hash + Eq,
+        Q: Hash + Eq + ?Sized,
+        R: Hash + Eq + ?Sized + 'a,
+        I: IntoIterator<Item = &'a R>;
+
+    fn sub_contains_any<'a, Q, R, I>(&self, key: &Q, values: I) -> bool
+    where
+        K: Borrow<Q> + Hash + Eq,
+        V: Borrow<R> + Hash + Eq,
+        Q: Hash + Eq + ?Sized,
+        R: Hash + Eq + ?Sized + 'a,
+        I: IntoIterator<Item = &'a R>;
+
+    fn duplicate_value_sets(&self) -> Vec<Vec<&K>>
+    where
+        K: Hash + Eq,
+        V: Hash + Eq;
+
+    #[cfg(feature = "rand")]
+    fn sub_sample<G>(&self, rng: &mut G, n: usize) -> Vec<(&K, &V)>
+    where
+        G: Rng + ?Sized;
+
+    #[cfg(feature = "rand")]
+    fn sub_sample_key<Q, G>(&self, rng: &mut G, key: &Q, n: usize) -> Vec<&V>
+    where
+        K: Borrow<Q> + Hash + Eq,
+        Q: Hash + Eq + ?Sized,
+        G: Rng + ?Sized;
+
+    fn sub_append(&mut self, key: K, set: HashSet<V>) -> usize
+    where
+        K: Hash + Eq,
+        V: Hash + Eq;
+
+    fn sub_clear_key<Q>(&mut self, key: &Q) -> usize
+    where
+        K: Borrow<Q> + Hash + Eq,
+        Q: Hash + Eq + ?Sized;
+
+    fn sub_clear_key_prune<Q>(&mut self, key: &Q) -> usize
+    where
+        K: Borrow<Q> + Hash + Eq,
+        Q: Hash + Eq + ?Sized;
+
+    fn sub_retain<F>(&mut self, f: F)
+    where
+        F: FnMut(&K, &V) -> bool,
+        K: Hash + Eq,
+        V: Hash + Eq;
+
+    fn retain_values<F>(&mut self, f: F)
+    where
+        F: FnMut(&V) -> bool,
+        K: Hash + Eq,
+        V: Hash + Eq;
+
+    fn map_values<W, F>(&self, f: F) -> HashMapToSet<K, W>
+    where
+        F: FnMut(&V) -> W,
+        K: Clone + Hash + Eq,
+        W: Hash + Eq;
+
+    fn filter_keys<F>(&self, f: F) -> HashMapToSet<K, V>
+    where
+        F: FnMut(&K) -> bool,
+        K: Clone + Hash + Eq,
+        V: Clone + Hash + Eq;
+
+    fn sub_extract_if<F>(&mut self, f: F) -> std::vec::IntoIter<(K, V)>
+    where
+        F: FnMut(&K, &V) -> bool,
+        K: Clone + Hash + Eq,
+        V: Hash + Eq;
+
+    fn sub_swap(&mut self, key_a: K, key_b: K)
+    where
+        K: Hash + Eq;
+
+    fn sub_toggle(&mut self, key: K, value: V) -> bool
+    where
+        K: Hash + Eq,
+        V: Hash + Eq;
+
+    fn distinct_value_count(&self) -> usize
+    where
+        V: Hash + Eq;
+
+    fn sub_eq(&self, other: &HashMapToSet<K, V>) -> bool
     where
         K: Hash + Eq,
         V: Hash + Eq;
@@ -27,9 +327,9 @@ impl<K, V> HashMapToSetExt<K, V> for HashMapToSet<K, V> {
     
     /// Return `true` if the collection contains a sub-key-value item.
     ///
-    /// The value may be any borrowed form of the set's value type, but
-    /// [`Hash`] and [`Eq`] on the borrowed form *must* match those for
-    /// the value type.
+    /// The key and value may be any borrowed form of the map's key type
+    /// and the set's value type, but [`Hash`] and [`Eq`] on the borrowed
+    /// forms *must* match those for the key type and value type.
     ///
     /// # Examples
     ///
@@ -39,12 +339,18 @@ impl<K, V> HashMapToSetExt<K, V> for HashMapToSet<K, V> {
     /// a.sub_insert(1, 2);
     /// assert_eq!(a.sub_contains(&1, &2), true);
     /// assert_eq!(a.sub_contains(&3, &4), false);
+    ///
+    /// let mut b: HashMapToSet<String, String> = HashMapToSet::new();
+    /// b.sub_insert(String::from("alpha"), String::from("bravo"));
+    /// assert_eq!(b.sub_contains("alpha", "bravo"), true);
     /// ```
     #[inline]
-    fn sub_contains(&self, key: &K, value: &V) -> bool
+    fn sub_contains<Q, R>(&self, key: &Q, value: &R) -> bool
     where
-        K: Hash + Eq,
-        V: Hash + Eq,
+        K: Borrow<Q> + Hash + Eq,
+        V: Borrow<R> + Hash + Eq,
+        Q: Hash + Eq + ?Sized,
+        R: Hash + Eq + ?Sized,
     {
         match self.get(key) {
             Some(set) => set.contains(value),
@@ -71,7 +377,7 @@ impl<K, V> HashMapToSetExt<K, V> for HashMapToSet<K, V> {
         V: Hash + Eq,
     {
         self.entry(key)
-        .or_insert(HashSet::new())
+        .or_default()
         .insert(value)
     }
 
@@ -79,9 +385,12 @@ impl<K, V> HashMapToSetExt<K, V> for HashMapToSet<K, V> {
     ///
     /// Return whether the value was present in the set.
     ///
-    /// The value may be any borrowed form of the set's value type, but
-    /// [`Hash`] and [`Eq`] on the borrowed form *must* match those for
-    /// the value type.
+    /// The key stays in the collection even when its set becomes empty;
+    /// use `sub_remove_prune` to also remove the key.
+    ///
+    /// The key and value may be any borrowed form of the map's key type
+    /// and the set's value type, but [`Hash`] and [`Eq`] on the borrowed
+    /// forms *must* match those for the key type and value type.
     ///
     /// # Examples
     ///
@@ -94,88 +403,3153 @@ impl<K, V> HashMapToSetExt<K, V> for HashMapToSet<K, V> {
     /// assert_eq!(a.sub_contains(&1, &2), false);
     /// ```
     #[inline]
-    fn sub_remove(&mut self, key: &K, value: &V) -> bool 
+    fn sub_remove<Q, R>(&mut self, key: &Q, value: &R) -> bool
     where
-        K: Hash + Eq,
-        V: Hash + Eq,
+        K: Borrow<Q> + Hash + Eq,
+        V: Borrow<R> + Hash + Eq,
+        Q: Hash + Eq + ?Sized,
+        R: Hash + Eq + ?Sized,
     {
         match self.get_mut(key) {
-            Some(set) => set.remove(&value),
+            Some(set) => set.remove(value),
             None => false,
         }
     }
 
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use sixarm_assert::*;
-    use crate::hash_map_to_set::HashMapToSet;
+    /// Return an iterator over every sub-key-value pair in the collection.
+    ///
+    /// The iterator yields `(&K, &V)` pairs in arbitrary order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToSet<u8, u8> = HashMapToSet::new();
+    /// a.sub_insert(1, 2);
+    /// a.sub_insert(1, 3);
+    /// a.sub_insert(4, 5);
+    /// assert_eq!(a.sub_iter().count(), 3);
+    /// assert!(a.sub_iter().any(|(k, v)| *k == 1 && *v == 3));
+    /// ```
+    #[inline]
+    fn sub_iter<'a>(&'a self) -> impl Iterator<Item = (&'a K, &'a V)>
+    where
+        K: 'a,
+        V: 'a,
+    {
+        self.iter()
+        .flat_map(|(key, set)| set.iter().map(move |value| (key, value)))
+    }
 
-    #[test]
-    /// Test `sub_contains` with some items.
-    fn test_sub_contains() {
-        let mut subject: HashMapToSet<u8, u8> = HashMapToSet::new();
-        let k = 1;
-        let v = 2;
-        let absent = 3;
-        assert_eq!(subject.sub_insert(k, v), true);
-        assert_eq!(subject.sub_contains(&k, &v), true);
-        assert_eq!(subject.sub_contains(&k, &absent), false);
-        assert_eq!(subject.sub_contains(&absent, &v), false);
+    /// Consume the collection and return an iterator over every sub-key-value pair.
+    ///
+    /// The iterator yields owned `(K, V)` pairs in arbitrary order;
+    /// each key is cloned once per value in its set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToSet<u8, u8> = HashMapToSet::new();
+    /// a.sub_insert(1, 2);
+    /// a.sub_insert(1, 3);
+    /// a.sub_insert(4, 5);
+    /// let mut pairs: Vec<(u8, u8)> = a.into_sub_iter().collect();
+    /// pairs.sort();
+    /// assert_eq!(pairs, vec![(1, 2), (1, 3), (4, 5)]);
+    /// ```
+    #[inline]
+    fn into_sub_iter(self) -> impl Iterator<Item = (K, V)>
+    where
+        K: Clone,
+    {
+        self.into_iter()
+        .flat_map(|(key, set)| set.into_iter().map(move |value| (key.clone(), value)))
     }
-    #[test]
-    /// Test `sub_insert` with some items.
-    fn test_sub_insert() {
-        let mut subject: HashMapToSet<u8, u8> = HashMapToSet::new();
-        let k1 = 1;
-        let k2 = 2;
-        let v1 = 3;
-        let v2 = 4;
-        let v3 = 5;
-        let v4 = 7;
-        // Item 1
-        assert_eq!(subject.sub_insert(k1, v1), true);
-        //assert_set_eq!(subject.keys(), [k1]);
-        assert_set_eq!(subject.get(&k1).unwrap(), [v1]);
-        // Item 2
-        assert_eq!(subject.sub_insert(k1, v2), true);
-        //assert_set_eq!(subject.keys(), [k1]);  
-        assert_set_eq!(subject.get(&k1).unwrap(), [v1, v2]);
-        // Item 3
-        assert_eq!(subject.sub_insert(k2, v3), true);
-        //assert_set_eq!(subject.keys(), [k1, k2]);  
-        assert_set_eq!(subject.get(&k1).unwrap(), [v1, v2]);
-        assert_set_eq!(subject.get(&k2).unwrap(), [v3]);
-        // Item 4
-        assert_eq!(subject.sub_insert(k2, v4), true);
-        //assert_set_eq!(subject.keys(), [k1, k2]);
-        assert_set_eq!(subject.get(&k1).unwrap(), [v1, v2]);
-        assert_set_eq!(subject.get(&k2).unwrap(), [v3, v4]);
+
+    /// Return the total number of sub-key-value items in the collection.
+    ///
+    /// This counts every value in every set, unlike `len` which counts keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToSet<u8, u8> = HashMapToSet::new();
+    /// a.sub_insert(1, 2);
+    /// a.sub_insert(1, 3);
+    /// a.sub_insert(4, 5);
+    /// assert_eq!(a.len(), 2);
+    /// assert_eq!(a.sub_len(), 3);
+    /// ```
+    #[inline]
+    fn sub_len(&self) -> usize {
+        self.values().map(|set| set.len()).sum()
     }
 
-    #[test]
-    /// Test `remove` with a present item.
-    fn test_sub_remove_x_present_item() {
-        let mut subject: HashMapToSet<u8, u8> = HashMapToSet::new();
-        let k = 1;
-        let v = 2;
-        assert_eq!(subject.sub_insert(k, v), true);
-        assert_eq!(subject.sub_contains(&k, &v), true);
-        assert_eq!(subject.sub_remove(&k, &v), true);
-        assert_eq!(subject.sub_contains(&k, &v), false);
+    /// Return the number of values in the set for a key.
+    ///
+    /// Return 0 if the key is absent or if its set is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToSet<u8, u8> = HashMapToSet::new();
+    /// a.sub_insert(1, 2);
+    /// a.sub_insert(1, 3);
+    /// assert_eq!(a.sub_len_of(&1), 2);
+    /// assert_eq!(a.sub_len_of(&4), 0);
+    /// ```
+    #[inline]
+    fn sub_len_of<Q>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q> + Hash + Eq,
+        Q: Hash + Eq + ?Sized,
+    {
+        match self.get(key) {
+            Some(set) => set.len(),
+            None => 0,
+        }
     }
 
-    #[test]
-    /// Test `remove` with an absent item.
-    fn test_sub_remove_x_absent_item() {
+    /// Return `true` if the collection contains a key with a non-empty set.
+    ///
+    /// Return `false` if the key is absent or if its set is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToSet<u8, u8> = HashMapToSet::new();
+    /// a.sub_insert(1, 2);
+    /// assert_eq!(a.sub_key_is_populated(&1), true);
+    /// a.sub_remove(&1, &2);
+    /// assert_eq!(a.contains_key(&1), true);
+    /// assert_eq!(a.sub_key_is_populated(&1), false);
+    /// ```
+    #[inline]
+    fn sub_key_is_populated<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q> + Hash + Eq,
+        Q: Hash + Eq + ?Sized,
+    {
+        match self.get(key) {
+            Some(set) => !set.is_empty(),
+            None => false,
+        }
+    }
+
+    /// Remove a sub-key-value pair from the collection, and prune the key if its set becomes empty.
+    ///
+    /// Return whether the value was present in the set.
+    ///
+    /// Unlike `sub_remove`, this removes the key from the collection
+    /// when the set is empty afterwards, so `len` and `keys` reflect
+    /// only keys that have values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToSet<u8, u8> = HashMapToSet::new();
+    /// a.sub_insert(1, 2);
+    /// a.sub_insert(1, 3);
+    /// a.sub_remove_prune(&1, &2);
+    /// assert_eq!(a.contains_key(&1), true);
+    /// a.sub_remove_prune(&1, &3);
+    /// assert_eq!(a.contains_key(&1), false);
+    /// ```
+    #[inline]
+    fn sub_remove_prune<Q, R>(&mut self, key: &Q, value: &R) -> bool
+    where
+        K: Borrow<Q> + Hash + Eq,
+        V: Borrow<R> + Hash + Eq,
+        Q: Hash + Eq + ?Sized,
+        R: Hash + Eq + ?Sized,
+    {
+        match self.get_mut(key) {
+            Some(set) => {
+                let removed = set.remove(value);
+                if set.is_empty() {
+                    self.remove(key);
+                }
+                removed
+            },
+            None => false,
+        }
+    }
+
+    /// Add sub-key-value items to the collection from an iterator of pairs.
+    ///
+    /// Return the number of items that are newly added in the sets.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToSet<u8, u8> = HashMapToSet::new();
+    /// let count = a.sub_extend(vec![(1, 2), (1, 3), (1, 2)]);
+    /// assert_eq!(count, 2);
+    /// assert_eq!(a.sub_contains(&1, &2), true);
+    /// assert_eq!(a.sub_contains(&1, &3), true);
+    /// ```
+    #[inline]
+    fn sub_extend<I>(&mut self, iter: I) -> usize
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Hash + Eq,
+        V: Hash + Eq,
+    {
+        iter.into_iter()
+        .map(|(key, value)| self.sub_insert(key, value))
+        .filter(|inserted| *inserted)
+        .count()
+    }
+
+    /// Get a handle to the set for a key, for several lookups and inserts.
+    ///
+    /// The key is resolved once. If the key is absent, then its set is
+    /// created lazily by the first insert via the handle.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToSet<u8, u8> = HashMapToSet::new();
+    /// let mut entry = a.sub_entry(1);
+    /// assert_eq!(entry.contains(&2), false);
+    /// entry.insert(2);
+    /// entry.insert(3);
+    /// assert_eq!(entry.len(), 2);
+    /// assert_eq!(a.sub_contains(&1, &2), true);
+    /// assert_eq!(a.sub_contains(&1, &3), true);
+    /// ```
+    #[inline]
+    fn sub_entry(&mut self, key: K) -> HashMapToSetEntry<'_, K, V>
+    where
+        K: Hash + Eq,
+        V: Hash + Eq,
+    {
+        match self.entry(key) {
+            Entry::Occupied(entry) => HashMapToSetEntry { set: Some(entry.into_mut()), vacant: None },
+            Entry::Vacant(entry) => HashMapToSetEntry { set: None, vacant: Some(entry) },
+        }
+    }
+
+    /// Add many values to the set for one key.
+    ///
+    /// The key is resolved once, then each value is added to its set.
+    ///
+    /// Return the number of values that are newly added in the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToSet<u8, u8> = HashMapToSet::new();
+    /// a.sub_insert(1, 2);
+    /// let count = a.sub_insert_many(1, vec![2, 3, 4]);
+    /// assert_eq!(count, 2);
+    /// assert_eq!(a.sub_len_of(&1), 3);
+    /// ```
+    #[inline]
+    fn sub_insert_many<I>(&mut self, key: K, values: I) -> usize
+    where
+        I: IntoIterator<Item = V>,
+        K: Hash + Eq,
+        V: Hash + Eq,
+    {
+        let set = self.entry(key).or_default();
+        let len = set.len();
+        set.extend(values);
+        set.len() - len
+    }
+
+    /// Remove a sub-key-value pair from the collection, and return the owned value.
+    ///
+    /// Return `None` if the value was not present in the set.
+    ///
+    /// This is like `sub_remove`, and mirrors `HashSet::take`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToSet<u8, String> = HashMapToSet::new();
+    /// a.sub_insert(1, String::from("alpha"));
+    /// let taken = a.sub_take(&1, &String::from("alpha"));
+    /// assert_eq!(taken, Some(String::from("alpha")));
+    /// assert_eq!(a.sub_contains(&1, &String::from("alpha")), false);
+    /// ```
+    #[inline]
+    fn sub_take<Q, R>(&mut self, key: &Q, value: &R) -> Option<V>
+    where
+        K: Borrow<Q> + Hash + Eq,
+        V: Borrow<R> + Hash + Eq,
+        Q: Hash + Eq + ?Sized,
+        R: Hash + Eq + ?Sized,
+    {
+        match self.get_mut(key) {
+            Some(set) => set.take(value),
+            None => None,
+        }
+    }
+
+    /// Move a value from the set for one key to the set for another key.
+    ///
+    /// Return whether the value was present in the set for `from_key`,
+    /// and thus was moved. If the value was absent, then nothing changes.
+    ///
+    /// The key `from_key` stays in the collection even when its set becomes
+    /// empty, like `sub_remove`. If the set for `to_key` already contains
+    /// the value, then the value is still removed from `from_key`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToSet<u8, u8> = HashMapToSet::new();
+    /// a.sub_insert(1, 2);
+    /// assert_eq!(a.sub_move(&1, 3, &2), true);
+    /// assert_eq!(a.sub_contains(&1, &2), false);
+    /// assert_eq!(a.sub_contains(&3, &2), true);
+    /// ```
+    #[inline]
+    fn sub_move<Q, R>(&mut self, from_key: &Q, to_key: K, value: &R) -> bool
+    where
+        K: Borrow<Q> + Hash + Eq,
+        V: Borrow<R> + Hash + Eq,
+        Q: Hash + Eq + ?Sized,
+        R: Hash + Eq + ?Sized,
+    {
+        let taken = match self.get_mut(from_key) {
+            Some(set) => set.take(value),
+            None => None,
+        };
+        match taken {
+            Some(value) => {
+                self.sub_insert(to_key, value);
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Remove all sub-key-value pairs from the collection, and return them as an iterator.
+    ///
+    /// The iterator yields owned `(K, V)` pairs; each key is cloned once
+    /// per value in its set.
+    ///
+    /// The collection is empty afterwards, and keeps its allocated capacity.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToSet<u8, u8> = HashMapToSet::new();
+    /// a.sub_insert(1, 2);
+    /// a.sub_insert(1, 3);
+    /// let mut pairs: Vec<(u8, u8)> = a.sub_drain().collect();
+    /// pairs.sort();
+    /// assert_eq!(pairs, vec![(1, 2), (1, 3)]);
+    /// assert!(a.is_empty());
+    /// ```
+    #[inline]
+    fn sub_drain(&mut self) -> impl Iterator<Item = (K, V)>
+    where
+        K: Clone,
+    {
+        self.drain()
+        .flat_map(|(key, set)| set.into_iter().map(move |value| (key.clone(), value)))
+    }
+
+    /// Remove all values from the set for one key, and return them as an iterator.
+    ///
+    /// The key stays in the collection with an empty set, and the set
+    /// keeps its allocated capacity.
+    /// If the key is absent, then the iterator is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToSet<u8, u8> = HashMapToSet::new();
+    /// a.sub_insert(1, 2);
+    /// a.sub_insert(4, 5);
+    /// let values: Vec<u8> = a.sub_drain_key(&1).collect();
+    /// assert_eq!(values, vec![2]);
+    /// assert_eq!(a.sub_len_of(&1), 0);
+    /// assert_eq!(a.sub_len_of(&4), 1);
+    /// ```
+    #[inline]
+    fn sub_drain_key<Q>(&mut self, key: &Q) -> impl Iterator<Item = V>
+    where
+        K: Borrow<Q> + Hash + Eq,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get_mut(key)
+        .into_iter()
+        .flat_map(|set| set.drain())
+    }
+
+    /// Return an iterator over the keys whose sets contain a value.
+    ///
+    /// This is a reverse lookup, and scans every set in the collection.
+    /// The iterator yields keys in arbitrary order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToSet<u8, u8> = HashMapToSet::new();
+    /// a.sub_insert(1, 2);
+    /// a.sub_insert(3, 2);
+    /// a.sub_insert(4, 5);
+    /// let mut keys: Vec<&u8> = a.sub_keys_with_value(&2).collect();
+    /// keys.sort();
+    /// assert_eq!(keys, vec![&1, &3]);
+    /// ```
+    #[inline]
+    fn sub_keys_with_value<'a, R>(&'a self, value: &'a R) -> impl Iterator<Item = &'a K>
+    where
+        K: 'a,
+        V: Borrow<R> + Hash + Eq + 'a,
+        R: Hash + Eq + ?Sized,
+    {
+        self.iter()
+        .filter(move |(_, set)| set.contains(value))
+        .map(|(key, _)| key)
+    }
+
+    /// Return an iterator over every value in every set in the collection.
+    ///
+    /// A value that is in the sets of several keys is yielded once per key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToSet<u8, u8> = HashMapToSet::new();
+    /// a.sub_insert(1, 2);
+    /// a.sub_insert(3, 2);
+    /// a.sub_insert(3, 4);
+    /// let mut values: Vec<&u8> = a.sub_values().collect();
+    /// values.sort();
+    /// assert_eq!(values, vec![&2, &2, &4]);
+    /// ```
+    #[inline]
+    fn sub_values<'a>(&'a self) -> impl Iterator<Item = &'a V>
+    where
+        V: 'a,
+    {
+        self.values()
+        .flatten()
+    }
+
+    /// Return an iterator over the distinct values in all sets in the collection.
+    ///
+    /// A value that is in the sets of several keys is yielded once.
+    /// The iterator yields values in arbitrary order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToSet<u8, u8> = HashMapToSet::new();
+    /// a.sub_insert(1, 2);
+    /// a.sub_insert(3, 2);
+    /// a.sub_insert(3, 4);
+    /// let mut values: Vec<&u8> = a.sub_values_distinct().collect();
+    /// values.sort();
+    /// assert_eq!(values, vec![&2, &4]);
+    /// ```
+    #[inline]
+    fn sub_values_distinct<'a>(&'a self) -> impl Iterator<Item = &'a V>
+    where
+        V: Hash + Eq + 'a,
+    {
+        self.sub_values()
+        .collect::<HashSet<&V>>()
+        .into_iter()
+    }
+
+    /// Return a map of each key to the number of values in its set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToSet<u8, u8> = HashMapToSet::new();
+    /// a.sub_insert(1, 2);
+    /// a.sub_insert(1, 3);
+    /// a.sub_insert(4, 5);
+    /// let counts = a.sub_counts();
+    /// assert_eq!(counts.get(&1), Some(&2));
+    /// assert_eq!(counts.get(&4), Some(&1));
+    /// ```
+    #[inline]
+    fn sub_counts(&self) -> HashMap<K, usize>
+    where
+        K: Clone + Hash + Eq,
+    {
+        self.iter()
+        .map(|(key, set)| (key.clone(), set.len()))
+        .collect()
+    }
+
+    /// Merge another collection into this collection, consuming the other collection.
+    ///
+    /// A key that is only in the other collection is moved in with its set.
+    /// A key that is in both collections is combined using the strategy:
+    ///
+    ///   * `MergeStrategy::Union`: union the sets.
+    ///
+    ///   * `MergeStrategy::KeepSelf`: keep the set from this collection.
+    ///
+    ///   * `MergeStrategy::KeepOther`: keep the set from the other collection.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToSet<u8, u8> = HashMapToSet::new();
+    /// a.sub_insert(1, 2);
+    /// let mut b: HashMapToSet<u8, u8> = HashMapToSet::new();
+    /// b.sub_insert(1, 3);
+    /// b.sub_insert(4, 5);
+    /// a.sub_merge(b, MergeStrategy::Union);
+    /// assert_eq!(a.sub_contains(&1, &2), true);
+    /// assert_eq!(a.sub_contains(&1, &3), true);
+    /// assert_eq!(a.sub_contains(&4, &5), true);
+    /// ```
+    #[inline]
+    fn sub_merge(&mut self, other: HashMapToSet<K, V>, strategy: MergeStrategy)
+    where
+        K: Hash + Eq,
+        V: Hash + Eq,
+    {
+        for (key, mut set) in other {
+            match self.entry(key) {
+                Entry::Vacant(entry) => {
+                    entry.insert(set);
+                },
+                Entry::Occupied(mut entry) => match strategy {
+                    MergeStrategy::Union => {
+                        let existing = entry.get_mut();
+                        if existing.len() < set.len() {
+                            std::mem::swap(existing, &mut set);
+                        }
+                        existing.extend(set);
+                    },
+                    MergeStrategy::KeepSelf => {},
+                    MergeStrategy::KeepOther => {
+                        entry.insert(set);
+                    },
+                },
+            }
+        }
+    }
+
+    /// Return a new collection that is the keywise union of this collection and another.
+    ///
+    /// The result has every key from either collection, and the set for each
+    /// key has every value from either collection's set for that key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToSet<u8, u8> = HashMapToSet::new();
+    /// a.sub_insert(1, 2);
+    /// let mut b: HashMapToSet<u8, u8> = HashMapToSet::new();
+    /// b.sub_insert(1, 3);
+    /// b.sub_insert(4, 5);
+    /// let c = a.sub_union(&b);
+    /// assert_eq!(c.sub_len(), 3);
+    /// assert_eq!(c.sub_contains(&1, &2), true);
+    /// assert_eq!(c.sub_contains(&1, &3), true);
+    /// assert_eq!(c.sub_contains(&4, &5), true);
+    /// ```
+    #[inline]
+    fn sub_union(&self, other: &HashMapToSet<K, V>) -> HashMapToSet<K, V>
+    where
+        K: Clone + Hash + Eq,
+        V: Clone + Hash + Eq,
+    {
+        let mut result = self.clone();
+        for (key, set) in other {
+            result.entry(key.clone())
+            .or_default()
+            .extend(set.iter().cloned());
+        }
+        result
+    }
+
+    /// Return a new collection that is the keywise intersection of this collection and another.
+    ///
+    /// The result has only keys that are in both collections, and the set
+    /// for each key has only values that are in both collections' sets for
+    /// that key. A key whose intersection is empty is omitted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToSet<u8, u8> = HashMapToSet::new();
+    /// a.sub_insert(1, 2);
+    /// a.sub_insert(1, 3);
+    /// a.sub_insert(4, 5);
+    /// let mut b: HashMapToSet<u8, u8> = HashMapToSet::new();
+    /// b.sub_insert(1, 3);
+    /// b.sub_insert(4, 6);
+    /// let c = a.sub_intersection(&b);
+    /// assert_eq!(c.len(), 1);
+    /// assert_eq!(c.sub_contains(&1, &3), true);
+    /// ```
+    #[inline]
+    fn sub_intersection(&self, other: &HashMapToSet<K, V>) -> HashMapToSet<K, V>
+    where
+        K: Clone + Hash + Eq,
+        V: Clone + Hash + Eq,
+    {
+        self.iter()
+        .filter_map(|(key, set)| {
+            let other_set = other.get(key)?;
+            let intersection: HashSet<V> = set.intersection(other_set).cloned().collect();
+            if intersection.is_empty() {
+                None
+            } else {
+                Some((key.clone(), intersection))
+            }
+        })
+        .collect()
+    }
+
+    /// Return a new collection of the sub-key-value pairs that are in this collection but not in another.
+    ///
+    /// A key whose difference is empty is omitted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToSet<u8, u8> = HashMapToSet::new();
+    /// a.sub_insert(1, 2);
+    /// a.sub_insert(1, 3);
+    /// a.sub_insert(4, 5);
+    /// let mut b: HashMapToSet<u8, u8> = HashMapToSet::new();
+    /// b.sub_insert(1, 3);
+    /// b.sub_insert(4, 5);
+    /// let c = a.sub_difference(&b);
+    /// assert_eq!(c.len(), 1);
+    /// assert_eq!(c.sub_contains(&1, &2), true);
+    /// ```
+    #[inline]
+    fn sub_difference(&self, other: &HashMapToSet<K, V>) -> HashMapToSet<K, V>
+    where
+        K: Clone + Hash + Eq,
+        V: Clone + Hash + Eq,
+    {
+        self.iter()
+        .filter_map(|(key, set)| {
+            let difference: HashSet<V> = match other.get(key) {
+                Some(other_set) => set.difference(other_set).cloned().collect(),
+                None => set.clone(),
+            };
+            if difference.is_empty() {
+                None
+            } else {
+                Some((key.clone(), difference))
+            }
+        })
+        .collect()
+    }
+
+    /// Return a new collection of the sub-key-value pairs that are in exactly one of this collection and another.
+    ///
+    /// A key whose symmetric difference is empty is omitted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToSet<u8, u8> = HashMapToSet::new();
+    /// a.sub_insert(1, 2);
+    /// a.sub_insert(1, 3);
+    /// let mut b: HashMapToSet<u8, u8> = HashMapToSet::new();
+    /// b.sub_insert(1, 3);
+    /// b.sub_insert(4, 5);
+    /// let c = a.sub_symmetric_difference(&b);
+    /// assert_eq!(c.sub_len(), 2);
+    /// assert_eq!(c.sub_contains(&1, &2), true);
+    /// assert_eq!(c.sub_contains(&4, &5), true);
+    /// ```
+    #[inline]
+    fn sub_symmetric_difference(&self, other: &HashMapToSet<K, V>) -> HashMapToSet<K, V>
+    where
+        K: Clone + Hash + Eq,
+        V: Clone + Hash + Eq,
+    {
+        let mut result = self.sub_difference(other);
+        result.sub_merge(other.sub_difference(self), MergeStrategy::Union);
+        result
+    }
+
+    /// Return `true` if every sub-key-value pair in this collection is also in another.
+    ///
+    /// A key with an empty set has no pairs, so it does not need to be in
+    /// the other collection.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToSet<u8, u8> = HashMapToSet::new();
+    /// a.sub_insert(1, 2);
+    /// let mut b: HashMapToSet<u8, u8> = HashMapToSet::new();
+    /// b.sub_insert(1, 2);
+    /// b.sub_insert(1, 3);
+    /// assert_eq!(a.is_sub_multimap(&b), true);
+    /// assert_eq!(b.is_sub_multimap(&a), false);
+    /// ```
+    #[inline]
+    fn is_sub_multimap(&self, other: &HashMapToSet<K, V>) -> bool
+    where
+        K: Hash + Eq,
+        V: Hash + Eq,
+    {
+        self.iter()
+        .all(|(key, set)| {
+            set.is_empty() ||
+            match other.get(key) {
+                Some(other_set) => set.is_subset(other_set),
+                None => false,
+            }
+        })
+    }
+
+    /// Return `true` if every sub-key-value pair in another collection is also in this collection.
+    ///
+    /// This is the reverse of `is_sub_multimap`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToSet<u8, u8> = HashMapToSet::new();
+    /// a.sub_insert(1, 2);
+    /// a.sub_insert(1, 3);
+    /// let mut b: HashMapToSet<u8, u8> = HashMapToSet::new();
+    /// b.sub_insert(1, 2);
+    /// assert_eq!(a.is_super_multimap(&b), true);
+    /// assert_eq!(b.is_super_multimap(&a), false);
+    /// ```
+    #[inline]
+    fn is_super_multimap(&self, other: &HashMapToSet<K, V>) -> bool
+    where
+        K: Hash + Eq,
+        V: Hash + Eq,
+    {
+        other.is_sub_multimap(self)
+    }
+
+    /// Return a new collection that maps each value to the set of keys whose sets contain it.
+    ///
+    /// This borrows the collection, and clones each key and value.
+    /// A key with an empty set is not in the result.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToSet<u8, char> = HashMapToSet::new();
+    /// a.sub_insert(1, 'x');
+    /// a.sub_insert(2, 'x');
+    /// a.sub_insert(2, 'y');
+    /// let b: HashMapToSet<char, u8> = a.invert();
+    /// assert_eq!(b.sub_contains(&'x', &1), true);
+    /// assert_eq!(b.sub_contains(&'x', &2), true);
+    /// assert_eq!(b.sub_contains(&'y', &2), true);
+    /// ```
+    #[inline]
+    fn invert(&self) -> HashMapToSet<V, K>
+    where
+        K: Clone + Hash + Eq,
+        V: Clone + Hash + Eq,
+    {
+        let mut result = HashMapToSet::new();
+        for (key, value) in self.sub_iter() {
+            result.sub_insert(value.clone(), key.clone());
+        }
+        result
+    }
+
+    /// Consume the collection and return a new collection that maps each value to the set of keys whose sets contain it.
+    ///
+    /// This moves each value, and clones each key once per value in its set.
+    /// A key with an empty set is not in the result.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToSet<u8, String> = HashMapToSet::new();
+    /// a.sub_insert(1, String::from("x"));
+    /// a.sub_insert(2, String::from("x"));
+    /// let b: HashMapToSet<String, u8> = a.into_invert();
+    /// assert_eq!(b.sub_len_of("x"), 2);
+    /// ```
+    #[inline]
+    fn into_invert(self) -> HashMapToSet<V, K>
+    where
+        K: Clone + Hash + Eq,
+        V: Hash + Eq,
+    {
+        let mut result = HashMapToSet::new();
+        for (key, value) in self.into_sub_iter() {
+            result.sub_insert(value, key);
+        }
+        result
+    }
+
+    /// Remove every key whose set is empty.
+    ///
+    /// Return the number of keys that are removed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToSet<u8, u8> = HashMapToSet::new();
+    /// a.sub_insert(1, 2);
+    /// a.sub_insert(3, 4);
+    /// a.sub_remove(&1, &2);
+    /// assert_eq!(a.prune_empty(), 1);
+    /// assert_eq!(a.contains_key(&1), false);
+    /// assert_eq!(a.contains_key(&3), true);
+    /// ```
+    #[inline]
+    fn prune_empty(&mut self) -> usize
+    where
+        K: Hash + Eq,
+    {
+        let len = self.len();
+        self.retain(|_, set| !set.is_empty());
+        len - self.len()
+    }
+
+    /// Put a set under a key, and return the set that was there.
+    ///
+    /// Return `None` if the key was absent.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// use std::collections::HashSet;
+    /// let mut a: HashMapToSet<u8, u8> = HashMapToSet::new();
+    /// a.sub_insert(1, 2);
+    /// let old = a.sub_replace(1, HashSet::from([3, 4]));
+    /// assert_eq!(old, Some(HashSet::from([2])));
+    /// assert_eq!(a.sub_contains(&1, &2), false);
+    /// assert_eq!(a.sub_contains(&1, &3), true);
+    /// ```
+    #[inline]
+    fn sub_replace(&mut self, key: K, set: HashSet<V>) -> Option<HashSet<V>>
+    where
+        K: Hash + Eq,
+    {
+        self.insert(key, set)
+    }
+
+    /// Create a collection from an iterator of sub-key-value pairs, grouping values by key.
+    ///
+    /// This is the grouping constructor. Note that `collect` into a `HashMapToSet`
+    /// uses the `FromIterator` implementation of `HashMap`, which expects
+    /// pairs of `(K, HashSet<V>)`, so use `from_pairs` for pairs of `(K, V)`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: HashMapToSet<u8, u8> = HashMapToSet::from_pairs(vec![(1, 2), (1, 3), (4, 5)]);
+    /// assert_eq!(a.len(), 2);
+    /// assert_eq!(a.sub_len(), 3);
+    /// ```
+    #[inline]
+    fn from_pairs<I>(iter: I) -> Self
+    where
+        Self: Sized,
+        I: IntoIterator<Item = (K, V)>,
+        K: Hash + Eq,
+        V: Hash + Eq,
+    {
+        let mut result = HashMapToSet::new();
+        result.sub_extend(iter);
+        result
+    }
+
+    /// Get an adapter that implements `Extend<(K, V)>` by adding each pair to the sets.
+    ///
+    /// Use this to pass the collection to generic code that is bounded by
+    /// `Extend<(K, V)>`. Note that `extend` on the collection itself uses
+    /// the `Extend` implementation of `HashMap`, which replaces whole sets.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// fn load<E: Extend<(u8, u8)>>(mut e: E) {
+    ///     e.extend(vec![(1, 2), (1, 3)]);
+    /// }
+    /// let mut a: HashMapToSet<u8, u8> = HashMapToSet::new();
+    /// load(a.sub_extender());
+    /// assert_eq!(a.sub_contains(&1, &2), true);
+    /// assert_eq!(a.sub_contains(&1, &3), true);
+    /// ```
+    #[inline]
+    fn sub_extender(&mut self) -> HashMapToSetExtender<'_, K, V> {
+        HashMapToSetExtender { map: self }
+    }
+
+    /// Create a collection from an iterator of values, grouping each value by a key derived from it.
+    ///
+    /// The key function is called once per value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let words = vec!["apple", "avocado", "banana"];
+    /// let a: HashMapToSet<char, &str> = HashMapToSet::from_grouping(words, |word| word.chars().next().unwrap());
+    /// assert_eq!(a.sub_len_of(&'a'), 2);
+    /// assert_eq!(a.sub_len_of(&'b'), 1);
+    /// ```
+    #[inline]
+    fn from_grouping<I, F>(items: I, mut key_fn: F) -> Self
+    where
+        Self: Sized,
+        I: IntoIterator<Item = V>,
+        F: FnMut(&V) -> K,
+        K: Hash + Eq,
+        V: Hash + Eq,
+    {
+        let mut result = HashMapToSet::new();
+        for item in items {
+            result.sub_insert(key_fn(&item), item);
+        }
+        result
+    }
+
+    /// Consume the collection and return a vector of every sub-key-value pair.
+    ///
+    /// The vector has owned `(K, V)` pairs; each key is cloned once
+    /// per value in its set. This is like `into_sub_iter` then `collect`,
+    /// and allocates the vector once.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToSet<u8, u8> = HashMapToSet::new();
+    /// a.sub_insert(1, 2);
+    /// a.sub_insert(1, 3);
+    /// let mut pairs = a.into_pairs();
+    /// pairs.sort();
+    /// assert_eq!(pairs, vec![(1, 2), (1, 3)]);
+    /// ```
+    #[inline]
+    fn into_pairs(self) -> Vec<(K, V)>
+    where
+        K: Clone,
+    {
+        let mut result = Vec::with_capacity(self.sub_len());
+        result.extend(self.into_sub_iter());
+        result
+    }
+
+    /// Consume the collection and return a vector of each key with its set.
+    ///
+    /// The vector has owned `(K, HashSet<V>)` pairs, and no key is cloned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToSet<u8, u8> = HashMapToSet::new();
+    /// a.sub_insert(1, 2);
+    /// a.sub_insert(1, 3);
+    /// let groups = a.into_groups();
+    /// assert_eq!(groups.len(), 1);
+    /// assert_eq!(groups[0].1.len(), 2);
+    /// ```
+    #[inline]
+    fn into_groups(self) -> Vec<(K, HashSet<V>)> {
+        self.into_iter().collect()
+    }
+
+    /// Remove an arbitrary value from the set for a key, and return it.
+    ///
+    /// Return `None` if the key is absent or if its set is empty.
+    /// The key is removed from the collection when its set becomes empty,
+    /// like `sub_remove_prune`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToSet<u8, u8> = HashMapToSet::new();
+    /// a.sub_insert(1, 2);
+    /// assert_eq!(a.sub_pop(&1), Some(2));
+    /// assert_eq!(a.contains_key(&1), false);
+    /// assert_eq!(a.sub_pop(&1), None);
+    /// ```
+    #[inline]
+    fn sub_pop<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q> + Hash + Eq,
+        V: Clone + Hash + Eq,
+        Q: Hash + Eq + ?Sized,
+    {
+        let set = self.get_mut(key)?;
+        let value = set.iter().next().cloned().and_then(|value| set.take(&value));
+        if set.is_empty() {
+            self.remove(key);
+        }
+        value
+    }
+
+    /// Return the `n` keys with the largest sets, with each key's set cardinality.
+    ///
+    /// The result is ordered by descending cardinality; keys with equal
+    /// cardinality are in arbitrary order. This uses a bounded heap of size `n`, rather
+    /// than sorting every key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToSet<u8, u8> = HashMapToSet::new();
+    /// a.sub_insert_many(1, vec![1]);
+    /// a.sub_insert_many(2, vec![1, 2, 3]);
+    /// a.sub_insert_many(3, vec![1, 2]);
+    /// assert_eq!(a.top_keys_by_count(2), vec![(&2, 3), (&3, 2)]);
+    /// ```
+    #[inline]
+    fn top_keys_by_count(&self, n: usize) -> Vec<(&K, usize)> {
+        top_by_count(self.iter().map(|(key, set)| (key, set.len())), n)
+    }
+
+    /// Return a mutable reference to the set for a key, creating the set with a factory if the key is absent.
+    ///
+    /// The factory is only called when the key is absent, so it can
+    /// choose how the set is created, such as with a capacity.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// use std::collections::HashSet;
+    /// let mut a: HashMapToSet<u8, u8> = HashMapToSet::new();
+    /// let set = a.sub_get_or_insert_with(1, || HashSet::with_capacity(8));
+    /// set.insert(2);
+    /// set.insert(3);
+    /// assert_eq!(a.sub_len_of(&1), 2);
+    /// ```
+    #[inline]
+    fn sub_get_or_insert_with<F>(&mut self, key: K, factory: F) -> &mut HashSet<V>
+    where
+        F: FnOnce() -> HashSet<V>,
+        K: Hash + Eq,
+    {
+        self.entry(key).or_insert_with(factory)
+    }
+
+    /// Return a read-only view of the set for a key, which is empty if the key is absent.
+    ///
+    /// This never returns `None`, so a caller can iterate the view or ask
+    /// for its length without matching on an option.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToSet<u8, u8> = HashMapToSet::new();
+    /// a.sub_insert(1, 2);
+    /// for value in a.sub_get(&1) {
+    ///     assert_eq!(value, &2);
+    /// }
+    /// assert_eq!(a.sub_get(&1).len(), 1);
+    /// assert_eq!(a.sub_get(&4).is_empty(), true);
+    /// ```
+    #[inline]
+    fn sub_get<Q>(&self, key: &Q) -> HashMapToSetView<'_, V>
+    where
+        K: Borrow<Q> + Hash + Eq,
+        Q: Hash + Eq + ?Sized,
+    {
+        HashMapToSetView { set: self.get(key) }
+    }
+
+    /// Return the number of values that are in both the set for one key and the set for another key.
+    ///
+    /// An absent key has an empty set. This scans the smaller set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToSet<u8, u8> = HashMapToSet::new();
+    /// a.sub_insert_many(1, vec![1, 2, 3]);
+    /// a.sub_insert_many(2, vec![2, 3, 4]);
+    /// assert_eq!(a.sub_overlap_count(&1, &2), 2);
+    /// assert_eq!(a.sub_overlap_count(&1, &9), 0);
+    /// ```
+    #[inline]
+    fn sub_overlap_count<Q>(&self, key_a: &Q, key_b: &Q) -> usize
+    where
+        K: Borrow<Q> + Hash + Eq,
+        V: Hash + Eq,
+        Q: Hash + Eq + ?Sized,
+    {
+        match (self.get(key_a), self.get(key_b)) {
+            (Some(a), Some(b)) => {
+                let (small, large) = if a.len() <= b.len() { (a, b) } else { (b, a) };
+                small.iter().filter(|value| large.contains(*value)).count()
+            },
+            _ => 0,
+        }
+    }
+
+    /// Return the Jaccard similarity of the set for one key and the set for another key.
+    ///
+    /// This is the size of the intersection divided by the size of the
+    /// union, from 0.0 for disjoint sets to 1.0 for equal sets. An absent
+    /// key has an empty set. If both sets are empty, then return 0.0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToSet<u8, u8> = HashMapToSet::new();
+    /// a.sub_insert_many(1, vec![1, 2, 3]);
+    /// a.sub_insert_many(2, vec![2, 3, 4]);
+    /// assert_eq!(a.sub_similarity(&1, &2), 0.5);
+    /// assert_eq!(a.sub_similarity(&1, &1), 1.0);
+    /// assert_eq!(a.sub_similarity(&1, &9), 0.0);
+    /// ```
+    #[inline]
+    fn sub_similarity<Q>(&self, key_a: &Q, key_b: &Q) -> f64
+    where
+        K: Borrow<Q> + Hash + Eq,
+        V: Hash + Eq,
+        Q: Hash + Eq + ?Sized,
+    {
+        let overlap = self.sub_overlap_count(key_a, key_b);
+        let union = self.sub_len_of(key_a) + self.sub_len_of(key_b) - overlap;
+        if union == 0 {
+            0.0
+        } else {
+            overlap as f64 / union as f64
+        }
+    }
+
+    /// Return `true` if the set for a key contains every value from an iterator.
+    ///
+    /// This stops at the first value that is absent. If the iterator is
+    /// empty, then return `true`, even if the key is absent.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToSet<u8, u8> = HashMapToSet::new();
+    /// a.sub_insert_many(1, vec![2, 3, 4]);
+    /// assert_eq!(a.sub_contains_all(&1, &[2, 3]), true);
+    /// assert_eq!(a.sub_contains_all(&1, &[2, 5]), false);
+    /// ```
+    #[inline]
+    fn sub_contains_all<'a, Q, R, I>(&self, key: &Q, values: I) -> bool
+    where
+        K: Borrow<Q> + Hash + Eq,
+        V: Borrow<R> + Hash + Eq,
+        Q: Hash + Eq + ?Sized,
+        R: Hash + Eq + ?Sized + 'a,
+        I: IntoIterator<Item = &'a R>,
+    {
+        match self.get(key) {
+            Some(set) => values.into_iter().all(|value| set.contains(value)),
+            None => values.into_iter().next().is_none(),
+        }
+    }
+
+    /// Return `true` if the set for a key contains any value from an iterator.
+    ///
+    /// This stops at the first value that is present. If the iterator is
+    /// empty, or the key is absent, then return `false`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToSet<u8, u8> = HashMapToSet::new();
+    /// a.sub_insert_many(1, vec![2, 3, 4]);
+    /// assert_eq!(a.sub_contains_any(&1, &[5, 3]), true);
+    /// assert_eq!(a.sub_contains_any(&1, &[5, 6]), false);
+    /// ```
+    #[inline]
+    fn sub_contains_any<'a, Q, R, I>(&self, key: &Q, values: I) -> bool
+    where
+        K: Borrow<Q> + Hash + Eq,
+        V: Borrow<R> + Hash + Eq,
+        Q: Hash + Eq + ?Sized,
+        R: Hash + Eq + ?Sized + 'a,
+        I: IntoIterator<Item = &'a R>,
+    {
+        match self.get(key) {
+            Some(set) => values.into_iter().any(|value| set.contains(value)),
+            None => false,
+        }
+    }
+
+    /// Return groups of keys whose sets are equal.
+    ///
+    /// Each group has two or more keys. A key that has a set unlike any
+    /// other key's set is not in the result. Keys with empty sets are
+    /// equal to each other.
+    ///
+    /// This buckets sets by length and by an order-independent fingerprint
+    /// of their values, then compares sets within a bucket for equality.
+    /// The groups are in arbitrary order, and the keys within a group are
+    /// in arbitrary order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToSet<u8, u8> = HashMapToSet::new();
+    /// a.sub_insert_many(1, vec![5, 6]);
+    /// a.sub_insert_many(2, vec![6, 5]);
+    /// a.sub_insert_many(3, vec![5]);
+    /// let mut groups = a.duplicate_value_sets();
+    /// for group in groups.iter_mut() { group.sort(); }
+    /// assert_eq!(groups, vec![vec![&1, &2]]);
+    /// ```
+    #[inline]
+    fn duplicate_value_sets(&self) -> Vec<Vec<&K>>
+    where
+        K: Hash + Eq,
+        V: Hash + Eq,
+    {
+        let mut buckets = HashMap::new();
+        for (key, set) in self.iter() {
+            let fingerprint = set.iter()
+            .map(|value| {
+                let mut hasher = DefaultHasher::new();
+                value.hash(&mut hasher);
+                hasher.finish()
+            })
+            .fold(0u64, |acc, hash| acc.wrapping_add(hash));
+            let groups: &mut Vec<(&HashSet<V>, Vec<&K>)> = buckets.entry((set.len(), fingerprint)).or_default();
+            match groups.iter_mut().find(|(group_set, _)| *group_set == set) {
+                Some((_, keys)) => keys.push(key),
+                None => groups.push((set, vec![key])),
+            }
+        }
+        buckets.into_values()
+        .flatten()
+        .map(|(_, keys)| keys)
+        .filter(|keys| keys.len() > 1)
+        .collect()
+    }
+
+    /// Return a uniform random sample of `n` distinct sub-key-value pairs.
+    ///
+    /// Each pair in the collection is equally likely to be chosen, so a key
+    /// is chosen in proportion to the size of its set. If the collection has
+    /// fewer than `n` pairs, then return all pairs. The order of the result
+    /// is not random; shuffle it if needed.
+    ///
+    /// This uses reservoir sampling, so it takes one pass over the pairs.
+    /// This requires the `rand` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// use rand::SeedableRng;
+    /// let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+    /// let mut a: HashMapToSet<u8, u8> = HashMapToSet::new();
+    /// a.sub_insert_many(1, vec![2, 3, 4]);
+    /// a.sub_insert_many(5, vec![6]);
+    /// let sample = a.sub_sample(&mut rng, 2);
+    /// assert_eq!(sample.len(), 2);
+    /// assert!(sample.iter().all(|(k, v)| a.sub_contains(*k, *v)));
+    /// ```
+    #[cfg(feature = "rand")]
+    #[inline]
+    fn sub_sample<G>(&self, rng: &mut G, n: usize) -> Vec<(&K, &V)>
+    where
+        G: Rng + ?Sized,
+    {
+        self.sub_iter().sample(rng, n)
+    }
+
+    /// Return a uniform random sample of `n` distinct values from the set for a key.
+    ///
+    /// If the set has fewer than `n` values, then return all its values.
+    /// If the key is absent, then return an empty vector.
+    ///
+    /// This requires the `rand` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// use rand::SeedableRng;
+    /// let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+    /// let mut a: HashMapToSet<u8, u8> = HashMapToSet::new();
+    /// a.sub_insert_many(1, vec![2, 3, 4]);
+    /// let sample = a.sub_sample_key(&mut rng, &1, 2);
+    /// assert_eq!(sample.len(), 2);
+    /// assert!(sample.iter().all(|v| a.sub_contains(&1, *v)));
+    /// ```
+    #[cfg(feature = "rand")]
+    #[inline]
+    fn sub_sample_key<Q, G>(&self, rng: &mut G, key: &Q, n: usize) -> Vec<&V>
+    where
+        K: Borrow<Q> + Hash + Eq,
+        Q: Hash + Eq + ?Sized,
+        G: Rng + ?Sized,
+    {
+        self.get(key)
+        .into_iter()
+        .flatten()
+        .sample(rng, n)
+    }
+
+    /// Add every value from a set to the set for a key.
+    ///
+    /// If the key is absent, then the set is moved in as-is. If the key is
+    /// present, then the smaller set is merged into the larger set.
+    ///
+    /// Return the number of values that are newly added in the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// use std::collections::HashSet;
+    /// let mut a: HashMapToSet<u8, u8> = HashMapToSet::new();
+    /// a.sub_insert(1, 2);
+    /// let count = a.sub_append(1, HashSet::from([2, 3, 4]));
+    /// assert_eq!(count, 2);
+    /// assert_eq!(a.sub_len_of(&1), 3);
+    /// ```
+    #[inline]
+    fn sub_append(&mut self, key: K, mut set: HashSet<V>) -> usize
+    where
+        K: Hash + Eq,
+        V: Hash + Eq,
+    {
+        match self.entry(key) {
+            Entry::Vacant(entry) => {
+                let len = set.len();
+                entry.insert(set);
+                len
+            },
+            Entry::Occupied(mut entry) => {
+                let existing = entry.get_mut();
+                let len = existing.len();
+                if existing.len() < set.len() {
+                    std::mem::swap(existing, &mut set);
+                }
+                existing.extend(set);
+                existing.len() - len
+            },
+        }
+    }
+
+    /// Remove every value from the set for a key, and keep the key with an empty set.
+    ///
+    /// Return the number of values that are removed. If the key is absent,
+    /// then return 0, and the key stays absent.
+    ///
+    /// Use this to keep a key as registered but idle; use
+    /// `sub_clear_key_prune` to also remove the key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToSet<u8, u8> = HashMapToSet::new();
+    /// a.sub_insert_many(1, vec![2, 3]);
+    /// assert_eq!(a.sub_clear_key(&1), 2);
+    /// assert_eq!(a.contains_key(&1), true);
+    /// assert_eq!(a.sub_len_of(&1), 0);
+    /// ```
+    #[inline]
+    fn sub_clear_key<Q>(&mut self, key: &Q) -> usize
+    where
+        K: Borrow<Q> + Hash + Eq,
+        Q: Hash + Eq + ?Sized,
+    {
+        match self.get_mut(key) {
+            Some(set) => {
+                let len = set.len();
+                set.clear();
+                len
+            },
+            None => 0,
+        }
+    }
+
+    /// Remove every value from the set for a key, and remove the key.
+    ///
+    /// Return the number of values that are removed. If the key is absent,
+    /// then return 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToSet<u8, u8> = HashMapToSet::new();
+    /// a.sub_insert_many(1, vec![2, 3]);
+    /// assert_eq!(a.sub_clear_key_prune(&1), 2);
+    /// assert_eq!(a.contains_key(&1), false);
+    /// ```
+    #[inline]
+    fn sub_clear_key_prune<Q>(&mut self, key: &Q) -> usize
+    where
+        K: Borrow<Q> + Hash + Eq,
+        Q: Hash + Eq + ?Sized,
+    {
+        match self.remove(key) {
+            Some(set) => set.len(),
+            None => 0,
+        }
+    }
+
+    /// Keep only the sub-key-value pairs that match a predicate, and prune emptied keys.
+    ///
+    /// The predicate is called with each key and value. A key whose set is
+    /// empty afterwards is removed from the collection.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToSet<u8, u8> = HashMapToSet::new();
+    /// a.sub_insert_many(1, vec![1, 2]);
+    /// a.sub_insert_many(2, vec![3]);
+    /// a.sub_retain(|k, v| k + v > 3);
+    /// assert_eq!(a.sub_len(), 1);
+    /// assert_eq!(a.sub_contains(&2, &3), true);
+    /// assert_eq!(a.contains_key(&1), false);
+    /// ```
+    #[inline]
+    fn sub_retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&K, &V) -> bool,
+        K: Hash + Eq,
+        V: Hash + Eq,
+    {
+        self.retain(|key, set| {
+            set.retain(|value| f(key, value));
+            !set.is_empty()
+        });
+    }
+
+    /// Keep only the values that match a predicate, across all keys, and prune emptied keys.
+    ///
+    /// The predicate is called with each value, without its key. This is
+    /// like `sub_retain` for the common case where only the value matters.
+    /// A key whose set is empty afterwards is removed from the collection.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToSet<u8, i8> = HashMapToSet::new();
+    /// a.sub_insert_many(1, vec![1, -1]);
+    /// a.sub_insert_many(2, vec![-2]);
+    /// a.retain_values(|v| *v > 0);
+    /// assert_eq!(a.sub_len(), 1);
+    /// assert_eq!(a.contains_key(&2), false);
+    /// ```
+    #[inline]
+    fn retain_values<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&V) -> bool,
+        K: Hash + Eq,
+        V: Hash + Eq,
+    {
+        self.retain(|_, set| {
+            set.retain(|value| f(value));
+            !set.is_empty()
+        });
+    }
+
+    /// Return a new collection with a function applied to every value.
+    ///
+    /// Each key keeps its set, with each value mapped by the function.
+    /// Distinct values that map to the same new value are collapsed into
+    /// one, so a set can become smaller. A key with an empty set stays.
+    ///
+    /// The result is pre-sized: the map has the capacity for every key,
+    /// and each set has the capacity for every value of its source set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToSet<u8, i8> = HashMapToSet::new();
+    /// a.sub_insert_many(1, vec![-2, 2, 3]);
+    /// let b: HashMapToSet<u8, u8> = a.map_values(|v| v.unsigned_abs());
+    /// assert_eq!(b.sub_len_of(&1), 2);
+    /// assert_eq!(b.sub_contains(&1, &2), true);
+    /// ```
+    #[inline]
+    fn map_values<W, F>(&self, mut f: F) -> HashMapToSet<K, W>
+    where
+        F: FnMut(&V) -> W,
+        K: Clone + Hash + Eq,
+        W: Hash + Eq,
+    {
+        let mut result = HashMapToSet::with_capacity(self.len());
+        for (key, set) in self.iter() {
+            let mut mapped = HashSet::with_capacity(set.len());
+            mapped.extend(set.iter().map(&mut f));
+            result.insert(key.clone(), mapped);
+        }
+        result
+    }
+
+    /// Return a new collection with only the keys that match a predicate, with their sets.
+    ///
+    /// This borrows the collection, and clones each matching key and its set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToSet<String, u8> = HashMapToSet::new();
+    /// a.sub_insert(String::from("app/alpha"), 1);
+    /// a.sub_insert(String::from("app/bravo"), 2);
+    /// a.sub_insert(String::from("lib/charlie"), 3);
+    /// let b = a.filter_keys(|k| k.starts_with("app/"));
+    /// assert_eq!(b.len(), 2);
+    /// assert_eq!(b.contains_key("lib/charlie"), false);
+    /// ```
+    #[inline]
+    fn filter_keys<F>(&self, mut f: F) -> HashMapToSet<K, V>
+    where
+        F: FnMut(&K) -> bool,
+        K: Clone + Hash + Eq,
+        V: Clone + Hash + Eq,
+    {
+        self.iter()
+        .filter(|(key, _)| f(key))
+        .map(|(key, set)| (key.clone(), set.clone()))
+        .collect()
+    }
+
+    /// Remove the sub-key-value pairs that match a predicate, prune emptied keys, and return the removed pairs.
+    ///
+    /// This is like `sub_retain` with the predicate inverted, and also
+    /// gives back the removed pairs, e.g. to archive them. The pairs are
+    /// removed eagerly, before the iterator is returned, so dropping the
+    /// iterator early still removes every matching pair. Each key is
+    /// cloned once per removed value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToSet<u8, u8> = HashMapToSet::new();
+    /// a.sub_insert_many(1, vec![1, 2]);
+    /// a.sub_insert_many(2, vec![4]);
+    /// let mut removed: Vec<(u8, u8)> = a.sub_extract_if(|_, v| v % 2 == 0).collect();
+    /// removed.sort();
+    /// assert_eq!(removed, vec![(1, 2), (2, 4)]);
+    /// assert_eq!(a.sub_len(), 1);
+    /// assert_eq!(a.contains_key(&2), false);
+    /// ```
+    #[inline]
+    fn sub_extract_if<F>(&mut self, mut f: F) -> std::vec::IntoIter<(K, V)>
+    where
+        F: FnMut(&K, &V) -> bool,
+        K: Clone + Hash + Eq,
+        V: Hash + Eq,
+    {
+        let mut extracted = Vec::new();
+        self.retain(|key, set| {
+            let (matched, kept): (HashSet<V>, HashSet<V>) = std::mem::take(set).into_iter().partition(|value| f(key, value));
+            *set = kept;
+            extracted.extend(matched.into_iter().map(|value| (key.clone(), value)));
+            !set.is_empty()
+        });
+        extracted.into_iter()
+    }
+
+    /// Exchange the set for one key with the set for another key.
+    ///
+    /// An absent key is treated as having an empty set, so afterwards both
+    /// keys are in the collection. This moves the sets without cloning any values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToSet<u8, u8> = HashMapToSet::new();
+    /// a.sub_insert(1, 2);
+    /// a.sub_swap(1, 3);
+    /// assert_eq!(a.sub_len_of(&1), 0);
+    /// assert_eq!(a.sub_contains(&3, &2), true);
+    /// ```
+    #[inline]
+    fn sub_swap(&mut self, key_a: K, key_b: K)
+    where
+        K: Hash + Eq,
+    {
+        if key_a == key_b {
+            self.entry(key_a).or_default();
+            return;
+        }
+        let set_a = self.remove(&key_a).unwrap_or_default();
+        let set_b = std::mem::replace(self.entry(key_b).or_default(), set_a);
+        self.insert(key_a, set_b);
+    }
+
+    /// Add a sub-key-value pair if it is absent, or remove it if it is present.
+    ///
+    /// Return whether the pair is in the collection afterwards.
+    /// The key is removed from the collection when its set becomes empty,
+    /// like `sub_remove_prune`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToSet<u8, u8> = HashMapToSet::new();
+    /// assert_eq!(a.sub_toggle(1, 2), true);
+    /// assert_eq!(a.sub_contains(&1, &2), true);
+    /// assert_eq!(a.sub_toggle(1, 2), false);
+    /// assert_eq!(a.contains_key(&1), false);
+    /// ```
+    #[inline]
+    fn sub_toggle(&mut self, key: K, value: V) -> bool
+    where
+        K: Hash + Eq,
+        V: Hash + Eq,
+    {
+        match self.entry(key) {
+            Entry::Vacant(entry) => {
+                entry.insert(HashSet::new()).insert(value);
+                true
+            },
+            Entry::Occupied(mut entry) => {
+                let set = entry.get_mut();
+                if set.remove(&value) {
+                    if set.is_empty() {
+                        entry.remove();
+                    }
+                    false
+                } else {
+                    set.insert(value);
+                    true
+                }
+            },
+        }
+    }
+
+    /// Return the number of distinct values in all sets in the collection.
+    ///
+    /// A value that is in the sets of several keys is counted once, unlike
+    /// `sub_len` which counts pairs. This builds a transient set of value
+    /// references, except when there is at most one key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToSet<u8, u8> = HashMapToSet::new();
+    /// a.sub_insert_many(1, vec![1, 2]);
+    /// a.sub_insert_many(2, vec![2, 3]);
+    /// assert_eq!(a.sub_len(), 4);
+    /// assert_eq!(a.distinct_value_count(), 3);
+    /// ```
+    #[inline]
+    fn distinct_value_count(&self) -> usize
+    where
+        V: Hash + Eq,
+    {
+        if self.len() <= 1 {
+            return self.sub_len();
+        }
+        self.sub_values().collect::<HashSet<&V>>().len()
+    }
+
+    /// Return `true` if this collection and another have the same sub-key-value pairs.
+    ///
+    /// This ignores keys with empty sets, unlike `==`, which compares keys
+    /// too. So two collections that differ only by keys with empty sets are
+    /// equal by `sub_eq`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToSet<u8, u8> = HashMapToSet::new();
+    /// a.sub_insert(1, 2);
+    /// let mut b = a.clone();
+    /// b.sub_insert_many(3, Vec::new());
+    /// assert_eq!(a == b, false);
+    /// assert_eq!(a.sub_eq(&b), true);
+    /// ```
+    #[inline]
+    fn sub_eq(&self, other: &HashMapToSet<K, V>) -> bool
+    where
+        K: Hash + Eq,
+        V: Hash + Eq,
+    {
+        let populated = |map: &HashMapToSet<K, V>| map.values().filter(|set| !set.is_empty()).count();
+        populated(self) == populated(other) &&
+        self.iter().all(|(key, set)| set.is_empty() || other.get(key) == Some(set))
+    }
+
+}
+
+/// A handle to the set for one key in a `HashMapToSet`.
+///
+/// This is returned by `sub_entry`. If the key is absent, then the handle
+/// holds the vacant map entry, and creates the set on the first insert.
+pub struct HashMapToSetEntry<'a, K, V> {
+    set: Option<&'a mut HashSet<V>>,
+    vacant: Option<VacantEntry<'a, K, HashSet<V>>>,
+}
+
+impl<'a, K, V> HashMapToSetEntry<'a, K, V>
+where
+    K: Hash + Eq,
+    V: Hash + Eq,
+{
+
+    /// Return `true` if the set contains the value.
+    #[inline]
+    pub fn contains<R>(&self, value: &R) -> bool
+    where
+        V: Borrow<R>,
+        R: Hash + Eq + ?Sized,
+    {
+        match &self.set {
+            Some(set) => set.contains(value),
+            None => false,
+        }
+    }
+
+    /// Add a value to the set, creating the set if needed.
+    ///
+    /// Return whether the value is added in the set.
+    #[inline]
+    pub fn insert(&mut self, value: V) -> bool {
+        self.set_mut().insert(value)
+    }
+
+    /// Remove a value from the set.
+    ///
+    /// Return whether the value was present in the set.
+    #[inline]
+    pub fn remove<R>(&mut self, value: &R) -> bool
+    where
+        V: Borrow<R>,
+        R: Hash + Eq + ?Sized,
+    {
+        match &mut self.set {
+            Some(set) => set.remove(value),
+            None => false,
+        }
+    }
+
+    /// Return the number of values in the set.
+    #[inline]
+    pub fn len(&self) -> usize {
+        match &self.set {
+            Some(set) => set.len(),
+            None => 0,
+        }
+    }
+
+    /// Return `true` if the set has no values, or does not exist yet.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Convert the handle into a mutable reference to the set, creating the set if needed.
+    #[inline]
+    pub fn into_set_mut(mut self) -> &'a mut HashSet<V> {
+        self.create();
+        self.set.expect("set")
+    }
+
+    #[inline]
+    fn set_mut(&mut self) -> &mut HashSet<V> {
+        self.create();
+        self.set.as_mut().expect("set")
+    }
+
+    #[inline]
+    fn create(&mut self) {
+        if let Some(vacant) = self.vacant.take() {
+            self.set = Some(vacant.insert(HashSet::new()));
+        }
+    }
+
+}
+
+/// An adapter that implements `Extend<(K, V)>` for a `HashMapToSet`.
+///
+/// This is returned by `sub_extender`. Each pair is added to the set for
+/// its key, like `sub_insert`.
+pub struct HashMapToSetExtender<'a, K, V> {
+    map: &'a mut HashMapToSet<K, V>,
+}
+
+impl<'a, K, V> Extend<(K, V)> for HashMapToSetExtender<'a, K, V>
+where
+    K: Hash + Eq,
+    V: Hash + Eq,
+{
+
+    #[inline]
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        self.map.sub_extend(iter);
+    }
+
+}
+
+/// A read-only view of the set for one key in a `HashMapToSet`.
+///
+/// This is returned by `sub_get`. If the key is absent, then the view is
+/// empty.
+#[derive(Debug)]
+pub struct HashMapToSetView<'a, V> {
+    set: Option<&'a HashSet<V>>,
+}
+
+impl<'a, V> HashMapToSetView<'a, V> {
+
+    /// Return `true` if the set contains the value.
+    #[inline]
+    pub fn contains<R>(&self, value: &R) -> bool
+    where
+        V: Borrow<R> + Hash + Eq,
+        R: Hash + Eq + ?Sized,
+    {
+        match self.set {
+            Some(set) => set.contains(value),
+            None => false,
+        }
+    }
+
+    /// Return the number of values in the set.
+    #[inline]
+    pub fn len(&self) -> usize {
+        match self.set {
+            Some(set) => set.len(),
+            None => 0,
+        }
+    }
+
+    /// Return `true` if the set has no values, or the key is absent.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Return an iterator over the values in the set.
+    #[inline]
+    pub fn iter(&self) -> std::iter::Flatten<std::option::IntoIter<&'a HashSet<V>>> {
+        self.set.into_iter().flatten()
+    }
+
+    /// Return the set, or `None` if the key is absent.
+    #[inline]
+    pub fn as_set(&self) -> Option<&'a HashSet<V>> {
+        self.set
+    }
+
+}
+
+impl<'a, V> Clone for HashMapToSetView<'a, V> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, V> Copy for HashMapToSetView<'a, V> {}
+
+impl<'a, V> IntoIterator for HashMapToSetView<'a, V> {
+    type Item = &'a V;
+    type IntoIter = std::iter::Flatten<std::option::IntoIter<&'a HashSet<V>>>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// A newtype of `HashMapToSet` that implements set-algebra operators.
+///
+/// The operators work on references, and return a new collection:
+///
+///   * `&a | &b` is `sub_union`.
+///
+///   * `&a & &b` is `sub_intersection`.
+///
+///   * `&a - &b` is `sub_difference`.
+///
+///   * `&a ^ &b` is `sub_symmetric_difference`.
+///
+/// The newtype derefs to the `HashMapToSet`, so every `HashMapToSetExt` method works on it.
+///
+/// # Examples
+///
+/// ```
+/// use sixarm_collections::*;
+/// let a = HashMapToSetOps(HashMapToSet::from_pairs(vec![(1, 2), (1, 3)]));
+/// let b = HashMapToSetOps(HashMapToSet::from_pairs(vec![(1, 3), (4, 5)]));
+/// assert_eq!((&a | &b).sub_len(), 3);
+/// assert_eq!((&a & &b).sub_len(), 1);
+/// assert_eq!((&a - &b).sub_len(), 1);
+/// assert_eq!((&a ^ &b).sub_len(), 2);
+/// ```
+#[derive(Debug, Clone)]
+pub struct HashMapToSetOps<K, V>(pub HashMapToSet<K, V>);
+
+impl<K, V> Default for HashMapToSetOps<K, V> {
+    fn default() -> Self {
+        HashMapToSetOps(HashMapToSet::new())
+    }
+}
+
+impl<K, V> PartialEq for HashMapToSetOps<K, V>
+where
+    K: Hash + Eq,
+    V: Hash + Eq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<K, V> Eq for HashMapToSetOps<K, V>
+where
+    K: Hash + Eq,
+    V: Hash + Eq,
+{}
+
+impl<K, V> Deref for HashMapToSetOps<K, V> {
+    type Target = HashMapToSet<K, V>;
+
+    fn deref(&self) -> &HashMapToSet<K, V> {
+        &self.0
+    }
+}
+
+impl<K, V> DerefMut for HashMapToSetOps<K, V> {
+    fn deref_mut(&mut self) -> &mut HashMapToSet<K, V> {
+        &mut self.0
+    }
+}
+
+impl<K, V> From<HashMapToSet<K, V>> for HashMapToSetOps<K, V> {
+    fn from(map: HashMapToSet<K, V>) -> Self {
+        HashMapToSetOps(map)
+    }
+}
+
+impl<K, V> BitOr<&HashMapToSetOps<K, V>> for &HashMapToSetOps<K, V>
+where
+    K: Clone + Hash + Eq,
+    V: Clone + Hash + Eq,
+{
+    type Output = HashMapToSetOps<K, V>;
+
+    /// Return the keywise union, like `sub_union`.
+    #[inline]
+    fn bitor(self, other: &HashMapToSetOps<K, V>) -> HashMapToSetOps<K, V> {
+        HashMapToSetOps(self.0.sub_union(&other.0))
+    }
+}
+
+impl<K, V> BitAnd<&HashMapToSetOps<K, V>> for &HashMapToSetOps<K, V>
+where
+    K: Clone + Hash + Eq,
+    V: Clone + Hash + Eq,
+{
+    type Output = HashMapToSetOps<K, V>;
+
+    /// Return the keywise intersection, like `sub_intersection`.
+    #[inline]
+    fn bitand(self, other: &HashMapToSetOps<K, V>) -> HashMapToSetOps<K, V> {
+        HashMapToSetOps(self.0.sub_intersection(&other.0))
+    }
+}
+
+impl<K, V> Sub<&HashMapToSetOps<K, V>> for &HashMapToSetOps<K, V>
+where
+    K: Clone + Hash + Eq,
+    V: Clone + Hash + Eq,
+{
+    type Output = HashMapToSetOps<K, V>;
+
+    /// Return the difference, like `sub_difference`.
+    #[inline]
+    fn sub(self, other: &HashMapToSetOps<K, V>) -> HashMapToSetOps<K, V> {
+        HashMapToSetOps(self.0.sub_difference(&other.0))
+    }
+}
+
+impl<K, V> BitXor<&HashMapToSetOps<K, V>> for &HashMapToSetOps<K, V>
+where
+    K: Clone + Hash + Eq,
+    V: Clone + Hash + Eq,
+{
+    type Output = HashMapToSetOps<K, V>;
+
+    /// Return the symmetric difference, like `sub_symmetric_difference`.
+    #[inline]
+    fn bitxor(self, other: &HashMapToSetOps<K, V>) -> HashMapToSetOps<K, V> {
+        HashMapToSetOps(self.0.sub_symmetric_difference(&other.0))
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+    use sixarm_assert::*;
+    use crate::hash_map_to_set::HashMapToSet;
+
+    #[test]
+    /// Test `sub_contains` with some items.
+    fn test_sub_contains() {
+        let mut subject: HashMapToSet<u8, u8> = HashMapToSet::new();
+        let k = 1;
+        let v = 2;
+        let absent = 3;
+        assert_eq!(subject.sub_insert(k, v), true);
+        assert_eq!(subject.sub_contains(&k, &v), true);
+        assert_eq!(subject.sub_contains(&k, &absent), false);
+        assert_eq!(subject.sub_contains(&absent, &v), false);
+    }
+    #[test]
+    /// Test `sub_insert` with some items.
+    fn test_sub_insert() {
+        let mut subject: HashMapToSet<u8, u8> = HashMapToSet::new();
+        let k1 = 1;
+        let k2 = 2;
+        let v1 = 3;
+        let v2 = 4;
+        let v3 = 5;
+        let v4 = 7;
+        // Item 1
+        assert_eq!(subject.sub_insert(k1, v1), true);
+        //assert_set_eq!(subject.keys(), [k1]);
+        assert_set_eq!(subject.get(&k1).unwrap(), [v1]);
+        // Item 2
+        assert_eq!(subject.sub_insert(k1, v2), true);
+        //assert_set_eq!(subject.keys(), [k1]);  
+        assert_set_eq!(subject.get(&k1).unwrap(), [v1, v2]);
+        // Item 3
+        assert_eq!(subject.sub_insert(k2, v3), true);
+        //assert_set_eq!(subject.keys(), [k1, k2]);  
+        assert_set_eq!(subject.get(&k1).unwrap(), [v1, v2]);
+        assert_set_eq!(subject.get(&k2).unwrap(), [v3]);
+        // Item 4
+        assert_eq!(subject.sub_insert(k2, v4), true);
+        //assert_set_eq!(subject.keys(), [k1, k2]);
+        assert_set_eq!(subject.get(&k1).unwrap(), [v1, v2]);
+        assert_set_eq!(subject.get(&k2).unwrap(), [v3, v4]);
+    }
+
+    #[test]
+    /// Test `remove` with a present item.
+    fn test_sub_remove_x_present_item() {
+        let mut subject: HashMapToSet<u8, u8> = HashMapToSet::new();
+        let k = 1;
+        let v = 2;
+        assert_eq!(subject.sub_insert(k, v), true);
+        assert_eq!(subject.sub_contains(&k, &v), true);
+        assert_eq!(subject.sub_remove(&k, &v), true);
+        assert_eq!(subject.sub_contains(&k, &v), false);
+    }
+
+    #[test]
+    /// Test `remove` with an absent item.
+    fn test_sub_remove_x_absent_item() {
+        let mut subject: HashMapToSet<u8, u8> = HashMapToSet::new();
+        let k = 1;
+        let v = 2;
+        let z = 3;
+        assert_eq!(subject.sub_remove(&k, &z), false);
+        assert_eq!(subject.sub_remove(&z, &v), false);
+    }
+
+    #[test]
+    /// Test `sub_iter` with some items.
+    fn test_sub_iter() {
+        let mut subject: HashMapToSet<u8, u8> = HashMapToSet::new();
+        let k1 = 1;
+        let k2 = 2;
+        let v1 = 3;
+        let v2 = 4;
+        let v3 = 5;
+        subject.sub_insert(k1, v1);
+        subject.sub_insert(k1, v2);
+        subject.sub_insert(k2, v3);
+        let mut actual: Vec<(u8, u8)> = subject.sub_iter().map(|(k, v)| (*k, *v)).collect();
+        actual.sort();
+        assert_eq!(actual, vec![(k1, v1), (k1, v2), (k2, v3)]);
+    }
+
+    #[test]
+    /// Test `sub_iter` with no items.
+    fn test_sub_iter_x_empty() {
+        let subject: HashMapToSet<u8, u8> = HashMapToSet::new();
+        assert_eq!(subject.sub_iter().next(), None);
+    }
+
+    #[test]
+    /// Test `into_sub_iter` with some items.
+    fn test_into_sub_iter() {
+        let mut subject: HashMapToSet<u8, u8> = HashMapToSet::new();
+        let k1 = 1;
+        let k2 = 2;
+        let v1 = 3;
+        let v2 = 4;
+        let v3 = 5;
+        subject.sub_insert(k1, v1);
+        subject.sub_insert(k1, v2);
+        subject.sub_insert(k2, v3);
+        let mut actual: Vec<(u8, u8)> = subject.into_sub_iter().collect();
+        actual.sort();
+        assert_eq!(actual, vec![(k1, v1), (k1, v2), (k2, v3)]);
+    }
+
+    #[test]
+    /// Test `sub_len` with some items.
+    fn test_sub_len() {
+        let mut subject: HashMapToSet<u8, u8> = HashMapToSet::new();
+        assert_eq!(subject.sub_len(), 0);
+        subject.sub_insert(1, 2);
+        assert_eq!(subject.sub_len(), 1);
+        subject.sub_insert(1, 3);
+        assert_eq!(subject.sub_len(), 2);
+        subject.sub_insert(4, 5);
+        assert_eq!(subject.sub_len(), 3);
+        subject.sub_remove(&1, &2);
+        assert_eq!(subject.sub_len(), 2);
+    }
+
+    #[test]
+    /// Test `sub_len_of` with present and absent keys.
+    fn test_sub_len_of() {
+        let mut subject: HashMapToSet<u8, u8> = HashMapToSet::new();
+        let k = 1;
+        let absent = 9;
+        assert_eq!(subject.sub_len_of(&k), 0);
+        subject.sub_insert(k, 2);
+        subject.sub_insert(k, 3);
+        assert_eq!(subject.sub_len_of(&k), 2);
+        assert_eq!(subject.sub_len_of(&absent), 0);
+    }
+
+    #[test]
+    /// Test `sub_key_is_populated` with a present key, an emptied key, and an absent key.
+    fn test_sub_key_is_populated() {
+        let mut subject: HashMapToSet<u8, u8> = HashMapToSet::new();
+        let k = 1;
+        let v = 2;
+        let absent = 9;
+        subject.sub_insert(k, v);
+        assert_eq!(subject.sub_key_is_populated(&k), true);
+        subject.sub_remove(&k, &v);
+        assert_eq!(subject.sub_key_is_populated(&k), false);
+        assert_eq!(subject.sub_key_is_populated(&absent), false);
+    }
+
+    #[test]
+    /// Test `sub_remove_prune` with a present item that is the last in its set.
+    fn test_sub_remove_prune_x_last_item() {
+        let mut subject: HashMapToSet<u8, u8> = HashMapToSet::new();
+        let k = 1;
+        let v1 = 2;
+        let v2 = 3;
+        subject.sub_insert(k, v1);
+        subject.sub_insert(k, v2);
+        assert_eq!(subject.sub_remove_prune(&k, &v1), true);
+        assert_eq!(subject.contains_key(&k), true);
+        assert_eq!(subject.sub_remove_prune(&k, &v2), true);
+        assert_eq!(subject.contains_key(&k), false);
+        assert_eq!(subject.len(), 0);
+    }
+
+    #[test]
+    /// Test `sub_remove_prune` with an absent item.
+    fn test_sub_remove_prune_x_absent_item() {
+        let mut subject: HashMapToSet<u8, u8> = HashMapToSet::new();
+        let k = 1;
+        let v = 2;
+        let z = 3;
+        subject.sub_insert(k, v);
+        assert_eq!(subject.sub_remove_prune(&k, &z), false);
+        assert_eq!(subject.sub_remove_prune(&z, &v), false);
+        assert_eq!(subject.sub_contains(&k, &v), true);
+    }
+
+    #[test]
+    /// Test `sub_extend` with new items and duplicate items.
+    fn test_sub_extend() {
+        let mut subject: HashMapToSet<u8, u8> = HashMapToSet::new();
+        let k1 = 1;
+        let k2 = 2;
+        let v1 = 3;
+        let v2 = 4;
+        assert_eq!(subject.sub_extend(vec![(k1, v1), (k1, v2), (k2, v1)]), 3);
+        assert_set_eq!(subject.get(&k1).unwrap(), [v1, v2]);
+        assert_set_eq!(subject.get(&k2).unwrap(), [v1]);
+        assert_eq!(subject.sub_extend(vec![(k1, v1), (k2, v2)]), 1);
+        assert_set_eq!(subject.get(&k2).unwrap(), [v1, v2]);
+    }
+
+    #[test]
+    /// Test `sub_entry` with an absent key, which must not create a set until an insert.
+    fn test_sub_entry_x_absent_key() {
+        let mut subject: HashMapToSet<u8, u8> = HashMapToSet::new();
+        let k = 1;
+        let v = 2;
+        {
+            let mut entry = subject.sub_entry(k);
+            assert_eq!(entry.is_empty(), true);
+            assert_eq!(entry.contains(&v), false);
+            assert_eq!(entry.remove(&v), false);
+        }
+        assert_eq!(subject.contains_key(&k), false);
+        {
+            let mut entry = subject.sub_entry(k);
+            assert_eq!(entry.insert(v), true);
+            assert_eq!(entry.insert(v), false);
+            assert_eq!(entry.contains(&v), true);
+        }
+        assert_set_eq!(subject.get(&k).unwrap(), [v]);
+    }
+
+    #[test]
+    /// Test `sub_entry` with a present key.
+    fn test_sub_entry_x_present_key() {
+        let mut subject: HashMapToSet<u8, u8> = HashMapToSet::new();
+        let k = 1;
+        let v1 = 2;
+        let v2 = 3;
+        subject.sub_insert(k, v1);
+        let mut entry = subject.sub_entry(k);
+        assert_eq!(entry.contains(&v1), true);
+        assert_eq!(entry.insert(v2), true);
+        assert_eq!(entry.remove(&v1), true);
+        assert_eq!(entry.len(), 1);
+        let set = entry.into_set_mut();
+        set.insert(v1);
+        assert_set_eq!(subject.get(&k).unwrap(), [v1, v2]);
+    }
+
+    #[test]
+    /// Test `sub_insert_many` with new values and duplicate values.
+    fn test_sub_insert_many() {
+        let mut subject: HashMapToSet<u8, u8> = HashMapToSet::new();
+        let k = 1;
+        let v1 = 2;
+        let v2 = 3;
+        let v3 = 4;
+        assert_eq!(subject.sub_insert_many(k, vec![v1, v2, v1]), 2);
+        assert_set_eq!(subject.get(&k).unwrap(), [v1, v2]);
+        assert_eq!(subject.sub_insert_many(k, vec![v2, v3]), 1);
+        assert_set_eq!(subject.get(&k).unwrap(), [v1, v2, v3]);
+    }
+
+    #[test]
+    /// Test `sub_insert_many` with no values, which creates an empty set.
+    fn test_sub_insert_many_x_empty() {
+        let mut subject: HashMapToSet<u8, u8> = HashMapToSet::new();
+        let k = 1;
+        assert_eq!(subject.sub_insert_many(k, Vec::new()), 0);
+        assert_eq!(subject.contains_key(&k), true);
+        assert_eq!(subject.sub_len_of(&k), 0);
+    }
+
+    #[test]
+    /// Test `sub_take` with a present item and an absent item.
+    fn test_sub_take() {
+        let mut subject: HashMapToSet<u8, String> = HashMapToSet::new();
+        let k = 1;
+        let absent = 2;
+        let v = String::from("alpha");
+        subject.sub_insert(k, v.clone());
+        assert_eq!(subject.sub_take(&absent, &v), None);
+        assert_eq!(subject.sub_take(&k, &v), Some(v.clone()));
+        assert_eq!(subject.sub_take(&k, &v), None);
+        assert_eq!(subject.sub_contains(&k, &v), false);
+    }
+
+    #[test]
+    /// Test `sub_contains`, `sub_remove`, and `sub_len_of` with borrowed forms of the key and value.
+    fn test_sub_x_borrowed_forms() {
+        let mut subject: HashMapToSet<String, String> = HashMapToSet::new();
+        subject.sub_insert(String::from("alpha"), String::from("bravo"));
+        subject.sub_insert(String::from("alpha"), String::from("charlie"));
+        assert_eq!(subject.sub_contains("alpha", "bravo"), true);
+        assert_eq!(subject.sub_contains("alpha", "delta"), false);
+        assert_eq!(subject.sub_len_of("alpha"), 2);
+        assert_eq!(subject.sub_key_is_populated("alpha"), true);
+        assert_eq!(subject.sub_remove("alpha", "bravo"), true);
+        assert_eq!(subject.sub_take("alpha", "charlie"), Some(String::from("charlie")));
+        assert_eq!(subject.sub_remove_prune("alpha", "charlie"), false);
+        assert_eq!(subject.sub_entry(String::from("alpha")).contains("charlie"), false);
+    }
+
+    #[test]
+    /// Test `sub_move` with a present item and an absent item.
+    fn test_sub_move() {
+        let mut subject: HashMapToSet<u8, u8> = HashMapToSet::new();
+        let k1 = 1;
+        let k2 = 2;
+        let v = 3;
+        let absent = 4;
+        subject.sub_insert(k1, v);
+        assert_eq!(subject.sub_move(&k1, k2, &v), true);
+        assert_eq!(subject.sub_contains(&k1, &v), false);
+        assert_eq!(subject.sub_contains(&k2, &v), true);
+        assert_eq!(subject.sub_move(&k1, k2, &v), false);
+        assert_eq!(subject.sub_move(&k2, k1, &absent), false);
+        assert_eq!(subject.contains_key(&k1), true);
+        assert_set_eq!(subject.get(&k2).unwrap(), [v]);
+    }
+
+    #[test]
+    /// Test `sub_move` with the same key for source and destination.
+    fn test_sub_move_x_same_key() {
+        let mut subject: HashMapToSet<u8, u8> = HashMapToSet::new();
+        let k = 1;
+        let v = 2;
+        subject.sub_insert(k, v);
+        assert_eq!(subject.sub_move(&k, k, &v), true);
+        assert_set_eq!(subject.get(&k).unwrap(), [v]);
+    }
+
+    #[test]
+    /// Test `sub_drain` with some items.
+    fn test_sub_drain() {
+        let mut subject: HashMapToSet<u8, u8> = HashMapToSet::new();
+        let k1 = 1;
+        let k2 = 2;
+        let v1 = 3;
+        let v2 = 4;
+        subject.sub_insert(k1, v1);
+        subject.sub_insert(k1, v2);
+        subject.sub_insert(k2, v1);
+        let mut actual: Vec<(u8, u8)> = subject.sub_drain().collect();
+        actual.sort();
+        assert_eq!(actual, vec![(k1, v1), (k1, v2), (k2, v1)]);
+        assert_eq!(subject.len(), 0);
+    }
+
+    #[test]
+    /// Test `sub_drain_key` with a present key and an absent key.
+    fn test_sub_drain_key() {
+        let mut subject: HashMapToSet<u8, u8> = HashMapToSet::new();
+        let k1 = 1;
+        let k2 = 2;
+        let absent = 9;
+        let v1 = 3;
+        let v2 = 4;
+        subject.sub_insert(k1, v1);
+        subject.sub_insert(k1, v2);
+        subject.sub_insert(k2, v1);
+        let mut actual: Vec<u8> = subject.sub_drain_key(&k1).collect();
+        actual.sort();
+        assert_eq!(actual, vec![v1, v2]);
+        assert_eq!(subject.sub_len_of(&k1), 0);
+        assert_set_eq!(subject.get(&k2).unwrap(), [v1]);
+        assert_eq!(subject.sub_drain_key(&absent).count(), 0);
+    }
+
+    #[test]
+    /// Test `sub_keys_with_value` with a present value and an absent value.
+    fn test_sub_keys_with_value() {
+        let mut subject: HashMapToSet<u8, u8> = HashMapToSet::new();
+        let k1 = 1;
+        let k2 = 2;
+        let k3 = 3;
+        let v1 = 4;
+        let v2 = 5;
+        let absent = 6;
+        subject.sub_insert(k1, v1);
+        subject.sub_insert(k2, v1);
+        subject.sub_insert(k2, v2);
+        subject.sub_insert(k3, v2);
+        let mut actual: Vec<u8> = subject.sub_keys_with_value(&v1).cloned().collect();
+        actual.sort();
+        assert_eq!(actual, vec![k1, k2]);
+        assert_eq!(subject.sub_keys_with_value(&absent).count(), 0);
+    }
+
+    #[test]
+    /// Test `sub_values` with values in several keys.
+    fn test_sub_values() {
+        let mut subject: HashMapToSet<u8, u8> = HashMapToSet::new();
+        let k1 = 1;
+        let k2 = 2;
+        let v1 = 3;
+        let v2 = 4;
+        subject.sub_insert(k1, v1);
+        subject.sub_insert(k2, v1);
+        subject.sub_insert(k2, v2);
+        let mut actual: Vec<u8> = subject.sub_values().cloned().collect();
+        actual.sort();
+        assert_eq!(actual, vec![v1, v1, v2]);
+    }
+
+    #[test]
+    /// Test `sub_values_distinct` with values in several keys.
+    fn test_sub_values_distinct() {
+        let mut subject: HashMapToSet<u8, u8> = HashMapToSet::new();
+        let k1 = 1;
+        let k2 = 2;
+        let v1 = 3;
+        let v2 = 4;
+        subject.sub_insert(k1, v1);
+        subject.sub_insert(k2, v1);
+        subject.sub_insert(k2, v2);
+        let mut actual: Vec<u8> = subject.sub_values_distinct().cloned().collect();
+        actual.sort();
+        assert_eq!(actual, vec![v1, v2]);
+    }
+
+    #[test]
+    /// Test `sub_counts` with some items, including an emptied key.
+    fn test_sub_counts() {
+        let mut subject: HashMapToSet<u8, u8> = HashMapToSet::new();
+        let k1 = 1;
+        let k2 = 2;
+        let v1 = 3;
+        let v2 = 4;
+        subject.sub_insert(k1, v1);
+        subject.sub_insert(k1, v2);
+        subject.sub_insert(k2, v1);
+        subject.sub_remove(&k2, &v1);
+        let actual = subject.sub_counts();
+        assert_eq!(actual.len(), 2);
+        assert_eq!(actual[&k1], 2);
+        assert_eq!(actual[&k2], 0);
+    }
+
+    #[test]
+    /// Test `sub_merge` with each strategy.
+    fn test_sub_merge() {
+        let k1 = 1;
+        let k2 = 2;
+        let k3 = 3;
+        let v1 = 4;
+        let v2 = 5;
+        let v3 = 6;
+        let mut x: HashMapToSet<u8, u8> = HashMapToSet::new();
+        x.sub_insert(k1, v1);
+        x.sub_insert(k2, v1);
+        let mut y: HashMapToSet<u8, u8> = HashMapToSet::new();
+        y.sub_insert(k2, v2);
+        y.sub_insert(k2, v3);
+        y.sub_insert(k3, v3);
+        // Union
+        let mut subject = x.clone();
+        subject.sub_merge(y.clone(), MergeStrategy::Union);
+        assert_set_eq!(subject.get(&k1).unwrap(), [v1]);
+        assert_set_eq!(subject.get(&k2).unwrap(), [v1, v2, v3]);
+        assert_set_eq!(subject.get(&k3).unwrap(), [v3]);
+        // Keep self
+        let mut subject = x.clone();
+        subject.sub_merge(y.clone(), MergeStrategy::KeepSelf);
+        assert_set_eq!(subject.get(&k2).unwrap(), [v1]);
+        assert_set_eq!(subject.get(&k3).unwrap(), [v3]);
+        // Keep other
+        let mut subject = x.clone();
+        subject.sub_merge(y.clone(), MergeStrategy::KeepOther);
+        assert_set_eq!(subject.get(&k2).unwrap(), [v2, v3]);
+        assert_set_eq!(subject.get(&k3).unwrap(), [v3]);
+    }
+
+    #[test]
+    /// Test `sub_union` with overlapping keys and values.
+    fn test_sub_union() {
+        let k1 = 1;
+        let k2 = 2;
+        let k3 = 3;
+        let v1 = 4;
+        let v2 = 5;
+        let mut x: HashMapToSet<u8, u8> = HashMapToSet::new();
+        x.sub_insert(k1, v1);
+        x.sub_insert(k2, v1);
+        let mut y: HashMapToSet<u8, u8> = HashMapToSet::new();
+        y.sub_insert(k2, v1);
+        y.sub_insert(k2, v2);
+        y.sub_insert(k3, v2);
+        let subject = x.sub_union(&y);
+        assert_eq!(subject.len(), 3);
+        assert_set_eq!(subject.get(&k1).unwrap(), [v1]);
+        assert_set_eq!(subject.get(&k2).unwrap(), [v1, v2]);
+        assert_set_eq!(subject.get(&k3).unwrap(), [v2]);
+        assert_eq!(x.sub_len(), 2);
+        assert_eq!(y.sub_len(), 3);
+    }
+
+    #[test]
+    /// Test `sub_intersection` with overlapping keys and values.
+    fn test_sub_intersection() {
+        let k1 = 1;
+        let k2 = 2;
+        let k3 = 3;
+        let v1 = 4;
+        let v2 = 5;
+        let v3 = 6;
+        let mut x: HashMapToSet<u8, u8> = HashMapToSet::new();
+        x.sub_insert(k1, v1);
+        x.sub_insert(k2, v1);
+        x.sub_insert(k2, v2);
+        x.sub_insert(k3, v1);
+        let mut y: HashMapToSet<u8, u8> = HashMapToSet::new();
+        y.sub_insert(k2, v2);
+        y.sub_insert(k2, v3);
+        y.sub_insert(k3, v3);
+        let subject = x.sub_intersection(&y);
+        assert_eq!(subject.len(), 1);
+        assert_set_eq!(subject.get(&k2).unwrap(), [v2]);
+    }
+
+    #[test]
+    /// Test `sub_difference` with overlapping keys and values.
+    fn test_sub_difference() {
+        let k1 = 1;
+        let k2 = 2;
+        let k3 = 3;
+        let v1 = 4;
+        let v2 = 5;
+        let v3 = 6;
+        let mut x: HashMapToSet<u8, u8> = HashMapToSet::new();
+        x.sub_insert(k1, v1);
+        x.sub_insert(k2, v1);
+        x.sub_insert(k2, v2);
+        x.sub_insert(k3, v3);
+        let mut y: HashMapToSet<u8, u8> = HashMapToSet::new();
+        y.sub_insert(k2, v2);
+        y.sub_insert(k3, v3);
+        let subject = x.sub_difference(&y);
+        assert_eq!(subject.len(), 2);
+        assert_set_eq!(subject.get(&k1).unwrap(), [v1]);
+        assert_set_eq!(subject.get(&k2).unwrap(), [v1]);
+    }
+
+    #[test]
+    /// Test `sub_symmetric_difference` with overlapping keys and values.
+    fn test_sub_symmetric_difference() {
+        let k1 = 1;
+        let k2 = 2;
+        let k3 = 3;
+        let v1 = 4;
+        let v2 = 5;
+        let v3 = 6;
+        let mut x: HashMapToSet<u8, u8> = HashMapToSet::new();
+        x.sub_insert(k1, v1);
+        x.sub_insert(k2, v1);
+        x.sub_insert(k2, v2);
+        let mut y: HashMapToSet<u8, u8> = HashMapToSet::new();
+        y.sub_insert(k1, v1);
+        y.sub_insert(k2, v2);
+        y.sub_insert(k2, v3);
+        y.sub_insert(k3, v3);
+        let subject = x.sub_symmetric_difference(&y);
+        assert_eq!(subject.len(), 2);
+        assert_set_eq!(subject.get(&k2).unwrap(), [v1, v3]);
+        assert_set_eq!(subject.get(&k3).unwrap(), [v3]);
+        assert_eq!(subject, y.sub_symmetric_difference(&x));
+    }
+
+    #[test]
+    /// Test `is_sub_multimap` and `is_super_multimap` with subsets, supersets, and empty sets.
+    fn test_is_sub_multimap_and_is_super_multimap() {
+        let k1 = 1;
+        let k2 = 2;
+        let v1 = 3;
+        let v2 = 4;
+        let mut x: HashMapToSet<u8, u8> = HashMapToSet::new();
+        x.sub_insert(k1, v1);
+        let mut y: HashMapToSet<u8, u8> = HashMapToSet::new();
+        y.sub_insert(k1, v1);
+        y.sub_insert(k1, v2);
+        y.sub_insert(k2, v1);
+        assert_eq!(x.is_sub_multimap(&y), true);
+        assert_eq!(y.is_sub_multimap(&x), false);
+        assert_eq!(y.is_super_multimap(&x), true);
+        assert_eq!(x.is_super_multimap(&y), false);
+        assert_eq!(x.is_sub_multimap(&x), true);
+        // A key with an empty set contributes no pairs.
+        x.sub_insert_many(k2 + 10, Vec::new());
+        assert_eq!(x.is_sub_multimap(&y), true);
+    }
+
+    #[test]
+    /// Test `invert` and `into_invert` with some items, including an emptied key.
+    fn test_invert_and_into_invert() {
+        let k1 = 1;
+        let k2 = 2;
+        let k3 = 3;
+        let v1 = 4;
+        let v2 = 5;
+        let mut subject: HashMapToSet<u8, u8> = HashMapToSet::new();
+        subject.sub_insert(k1, v1);
+        subject.sub_insert(k2, v1);
+        subject.sub_insert(k2, v2);
+        subject.sub_insert(k3, v2);
+        subject.sub_remove(&k3, &v2);
+        let inverted = subject.invert();
+        assert_eq!(inverted.len(), 2);
+        assert_set_eq!(inverted.get(&v1).unwrap(), [k1, k2]);
+        assert_set_eq!(inverted.get(&v2).unwrap(), [k2]);
+        assert_eq!(subject.into_invert(), inverted);
+    }
+
+    #[test]
+    /// Test `prune_empty` with some emptied keys.
+    fn test_prune_empty() {
+        let k1 = 1;
+        let k2 = 2;
+        let k3 = 3;
+        let v = 4;
+        let mut subject: HashMapToSet<u8, u8> = HashMapToSet::new();
+        subject.sub_insert(k1, v);
+        subject.sub_insert(k2, v);
+        subject.sub_insert(k3, v);
+        subject.sub_remove(&k1, &v);
+        subject.sub_remove(&k3, &v);
+        assert_eq!(subject.prune_empty(), 2);
+        assert_eq!(subject.len(), 1);
+        assert_set_eq!(subject.get(&k2).unwrap(), [v]);
+        assert_eq!(subject.prune_empty(), 0);
+    }
+
+    #[test]
+    /// Test `sub_replace` with a present key and an absent key.
+    fn test_sub_replace() {
+        let k1 = 1;
+        let k2 = 2;
+        let v1 = 3;
+        let v2 = 4;
         let mut subject: HashMapToSet<u8, u8> = HashMapToSet::new();
+        subject.sub_insert(k1, v1);
+        let old = subject.sub_replace(k1, [v2].iter().cloned().collect());
+        assert_set_eq!(old.unwrap(), [v1]);
+        assert_set_eq!(subject.get(&k1).unwrap(), [v2]);
+        assert_eq!(subject.sub_replace(k2, HashSet::new()), None);
+        assert_eq!(subject.contains_key(&k2), true);
+    }
+
+    #[test]
+    /// Test `from_pairs` with duplicate pairs.
+    fn test_from_pairs() {
+        let k1 = 1;
+        let k2 = 2;
+        let v1 = 3;
+        let v2 = 4;
+        let subject: HashMapToSet<u8, u8> = HashMapToSet::from_pairs(vec![(k1, v1), (k1, v2), (k2, v1), (k1, v1)]);
+        assert_eq!(subject.len(), 2);
+        assert_set_eq!(subject.get(&k1).unwrap(), [v1, v2]);
+        assert_set_eq!(subject.get(&k2).unwrap(), [v1]);
+    }
+
+    #[test]
+    /// Test `sub_extender` with generic code that is bounded by `Extend`.
+    fn test_sub_extender() {
+        fn load<E: Extend<(u8, u8)>>(e: &mut E, pairs: Vec<(u8, u8)>) {
+            e.extend(pairs);
+        }
         let k = 1;
-        let v = 2;
-        let z = 3;
-        assert_eq!(subject.sub_remove(&k, &z), false);
-        assert_eq!(subject.sub_remove(&z, &v), false);
+        let v1 = 2;
+        let v2 = 3;
+        let mut subject: HashMapToSet<u8, u8> = HashMapToSet::new();
+        {
+            let mut extender = subject.sub_extender();
+            load(&mut extender, vec![(k, v1)]);
+            load(&mut extender, vec![(k, v2)]);
+        }
+        assert_set_eq!(subject.get(&k).unwrap(), [v1, v2]);
+    }
+
+    #[test]
+    /// Test `from_grouping` with a key function.
+    fn test_from_grouping() {
+        let subject: HashMapToSet<u8, u8> = HashMapToSet::from_grouping(vec![1, 2, 3, 4, 5, 5], |v| v % 2);
+        assert_eq!(subject.len(), 2);
+        assert_set_eq!(subject.get(&0).unwrap(), [2, 4]);
+        assert_set_eq!(subject.get(&1).unwrap(), [1, 3, 5]);
+    }
+
+    #[test]
+    /// Test `into_pairs` with some items.
+    fn test_into_pairs() {
+        let k1 = 1;
+        let k2 = 2;
+        let v1 = 3;
+        let v2 = 4;
+        let mut subject: HashMapToSet<u8, u8> = HashMapToSet::new();
+        subject.sub_insert(k1, v1);
+        subject.sub_insert(k1, v2);
+        subject.sub_insert(k2, v1);
+        let mut actual = subject.into_pairs();
+        actual.sort();
+        assert_eq!(actual, vec![(k1, v1), (k1, v2), (k2, v1)]);
+    }
+
+    #[test]
+    /// Test `into_groups` with some items.
+    fn test_into_groups() {
+        let k1 = 1;
+        let k2 = 2;
+        let v1 = 3;
+        let v2 = 4;
+        let mut subject: HashMapToSet<u8, u8> = HashMapToSet::new();
+        subject.sub_insert(k1, v1);
+        subject.sub_insert(k1, v2);
+        subject.sub_insert(k2, v1);
+        let mut actual = subject.into_groups();
+        actual.sort_by_key(|(key, _)| *key);
+        assert_eq!(actual.len(), 2);
+        assert_eq!(actual[0].0, k1);
+        assert_set_eq!(actual[0].1, [v1, v2]);
+        assert_eq!(actual[1].0, k2);
+        assert_set_eq!(actual[1].1, [v1]);
+    }
+
+    #[test]
+    /// Test `sub_pop` until the key is pruned.
+    fn test_sub_pop() {
+        let k = 1;
+        let absent = 2;
+        let v1 = 3;
+        let v2 = 4;
+        let mut subject: HashMapToSet<u8, u8> = HashMapToSet::new();
+        subject.sub_insert(k, v1);
+        subject.sub_insert(k, v2);
+        let mut actual = vec![subject.sub_pop(&k).unwrap(), subject.sub_pop(&k).unwrap()];
+        actual.sort();
+        assert_eq!(actual, vec![v1, v2]);
+        assert_eq!(subject.contains_key(&k), false);
+        assert_eq!(subject.sub_pop(&k), None);
+        assert_eq!(subject.sub_pop(&absent), None);
+    }
+
+    #[test]
+    /// Test `top_keys_by_count` with more keys than `n`, fewer keys than `n`, and zero.
+    fn test_top_keys_by_count() {
+        let mut subject: HashMapToSet<u8, u8> = HashMapToSet::new();
+        subject.sub_insert_many(1, vec![1, 2]);
+        subject.sub_insert_many(2, vec![1, 2, 3, 4]);
+        subject.sub_insert_many(3, vec![1]);
+        subject.sub_insert_many(4, vec![1, 2, 3]);
+        assert_eq!(subject.top_keys_by_count(2), vec![(&2, 4), (&4, 3)]);
+        assert_eq!(subject.top_keys_by_count(9), vec![(&2, 4), (&4, 3), (&1, 2), (&3, 1)]);
+        assert_eq!(subject.top_keys_by_count(0), vec![]);
+    }
+
+    #[test]
+    /// Test `sub_get_or_insert_with` calls the factory only for an absent key.
+    fn test_sub_get_or_insert_with() {
+        let k = 1;
+        let v1 = 2;
+        let v2 = 3;
+        let mut subject: HashMapToSet<u8, u8> = HashMapToSet::new();
+        let mut calls = 0;
+        subject.sub_get_or_insert_with(k, || { calls += 1; HashSet::new() }).insert(v1);
+        subject.sub_get_or_insert_with(k, || { calls += 1; HashSet::new() }).insert(v2);
+        assert_eq!(calls, 1);
+        assert_set_eq!(subject.get(&k).unwrap(), [v1, v2]);
+    }
+
+    #[test]
+    /// Test `sub_get` with a present key and an absent key.
+    fn test_sub_get() {
+        let k = 1;
+        let absent = 2;
+        let v1 = 3;
+        let v2 = 4;
+        let mut subject: HashMapToSet<u8, u8> = HashMapToSet::new();
+        subject.sub_insert(k, v1);
+        subject.sub_insert(k, v2);
+        let view = subject.sub_get(&k);
+        assert_eq!(view.len(), 2);
+        assert_eq!(view.contains(&v1), true);
+        assert_eq!(view.as_set(), subject.get(&k));
+        let mut actual: Vec<u8> = view.into_iter().cloned().collect();
+        actual.sort();
+        assert_eq!(actual, vec![v1, v2]);
+        let view = subject.sub_get(&absent);
+        assert_eq!(view.is_empty(), true);
+        assert_eq!(view.contains(&v1), false);
+        assert_eq!(view.as_set(), None);
+        assert_eq!(view.iter().count(), 0);
+    }
+
+    #[test]
+    /// Test `sub_overlap_count` with overlapping, disjoint, and absent keys.
+    fn test_sub_overlap_count() {
+        let mut subject: HashMapToSet<u8, u8> = HashMapToSet::new();
+        subject.sub_insert_many(1, vec![1, 2, 3, 4]);
+        subject.sub_insert_many(2, vec![3, 4, 5]);
+        subject.sub_insert_many(3, vec![9]);
+        assert_eq!(subject.sub_overlap_count(&1, &2), 2);
+        assert_eq!(subject.sub_overlap_count(&2, &1), 2);
+        assert_eq!(subject.sub_overlap_count(&1, &3), 0);
+        assert_eq!(subject.sub_overlap_count(&1, &9), 0);
+    }
+
+    #[test]
+    /// Test `sub_similarity` with overlapping, disjoint, equal, and empty sets.
+    fn test_sub_similarity() {
+        let mut subject: HashMapToSet<u8, u8> = HashMapToSet::new();
+        subject.sub_insert_many(1, vec![1, 2, 3, 4]);
+        subject.sub_insert_many(2, vec![3, 4, 5]);
+        subject.sub_insert_many(3, vec![9]);
+        assert_eq!(subject.sub_similarity(&1, &2), 0.4);
+        assert_eq!(subject.sub_similarity(&1, &3), 0.0);
+        assert_eq!(subject.sub_similarity(&2, &2), 1.0);
+        assert_eq!(subject.sub_similarity(&8, &9), 0.0);
+    }
+
+    #[test]
+    /// Test `sub_contains_all` with present, absent, and empty values, and an absent key.
+    fn test_sub_contains_all() {
+        let mut subject: HashMapToSet<u8, u8> = HashMapToSet::new();
+        subject.sub_insert_many(1, vec![2, 3, 4]);
+        assert_eq!(subject.sub_contains_all(&1, &[2, 3, 4]), true);
+        assert_eq!(subject.sub_contains_all(&1, &[2, 9]), false);
+        assert_eq!(subject.sub_contains_all(&1, &[]), true);
+        assert_eq!(subject.sub_contains_all(&9, &[2]), false);
+        assert_eq!(subject.sub_contains_all(&9, &[]), true);
+    }
+
+    #[test]
+    /// Test `sub_contains_any` with present, absent, and empty values, and an absent key.
+    fn test_sub_contains_any() {
+        let mut subject: HashMapToSet<u8, u8> = HashMapToSet::new();
+        subject.sub_insert_many(1, vec![2, 3, 4]);
+        assert_eq!(subject.sub_contains_any(&1, &[9, 4]), true);
+        assert_eq!(subject.sub_contains_any(&1, &[8, 9]), false);
+        assert_eq!(subject.sub_contains_any(&1, &[]), false);
+        assert_eq!(subject.sub_contains_any(&9, &[2]), false);
+    }
+
+    #[test]
+    /// Test `sub_contains_all` and `sub_contains_any` with borrowed forms of the values.
+    fn test_sub_contains_all_and_any_x_borrowed_forms() {
+        let mut subject: HashMapToSet<u8, String> = HashMapToSet::new();
+        subject.sub_insert_many(1, vec![String::from("alpha"), String::from("bravo")]);
+        assert_eq!(subject.sub_contains_all(&1, vec!["alpha", "bravo"]), true);
+        assert_eq!(subject.sub_contains_any(&1, vec!["charlie", "bravo"]), true);
+    }
+
+    #[test]
+    /// Test `duplicate_value_sets` with several groups, a unique set, and empty sets.
+    fn test_duplicate_value_sets() {
+        let mut subject: HashMapToSet<u8, u8> = HashMapToSet::new();
+        subject.sub_insert_many(1, vec![1, 2]);
+        subject.sub_insert_many(2, vec![3]);
+        subject.sub_insert_many(3, vec![2, 1]);
+        subject.sub_insert_many(4, vec![1, 2, 3]);
+        subject.sub_insert_many(5, vec![3]);
+        subject.sub_insert_many(6, vec![1, 2]);
+        subject.sub_insert_many(7, Vec::new());
+        subject.sub_insert_many(8, Vec::new());
+        let mut actual = subject.duplicate_value_sets();
+        for group in actual.iter_mut() {
+            group.sort();
+        }
+        actual.sort();
+        assert_eq!(actual, vec![vec![&1, &3, &6], vec![&2, &5], vec![&7, &8]]);
+    }
+
+    #[test]
+    /// Test `duplicate_value_sets` with all sets unique.
+    fn test_duplicate_value_sets_x_unique() {
+        let mut subject: HashMapToSet<u8, u8> = HashMapToSet::new();
+        subject.sub_insert_many(1, vec![1, 2]);
+        subject.sub_insert_many(2, vec![1]);
+        assert_eq!(subject.duplicate_value_sets(), Vec::<Vec<&u8>>::new());
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    /// Test `sub_sample` with fewer pairs and more pairs than `n`.
+    fn test_sub_sample() {
+        use rand::SeedableRng;
+        let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+        let mut subject: HashMapToSet<u8, u8> = HashMapToSet::new();
+        subject.sub_insert_many(1, vec![1, 2, 3]);
+        subject.sub_insert_many(2, vec![4]);
+        let actual = subject.sub_sample(&mut rng, 9);
+        assert_eq!(actual.len(), 4);
+        let actual = subject.sub_sample(&mut rng, 2);
+        assert_eq!(actual.len(), 2);
+        assert_ne!(actual[0], actual[1]);
+        assert!(actual.iter().all(|(k, v)| subject.sub_contains(*k, *v)));
+        assert_eq!(subject.sub_sample(&mut rng, 0).len(), 0);
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    /// Test `sub_sample` weights each key by the size of its set.
+    fn test_sub_sample_x_weighted_by_set_size() {
+        use rand::SeedableRng;
+        let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+        let mut subject: HashMapToSet<u8, u8> = HashMapToSet::new();
+        subject.sub_insert_many(1, vec![1, 2, 3]);
+        subject.sub_insert_many(2, vec![4]);
+        let trials = 4000;
+        let hits = (0..trials)
+        .filter(|_| *subject.sub_sample(&mut rng, 1)[0].0 == 1)
+        .count();
+        // Expect 3/4 of the trials to choose key 1.
+        assert!(hits > trials * 70 / 100 && hits < trials * 80 / 100, "hits: {}", hits);
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    /// Test `sub_sample_key` with a present key and an absent key.
+    fn test_sub_sample_key() {
+        use rand::SeedableRng;
+        let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+        let mut subject: HashMapToSet<u8, u8> = HashMapToSet::new();
+        subject.sub_insert_many(1, vec![1, 2, 3]);
+        let actual = subject.sub_sample_key(&mut rng, &1, 2);
+        assert_eq!(actual.len(), 2);
+        assert!(actual.iter().all(|v| subject.sub_contains(&1, *v)));
+        assert_eq!(subject.sub_sample_key(&mut rng, &1, 9).len(), 3);
+        assert_eq!(subject.sub_sample_key(&mut rng, &9, 2).len(), 0);
+    }
+
+    #[test]
+    /// Test `sub_append` with an absent key, a smaller set, and a larger set.
+    fn test_sub_append() {
+        let k1 = 1;
+        let k2 = 2;
+        let mut subject: HashMapToSet<u8, u8> = HashMapToSet::new();
+        assert_eq!(subject.sub_append(k1, [1, 2].iter().cloned().collect()), 2);
+        assert_set_eq!(subject.get(&k1).unwrap(), [1, 2]);
+        assert_eq!(subject.sub_append(k1, [2].iter().cloned().collect()), 0);
+        assert_eq!(subject.sub_append(k1, [2, 3, 4, 5].iter().cloned().collect()), 3);
+        assert_set_eq!(subject.get(&k1).unwrap(), [1, 2, 3, 4, 5]);
+        assert_eq!(subject.sub_append(k2, HashSet::new()), 0);
+        assert_eq!(subject.contains_key(&k2), true);
+    }
+
+    #[test]
+    /// Test `sub_clear_key` with a present key and an absent key.
+    fn test_sub_clear_key() {
+        let k1 = 1;
+        let k2 = 2;
+        let absent = 9;
+        let mut subject: HashMapToSet<u8, u8> = HashMapToSet::new();
+        subject.sub_insert_many(k1, vec![1, 2]);
+        subject.sub_insert_many(k2, vec![3]);
+        assert_eq!(subject.sub_clear_key(&k1), 2);
+        assert_eq!(subject.contains_key(&k1), true);
+        assert_eq!(subject.sub_len_of(&k1), 0);
+        assert_eq!(subject.sub_len_of(&k2), 1);
+        assert_eq!(subject.sub_clear_key(&absent), 0);
+        assert_eq!(subject.contains_key(&absent), false);
+    }
+
+    #[test]
+    /// Test `sub_clear_key_prune` with a present key and an absent key.
+    fn test_sub_clear_key_prune() {
+        let k1 = 1;
+        let k2 = 2;
+        let absent = 9;
+        let mut subject: HashMapToSet<u8, u8> = HashMapToSet::new();
+        subject.sub_insert_many(k1, vec![1, 2]);
+        subject.sub_insert_many(k2, vec![3]);
+        assert_eq!(subject.sub_clear_key_prune(&k1), 2);
+        assert_eq!(subject.contains_key(&k1), false);
+        assert_eq!(subject.sub_len_of(&k2), 1);
+        assert_eq!(subject.sub_clear_key_prune(&absent), 0);
+    }
+
+    #[test]
+    /// Test `HashMapToSetOps` operators match the named methods.
+    fn test_ops() {
+        let mut x: HashMapToSetOps<u8, u8> = HashMapToSetOps::default();
+        x.sub_insert_many(1, vec![1, 2]);
+        x.sub_insert_many(2, vec![3]);
+        let mut y: HashMapToSetOps<u8, u8> = HashMapToSetOps::default();
+        y.sub_insert_many(1, vec![2, 4]);
+        y.sub_insert_many(3, vec![5]);
+        assert_eq!((&x | &y).0, x.sub_union(&y));
+        assert_eq!((&x & &y).0, x.sub_intersection(&y));
+        assert_eq!((&x - &y).0, x.sub_difference(&y));
+        assert_eq!((&x ^ &y).0, x.sub_symmetric_difference(&y));
+        assert_eq!(&x - &x, HashMapToSetOps::default());
+    }
+
+    #[test]
+    /// Test `sub_retain` with a predicate on keys and values.
+    fn test_sub_retain() {
+        let mut subject: HashMapToSet<u8, u8> = HashMapToSet::new();
+        subject.sub_insert_many(1, vec![1, 2, 3]);
+        subject.sub_insert_many(2, vec![1]);
+        subject.sub_insert_many(3, Vec::new());
+        subject.sub_retain(|k, v| k < v);
+        assert_eq!(subject.len(), 1);
+        assert_set_eq!(subject.get(&1).unwrap(), [2, 3]);
+    }
+
+    #[test]
+    /// Test `retain_values` with a predicate on values.
+    fn test_retain_values() {
+        let mut subject: HashMapToSet<u8, u8> = HashMapToSet::new();
+        subject.sub_insert_many(1, vec![1, 2, 3]);
+        subject.sub_insert_many(2, vec![1]);
+        subject.sub_insert_many(3, vec![4]);
+        subject.retain_values(|v| *v != 1);
+        assert_eq!(subject.len(), 2);
+        assert_set_eq!(subject.get(&1).unwrap(), [2, 3]);
+        assert_set_eq!(subject.get(&3).unwrap(), [4]);
+    }
+
+    #[test]
+    /// Test `map_values` with values that collapse and an empty set.
+    fn test_map_values() {
+        let mut subject: HashMapToSet<u8, u8> = HashMapToSet::new();
+        subject.sub_insert_many(1, vec![1, 2, 3, 4]);
+        subject.sub_insert_many(2, vec![5]);
+        subject.sub_insert_many(3, Vec::new());
+        let actual: HashMapToSet<u8, bool> = subject.map_values(|v| v % 2 == 0);
+        assert_eq!(actual.len(), 3);
+        assert_set_eq!(actual.get(&1).unwrap(), [false, true]);
+        assert_set_eq!(actual.get(&2).unwrap(), [false]);
+        assert_eq!(actual.get(&3).unwrap().is_empty(), true);
+    }
+
+    #[test]
+    /// Test `filter_keys` with a predicate on keys.
+    fn test_filter_keys() {
+        let mut subject: HashMapToSet<u8, u8> = HashMapToSet::new();
+        subject.sub_insert_many(1, vec![1, 2]);
+        subject.sub_insert_many(2, vec![3]);
+        subject.sub_insert_many(3, vec![4]);
+        let actual = subject.filter_keys(|k| k % 2 == 1);
+        assert_eq!(actual.len(), 2);
+        assert_set_eq!(actual.get(&1).unwrap(), [1, 2]);
+        assert_set_eq!(actual.get(&3).unwrap(), [4]);
+        assert_eq!(subject.len(), 3);
+    }
+
+    #[test]
+    /// Test `sub_extract_if` removes and returns matching pairs, and prunes emptied keys.
+    fn test_sub_extract_if() {
+        let mut subject: HashMapToSet<u8, u8> = HashMapToSet::new();
+        subject.sub_insert_many(1, vec![1, 2, 3]);
+        subject.sub_insert_many(2, vec![2]);
+        subject.sub_insert_many(3, vec![5]);
+        let mut actual: Vec<(u8, u8)> = subject.sub_extract_if(|k, v| k <= v && v % 2 == 0).collect();
+        actual.sort();
+        assert_eq!(actual, vec![(1, 2), (2, 2)]);
+        assert_eq!(subject.len(), 2);
+        assert_set_eq!(subject.get(&1).unwrap(), [1, 3]);
+        assert_set_eq!(subject.get(&3).unwrap(), [5]);
+    }
+
+    #[test]
+    /// Test `sub_extract_if` removes every matching pair even if the iterator is dropped.
+    fn test_sub_extract_if_x_dropped() {
+        let mut subject: HashMapToSet<u8, u8> = HashMapToSet::new();
+        subject.sub_insert_many(1, vec![1, 2, 3]);
+        drop(subject.sub_extract_if(|_, _| true));
+        assert_eq!(subject.len(), 0);
+    }
+
+    #[test]
+    /// Test `sub_swap` with present keys, an absent key, and the same key.
+    fn test_sub_swap() {
+        let k1 = 1;
+        let k2 = 2;
+        let k3 = 3;
+        let mut subject: HashMapToSet<u8, u8> = HashMapToSet::new();
+        subject.sub_insert_many(k1, vec![1, 2]);
+        subject.sub_insert_many(k2, vec![3]);
+        subject.sub_swap(k1, k2);
+        assert_set_eq!(subject.get(&k1).unwrap(), [3]);
+        assert_set_eq!(subject.get(&k2).unwrap(), [1, 2]);
+        subject.sub_swap(k3, k2);
+        assert_set_eq!(subject.get(&k3).unwrap(), [1, 2]);
+        assert_eq!(subject.get(&k2).unwrap().is_empty(), true);
+        subject.sub_swap(k1, k1);
+        assert_set_eq!(subject.get(&k1).unwrap(), [3]);
+    }
+
+    #[test]
+    /// Test `sub_toggle` adds, removes, and prunes.
+    fn test_sub_toggle() {
+        let k = 1;
+        let v1 = 2;
+        let v2 = 3;
+        let mut subject: HashMapToSet<u8, u8> = HashMapToSet::new();
+        assert_eq!(subject.sub_toggle(k, v1), true);
+        assert_eq!(subject.sub_toggle(k, v2), true);
+        assert_set_eq!(subject.get(&k).unwrap(), [v1, v2]);
+        assert_eq!(subject.sub_toggle(k, v1), false);
+        assert_set_eq!(subject.get(&k).unwrap(), [v2]);
+        assert_eq!(subject.sub_toggle(k, v2), false);
+        assert_eq!(subject.contains_key(&k), false);
+    }
+
+    #[test]
+    /// Test `distinct_value_count` with no keys, one key, and overlapping keys.
+    fn test_distinct_value_count() {
+        let mut subject: HashMapToSet<u8, u8> = HashMapToSet::new();
+        assert_eq!(subject.distinct_value_count(), 0);
+        subject.sub_insert_many(1, vec![1, 2, 3]);
+        assert_eq!(subject.distinct_value_count(), 3);
+        subject.sub_insert_many(2, vec![2, 3, 4]);
+        subject.sub_insert_many(3, vec![4]);
+        assert_eq!(subject.distinct_value_count(), 4);
+    }
+
+    #[test]
+    /// Test `sub_eq` ignores keys with empty sets, and compares pairs.
+    fn test_sub_eq() {
+        let mut x: HashMapToSet<u8, u8> = HashMapToSet::new();
+        x.sub_insert_many(1, vec![1, 2]);
+        x.sub_insert_many(2, Vec::new());
+        let mut y: HashMapToSet<u8, u8> = HashMapToSet::new();
+        y.sub_insert_many(1, vec![2, 1]);
+        y.sub_insert_many(3, Vec::new());
+        assert_eq!(x.sub_eq(&y), true);
+        assert_eq!(y.sub_eq(&x), true);
+        y.sub_insert(3, 4);
+        assert_eq!(x.sub_eq(&y), false);
+        assert_eq!(y.sub_eq(&x), false);
+        y.sub_remove(&3, &4);
+        y.sub_remove(&1, &2);
+        assert_eq!(x.sub_eq(&y), false);
     }
 
 }
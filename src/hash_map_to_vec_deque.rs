@@ -0,0 +1,367 @@
+use std::collections::{HashMap, VecDeque};
+use std::borrow::Borrow;
+use std::cmp::Eq;
+use std::hash::Hash;
+
+pub type HashMapToVecDeque<K, V> = HashMap<K, VecDeque<V>>;
+
+pub trait HashMapToVecDequeExt<K, V> {
+
+    fn sub_push_back(&mut self, key: K, value: V)
+    where
+        K: Hash + Eq;
+
+    fn sub_push_front(&mut self, key: K, value: V)
+    where
+        K: Hash + Eq;
+
+    fn sub_pop_front<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q> + Hash + Eq,
+        Q: Hash + Eq + ?Sized;
+
+    fn sub_pop_back<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q> + Hash + Eq,
+        Q: Hash + Eq + ?Sized;
+
+    fn sub_front<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q> + Hash + Eq,
+        Q: Hash + Eq + ?Sized;
+
+    fn sub_len(&self) -> usize;
+
+    fn sub_len_of<Q>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q> + Hash + Eq,
+        Q: Hash + Eq + ?Sized;
+
+    fn sub_iter<'a>(&'a self) -> impl Iterator<Item = (&'a K, &'a V)>
+    where
+        K: 'a,
+        V: 'a;
+
+    fn sub_drain(&mut self) -> impl Iterator<Item = (K, V)>
+    where
+        K: Clone;
+
+    fn sub_drain_key<Q>(&mut self, key: &Q) -> impl Iterator<Item = V>
+    where
+        K: Borrow<Q> + Hash + Eq,
+        Q: Hash + Eq + ?Sized;
+
+}
+
+impl<K, V> HashMapToVecDequeExt<K, V> for HashMapToVecDeque<K, V> {
+
+    /// Add a value to the back of the queue for a key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToVecDeque<u8, u8> = HashMapToVecDeque::new();
+    /// a.sub_push_back(1, 2);
+    /// a.sub_push_back(1, 3);
+    /// assert_eq!(a.sub_front(&1), Some(&2));
+    /// ```
+    #[inline]
+    fn sub_push_back(&mut self, key: K, value: V)
+    where
+        K: Hash + Eq,
+    {
+        self.entry(key)
+        .or_default()
+        .push_back(value)
+    }
+
+    /// Add a value to the front of the queue for a key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToVecDeque<u8, u8> = HashMapToVecDeque::new();
+    /// a.sub_push_back(1, 2);
+    /// a.sub_push_front(1, 3);
+    /// assert_eq!(a.sub_front(&1), Some(&3));
+    /// ```
+    #[inline]
+    fn sub_push_front(&mut self, key: K, value: V)
+    where
+        K: Hash + Eq,
+    {
+        self.entry(key)
+        .or_default()
+        .push_front(value)
+    }
+
+    /// Remove the value at the front of the queue for a key, and return it.
+    ///
+    /// Return `None` if the key is absent or if its queue is empty.
+    /// The key stays in the collection even when its queue becomes empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToVecDeque<u8, u8> = HashMapToVecDeque::new();
+    /// a.sub_push_back(1, 2);
+    /// a.sub_push_back(1, 3);
+    /// assert_eq!(a.sub_pop_front(&1), Some(2));
+    /// assert_eq!(a.sub_pop_front(&1), Some(3));
+    /// assert_eq!(a.sub_pop_front(&1), None);
+    /// ```
+    #[inline]
+    fn sub_pop_front<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q> + Hash + Eq,
+        Q: Hash + Eq + ?Sized,
+    {
+        match self.get_mut(key) {
+            Some(queue) => queue.pop_front(),
+            None => None,
+        }
+    }
+
+    /// Remove the value at the back of the queue for a key, and return it.
+    ///
+    /// Return `None` if the key is absent or if its queue is empty.
+    /// The key stays in the collection even when its queue becomes empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToVecDeque<u8, u8> = HashMapToVecDeque::new();
+    /// a.sub_push_back(1, 2);
+    /// a.sub_push_back(1, 3);
+    /// assert_eq!(a.sub_pop_back(&1), Some(3));
+    /// ```
+    #[inline]
+    fn sub_pop_back<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q> + Hash + Eq,
+        Q: Hash + Eq + ?Sized,
+    {
+        match self.get_mut(key) {
+            Some(queue) => queue.pop_back(),
+            None => None,
+        }
+    }
+
+    /// Return the value at the front of the queue for a key.
+    ///
+    /// Return `None` if the key is absent or if its queue is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToVecDeque<u8, u8> = HashMapToVecDeque::new();
+    /// a.sub_push_back(1, 2);
+    /// assert_eq!(a.sub_front(&1), Some(&2));
+    /// assert_eq!(a.sub_front(&4), None);
+    /// ```
+    #[inline]
+    fn sub_front<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q> + Hash + Eq,
+        Q: Hash + Eq + ?Sized,
+    {
+        match self.get(key) {
+            Some(queue) => queue.front(),
+            None => None,
+        }
+    }
+
+    /// Return the total number of values in every queue.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToVecDeque<u8, u8> = HashMapToVecDeque::new();
+    /// a.sub_push_back(1, 2);
+    /// a.sub_push_back(1, 2);
+    /// a.sub_push_back(4, 5);
+    /// assert_eq!(a.sub_len(), 3);
+    /// ```
+    #[inline]
+    fn sub_len(&self) -> usize {
+        self.values().map(|queue| queue.len()).sum()
+    }
+
+    /// Return the number of values in the queue for a key.
+    ///
+    /// Return 0 if the key is absent.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToVecDeque<u8, u8> = HashMapToVecDeque::new();
+    /// a.sub_push_back(1, 2);
+    /// assert_eq!(a.sub_len_of(&1), 1);
+    /// assert_eq!(a.sub_len_of(&4), 0);
+    /// ```
+    #[inline]
+    fn sub_len_of<Q>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q> + Hash + Eq,
+        Q: Hash + Eq + ?Sized,
+    {
+        match self.get(key) {
+            Some(queue) => queue.len(),
+            None => 0,
+        }
+    }
+
+    /// Return an iterator over every sub-key-value pair in the collection.
+    ///
+    /// The iterator yields `(&K, &V)` pairs with keys in arbitrary order,
+    /// and each key's values from front to back.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToVecDeque<u8, u8> = HashMapToVecDeque::new();
+    /// a.sub_push_back(1, 3);
+    /// a.sub_push_back(1, 2);
+    /// let pairs: Vec<(&u8, &u8)> = a.sub_iter().collect();
+    /// assert_eq!(pairs, vec![(&1, &3), (&1, &2)]);
+    /// ```
+    #[inline]
+    fn sub_iter<'a>(&'a self) -> impl Iterator<Item = (&'a K, &'a V)>
+    where
+        K: 'a,
+        V: 'a,
+    {
+        self.iter()
+        .flat_map(|(key, queue)| queue.iter().map(move |value| (key, value)))
+    }
+
+    /// Remove all sub-key-value pairs from the collection, and return them as an iterator.
+    ///
+    /// The iterator yields owned `(K, V)` pairs, with each key's values
+    /// from front to back; each key is cloned once per value in its queue.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToVecDeque<u8, u8> = HashMapToVecDeque::new();
+    /// a.sub_push_back(1, 3);
+    /// a.sub_push_back(1, 2);
+    /// let pairs: Vec<(u8, u8)> = a.sub_drain().collect();
+    /// assert_eq!(pairs, vec![(1, 3), (1, 2)]);
+    /// assert!(a.is_empty());
+    /// ```
+    #[inline]
+    fn sub_drain(&mut self) -> impl Iterator<Item = (K, V)>
+    where
+        K: Clone,
+    {
+        self.drain()
+        .flat_map(|(key, queue)| queue.into_iter().map(move |value| (key.clone(), value)))
+    }
+
+    /// Remove all values from the queue for one key, and return them from front to back.
+    ///
+    /// The key stays in the collection with an empty queue.
+    /// If the key is absent, then the iterator is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToVecDeque<u8, u8> = HashMapToVecDeque::new();
+    /// a.sub_push_back(1, 3);
+    /// a.sub_push_back(1, 2);
+    /// let values: Vec<u8> = a.sub_drain_key(&1).collect();
+    /// assert_eq!(values, vec![3, 2]);
+    /// assert_eq!(a.sub_len_of(&1), 0);
+    /// ```
+    #[inline]
+    fn sub_drain_key<Q>(&mut self, key: &Q) -> impl Iterator<Item = V>
+    where
+        K: Borrow<Q> + Hash + Eq,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get_mut(key)
+        .into_iter()
+        .flat_map(|queue| queue.drain(..))
+    }
+
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+
+    #[test]
+    /// Test `sub_push_back` and `sub_pop_front` behave as a FIFO queue per key.
+    fn test_fifo() {
+        let mut subject: HashMapToVecDeque<u8, u8> = HashMapToVecDeque::new();
+        let k1 = 1;
+        let k2 = 2;
+        subject.sub_push_back(k1, 3);
+        subject.sub_push_back(k2, 4);
+        subject.sub_push_back(k1, 5);
+        assert_eq!(subject.sub_pop_front(&k1), Some(3));
+        assert_eq!(subject.sub_pop_front(&k1), Some(5));
+        assert_eq!(subject.sub_pop_front(&k1), None);
+        assert_eq!(subject.contains_key(&k1), true);
+        assert_eq!(subject.sub_pop_front(&k2), Some(4));
+    }
+
+    #[test]
+    /// Test `sub_push_front`, `sub_pop_back`, and `sub_front` with some items.
+    fn test_sub_push_front_and_sub_pop_back() {
+        let mut subject: HashMapToVecDeque<u8, u8> = HashMapToVecDeque::new();
+        let k = 1;
+        let absent = 9;
+        subject.sub_push_front(k, 2);
+        subject.sub_push_front(k, 3);
+        assert_eq!(subject.sub_front(&k), Some(&3));
+        assert_eq!(subject.sub_pop_back(&k), Some(2));
+        assert_eq!(subject.sub_pop_back(&k), Some(3));
+        assert_eq!(subject.sub_pop_back(&k), None);
+        assert_eq!(subject.sub_pop_back(&absent), None);
+        assert_eq!(subject.sub_front(&absent), None);
+    }
+
+    #[test]
+    /// Test `sub_len`, `sub_len_of`, and `sub_iter` with some items.
+    fn test_sub_len_and_sub_iter() {
+        let mut subject: HashMapToVecDeque<u8, u8> = HashMapToVecDeque::new();
+        subject.sub_push_back(1, 3);
+        subject.sub_push_back(1, 2);
+        subject.sub_push_back(4, 5);
+        assert_eq!(subject.sub_len(), 3);
+        assert_eq!(subject.sub_len_of(&1), 2);
+        assert_eq!(subject.sub_len_of(&9), 0);
+        let mut actual: Vec<(&u8, &u8)> = subject.sub_iter().collect();
+        actual.sort();
+        assert_eq!(actual, vec![(&1, &2), (&1, &3), (&4, &5)]);
+    }
+
+    #[test]
+    /// Test `sub_drain` and `sub_drain_key` keep front-to-back order.
+    fn test_sub_drain_and_sub_drain_key() {
+        let mut subject: HashMapToVecDeque<u8, u8> = HashMapToVecDeque::new();
+        subject.sub_push_back(1, 3);
+        subject.sub_push_back(1, 2);
+        subject.sub_push_back(4, 5);
+        let actual: Vec<u8> = subject.sub_drain_key(&1).collect();
+        assert_eq!(actual, vec![3, 2]);
+        assert_eq!(subject.sub_drain_key(&9).count(), 0);
+        let actual: Vec<(u8, u8)> = subject.sub_drain().collect();
+        assert_eq!(actual, vec![(4, 5)]);
+        assert_eq!(subject.is_empty(), true);
+    }
+
+}
@@ -0,0 +1,335 @@
+use std::collections::HashMap;
+use std::borrow::Borrow;
+use std::cmp::Eq;
+use std::hash::Hash;
+
+/// A map of keys to weighted sets, where each value has an `f64` weight.
+///
+/// A `HashMapToWeightedSet<K, V>` is a `HashMapToHashMap<K, V, f64>`, so
+/// the two-level methods such as `sub_contains_key`, `sub_remove`, and
+/// `sub_entries_len` also work.
+pub type HashMapToWeightedSet<K, V> = HashMap<K, HashMap<V, f64>>;
+
+pub trait HashMapToWeightedSetExt<K, V> {
+
+    fn sub_insert_weighted(&mut self, key: K, value: V, weight: f64) -> Option<f64>
+    where
+        K: Hash + Eq,
+        V: Hash + Eq;
+
+    fn sub_increment(&mut self, key: K, value: V, delta: f64) -> f64
+    where
+        K: Hash + Eq,
+        V: Hash + Eq;
+
+    fn sub_weight<Q, R>(&self, key: &Q, value: &R) -> Option<f64>
+    where
+        K: Borrow<Q> + Hash + Eq,
+        V: Borrow<R> + Hash + Eq,
+        Q: Hash + Eq + ?Sized,
+        R: Hash + Eq + ?Sized;
+
+    fn sub_total_weight<Q>(&self, key: &Q) -> f64
+    where
+        K: Borrow<Q> + Hash + Eq,
+        Q: Hash + Eq + ?Sized;
+
+    fn sub_top_n<Q>(&self, key: &Q, n: usize) -> Vec<(&V, f64)>
+    where
+        K: Borrow<Q> + Hash + Eq,
+        Q: Hash + Eq + ?Sized;
+
+    fn sub_normalize<Q>(&mut self, key: &Q) -> bool
+    where
+        K: Borrow<Q> + Hash + Eq,
+        Q: Hash + Eq + ?Sized;
+
+    fn sub_normalize_max<Q>(&mut self, key: &Q) -> bool
+    where
+        K: Borrow<Q> + Hash + Eq,
+        Q: Hash + Eq + ?Sized;
+
+    fn sub_normalize_all(&mut self) -> usize;
+
+}
+
+impl<K, V> HashMapToWeightedSetExt<K, V> for HashMapToWeightedSet<K, V> {
+
+    /// Set the weight of a value in a key's set.
+    ///
+    /// Return the previous weight, or `None` if the value is new.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToWeightedSet<&str, &str> = HashMapToWeightedSet::new();
+    /// assert_eq!(a.sub_insert_weighted("tags", "rust", 0.5), None);
+    /// assert_eq!(a.sub_insert_weighted("tags", "rust", 0.75), Some(0.5));
+    /// ```
+    #[inline]
+    fn sub_insert_weighted(&mut self, key: K, value: V, weight: f64) -> Option<f64>
+    where
+        K: Hash + Eq,
+        V: Hash + Eq,
+    {
+        self.entry(key)
+        .or_default()
+        .insert(value, weight)
+    }
+
+    /// Add a delta to the weight of a value in a key's set.
+    ///
+    /// A new value starts at weight 0. Return the new weight.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToWeightedSet<&str, &str> = HashMapToWeightedSet::new();
+    /// assert_eq!(a.sub_increment("tags", "rust", 1.0), 1.0);
+    /// assert_eq!(a.sub_increment("tags", "rust", 2.0), 3.0);
+    /// ```
+    #[inline]
+    fn sub_increment(&mut self, key: K, value: V, delta: f64) -> f64
+    where
+        K: Hash + Eq,
+        V: Hash + Eq,
+    {
+        let weight = self.entry(key)
+        .or_default()
+        .entry(value)
+        .or_insert(0.0);
+        *weight += delta;
+        *weight
+    }
+
+    /// Return the weight of a value in a key's set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToWeightedSet<&str, &str> = HashMapToWeightedSet::new();
+    /// a.sub_insert_weighted("tags", "rust", 0.5);
+    /// assert_eq!(a.sub_weight("tags", "rust"), Some(0.5));
+    /// assert_eq!(a.sub_weight("tags", "go"), None);
+    /// ```
+    #[inline]
+    fn sub_weight<Q, R>(&self, key: &Q, value: &R) -> Option<f64>
+    where
+        K: Borrow<Q> + Hash + Eq,
+        V: Borrow<R> + Hash + Eq,
+        Q: Hash + Eq + ?Sized,
+        R: Hash + Eq + ?Sized,
+    {
+        self.get(key).and_then(|set| set.get(value)).copied()
+    }
+
+    /// Return the sum of the weights in a key's set.
+    ///
+    /// Return 0 if the key is absent.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToWeightedSet<&str, &str> = HashMapToWeightedSet::new();
+    /// a.sub_insert_weighted("tags", "rust", 1.5);
+    /// a.sub_insert_weighted("tags", "go", 0.5);
+    /// assert_eq!(a.sub_total_weight("tags"), 2.0);
+    /// ```
+    #[inline]
+    fn sub_total_weight<Q>(&self, key: &Q) -> f64
+    where
+        K: Borrow<Q> + Hash + Eq,
+        Q: Hash + Eq + ?Sized,
+    {
+        match self.get(key) {
+            Some(set) => set.values().sum(),
+            None => 0.0,
+        }
+    }
+
+    /// Return up to `n` values of a key's set with the highest weights.
+    ///
+    /// The result is sorted by weight, highest first, using
+    /// `f64::total_cmp`; the order of equal weights is arbitrary.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToWeightedSet<&str, &str> = HashMapToWeightedSet::new();
+    /// a.sub_insert_weighted("tags", "rust", 3.0);
+    /// a.sub_insert_weighted("tags", "go", 1.0);
+    /// a.sub_insert_weighted("tags", "zig", 2.0);
+    /// assert_eq!(a.sub_top_n("tags", 2), vec![(&"rust", 3.0), (&"zig", 2.0)]);
+    /// ```
+    #[inline]
+    fn sub_top_n<Q>(&self, key: &Q, n: usize) -> Vec<(&V, f64)>
+    where
+        K: Borrow<Q> + Hash + Eq,
+        Q: Hash + Eq + ?Sized,
+    {
+        let mut top: Vec<(&V, f64)> = match self.get(key) {
+            Some(set) => set.iter().map(|(value, weight)| (value, *weight)).collect(),
+            None => return Vec::new(),
+        };
+        if n < top.len() {
+            top.select_nth_unstable_by(n, |a, b| b.1.total_cmp(&a.1));
+            top.truncate(n);
+        }
+        top.sort_unstable_by(|a, b| b.1.total_cmp(&a.1));
+        top
+    }
+
+    /// Scale the weights of a key's set so they sum to 1.
+    ///
+    /// Return `false`, and leave the weights unchanged, if the key is
+    /// absent or if the weights sum to 0 or to a non-finite number.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToWeightedSet<&str, &str> = HashMapToWeightedSet::new();
+    /// a.sub_insert_weighted("tags", "rust", 3.0);
+    /// a.sub_insert_weighted("tags", "go", 1.0);
+    /// assert_eq!(a.sub_normalize("tags"), true);
+    /// assert_eq!(a.sub_weight("tags", "rust"), Some(0.75));
+    /// ```
+    #[inline]
+    fn sub_normalize<Q>(&mut self, key: &Q) -> bool
+    where
+        K: Borrow<Q> + Hash + Eq,
+        Q: Hash + Eq + ?Sized,
+    {
+        match self.get_mut(key) {
+            Some(set) => scale(set, set.values().sum()),
+            None => false,
+        }
+    }
+
+    /// Scale the weights of a key's set so the largest weight is 1.
+    ///
+    /// Return `false`, and leave the weights unchanged, if the key is
+    /// absent or if the largest weight is not positive and finite.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToWeightedSet<&str, &str> = HashMapToWeightedSet::new();
+    /// a.sub_insert_weighted("tags", "rust", 4.0);
+    /// a.sub_insert_weighted("tags", "go", 1.0);
+    /// assert_eq!(a.sub_normalize_max("tags"), true);
+    /// assert_eq!(a.sub_weight("tags", "go"), Some(0.25));
+    /// ```
+    #[inline]
+    fn sub_normalize_max<Q>(&mut self, key: &Q) -> bool
+    where
+        K: Borrow<Q> + Hash + Eq,
+        Q: Hash + Eq + ?Sized,
+    {
+        match self.get_mut(key) {
+            Some(set) => scale(set, set.values().copied().fold(f64::NEG_INFINITY, f64::max)),
+            None => false,
+        }
+    }
+
+    /// Scale the weights of every key's set so each sums to 1.
+    ///
+    /// Return the number of sets that are normalized.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: HashMapToWeightedSet<&str, &str> = HashMapToWeightedSet::new();
+    /// a.sub_insert_weighted("a", "x", 2.0);
+    /// a.sub_insert_weighted("b", "y", 0.0);
+    /// assert_eq!(a.sub_normalize_all(), 1);
+    /// assert_eq!(a.sub_weight("a", "x"), Some(1.0));
+    /// ```
+    #[inline]
+    fn sub_normalize_all(&mut self) -> usize {
+        let mut count = 0;
+        for set in self.values_mut() {
+            let total = set.values().sum();
+            if scale(set, total) {
+                count += 1;
+            }
+        }
+        count
+    }
+
+}
+
+/// Divide every weight by a divisor, if the divisor is positive and finite.
+fn scale<V>(set: &mut HashMap<V, f64>, divisor: f64) -> bool {
+    if !(divisor.is_finite() && divisor > 0.0) {
+        return false;
+    }
+    for weight in set.values_mut() {
+        *weight /= divisor;
+    }
+    true
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+    use crate::hash_map_to_hash_map::HashMapToHashMapExt;
+
+    #[test]
+    /// Test `sub_insert_weighted`, `sub_increment`, and `sub_weight` with some items.
+    fn test_sub_insert_weighted_and_sub_increment() {
+        let mut subject: HashMapToWeightedSet<u8, char> = HashMapToWeightedSet::new();
+        let k = 1;
+        let absent = 9;
+        assert_eq!(subject.sub_insert_weighted(k, 'a', 2.0), None);
+        assert_eq!(subject.sub_increment(k, 'a', 0.5), 2.5);
+        assert_eq!(subject.sub_increment(k, 'b', -1.0), -1.0);
+        assert_eq!(subject.sub_weight(&k, &'a'), Some(2.5));
+        assert_eq!(subject.sub_weight(&absent, &'a'), None);
+        assert_eq!(subject.sub_total_weight(&k), 1.5);
+        assert_eq!(subject.sub_total_weight(&absent), 0.0);
+        assert_eq!(subject.sub_contains_key(&k, &'b'), true);
+        assert_eq!(subject.sub_entries_len(), 2);
+    }
+
+    #[test]
+    /// Test `sub_top_n` with fewer, equal, and more values than `n`.
+    fn test_sub_top_n() {
+        let mut subject: HashMapToWeightedSet<u8, char> = HashMapToWeightedSet::new();
+        let k = 1;
+        for (value, weight) in [('a', 1.0), ('b', 5.0), ('c', 3.0), ('d', 4.0), ('e', 2.0)] {
+            subject.sub_insert_weighted(k, value, weight);
+        }
+        assert_eq!(subject.sub_top_n(&k, 3), vec![(&'b', 5.0), (&'d', 4.0), (&'c', 3.0)]);
+        assert_eq!(subject.sub_top_n(&k, 9).len(), 5);
+        assert_eq!(subject.sub_top_n(&k, 0), vec![]);
+        assert_eq!(subject.sub_top_n(&9, 3), vec![]);
+    }
+
+    #[test]
+    /// Test `sub_normalize` and `sub_normalize_max` leave degenerate sets unchanged.
+    fn test_sub_normalize() {
+        let mut subject: HashMapToWeightedSet<u8, char> = HashMapToWeightedSet::new();
+        subject.sub_insert_weighted(1, 'a', 1.0);
+        subject.sub_insert_weighted(1, 'b', 3.0);
+        subject.sub_insert_weighted(2, 'a', 0.0);
+        assert_eq!(subject.sub_normalize(&1), true);
+        assert_eq!(subject.sub_weight(&1, &'a'), Some(0.25));
+        assert_eq!(subject.sub_normalize_max(&1), true);
+        assert_eq!(subject.sub_weight(&1, &'b'), Some(1.0));
+        assert_eq!(subject.sub_normalize(&2), false);
+        assert_eq!(subject.sub_normalize_max(&2), false);
+        assert_eq!(subject.sub_weight(&2, &'a'), Some(0.0));
+        assert_eq!(subject.sub_normalize(&9), false);
+        assert_eq!(subject.sub_normalize_all(), 1);
+    }
+
+}
@@ -0,0 +1,361 @@
+use std::collections::BTreeMap;
+use std::cmp::Ord;
+use std::iter::FromIterator;
+use std::ops::Range;
+
+/// A set of points stored as coalesced half-open ranges.
+///
+/// Each range `start..end` holds the points `start <= point < end`.
+/// Inserting a range merges it with every range that it overlaps or
+/// touches, so the set always holds the fewest ranges that cover its
+/// points, sorted by start, with gaps between them.
+///
+/// # Examples
+///
+/// ```
+/// use sixarm_collections::*;
+/// let mut a: IntervalSet<u64> = IntervalSet::new();
+/// a.insert(10..20);
+/// a.insert(20..30);
+/// a.insert(40..50);
+/// assert_eq!(a.iter().collect::<Vec<_>>(), vec![10..30, 40..50]);
+/// assert_eq!(a.gaps(&(0..60)), vec![0..10, 30..40, 50..60]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct IntervalSet<T> {
+    map: BTreeMap<T, T>,
+}
+
+impl<T> IntervalSet<T> {
+
+    /// Create an empty set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: IntervalSet<u64> = IntervalSet::new();
+    /// assert_eq!(a.is_empty(), true);
+    /// ```
+    #[inline]
+    pub fn new() -> Self {
+        IntervalSet { map: BTreeMap::new() }
+    }
+
+    /// Return the number of ranges, after coalescing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: IntervalSet<u64> = IntervalSet::new();
+    /// a.insert(0..5);
+    /// a.insert(3..8);
+    /// a.insert(10..12);
+    /// assert_eq!(a.len(), 2);
+    /// ```
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Return `true` if the set has no points.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: IntervalSet<u64> = IntervalSet::new();
+    /// assert_eq!(a.is_empty(), true);
+    /// ```
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Remove every range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: IntervalSet<u64> = IntervalSet::new();
+    /// a.insert(0..5);
+    /// a.clear();
+    /// assert_eq!(a.is_empty(), true);
+    /// ```
+    #[inline]
+    pub fn clear(&mut self) {
+        self.map.clear()
+    }
+
+}
+
+impl<T> IntervalSet<T>
+where
+    T: Ord + Copy,
+{
+
+    /// Return an iterator over the ranges, in order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: IntervalSet<u64> = IntervalSet::new();
+    /// a.insert(7..9);
+    /// a.insert(1..3);
+    /// assert_eq!(a.iter().collect::<Vec<_>>(), vec![1..3, 7..9]);
+    /// ```
+    #[inline]
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = Range<T>> + ExactSizeIterator + '_ {
+        self.map.iter().map(|(start, end)| *start..*end)
+    }
+
+    /// Add the points of a range, merging it with every range that it
+    /// overlaps or touches.
+    ///
+    /// Return `true` if any point is newly added. An empty range adds nothing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: IntervalSet<u64> = IntervalSet::new();
+    /// assert_eq!(a.insert(0..10), true);
+    /// assert_eq!(a.insert(2..5), false);
+    /// assert_eq!(a.insert(10..12), true);
+    /// assert_eq!(a.iter().collect::<Vec<_>>(), vec![0..12]);
+    /// ```
+    pub fn insert(&mut self, range: Range<T>) -> bool {
+        let Range { mut start, mut end } = range;
+        if start >= end {
+            return false;
+        }
+        if let Some((&prev_start, &prev_end)) = self.map.range(..=start).next_back() {
+            if prev_end >= end {
+                return false;
+            }
+            if prev_end >= start {
+                start = prev_start;
+            }
+        }
+        let merged: Vec<T> = self.map.range(start..=end).map(|(s, _)| *s).collect();
+        for s in merged {
+            if let Some(e) = self.map.remove(&s) {
+                if e > end {
+                    end = e;
+                }
+            }
+        }
+        self.map.insert(start, end);
+        true
+    }
+
+    /// Remove the points of a range, splitting any range that it cuts.
+    ///
+    /// Return `true` if any point is removed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: IntervalSet<u64> = IntervalSet::new();
+    /// a.insert(0..10);
+    /// assert_eq!(a.remove(&(3..5)), true);
+    /// assert_eq!(a.remove(&(3..5)), false);
+    /// assert_eq!(a.iter().collect::<Vec<_>>(), vec![0..3, 5..10]);
+    /// ```
+    pub fn remove(&mut self, range: &Range<T>) -> bool {
+        let (start, end) = (range.start, range.end);
+        if start >= end {
+            return false;
+        }
+        let mut cut: Vec<(T, T)> = Vec::new();
+        if let Some((&prev_start, &prev_end)) = self.map.range(..start).next_back() {
+            if prev_end > start {
+                cut.push((prev_start, prev_end));
+            }
+        }
+        cut.extend(self.map.range(start..end).map(|(s, e)| (*s, *e)));
+        for &(s, e) in &cut {
+            self.map.remove(&s);
+            if s < start {
+                self.map.insert(s, start);
+            }
+            if e > end {
+                self.map.insert(end, e);
+            }
+        }
+        !cut.is_empty()
+    }
+
+    /// Return `true` if the set contains a point.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: IntervalSet<u64> = IntervalSet::new();
+    /// a.insert(10..20);
+    /// assert_eq!(a.contains(&10), true);
+    /// assert_eq!(a.contains(&20), false);
+    /// ```
+    #[inline]
+    pub fn contains(&self, point: &T) -> bool {
+        self.map.range(..=*point).next_back().is_some_and(|(_, end)| end > point)
+    }
+
+    /// Return `true` if the set contains every point of a range.
+    ///
+    /// An empty range is always contained.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: IntervalSet<u64> = IntervalSet::new();
+    /// a.insert(10..20);
+    /// assert_eq!(a.contains_range(&(12..20)), true);
+    /// assert_eq!(a.contains_range(&(12..21)), false);
+    /// ```
+    #[inline]
+    pub fn contains_range(&self, range: &Range<T>) -> bool {
+        range.start >= range.end
+        || self.map.range(..=range.start).next_back().is_some_and(|(_, end)| *end >= range.end)
+    }
+
+    /// Return `true` if the set contains any point of a range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: IntervalSet<u64> = IntervalSet::new();
+    /// a.insert(10..20);
+    /// assert_eq!(a.overlaps(&(0..11)), true);
+    /// assert_eq!(a.overlaps(&(20..30)), false);
+    /// ```
+    #[inline]
+    pub fn overlaps(&self, range: &Range<T>) -> bool {
+        range.start < range.end
+        && self.map.range(..range.end).next_back().is_some_and(|(_, end)| *end > range.start)
+    }
+
+    /// Return the ranges of a query range that the set does not contain, in order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: IntervalSet<u64> = IntervalSet::new();
+    /// a.insert(10..20);
+    /// assert_eq!(a.gaps(&(0..30)), vec![0..10, 20..30]);
+    /// assert_eq!(a.gaps(&(12..18)), vec![]);
+    /// ```
+    pub fn gaps(&self, range: &Range<T>) -> Vec<Range<T>> {
+        let mut gaps = Vec::new();
+        if range.start >= range.end {
+            return gaps;
+        }
+        let mut cursor = range.start;
+        if let Some((_, &prev_end)) = self.map.range(..range.start).next_back() {
+            if prev_end > cursor {
+                cursor = prev_end;
+            }
+        }
+        for (&start, &end) in self.map.range(range.start..range.end) {
+            if start > cursor {
+                gaps.push(cursor..start);
+            }
+            if end > cursor {
+                cursor = end;
+            }
+        }
+        if cursor < range.end {
+            gaps.push(cursor..range.end);
+        }
+        gaps
+    }
+
+}
+
+impl<T> Default for IntervalSet<T> {
+    fn default() -> Self {
+        IntervalSet::new()
+    }
+}
+
+impl<T> FromIterator<Range<T>> for IntervalSet<T>
+where
+    T: Ord + Copy,
+{
+    fn from_iter<I: IntoIterator<Item = Range<T>>>(iter: I) -> Self {
+        let mut set = IntervalSet::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl<T> Extend<Range<T>> for IntervalSet<T>
+where
+    T: Ord + Copy,
+{
+    fn extend<I: IntoIterator<Item = Range<T>>>(&mut self, iter: I) {
+        for range in iter {
+            self.insert(range);
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+
+    #[test]
+    /// Test `insert` coalesces overlapping and touching ranges, and skips empty ones.
+    fn test_insert_x_coalesce() {
+        let mut subject: IntervalSet<u32> = IntervalSet::new();
+        assert_eq!(subject.insert(10..20), true);
+        assert_eq!(subject.insert(30..40), true);
+        assert_eq!(subject.insert(5..5), false);
+        assert_eq!(subject.len(), 2);
+        assert_eq!(subject.insert(20..30), true);
+        assert_eq!(subject.iter().collect::<Vec<_>>(), vec![10..40]);
+        assert_eq!(subject.insert(0..50), true);
+        assert_eq!(subject.iter().collect::<Vec<_>>(), vec![0..50]);
+        assert_eq!(subject.insert(10..50), false);
+        let subject: IntervalSet<u32> = vec![8..9, 1..3, 2..4, 6..8].into_iter().collect();
+        assert_eq!(subject.iter().collect::<Vec<_>>(), vec![1..4, 6..9]);
+    }
+
+    #[test]
+    /// Test `remove` splits, trims, and drops ranges.
+    fn test_remove() {
+        let mut subject: IntervalSet<u32> = vec![0..10, 20..30, 40..50].into_iter().collect();
+        assert_eq!(subject.remove(&(5..45)), true);
+        assert_eq!(subject.iter().collect::<Vec<_>>(), vec![0..5, 45..50]);
+        assert_eq!(subject.remove(&(10..40)), false);
+        assert_eq!(subject.remove(&(0..50)), true);
+        assert_eq!(subject.is_empty(), true);
+    }
+
+    #[test]
+    /// Test `contains`, `contains_range`, `overlaps`, and `gaps` at range edges.
+    fn test_queries_x_edges() {
+        let subject: IntervalSet<u32> = vec![10..20, 30..40].into_iter().collect();
+        assert_eq!(subject.contains(&9), false);
+        assert_eq!(subject.contains(&19), true);
+        assert_eq!(subject.contains(&20), false);
+        assert_eq!(subject.contains_range(&(10..20)), true);
+        assert_eq!(subject.contains_range(&(15..35)), false);
+        assert_eq!(subject.contains_range(&(25..25)), true);
+        assert_eq!(subject.overlaps(&(19..30)), true);
+        assert_eq!(subject.overlaps(&(20..30)), false);
+        assert_eq!(subject.gaps(&(15..35)), vec![20..30]);
+        assert_eq!(subject.gaps(&(0..50)), vec![0..10, 20..30, 40..50]);
+        assert_eq!(subject.gaps(&(22..28)), vec![22..28]);
+    }
+
+}
@@ -1,17 +1,241 @@
 pub mod btree_map_to_set;
+pub mod btree_map_to_vec;
+pub mod btree_map_to_hash_set;
+pub mod btree_bag;
+pub mod btree_map_to_btree_map;
 pub mod btree_map_of_file_len_to_set_of_path_buf;
+pub mod btree_map_of_mtime_to_set_of_path_buf;
+pub mod btree_map_of_extension_to_set_of_path_buf;
+pub mod btree_map_of_file_name_to_set_of_path_buf;
+pub mod btree_map_of_range_to_set;
 pub mod hash_map_to_set;
+pub mod hash_map_to_vec;
+pub mod hash_map_to_vec_deque;
+pub mod hash_map_to_btree_set;
+pub mod hash_map_to_arc_set;
+pub mod hash_map_to_interval_set;
+pub mod interval_set;
+pub mod hash_map_to_bag;
+pub mod hash_map_to_hash_map;
+pub mod hash_map_to_weighted_set;
+pub mod hash_map_to_sorted_vec;
+pub mod hash_bag;
+pub mod hash_bi_map;
+pub mod bi_multi_map;
+pub mod any_map_to_set;
+pub mod arena_multi_map;
+pub mod bloom_indexed_map_to_set;
 pub mod hash_map_of_file_len_to_set_of_path_buf;
+pub mod hash_map_of_mtime_to_set_of_path_buf;
+pub mod hash_map_of_extension_to_set_of_path_buf;
+pub mod hash_map_of_file_name_to_set_of_path_buf;
+#[cfg(unix)]
+pub mod hash_map_of_dev_inode_to_set_of_path_buf;
+pub mod hash_map_of_cow_str_to_set;
+#[cfg(any(feature = "blake3", feature = "sha2"))]
+pub mod hash_map_of_digest_to_set_of_path_buf;
+#[cfg(any(feature = "blake3", feature = "sha2"))]
+pub mod file_digest;
+pub mod file_compare;
+#[cfg(any(feature = "blake3", feature = "sha2"))]
+pub mod duplicate_finder;
+pub mod frozen_map_to_set;
+pub mod grouped_vec;
+#[cfg(feature = "indexmap")]
+pub mod hash_map_to_index_set;
+#[cfg(feature = "indexmap")]
+pub mod index_map_to_set;
+pub mod lru_map_to_set;
+pub mod merge_strategy;
+pub mod multi_key_map_to_set;
+pub mod ordered_multi_map;
+pub mod path_scan;
+pub mod prefix_map_to_set;
+pub mod set_of_sets;
+pub mod sorted_vec;
+pub mod sync_map_to_set;
+#[cfg(feature = "smallvec")]
+pub mod small_set;
+#[cfg(feature = "smallvec")]
+pub mod hash_map_to_small_set;
+#[cfg(feature = "enumset")]
+pub mod hash_map_to_enum_set;
+#[cfg(feature = "imbl")]
+pub mod persistent_map_to_set;
+mod top_entry;
+mod range_bounds;
 
 pub use self::btree_map_to_set::BTreeMapToSet;
 pub use self::btree_map_to_set::BTreeMapToSetExt;
+pub use self::btree_map_to_set::BTreeMapToSetEntry;
+pub use self::btree_map_to_set::BTreeMapToSetExtender;
+pub use self::btree_map_to_set::BTreeMapToSetView;
+pub use self::btree_map_to_set::BTreeMapToSetOps;
+
+pub use self::btree_map_to_vec::BTreeMapToVec;
+pub use self::btree_map_to_vec::BTreeMapToVecExt;
+
+pub use self::btree_map_to_hash_set::BTreeMapToHashSet;
+pub use self::btree_map_to_hash_set::BTreeMapToHashSetExt;
+
+pub use self::btree_bag::BTreeBag;
+
+pub use self::btree_map_to_btree_map::BTreeMapToBTreeMap;
+pub use self::btree_map_to_btree_map::BTreeMapToBTreeMapExt;
 
 pub use self::btree_map_of_file_len_to_set_of_path_buf::BTreeMapOfFileLenToSetOfPathBuf;
 pub use self::btree_map_of_file_len_to_set_of_path_buf::BTreeMapOfFileLenToSetOfPathBufExt;
+pub use self::btree_map_of_file_len_to_set_of_path_buf::BTreeMapOfFileLenToSetOfPathBufTryExt;
+
+pub use self::btree_map_of_mtime_to_set_of_path_buf::BTreeMapOfMtimeToSetOfPathBuf;
+pub use self::btree_map_of_mtime_to_set_of_path_buf::BTreeMapOfMtimeToSetOfPathBufExt;
+
+pub use self::btree_map_of_extension_to_set_of_path_buf::BTreeMapOfExtensionToSetOfPathBuf;
+pub use self::btree_map_of_extension_to_set_of_path_buf::BTreeMapOfExtensionToSetOfPathBufExt;
+
+pub use self::btree_map_of_file_name_to_set_of_path_buf::BTreeMapOfFileNameToSetOfPathBuf;
+pub use self::btree_map_of_file_name_to_set_of_path_buf::BTreeMapOfFileNameToSetOfPathBufExt;
+
+pub use self::btree_map_of_range_to_set::BTreeMapOfRangeToSet;
+pub use self::btree_map_of_range_to_set::RangeInsertError;
 
 pub use self::hash_map_to_set::HashMapToSet;
 pub use self::hash_map_to_set::HashMapToSetExt;
+pub use self::hash_map_to_set::HashMapToSetEntry;
+pub use self::hash_map_to_set::HashMapToSetExtender;
+pub use self::hash_map_to_set::HashMapToSetView;
+pub use self::hash_map_to_set::HashMapToSetOps;
+
+pub use self::hash_map_to_vec::HashMapToVec;
+pub use self::hash_map_to_vec::HashMapToVecExt;
+
+pub use self::hash_map_to_vec_deque::HashMapToVecDeque;
+pub use self::hash_map_to_vec_deque::HashMapToVecDequeExt;
+
+pub use self::hash_map_to_btree_set::HashMapToBTreeSet;
+pub use self::hash_map_to_btree_set::HashMapToBTreeSetExt;
+
+pub use self::hash_map_to_arc_set::HashMapToArcSet;
+pub use self::hash_map_to_arc_set::HashMapToArcSetExt;
+
+pub use self::hash_map_to_interval_set::HashMapToIntervalSet;
+pub use self::hash_map_to_interval_set::HashMapToIntervalSetExt;
+
+pub use self::interval_set::IntervalSet;
+
+pub use self::hash_map_to_bag::HashMapToBag;
+pub use self::hash_map_to_bag::HashMapToBagExt;
+
+pub use self::hash_map_to_hash_map::HashMapToHashMap;
+pub use self::hash_map_to_hash_map::HashMapToHashMapExt;
+
+pub use self::hash_map_to_weighted_set::HashMapToWeightedSet;
+pub use self::hash_map_to_weighted_set::HashMapToWeightedSetExt;
+
+pub use self::hash_map_to_sorted_vec::HashMapToSortedVec;
+pub use self::hash_map_to_sorted_vec::HashMapToSortedVecExt;
+
+pub use self::hash_bag::HashBag;
+
+pub use self::hash_bi_map::HashBiMap;
+
+pub use self::any_map_to_set::AnyMapToSet;
+
+pub use self::arena_multi_map::ArenaMultiMap;
+
+pub use self::bi_multi_map::BiMultiMap;
+
+pub use self::bloom_indexed_map_to_set::BloomIndexedMapToSet;
 
 pub use self::hash_map_of_file_len_to_set_of_path_buf::HashMapOfFileLenToSetOfPathBuf;
 pub use self::hash_map_of_file_len_to_set_of_path_buf::HashMapOfFileLenToSetOfPathBufExt;
+pub use self::hash_map_of_file_len_to_set_of_path_buf::HashMapOfFileLenToSetOfPathBufTryExt;
+
+pub use self::hash_map_of_mtime_to_set_of_path_buf::HashMapOfMtimeToSetOfPathBuf;
+pub use self::hash_map_of_mtime_to_set_of_path_buf::HashMapOfMtimeToSetOfPathBufExt;
+
+pub use self::hash_map_of_extension_to_set_of_path_buf::HashMapOfExtensionToSetOfPathBuf;
+pub use self::hash_map_of_extension_to_set_of_path_buf::HashMapOfExtensionToSetOfPathBufExt;
+
+pub use self::hash_map_of_file_name_to_set_of_path_buf::HashMapOfFileNameToSetOfPathBuf;
+pub use self::hash_map_of_file_name_to_set_of_path_buf::HashMapOfFileNameToSetOfPathBufExt;
+
+#[cfg(unix)]
+pub use self::hash_map_of_dev_inode_to_set_of_path_buf::HashMapOfDevInodeToSetOfPathBuf;
+#[cfg(unix)]
+pub use self::hash_map_of_dev_inode_to_set_of_path_buf::HashMapOfDevInodeToSetOfPathBufExt;
+
+pub use self::hash_map_of_cow_str_to_set::HashMapOfCowStrToSet;
+pub use self::hash_map_of_cow_str_to_set::HashMapOfCowStrToSetExt;
+
+#[cfg(any(feature = "blake3", feature = "sha2"))]
+pub use self::hash_map_of_digest_to_set_of_path_buf::HashMapOfDigestToSetOfPathBuf;
+#[cfg(any(feature = "blake3", feature = "sha2"))]
+pub use self::hash_map_of_digest_to_set_of_path_buf::HashMapOfDigestToSetOfPathBufExt;
+
+#[cfg(any(feature = "blake3", feature = "sha2"))]
+pub use self::duplicate_finder::DuplicateFinder;
+#[cfg(any(feature = "blake3", feature = "sha2"))]
+pub use self::duplicate_finder::DuplicateGroup;
+
+pub use self::file_compare::files_equal;
+pub use self::file_compare::partition_identical;
+pub use self::file_compare::readers_equal;
+
+#[cfg(any(feature = "blake3", feature = "sha2"))]
+pub use self::file_digest::DigestAlgorithm;
+#[cfg(any(feature = "blake3", feature = "sha2"))]
+pub use self::file_digest::FileDigest;
+
+#[cfg(feature = "indexmap")]
+pub use self::hash_map_to_index_set::HashMapToIndexSet;
+#[cfg(feature = "indexmap")]
+pub use self::hash_map_to_index_set::HashMapToIndexSetExt;
+
+#[cfg(feature = "indexmap")]
+pub use self::index_map_to_set::IndexMapToSet;
+#[cfg(feature = "indexmap")]
+pub use self::index_map_to_set::IndexMapToSetExt;
+
+#[cfg(feature = "smallvec")]
+pub use self::small_set::SmallSet;
+#[cfg(feature = "smallvec")]
+pub use self::small_set::SmallSetIntoIter;
+#[cfg(feature = "smallvec")]
+pub use self::hash_map_to_small_set::HashMapToSmallSet;
+#[cfg(feature = "smallvec")]
+pub use self::hash_map_to_small_set::HashMapToSmallSetExt;
+
+#[cfg(feature = "enumset")]
+pub use self::hash_map_to_enum_set::HashMapToEnumSet;
+#[cfg(feature = "enumset")]
+pub use self::hash_map_to_enum_set::HashMapToEnumSetExt;
+
+#[cfg(feature = "imbl")]
+pub use self::persistent_map_to_set::PersistentMapToSet;
+
+pub use self::frozen_map_to_set::FrozenMapToSet;
+pub use self::frozen_map_to_set::FrozenMapToSetBuilder;
+
+pub use self::grouped_vec::GroupedVec;
+
+pub use self::lru_map_to_set::LruMapToSet;
+
+pub use self::merge_strategy::MergeStrategy;
+
+pub use self::multi_key_map_to_set::MultiKeyMapToSet;
+
+pub use self::ordered_multi_map::OrderedMultiMap;
+
+pub use self::path_scan::PathErrors;
+pub use self::path_scan::SymlinkPolicy;
+pub use self::path_scan::WalkOptions;
+
+pub use self::prefix_map_to_set::PrefixMapToSet;
+
+pub use self::set_of_sets::SetOfSets;
+
+pub use self::sorted_vec::SortedVec;
 
+pub use self::sync_map_to_set::SyncMapToSet;
+pub use self::sync_map_to_set::SYNC_MAP_TO_SET_DEFAULT_SHARDS;
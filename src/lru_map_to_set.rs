@@ -0,0 +1,547 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::borrow::Borrow;
+use std::cmp::Eq;
+use std::fmt;
+use std::hash::Hash;
+
+/// A callback that receives each evicted key and its set.
+type OnEvict<K, V> = Box<dyn FnMut(K, HashSet<V>)>;
+
+/// A bounded map of keys to sets of values, which evicts whole keys in
+/// least-recently-used order.
+///
+/// The map can limit the number of keys, the number of sub-key-value
+/// pairs, or both. When an insert goes over a limit, the map evicts the
+/// least recently used keys, with their sets, until it is within its
+/// limits again. The most recently used key is never evicted, so one
+/// key with a large set can exceed the pair limit by itself, except
+/// that a limit of 0 evicts every key, so each insert is evicted at once.
+///
+/// A key is used when it is inserted into or fetched with `sub_get`;
+/// `sub_peek` and `sub_contains` do not change the order.
+///
+/// # Examples
+///
+/// ```
+/// use sixarm_collections::*;
+/// let mut a: LruMapToSet<u8, u8> = LruMapToSet::with_max_keys(2);
+/// a.sub_insert(1, 10);
+/// a.sub_insert(2, 20);
+/// a.sub_get(&1);
+/// a.sub_insert(3, 30);
+/// assert_eq!(a.contains_key(&1), true);
+/// assert_eq!(a.contains_key(&2), false);
+/// assert_eq!(a.contains_key(&3), true);
+/// ```
+pub struct LruMapToSet<K, V> {
+    map: HashMap<K, (u64, HashSet<V>)>,
+    order: BTreeMap<u64, K>,
+    tick: u64,
+    sub_len: usize,
+    max_keys: Option<usize>,
+    max_pairs: Option<usize>,
+    on_evict: Option<OnEvict<K, V>>,
+}
+
+impl<K, V> LruMapToSet<K, V> {
+
+    /// Create an empty map with optional limits on keys and on sub-key-value pairs.
+    ///
+    /// A limit of `None` means unbounded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: LruMapToSet<u8, u8> = LruMapToSet::with_limits(Some(100), Some(1000));
+    /// assert_eq!(a.max_keys(), Some(100));
+    /// assert_eq!(a.max_pairs(), Some(1000));
+    /// ```
+    #[inline]
+    pub fn with_limits(max_keys: Option<usize>, max_pairs: Option<usize>) -> Self {
+        LruMapToSet {
+            map: HashMap::new(),
+            order: BTreeMap::new(),
+            tick: 0,
+            sub_len: 0,
+            max_keys,
+            max_pairs,
+            on_evict: None,
+        }
+    }
+
+    /// Create an empty map that holds at most `max_keys` keys.
+    ///
+    /// A limit of 0 holds no keys: each insert is evicted at once.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: LruMapToSet<u8, u8> = LruMapToSet::with_max_keys(100);
+    /// assert_eq!(a.max_keys(), Some(100));
+    /// ```
+    #[inline]
+    pub fn with_max_keys(max_keys: usize) -> Self {
+        LruMapToSet::with_limits(Some(max_keys), None)
+    }
+
+    /// Create an empty map that holds at most `max_pairs` sub-key-value pairs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: LruMapToSet<u8, u8> = LruMapToSet::with_max_pairs(1000);
+    /// assert_eq!(a.max_pairs(), Some(1000));
+    /// ```
+    #[inline]
+    pub fn with_max_pairs(max_pairs: usize) -> Self {
+        LruMapToSet::with_limits(None, Some(max_pairs))
+    }
+
+    /// Set a callback that receives each evicted key and its set.
+    ///
+    /// The callback is called for evictions only, not for `remove`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// use std::rc::Rc;
+    /// use std::cell::RefCell;
+    /// let evicted = Rc::new(RefCell::new(Vec::new()));
+    /// let sink = evicted.clone();
+    /// let mut a: LruMapToSet<u8, u8> = LruMapToSet::with_max_keys(1);
+    /// a.set_on_evict(move |key, _set| sink.borrow_mut().push(key));
+    /// a.sub_insert(1, 10);
+    /// a.sub_insert(2, 20);
+    /// assert_eq!(*evicted.borrow(), vec![1]);
+    /// ```
+    #[inline]
+    pub fn set_on_evict<F>(&mut self, on_evict: F)
+    where
+        F: FnMut(K, HashSet<V>) + 'static,
+    {
+        self.on_evict = Some(Box::new(on_evict));
+    }
+
+    /// Return the limit on keys, if any.
+    #[inline]
+    pub fn max_keys(&self) -> Option<usize> {
+        self.max_keys
+    }
+
+    /// Return the limit on sub-key-value pairs, if any.
+    #[inline]
+    pub fn max_pairs(&self) -> Option<usize> {
+        self.max_pairs
+    }
+
+    /// Return the number of keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: LruMapToSet<u8, u8> = LruMapToSet::with_max_keys(10);
+    /// a.sub_insert(1, 2);
+    /// a.sub_insert(1, 3);
+    /// assert_eq!(a.len(), 1);
+    /// ```
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Return `true` if the map has no keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: LruMapToSet<u8, u8> = LruMapToSet::with_max_keys(10);
+    /// assert_eq!(a.is_empty(), true);
+    /// ```
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Return the total number of sub-key-value pairs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: LruMapToSet<u8, u8> = LruMapToSet::with_max_keys(10);
+    /// a.sub_insert(1, 2);
+    /// a.sub_insert(1, 3);
+    /// assert_eq!(a.sub_len(), 2);
+    /// ```
+    #[inline]
+    pub fn sub_len(&self) -> usize {
+        self.sub_len
+    }
+
+    /// Return an iterator over the keys from least to most recently used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: LruMapToSet<u8, u8> = LruMapToSet::with_max_keys(10);
+    /// a.sub_insert(1, 2);
+    /// a.sub_insert(3, 4);
+    /// a.sub_get(&1);
+    /// let keys: Vec<&u8> = a.keys_lru().collect();
+    /// assert_eq!(keys, vec![&3, &1]);
+    /// ```
+    #[inline]
+    pub fn keys_lru(&self) -> impl Iterator<Item = &K> {
+        self.order.values()
+    }
+
+}
+
+impl<K, V> LruMapToSet<K, V>
+where
+    K: Clone + Hash + Eq,
+    V: Hash + Eq,
+{
+
+    /// Return `true` if the map contains a key, without using it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: LruMapToSet<u8, u8> = LruMapToSet::with_max_keys(10);
+    /// a.sub_insert(1, 2);
+    /// assert_eq!(a.contains_key(&1), true);
+    /// ```
+    #[inline]
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.contains_key(key)
+    }
+
+    /// Return `true` if the map contains a sub-key-value pair, without using the key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: LruMapToSet<u8, u8> = LruMapToSet::with_max_keys(10);
+    /// a.sub_insert(1, 2);
+    /// assert_eq!(a.sub_contains(&1, &2), true);
+    /// assert_eq!(a.sub_contains(&1, &3), false);
+    /// ```
+    #[inline]
+    pub fn sub_contains<Q, R>(&self, key: &Q, value: &R) -> bool
+    where
+        K: Borrow<Q>,
+        V: Borrow<R>,
+        Q: Hash + Eq + ?Sized,
+        R: Hash + Eq + ?Sized,
+    {
+        match self.map.get(key) {
+            Some((_, set)) => set.contains(value),
+            None => false,
+        }
+    }
+
+    /// Add a sub-key-value pair, use the key, and evict keys that go over a limit.
+    ///
+    /// Return whether the value is newly added in the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: LruMapToSet<u8, u8> = LruMapToSet::with_max_pairs(2);
+    /// a.sub_insert(1, 10);
+    /// a.sub_insert(2, 20);
+    /// a.sub_insert(2, 21);
+    /// assert_eq!(a.contains_key(&1), false);
+    /// assert_eq!(a.sub_len(), 2);
+    /// ```
+    #[inline]
+    pub fn sub_insert(&mut self, key: K, value: V) -> bool {
+        self.tick += 1;
+        let tick = self.tick;
+        let added = match self.map.get_mut(&key) {
+            Some((used, set)) => {
+                let key = self.order.remove(used).unwrap_or(key);
+                *used = tick;
+                self.order.insert(tick, key);
+                set.insert(value)
+            },
+            None => {
+                let mut set = HashSet::new();
+                set.insert(value);
+                self.order.insert(tick, key.clone());
+                self.map.insert(key, (tick, set));
+                true
+            },
+        };
+        if added {
+            self.sub_len += 1;
+        }
+        self.evict();
+        added
+    }
+
+    /// Return the set for a key, and use the key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: LruMapToSet<u8, u8> = LruMapToSet::with_max_keys(10);
+    /// a.sub_insert(1, 2);
+    /// assert_eq!(a.sub_get(&1).map(|set| set.len()), Some(1));
+    /// assert_eq!(a.sub_get(&9), None);
+    /// ```
+    #[inline]
+    pub fn sub_get<Q>(&mut self, key: &Q) -> Option<&HashSet<V>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.tick += 1;
+        let tick = self.tick;
+        match self.map.get_mut(key) {
+            Some((used, set)) => {
+                if let Some(key) = self.order.remove(used) {
+                    self.order.insert(tick, key);
+                }
+                *used = tick;
+                Some(set)
+            },
+            None => None,
+        }
+    }
+
+    /// Return the set for a key, without using the key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: LruMapToSet<u8, u8> = LruMapToSet::with_max_keys(10);
+    /// a.sub_insert(1, 2);
+    /// assert_eq!(a.sub_peek(&1).map(|set| set.len()), Some(1));
+    /// ```
+    #[inline]
+    pub fn sub_peek<Q>(&self, key: &Q) -> Option<&HashSet<V>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.get(key).map(|(_, set)| set)
+    }
+
+    /// Remove a sub-key-value pair, without using the key.
+    ///
+    /// Return whether the value was present. The key stays in the map
+    /// even when its set becomes empty, like `sub_remove` on `HashMapToSet`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: LruMapToSet<u8, u8> = LruMapToSet::with_max_keys(10);
+    /// a.sub_insert(1, 2);
+    /// assert_eq!(a.sub_remove(&1, &2), true);
+    /// assert_eq!(a.sub_remove(&1, &2), false);
+    /// ```
+    #[inline]
+    pub fn sub_remove<Q, R>(&mut self, key: &Q, value: &R) -> bool
+    where
+        K: Borrow<Q>,
+        V: Borrow<R>,
+        Q: Hash + Eq + ?Sized,
+        R: Hash + Eq + ?Sized,
+    {
+        match self.map.get_mut(key) {
+            Some((_, set)) => {
+                let removed = set.remove(value);
+                if removed {
+                    self.sub_len -= 1;
+                }
+                removed
+            },
+            None => false,
+        }
+    }
+
+    /// Remove a key and return its set, without calling the eviction callback.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: LruMapToSet<u8, u8> = LruMapToSet::with_max_keys(10);
+    /// a.sub_insert(1, 2);
+    /// assert_eq!(a.remove(&1).map(|set| set.len()), Some(1));
+    /// assert_eq!(a.is_empty(), true);
+    /// ```
+    #[inline]
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<HashSet<V>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let (used, set) = self.map.remove(key)?;
+        self.order.remove(&used);
+        self.sub_len -= set.len();
+        Some(set)
+    }
+
+    /// Return `true` if the map is over its key limit or its pair limit.
+    fn is_over_limit(&self) -> bool {
+        self.max_keys.is_some_and(|max| self.map.len() > max)
+        || self.max_pairs.is_some_and(|max| self.sub_len > max)
+    }
+
+    /// Evict least recently used keys, except the most recently used key,
+    /// until the map is within its limits. A limit of 0 evicts every key.
+    fn evict(&mut self) {
+        let spare = if self.max_keys == Some(0) || self.max_pairs == Some(0) { 0 } else { 1 };
+        while self.is_over_limit() && self.map.len() > spare {
+            let key = match self.order.pop_first() {
+                Some((_, key)) => key,
+                None => break,
+            };
+            if let Some((_, set)) = self.map.remove(&key) {
+                self.sub_len -= set.len();
+                if let Some(on_evict) = self.on_evict.as_mut() {
+                    on_evict(key, set);
+                }
+            }
+        }
+    }
+
+}
+
+impl<K, V> fmt::Debug for LruMapToSet<K, V>
+where
+    K: fmt::Debug,
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LruMapToSet")
+        .field("map", &self.map)
+        .field("max_keys", &self.max_keys)
+        .field("max_pairs", &self.max_pairs)
+        .finish()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[test]
+    /// Test the key limit evicts the least recently inserted or fetched key.
+    fn test_max_keys() {
+        let mut subject: LruMapToSet<u8, u8> = LruMapToSet::with_max_keys(2);
+        subject.sub_insert(1, 10);
+        subject.sub_insert(2, 20);
+        subject.sub_insert(1, 11);
+        subject.sub_insert(3, 30);
+        assert_eq!(subject.contains_key(&2), false);
+        assert_eq!(subject.len(), 2);
+        subject.sub_get(&1);
+        subject.sub_insert(4, 40);
+        assert_eq!(subject.contains_key(&3), false);
+        assert_eq!(subject.keys_lru().collect::<Vec<&u8>>(), vec![&1, &4]);
+        assert_eq!(subject.sub_len(), 3);
+    }
+
+    #[test]
+    /// Test a key limit of 0 evicts every insert, and calls the eviction callback.
+    fn test_max_keys_x_zero() {
+        let evicted = Rc::new(RefCell::new(Vec::new()));
+        let sink = evicted.clone();
+        let mut subject: LruMapToSet<u8, u8> = LruMapToSet::with_max_keys(0);
+        subject.set_on_evict(move |key, set| sink.borrow_mut().push((key, set.len())));
+        assert_eq!(subject.sub_insert(1, 10), true);
+        assert_eq!(subject.is_empty(), true);
+        assert_eq!(subject.sub_len(), 0);
+        subject.sub_insert(2, 20);
+        assert_eq!(subject.len(), 0);
+        assert_eq!(subject.keys_lru().count(), 0);
+        assert_eq!(*RefCell::borrow(&evicted), vec![(1, 1), (2, 1)]);
+        let mut subject: LruMapToSet<u8, u8> = LruMapToSet::with_max_pairs(0);
+        subject.sub_insert(1, 10);
+        assert_eq!(subject.is_empty(), true);
+    }
+
+    #[test]
+    /// Test `sub_peek` and `sub_contains` do not change the order.
+    fn test_sub_peek_x_no_use() {
+        let mut subject: LruMapToSet<u8, u8> = LruMapToSet::with_max_keys(2);
+        subject.sub_insert(1, 10);
+        subject.sub_insert(2, 20);
+        assert_eq!(subject.sub_peek(&1).map(|set| set.len()), Some(1));
+        assert_eq!(subject.sub_contains(&1, &10), true);
+        subject.sub_insert(3, 30);
+        assert_eq!(subject.contains_key(&1), false);
+    }
+
+    #[test]
+    /// Test the pair limit evicts whole keys, and spares the most recently used key.
+    fn test_max_pairs() {
+        let mut subject: LruMapToSet<u8, u8> = LruMapToSet::with_max_pairs(3);
+        subject.sub_insert(1, 10);
+        subject.sub_insert(1, 11);
+        subject.sub_insert(2, 20);
+        subject.sub_insert(2, 21);
+        assert_eq!(subject.contains_key(&1), false);
+        assert_eq!(subject.sub_len(), 2);
+        for value in 22..30 {
+            subject.sub_insert(2, value);
+        }
+        assert_eq!(subject.len(), 1);
+        assert_eq!(subject.sub_len(), 10);
+    }
+
+    #[test]
+    /// Test the eviction callback receives each evicted key and set, and `remove` skips it.
+    fn test_set_on_evict() {
+        let evicted = Rc::new(RefCell::new(Vec::new()));
+        let sink = evicted.clone();
+        let mut subject: LruMapToSet<u8, u8> = LruMapToSet::with_limits(Some(2), Some(10));
+        subject.set_on_evict(move |key, set| sink.borrow_mut().push((key, set.len())));
+        subject.sub_insert(1, 10);
+        subject.sub_insert(1, 11);
+        subject.sub_insert(2, 20);
+        subject.remove(&2);
+        subject.sub_insert(3, 30);
+        subject.sub_insert(4, 40);
+        assert_eq!(*RefCell::borrow(&evicted), vec![(1, 2)]);
+        assert_eq!(subject.sub_len(), 2);
+    }
+
+    #[test]
+    /// Test `sub_remove` keeps `sub_len` in step.
+    fn test_sub_remove() {
+        let mut subject: LruMapToSet<u8, u8> = LruMapToSet::with_max_keys(10);
+        subject.sub_insert(1, 10);
+        subject.sub_insert(1, 11);
+        assert_eq!(subject.sub_remove(&1, &10), true);
+        assert_eq!(subject.sub_remove(&1, &10), false);
+        assert_eq!(subject.sub_remove(&9, &10), false);
+        assert_eq!(subject.sub_len(), 1);
+        assert_eq!(subject.contains_key(&1), true);
+    }
+
+}
@@ -0,0 +1,16 @@
+/// How to combine the sets of a key that is in both collections of a merge.
+///
+/// This is used by `sub_merge`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MergeStrategy {
+
+    /// Union the sets, so the key has every value from both collections.
+    Union,
+
+    /// Keep the set from the collection that absorbs the merge.
+    KeepSelf,
+
+    /// Keep the set from the collection that is merged in.
+    KeepOther,
+
+}
@@ -0,0 +1,524 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::borrow::Borrow;
+use std::cmp::{Ord, Ordering};
+use std::iter::FromIterator;
+use std::ops::Bound;
+
+/// A map of composite keys `(K1, K2)` to sets of values, with queries by
+/// the first key component alone.
+///
+/// The pairs are stored flat in a `BTreeMap<(K1, K2), BTreeSet<V>>`, so
+/// every key with a given `K1` sorts into one contiguous run. A partial
+/// query is one range scan over that run, bounded by probes that sort
+/// just below and just above every `(k1, _)`, so it needs no minimum or
+/// maximum `K2` value and no clones.
+///
+/// # Examples
+///
+/// ```
+/// use sixarm_collections::*;
+/// let mut a: MultiKeyMapToSet<&str, u16, &str> = MultiKeyMapToSet::new();
+/// a.sub_insert(("alice", 2024), "rust");
+/// a.sub_insert(("alice", 2025), "zig");
+/// a.sub_insert(("bob", 2024), "go");
+/// let pairs: Vec<(&u16, &&str)> = a.sub_iter_k1(&"alice").collect();
+/// assert_eq!(pairs, vec![(&2024, &"rust"), (&2025, &"zig")]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MultiKeyMapToSet<K1, K2, V> {
+    map: BTreeMap<(K1, K2), BTreeSet<V>>,
+}
+
+/// The second part of a composite key, or a bound below or above every second part.
+enum Part<'a, K2> {
+    Below,
+    Exact(&'a K2),
+    Above,
+}
+
+/// A composite key, or a probe that compares like one, for lookups in the
+/// flat map without building an owned `(K1, K2)`.
+trait CompositeKey<K1, K2> {
+    fn parts(&self) -> (&K1, Part<'_, K2>);
+}
+
+impl<K1, K2> CompositeKey<K1, K2> for (K1, K2) {
+    fn parts(&self) -> (&K1, Part<'_, K2>) {
+        (&self.0, Part::Exact(&self.1))
+    }
+}
+
+/// A probe that borrows both parts of a composite key.
+struct Pair<'a, K1, K2>(&'a K1, &'a K2);
+
+impl<K1, K2> CompositeKey<K1, K2> for Pair<'_, K1, K2> {
+    fn parts(&self) -> (&K1, Part<'_, K2>) {
+        (self.0, Part::Exact(self.1))
+    }
+}
+
+/// A probe that sorts below or above every composite key with one first part.
+struct Edge<'a, K1> {
+    k1: &'a K1,
+    above: bool,
+}
+
+impl<K1, K2> CompositeKey<K1, K2> for Edge<'_, K1> {
+    fn parts(&self) -> (&K1, Part<'_, K2>) {
+        (self.k1, if self.above { Part::Above } else { Part::Below })
+    }
+}
+
+impl<'a, K1: 'a, K2: 'a> Borrow<dyn CompositeKey<K1, K2> + 'a> for (K1, K2) {
+    fn borrow(&self) -> &(dyn CompositeKey<K1, K2> + 'a) {
+        self
+    }
+}
+
+impl<K1: Ord, K2: Ord> Ord for dyn CompositeKey<K1, K2> + '_ {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let (a1, a2) = self.parts();
+        let (b1, b2) = other.parts();
+        a1.cmp(b1).then_with(|| match (a2, b2) {
+            (Part::Exact(a), Part::Exact(b)) => a.cmp(b),
+            (Part::Below, Part::Below) | (Part::Above, Part::Above) => Ordering::Equal,
+            (Part::Below, _) | (_, Part::Above) => Ordering::Less,
+            (Part::Above, _) | (_, Part::Below) => Ordering::Greater,
+        })
+    }
+}
+
+impl<K1: Ord, K2: Ord> PartialOrd for dyn CompositeKey<K1, K2> + '_ {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<K1: Ord, K2: Ord> PartialEq for dyn CompositeKey<K1, K2> + '_ {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<K1: Ord, K2: Ord> Eq for dyn CompositeKey<K1, K2> + '_ {}
+
+impl<K1, K2, V> MultiKeyMapToSet<K1, K2, V> {
+
+    /// Create an empty map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: MultiKeyMapToSet<u8, u8, u8> = MultiKeyMapToSet::new();
+    /// assert_eq!(a.is_empty(), true);
+    /// ```
+    #[inline]
+    pub fn new() -> Self {
+        MultiKeyMapToSet { map: BTreeMap::new() }
+    }
+
+    /// Return the number of composite keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: MultiKeyMapToSet<u8, u8, u8> = MultiKeyMapToSet::new();
+    /// a.sub_insert((1, 2), 3);
+    /// a.sub_insert((1, 2), 4);
+    /// assert_eq!(a.len(), 1);
+    /// ```
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Return `true` if the map has no keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: MultiKeyMapToSet<u8, u8, u8> = MultiKeyMapToSet::new();
+    /// assert_eq!(a.is_empty(), true);
+    /// ```
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Return the total number of values across all keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: MultiKeyMapToSet<u8, u8, u8> = MultiKeyMapToSet::new();
+    /// a.sub_insert((1, 2), 3);
+    /// a.sub_insert((1, 4), 5);
+    /// assert_eq!(a.sub_len(), 2);
+    /// ```
+    #[inline]
+    pub fn sub_len(&self) -> usize {
+        self.map.values().map(|set| set.len()).sum()
+    }
+
+    /// Return a reference to the underlying map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: MultiKeyMapToSet<u8, u8, u8> = MultiKeyMapToSet::new();
+    /// a.sub_insert((1, 2), 3);
+    /// assert_eq!(a.as_map().contains_key(&(1, 2)), true);
+    /// ```
+    #[inline]
+    pub fn as_map(&self) -> &BTreeMap<(K1, K2), BTreeSet<V>> {
+        &self.map
+    }
+
+    /// Return an iterator over every (key, value) pair, in key order then value order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: MultiKeyMapToSet<u8, u8, u8> = MultiKeyMapToSet::new();
+    /// a.sub_insert((2, 1), 5);
+    /// a.sub_insert((1, 9), 6);
+    /// let pairs: Vec<(&(u8, u8), &u8)> = a.sub_iter().collect();
+    /// assert_eq!(pairs, vec![(&(1, 9), &6), (&(2, 1), &5)]);
+    /// ```
+    #[inline]
+    pub fn sub_iter(&self) -> impl Iterator<Item = (&(K1, K2), &V)> {
+        self.map.iter()
+        .flat_map(|(key, set)| set.iter().map(move |value| (key, value)))
+    }
+
+}
+
+impl<K1, K2, V> MultiKeyMapToSet<K1, K2, V>
+where
+    K1: Ord,
+    K2: Ord,
+    V: Ord,
+{
+
+    /// Add a value to a key's set, and return `true` if the value was newly added.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: MultiKeyMapToSet<u8, u8, u8> = MultiKeyMapToSet::new();
+    /// assert_eq!(a.sub_insert((1, 2), 3), true);
+    /// assert_eq!(a.sub_insert((1, 2), 3), false);
+    /// ```
+    #[inline]
+    pub fn sub_insert(&mut self, key: (K1, K2), value: V) -> bool {
+        self.map.entry(key)
+        .or_default()
+        .insert(value)
+    }
+
+    /// Return the set for a composite key, given references to its parts.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: MultiKeyMapToSet<u8, u8, u8> = MultiKeyMapToSet::new();
+    /// a.sub_insert((1, 2), 3);
+    /// assert_eq!(a.get(&1, &2).map(|set| set.len()), Some(1));
+    /// assert_eq!(a.get(&1, &9), None);
+    /// ```
+    #[inline]
+    pub fn get(&self, k1: &K1, k2: &K2) -> Option<&BTreeSet<V>> {
+        self.map.get(&Pair(k1, k2) as &dyn CompositeKey<K1, K2>)
+    }
+
+    /// Return `true` if the map has a composite key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: MultiKeyMapToSet<u8, u8, u8> = MultiKeyMapToSet::new();
+    /// a.sub_insert((1, 2), 3);
+    /// assert_eq!(a.contains_key(&1, &2), true);
+    /// ```
+    #[inline]
+    pub fn contains_key(&self, k1: &K1, k2: &K2) -> bool {
+        self.get(k1, k2).is_some()
+    }
+
+    /// Return `true` if a composite key's set has a value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: MultiKeyMapToSet<u8, u8, u8> = MultiKeyMapToSet::new();
+    /// a.sub_insert((1, 2), 3);
+    /// assert_eq!(a.sub_contains(&1, &2, &3), true);
+    /// assert_eq!(a.sub_contains(&1, &2, &4), false);
+    /// ```
+    #[inline]
+    pub fn sub_contains<R>(&self, k1: &K1, k2: &K2, value: &R) -> bool
+    where
+        V: Borrow<R>,
+        R: Ord + ?Sized,
+    {
+        match self.get(k1, k2) {
+            Some(set) => set.contains(value),
+            None => false,
+        }
+    }
+
+    /// Remove a value from a composite key's set, and return `true` if it was present.
+    ///
+    /// The key stays in the map even when its set becomes empty;
+    /// use `sub_remove_prune` to also remove the key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: MultiKeyMapToSet<u8, u8, u8> = MultiKeyMapToSet::new();
+    /// a.sub_insert((1, 2), 3);
+    /// assert_eq!(a.sub_remove(&1, &2, &3), true);
+    /// assert_eq!(a.contains_key(&1, &2), true);
+    /// ```
+    #[inline]
+    pub fn sub_remove<R>(&mut self, k1: &K1, k2: &K2, value: &R) -> bool
+    where
+        V: Borrow<R>,
+        R: Ord + ?Sized,
+    {
+        match self.map.get_mut(&Pair(k1, k2) as &dyn CompositeKey<K1, K2>) {
+            Some(set) => set.remove(value),
+            None => false,
+        }
+    }
+
+    /// Remove a value from a composite key's set, and prune the key if its set becomes empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: MultiKeyMapToSet<u8, u8, u8> = MultiKeyMapToSet::new();
+    /// a.sub_insert((1, 2), 3);
+    /// assert_eq!(a.sub_remove_prune(&1, &2, &3), true);
+    /// assert_eq!(a.contains_key(&1, &2), false);
+    /// ```
+    #[inline]
+    pub fn sub_remove_prune<R>(&mut self, k1: &K1, k2: &K2, value: &R) -> bool
+    where
+        V: Borrow<R>,
+        R: Ord + ?Sized,
+    {
+        let probe = Pair(k1, k2);
+        let probe = &probe as &dyn CompositeKey<K1, K2>;
+        match self.map.get_mut(probe) {
+            Some(set) => {
+                let removed = set.remove(value);
+                if set.is_empty() {
+                    self.map.remove(probe);
+                }
+                removed
+            },
+            None => false,
+        }
+    }
+
+    /// Return an iterator over each second key part and its set, for one first key part.
+    ///
+    /// The iterator yields pairs in `K2` order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: MultiKeyMapToSet<u8, u8, u8> = MultiKeyMapToSet::new();
+    /// a.sub_insert((1, 3), 5);
+    /// a.sub_insert((1, 2), 6);
+    /// a.sub_insert((2, 1), 7);
+    /// let keys: Vec<&u8> = a.iter_k1(&1).map(|(k2, _)| k2).collect();
+    /// assert_eq!(keys, vec![&2, &3]);
+    /// ```
+    #[inline]
+    pub fn iter_k1<'a>(&'a self, k1: &'a K1) -> impl Iterator<Item = (&'a K2, &'a BTreeSet<V>)> {
+        let below = Edge { k1, above: false };
+        let above = Edge { k1, above: true };
+        self.map.range::<dyn CompositeKey<K1, K2>, _>((
+            Bound::Excluded(&below as &dyn CompositeKey<K1, K2>),
+            Bound::Excluded(&above as &dyn CompositeKey<K1, K2>),
+        ))
+        .map(|((_, k2), set)| (k2, set))
+    }
+
+    /// Return an iterator over every (second key part, value) pair for one first key part.
+    ///
+    /// The iterator yields pairs in `K2` order then value order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: MultiKeyMapToSet<u8, u8, u8> = MultiKeyMapToSet::new();
+    /// a.sub_insert((1, 2), 5);
+    /// a.sub_insert((1, 2), 4);
+    /// a.sub_insert((2, 1), 7);
+    /// let pairs: Vec<(&u8, &u8)> = a.sub_iter_k1(&1).collect();
+    /// assert_eq!(pairs, vec![(&2, &4), (&2, &5)]);
+    /// ```
+    #[inline]
+    pub fn sub_iter_k1<'a>(&'a self, k1: &'a K1) -> impl Iterator<Item = (&'a K2, &'a V)> {
+        self.iter_k1(k1)
+        .flat_map(|(k2, set)| set.iter().map(move |value| (k2, value)))
+    }
+
+    /// Return `true` if any composite key has a first key part.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: MultiKeyMapToSet<u8, u8, u8> = MultiKeyMapToSet::new();
+    /// a.sub_insert((1, 2), 3);
+    /// assert_eq!(a.contains_k1(&1), true);
+    /// assert_eq!(a.contains_k1(&2), false);
+    /// ```
+    #[inline]
+    pub fn contains_k1(&self, k1: &K1) -> bool {
+        self.iter_k1(k1).next().is_some()
+    }
+
+    /// Return the total number of values across the composite keys with a first key part.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: MultiKeyMapToSet<u8, u8, u8> = MultiKeyMapToSet::new();
+    /// a.sub_insert((1, 2), 3);
+    /// a.sub_insert((1, 4), 5);
+    /// a.sub_insert((6, 7), 8);
+    /// assert_eq!(a.sub_len_k1(&1), 2);
+    /// ```
+    #[inline]
+    pub fn sub_len_k1(&self, k1: &K1) -> usize {
+        self.iter_k1(k1).map(|(_, set)| set.len()).sum()
+    }
+
+    /// Remove every composite key with a first key part, and return the number of keys removed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: MultiKeyMapToSet<u8, u8, u8> = MultiKeyMapToSet::new();
+    /// a.sub_insert((1, 2), 3);
+    /// a.sub_insert((1, 4), 5);
+    /// a.sub_insert((6, 7), 8);
+    /// assert_eq!(a.remove_k1(&1), 2);
+    /// assert_eq!(a.len(), 1);
+    /// ```
+    #[inline]
+    pub fn remove_k1(&mut self, k1: &K1) -> usize {
+        let below = Edge { k1, above: false };
+        let above = Edge { k1, above: true };
+        let mut tail = self.map.split_off(&below as &dyn CompositeKey<K1, K2>);
+        let mut rest = tail.split_off(&above as &dyn CompositeKey<K1, K2>);
+        let removed = tail.len();
+        self.map.append(&mut rest);
+        removed
+    }
+
+}
+
+impl<K1, K2, V> Default for MultiKeyMapToSet<K1, K2, V> {
+    fn default() -> Self {
+        MultiKeyMapToSet::new()
+    }
+}
+
+impl<K1, K2, V> FromIterator<((K1, K2), V)> for MultiKeyMapToSet<K1, K2, V>
+where
+    K1: Ord,
+    K2: Ord,
+    V: Ord,
+{
+    fn from_iter<I: IntoIterator<Item = ((K1, K2), V)>>(iter: I) -> Self {
+        let mut map = MultiKeyMapToSet::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K1, K2, V> Extend<((K1, K2), V)> for MultiKeyMapToSet<K1, K2, V>
+where
+    K1: Ord,
+    K2: Ord,
+    V: Ord,
+{
+    fn extend<I: IntoIterator<Item = ((K1, K2), V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.sub_insert(key, value);
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+
+    fn subject() -> MultiKeyMapToSet<u8, i32, char> {
+        vec![((1, i32::MIN), 'a'), ((1, 0), 'b'), ((1, i32::MAX), 'c'), ((0, i32::MAX), 'd'), ((2, i32::MIN), 'e')].into_iter().collect()
+    }
+
+    #[test]
+    /// Test `sub_iter_k1` includes the extreme second key parts and excludes neighbouring first key parts.
+    fn test_sub_iter_k1_x_edges() {
+        let subject = subject();
+        let actual: Vec<(&i32, &char)> = subject.sub_iter_k1(&1).collect();
+        assert_eq!(actual, vec![(&i32::MIN, &'a'), (&0, &'b'), (&i32::MAX, &'c')]);
+        assert_eq!(subject.sub_iter_k1(&0).count(), 1);
+        assert_eq!(subject.sub_iter_k1(&3).count(), 0);
+        assert_eq!(subject.sub_len_k1(&1), 3);
+        assert_eq!(subject.contains_k1(&2), true);
+    }
+
+    #[test]
+    /// Test `remove_k1` removes only one first key part and keeps the rest.
+    fn test_remove_k1() {
+        let mut subject = subject();
+        assert_eq!(subject.remove_k1(&1), 3);
+        assert_eq!(subject.remove_k1(&1), 0);
+        let actual: Vec<(&(u8, i32), &char)> = subject.sub_iter().collect();
+        assert_eq!(actual, vec![(&(0, i32::MAX), &'d'), (&(2, i32::MIN), &'e')]);
+    }
+
+    #[test]
+    /// Test `get`, `sub_contains`, `sub_remove`, and `sub_remove_prune` with borrowed key parts.
+    fn test_sub_methods() {
+        let mut subject: MultiKeyMapToSet<String, String, u8> = MultiKeyMapToSet::new();
+        let k1 = String::from("alpha");
+        let k2 = String::from("bravo");
+        assert_eq!(subject.sub_insert((k1.clone(), k2.clone()), 1), true);
+        assert_eq!(subject.sub_insert((k1.clone(), k2.clone()), 1), false);
+        subject.sub_insert((k1.clone(), k2.clone()), 2);
+        assert_eq!(subject.get(&k1, &k2).map(|set| set.len()), Some(2));
+        assert_eq!(subject.sub_contains(&k1, &k2, &1), true);
+        assert_eq!(subject.sub_remove(&k1, &k2, &1), true);
+        assert_eq!(subject.contains_key(&k1, &k2), true);
+        assert_eq!(subject.sub_remove_prune(&k1, &k2, &2), true);
+        assert_eq!(subject.contains_key(&k1, &k2), false);
+        assert_eq!(subject.sub_len(), 0);
+    }
+
+}
@@ -0,0 +1,448 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::borrow::Borrow;
+use std::cmp::Eq;
+use std::hash::Hash;
+use std::iter::FromIterator;
+
+/// A multimap that remembers the global insertion order of its pairs.
+///
+/// Iteration yields pairs in the exact order they were inserted across
+/// all keys, and by-key lookups yield a key's values in their insertion
+/// order. Like `HashMapToVec`, the map keeps duplicate pairs.
+///
+/// The map owns an auxiliary sequence: each pair gets an increasing
+/// sequence number, the pairs are stored by sequence number, and each
+/// key indexes the sequence numbers of its pairs.
+///
+/// # Examples
+///
+/// ```
+/// use sixarm_collections::*;
+/// let mut a: OrderedMultiMap<&str, u8> = OrderedMultiMap::new();
+/// a.insert("b", 1);
+/// a.insert("a", 2);
+/// a.insert("b", 3);
+/// let pairs: Vec<(&&str, &u8)> = a.iter().collect();
+/// assert_eq!(pairs, vec![(&"b", &1), (&"a", &2), (&"b", &3)]);
+/// let values: Vec<&u8> = a.values_of("b").collect();
+/// assert_eq!(values, vec![&1, &3]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct OrderedMultiMap<K, V> {
+    pairs: BTreeMap<u64, (K, V)>,
+    index: HashMap<K, BTreeSet<u64>>,
+    next: u64,
+}
+
+impl<K, V> OrderedMultiMap<K, V> {
+
+    /// Create an empty map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: OrderedMultiMap<u8, u8> = OrderedMultiMap::new();
+    /// assert_eq!(a.is_empty(), true);
+    /// ```
+    #[inline]
+    pub fn new() -> Self {
+        OrderedMultiMap { pairs: BTreeMap::new(), index: HashMap::new(), next: 0 }
+    }
+
+    /// Return the number of pairs, including duplicates.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: OrderedMultiMap<u8, u8> = OrderedMultiMap::new();
+    /// a.insert(1, 2);
+    /// a.insert(1, 2);
+    /// assert_eq!(a.len(), 2);
+    /// ```
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.pairs.len()
+    }
+
+    /// Return `true` if the map has no pairs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: OrderedMultiMap<u8, u8> = OrderedMultiMap::new();
+    /// assert_eq!(a.is_empty(), true);
+    /// ```
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.pairs.is_empty()
+    }
+
+    /// Return the number of distinct keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: OrderedMultiMap<u8, u8> = OrderedMultiMap::new();
+    /// a.insert(1, 2);
+    /// a.insert(1, 3);
+    /// assert_eq!(a.keys_len(), 1);
+    /// ```
+    #[inline]
+    pub fn keys_len(&self) -> usize {
+        self.index.len()
+    }
+
+    /// Return an iterator over every pair in global insertion order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: OrderedMultiMap<u8, u8> = OrderedMultiMap::new();
+    /// a.insert(3, 4);
+    /// a.insert(1, 2);
+    /// let pairs: Vec<(&u8, &u8)> = a.iter().collect();
+    /// assert_eq!(pairs, vec![(&3, &4), (&1, &2)]);
+    /// ```
+    #[inline]
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (&K, &V)> {
+        self.pairs.values().map(|(key, value)| (key, value))
+    }
+
+    /// Return the oldest pair.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: OrderedMultiMap<u8, u8> = OrderedMultiMap::new();
+    /// a.insert(3, 4);
+    /// a.insert(1, 2);
+    /// assert_eq!(a.first(), Some((&3, &4)));
+    /// ```
+    #[inline]
+    pub fn first(&self) -> Option<(&K, &V)> {
+        self.pairs.values().next().map(|(key, value)| (key, value))
+    }
+
+    /// Return the newest pair.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: OrderedMultiMap<u8, u8> = OrderedMultiMap::new();
+    /// a.insert(3, 4);
+    /// a.insert(1, 2);
+    /// assert_eq!(a.last(), Some((&1, &2)));
+    /// ```
+    #[inline]
+    pub fn last(&self) -> Option<(&K, &V)> {
+        self.pairs.values().next_back().map(|(key, value)| (key, value))
+    }
+
+}
+
+impl<K, V> OrderedMultiMap<K, V>
+where
+    K: Hash + Eq,
+{
+
+    /// Append a pair after every existing pair.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: OrderedMultiMap<u8, u8> = OrderedMultiMap::new();
+    /// a.insert(1, 2);
+    /// assert_eq!(a.len(), 1);
+    /// ```
+    #[inline]
+    pub fn insert(&mut self, key: K, value: V)
+    where
+        K: Clone,
+    {
+        let seq = self.next;
+        self.next += 1;
+        self.index.entry(key.clone())
+        .or_default()
+        .insert(seq);
+        self.pairs.insert(seq, (key, value));
+    }
+
+    /// Return an iterator over the values of a key in insertion order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: OrderedMultiMap<u8, u8> = OrderedMultiMap::new();
+    /// a.insert(1, 3);
+    /// a.insert(4, 5);
+    /// a.insert(1, 2);
+    /// let values: Vec<&u8> = a.values_of(&1).collect();
+    /// assert_eq!(values, vec![&3, &2]);
+    /// ```
+    #[inline]
+    pub fn values_of<Q>(&self, key: &Q) -> impl Iterator<Item = &V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.index.get(key)
+        .into_iter()
+        .flatten()
+        .filter_map(move |seq| self.pairs.get(seq))
+        .map(|(_, value)| value)
+    }
+
+    /// Return the number of pairs for a key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: OrderedMultiMap<u8, u8> = OrderedMultiMap::new();
+    /// a.insert(1, 2);
+    /// a.insert(1, 2);
+    /// assert_eq!(a.len_of(&1), 2);
+    /// assert_eq!(a.len_of(&9), 0);
+    /// ```
+    #[inline]
+    pub fn len_of<Q>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.index.get(key).map_or(0, |seqs| seqs.len())
+    }
+
+    /// Return `true` if the map has a key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: OrderedMultiMap<u8, u8> = OrderedMultiMap::new();
+    /// a.insert(1, 2);
+    /// assert_eq!(a.contains_key(&1), true);
+    /// ```
+    #[inline]
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.index.contains_key(key)
+    }
+
+    /// Return `true` if the map has a pair, by scanning the key's values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: OrderedMultiMap<u8, u8> = OrderedMultiMap::new();
+    /// a.insert(1, 2);
+    /// assert_eq!(a.contains(&1, &2), true);
+    /// assert_eq!(a.contains(&1, &3), false);
+    /// ```
+    #[inline]
+    pub fn contains<Q>(&self, key: &Q, value: &V) -> bool
+    where
+        K: Borrow<Q>,
+        V: PartialEq,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.values_of(key).any(|x| x == value)
+    }
+
+    /// Remove every pair of a key, and return the values in insertion order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: OrderedMultiMap<u8, u8> = OrderedMultiMap::new();
+    /// a.insert(1, 3);
+    /// a.insert(4, 5);
+    /// a.insert(1, 2);
+    /// assert_eq!(a.remove_key(&1), vec![3, 2]);
+    /// assert_eq!(a.len(), 1);
+    /// ```
+    #[inline]
+    pub fn remove_key<Q>(&mut self, key: &Q) -> Vec<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        match self.index.remove(key) {
+            Some(seqs) => seqs.iter()
+            .filter_map(|seq| self.pairs.remove(seq))
+            .map(|(_, value)| value)
+            .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Remove the oldest pair and return it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: OrderedMultiMap<u8, u8> = OrderedMultiMap::new();
+    /// a.insert(3, 4);
+    /// a.insert(1, 2);
+    /// assert_eq!(a.pop_first(), Some((3, 4)));
+    /// assert_eq!(a.contains_key(&3), false);
+    /// ```
+    #[inline]
+    pub fn pop_first(&mut self) -> Option<(K, V)> {
+        let (seq, (key, value)) = self.pairs.pop_first()?;
+        self.unindex(&key, seq);
+        Some((key, value))
+    }
+
+    /// Remove the newest pair and return it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: OrderedMultiMap<u8, u8> = OrderedMultiMap::new();
+    /// a.insert(3, 4);
+    /// a.insert(1, 2);
+    /// assert_eq!(a.pop_last(), Some((1, 2)));
+    /// ```
+    #[inline]
+    pub fn pop_last(&mut self) -> Option<(K, V)> {
+        let (seq, (key, value)) = self.pairs.pop_last()?;
+        self.unindex(&key, seq);
+        Some((key, value))
+    }
+
+    /// Remove a sequence number from a key's index, and prune the key if it has no pairs left.
+    fn unindex(&mut self, key: &K, seq: u64) {
+        if let Some(seqs) = self.index.get_mut(key) {
+            seqs.remove(&seq);
+            if seqs.is_empty() {
+                self.index.remove(key);
+            }
+        }
+    }
+
+}
+
+impl<K, V> Default for OrderedMultiMap<K, V> {
+    fn default() -> Self {
+        OrderedMultiMap::new()
+    }
+}
+
+impl<K, V> PartialEq for OrderedMultiMap<K, V>
+where
+    K: PartialEq,
+    V: PartialEq,
+{
+    /// Return `true` if both maps have the same pairs in the same order.
+    fn eq(&self, other: &Self) -> bool {
+        self.iter().eq(other.iter())
+    }
+}
+
+impl<K, V> Eq for OrderedMultiMap<K, V>
+where
+    K: Eq,
+    V: Eq,
+{}
+
+impl<K, V> FromIterator<(K, V)> for OrderedMultiMap<K, V>
+where
+    K: Clone + Hash + Eq,
+{
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = OrderedMultiMap::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K, V> Extend<(K, V)> for OrderedMultiMap<K, V>
+where
+    K: Clone + Hash + Eq,
+{
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+
+    #[test]
+    /// Test `iter` yields pairs in global insertion order across keys, with duplicates.
+    fn test_iter_x_global_order() {
+        let subject: OrderedMultiMap<u8, char> = vec![(2, 'a'), (1, 'b'), (2, 'c'), (1, 'b')].into_iter().collect();
+        let actual: Vec<(&u8, &char)> = subject.iter().collect();
+        assert_eq!(actual, vec![(&2, &'a'), (&1, &'b'), (&2, &'c'), (&1, &'b')]);
+        let actual: Vec<(&u8, &char)> = subject.iter().rev().take(1).collect();
+        assert_eq!(actual, vec![(&1, &'b')]);
+        assert_eq!(subject.len(), 4);
+        assert_eq!(subject.keys_len(), 2);
+    }
+
+    #[test]
+    /// Test `values_of`, `len_of`, and `contains` by key.
+    fn test_values_of() {
+        let subject: OrderedMultiMap<u8, char> = vec![(2, 'a'), (1, 'b'), (2, 'c')].into_iter().collect();
+        let actual: Vec<&char> = subject.values_of(&2).collect();
+        assert_eq!(actual, vec![&'a', &'c']);
+        assert_eq!(subject.values_of(&9).count(), 0);
+        assert_eq!(subject.len_of(&2), 2);
+        assert_eq!(subject.contains(&2, &'c'), true);
+        assert_eq!(subject.contains(&2, &'b'), false);
+    }
+
+    #[test]
+    /// Test `remove_key` keeps the order of the other keys' pairs.
+    fn test_remove_key() {
+        let mut subject: OrderedMultiMap<u8, char> = vec![(2, 'a'), (1, 'b'), (2, 'c'), (3, 'd')].into_iter().collect();
+        assert_eq!(subject.remove_key(&2), vec!['a', 'c']);
+        assert_eq!(subject.remove_key(&2), vec![]);
+        let actual: Vec<(&u8, &char)> = subject.iter().collect();
+        assert_eq!(actual, vec![(&1, &'b'), (&3, &'d')]);
+    }
+
+    #[test]
+    /// Test `pop_first` and `pop_last` update the key index.
+    fn test_pop_first_and_pop_last() {
+        let mut subject: OrderedMultiMap<u8, char> = vec![(2, 'a'), (1, 'b'), (2, 'c')].into_iter().collect();
+        assert_eq!(subject.pop_first(), Some((2, 'a')));
+        assert_eq!(subject.len_of(&2), 1);
+        assert_eq!(subject.pop_last(), Some((2, 'c')));
+        assert_eq!(subject.contains_key(&2), false);
+        assert_eq!(subject.pop_first(), Some((1, 'b')));
+        assert_eq!(subject.pop_first(), None);
+        assert_eq!(subject.keys_len(), 0);
+    }
+
+    #[test]
+    /// Test `eq` compares order, not only contents.
+    fn test_eq_x_order() {
+        let a: OrderedMultiMap<u8, char> = vec![(1, 'a'), (2, 'b')].into_iter().collect();
+        let b: OrderedMultiMap<u8, char> = vec![(2, 'b'), (1, 'a')].into_iter().collect();
+        assert_eq!(a == a.clone(), true);
+        assert_eq!(a == b, false);
+    }
+
+}
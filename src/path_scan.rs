@@ -0,0 +1,1036 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::hash::Hash;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// A report of the paths that a scan skipped, each with the error that
+/// stopped it, in the order the scan met them.
+pub type PathErrors = Vec<(PathBuf, io::Error)>;
+
+/// Return the bytes freed by keeping one of a group of same-length
+/// files and removing the rest.
+pub(crate) fn savings(len: u64, count: usize) -> u64 {
+    len.saturating_mul(count.saturating_sub(1) as u64)
+}
+
+/// Return the modification time of a file in whole seconds since the
+/// Unix epoch, rounded down, so a time before the epoch is negative.
+///
+/// Return an error if the platform doesn't record modification times.
+pub(crate) fn mtime_secs(metadata: &fs::Metadata) -> io::Result<i64> {
+    Ok(match metadata.modified()?.duration_since(UNIX_EPOCH) {
+        Ok(after) => after.as_secs() as i64,
+        Err(before) => {
+            let before = before.duration();
+            -(before.as_secs() as i64) - i64::from(before.subsec_nanos() > 0)
+        },
+    })
+}
+
+/// Return the extension of a path in lowercase, or `None` if there is
+/// no extension.
+pub(crate) fn extension_key(path: &Path) -> Option<String> {
+    path.extension().map(|extension| extension.to_string_lossy().to_lowercase())
+}
+
+/// How a walk or an insertion handles a symbolic link.
+///
+/// # Examples
+///
+/// ```
+/// use sixarm_collections::*;
+/// let options = WalkOptions::new().symlinks(SymlinkPolicy::Record);
+/// assert_eq!(options.get_symlinks(), SymlinkPolicy::Record);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum SymlinkPolicy {
+    /// Follow a link to its target, so a link to a file counts with the
+    /// target's metadata, and a link to a directory is walked.
+    Follow,
+    /// Skip a link entirely.
+    #[default]
+    Skip,
+    /// Record the link itself, with its own metadata from
+    /// `fs::symlink_metadata`, and don't walk through it.
+    Record,
+}
+
+/// Return the device and inode of a file, which hard links share, or
+/// `None` on a platform that doesn't expose them.
+#[cfg(any(feature = "blake3", feature = "sha2"))]
+pub(crate) fn file_id(metadata: &fs::Metadata) -> Option<(u64, u64)> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        Some((metadata.dev(), metadata.ino()))
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = metadata;
+        None
+    }
+}
+
+/// Return `true` if a path no longer exists, even as a broken symbolic link.
+///
+/// Any error other than not found, such as a permission error, means
+/// the path may still exist, so it returns `false`.
+pub(crate) fn is_missing(path: &Path) -> bool {
+    fs::symlink_metadata(path).is_err_and(|err| err.kind() == io::ErrorKind::NotFound)
+}
+
+/// A map of keys to sets of paths, which can drop paths and empty sets.
+pub(crate) trait PathSetMap {
+    /// Keep the paths that a predicate accepts, and remove each key
+    /// whose set becomes empty.
+    fn retain_paths<F: FnMut(&PathBuf) -> bool>(&mut self, f: F);
+}
+
+impl<K: Hash + Eq> PathSetMap for HashMap<K, HashSet<PathBuf>> {
+    fn retain_paths<F: FnMut(&PathBuf) -> bool>(&mut self, mut f: F) {
+        self.retain(|_, paths| {
+            paths.retain(&mut f);
+            !paths.is_empty()
+        });
+    }
+}
+
+impl<K: Ord> PathSetMap for BTreeMap<K, BTreeSet<PathBuf>> {
+    fn retain_paths<F: FnMut(&PathBuf) -> bool>(&mut self, mut f: F) {
+        self.retain(|_, paths| {
+            paths.retain(&mut f);
+            !paths.is_empty()
+        });
+    }
+}
+
+/// Remove every path whose file no longer exists from a map, and each
+/// key whose set becomes empty, and return the removed paths, sorted.
+///
+/// A path whose metadata can't be read for another reason, such as a
+/// permission error, stays, as in `is_missing`.
+pub(crate) fn prune_stale<M: PathSetMap>(map: &mut M) -> Vec<PathBuf> {
+    let mut removed = Vec::new();
+    map.retain_paths(|path| {
+        let missing = is_missing(path);
+        if missing {
+            removed.push(path.clone());
+        }
+        !missing
+    });
+    removed.sort();
+    removed
+}
+
+/// A path predicate for `WalkOptions`.
+type PathPredicate = Box<dyn Fn(&Path) -> bool + Send + Sync>;
+
+/// A path and metadata predicate for `WalkOptions`.
+type MetadataPredicate = Box<dyn Fn(&Path, &fs::Metadata) -> bool + Send + Sync>;
+
+/// Return `true` if the final component of a path starts with a dot.
+fn is_hidden(path: &Path) -> bool {
+    path.file_name().is_some_and(|name| name.to_string_lossy().starts_with('.'))
+}
+
+/// The options for a recursive walk of a directory tree.
+///
+/// The default walk has no depth limit, skips symbolic links, includes
+/// every regular file, and excludes nothing.
+///
+/// A root that is a symbolic link is always followed, like `find -H`,
+/// because the caller named it: a link to a directory is walked, and a
+/// link to a file counts with the target's metadata, except that
+/// `SymlinkPolicy::Record` records a link to a file as the link itself.
+///
+/// The root is at depth 0 and its entries are at depth 1, so a walk
+/// with a max depth of 1 reads the root directory and no subdirectory.
+///
+/// An exclude predicate skips any matching path, and a matching
+/// directory is not walked at all. Include predicates apply only to
+/// regular files, and to links that the walk records: when there are
+/// any, a file must match at least one.
+///
+/// A walk that skips hidden paths skips each file and directory below
+/// the root whose name starts with a dot. A filter gets the path and
+/// metadata of each file and directory, including the root, and skips
+/// any that it rejects, so a rejected directory is not walked at all.
+///
+/// A min length and a max length, both inclusive, skip each file whose
+/// length is outside them, without reading its content.
+///
+/// # Examples
+///
+/// ```
+/// use sixarm_collections::*;
+/// let options = WalkOptions::new()
+/// .max_depth(3)
+/// .follow_symlinks(true)
+/// .include(|path| path.extension().is_some_and(|ext| ext == "txt"))
+/// .exclude(|path| path.ends_with(".git"));
+/// assert_eq!(options.is_included(std::path::Path::new("alpha.txt")), true);
+/// ```
+#[derive(Default)]
+pub struct WalkOptions {
+    max_depth: Option<usize>,
+    symlinks: SymlinkPolicy,
+    includes: Vec<PathPredicate>,
+    excludes: Vec<PathPredicate>,
+    skip_hidden: bool,
+    filters: Vec<MetadataPredicate>,
+    min_len: Option<u64>,
+    max_len: Option<u64>,
+}
+
+impl WalkOptions {
+
+    /// Create the default options.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let options = WalkOptions::new();
+    /// assert_eq!(options.get_max_depth(), None);
+    /// ```
+    #[inline]
+    pub fn new() -> Self {
+        WalkOptions::default()
+    }
+
+    /// Set the deepest level to walk, where the root's entries are at depth 1.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let options = WalkOptions::new().max_depth(1);
+    /// assert_eq!(options.get_max_depth(), Some(1));
+    /// ```
+    #[inline]
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = Some(depth);
+        self
+    }
+
+    /// Set whether to follow symbolic links to files and directories,
+    /// which is `SymlinkPolicy::Follow` for `true` and
+    /// `SymlinkPolicy::Skip` for `false`.
+    ///
+    /// A walk that follows links visits each directory at most once, so
+    /// a link cycle does not loop.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let options = WalkOptions::new().follow_symlinks(true);
+    /// assert_eq!(options.get_follow_symlinks(), true);
+    /// ```
+    #[inline]
+    pub fn follow_symlinks(mut self, follow: bool) -> Self {
+        self.symlinks = if follow { SymlinkPolicy::Follow } else { SymlinkPolicy::Skip };
+        self
+    }
+
+    /// Set how to handle symbolic links.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let options = WalkOptions::new().symlinks(SymlinkPolicy::Follow);
+    /// assert_eq!(options.get_follow_symlinks(), true);
+    /// ```
+    #[inline]
+    pub fn symlinks(mut self, policy: SymlinkPolicy) -> Self {
+        self.symlinks = policy;
+        self
+    }
+
+    /// Add an include predicate for regular files.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// use std::path::Path;
+    /// let options = WalkOptions::new().include(|path| path.ends_with("alpha.txt"));
+    /// assert_eq!(options.is_included(Path::new("alpha.txt")), true);
+    /// assert_eq!(options.is_included(Path::new("bravo.txt")), false);
+    /// ```
+    #[inline]
+    pub fn include<F>(mut self, predicate: F) -> Self
+    where
+        F: Fn(&Path) -> bool + Send + Sync + 'static,
+    {
+        self.includes.push(Box::new(predicate));
+        self
+    }
+
+    /// Add an exclude predicate for files and directories.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// use std::path::Path;
+    /// let options = WalkOptions::new().exclude(|path| path.ends_with("target"));
+    /// assert_eq!(options.is_excluded(Path::new("target")), true);
+    /// ```
+    #[inline]
+    pub fn exclude<F>(mut self, predicate: F) -> Self
+    where
+        F: Fn(&Path) -> bool + Send + Sync + 'static,
+    {
+        self.excludes.push(Box::new(predicate));
+        self
+    }
+
+    /// Add an include predicate that matches a glob pattern, such as
+    /// "*.txt", against the whole path.
+    ///
+    /// Return an error if the pattern is invalid.
+    ///
+    /// This method needs the `globset` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// use std::path::Path;
+    /// let options = WalkOptions::new().include_glob("*.txt").unwrap();
+    /// assert_eq!(options.is_included(Path::new("one/alpha.txt")), true);
+    /// assert_eq!(options.is_included(Path::new("one/alpha.md")), false);
+    /// ```
+    #[cfg(feature = "globset")]
+    #[inline]
+    pub fn include_glob(self, pattern: &str) -> Result<Self, globset::Error> {
+        let matcher = globset::Glob::new(pattern)?.compile_matcher();
+        Ok(self.include(move |path| matcher.is_match(path)))
+    }
+
+    /// Add an exclude predicate that matches a glob pattern, such as
+    /// "**/target", against the whole path.
+    ///
+    /// Return an error if the pattern is invalid.
+    ///
+    /// This method needs the `globset` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// use std::path::Path;
+    /// let options = WalkOptions::new().exclude_glob("**/target").unwrap();
+    /// assert_eq!(options.is_excluded(Path::new("one/target")), true);
+    /// assert_eq!(options.is_excluded(Path::new("one/targets")), false);
+    /// ```
+    #[cfg(feature = "globset")]
+    #[inline]
+    pub fn exclude_glob(self, pattern: &str) -> Result<Self, globset::Error> {
+        let matcher = globset::Glob::new(pattern)?.compile_matcher();
+        Ok(self.exclude(move |path| matcher.is_match(path)))
+    }
+
+    /// Set whether to skip hidden files and directories, whose names
+    /// start with a dot.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let options = WalkOptions::new().skip_hidden(true);
+    /// assert_eq!(options.get_skip_hidden(), true);
+    /// ```
+    #[inline]
+    pub fn skip_hidden(mut self, skip: bool) -> Self {
+        self.skip_hidden = skip;
+        self
+    }
+
+    /// Add a filter of the path and metadata of files and directories.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// # std::fs::write("walk_options_filter.txt", "alpha");
+    /// let options = WalkOptions::new().filter(|_, metadata| !metadata.permissions().readonly());
+    /// let path = std::path::Path::new("walk_options_filter.txt");
+    /// assert_eq!(options.passes_filters(path, &std::fs::metadata(path)?), true);
+    /// # std::fs::remove_file("walk_options_filter.txt");
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[inline]
+    pub fn filter<F>(mut self, predicate: F) -> Self
+    where
+        F: Fn(&Path, &fs::Metadata) -> bool + Send + Sync + 'static,
+    {
+        self.filters.push(Box::new(predicate));
+        self
+    }
+
+    /// Set the least length of a file to add, inclusive.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let options = WalkOptions::new().min_len(4096);
+    /// assert_eq!(options.is_len_allowed(4095), false);
+    /// assert_eq!(options.is_len_allowed(4096), true);
+    /// ```
+    #[inline]
+    pub fn min_len(mut self, len: u64) -> Self {
+        self.min_len = Some(len);
+        self
+    }
+
+    /// Set the greatest length of a file to add, inclusive.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let options = WalkOptions::new().max_len(4096);
+    /// assert_eq!(options.is_len_allowed(4096), true);
+    /// assert_eq!(options.is_len_allowed(4097), false);
+    /// ```
+    #[inline]
+    pub fn max_len(mut self, len: u64) -> Self {
+        self.max_len = Some(len);
+        self
+    }
+
+    /// Return the deepest level to walk, or `None` for no limit.
+    #[inline]
+    pub fn get_max_depth(&self) -> Option<usize> {
+        self.max_depth
+    }
+
+    /// Return whether the walk follows symbolic links.
+    #[inline]
+    pub fn get_follow_symlinks(&self) -> bool {
+        self.symlinks == SymlinkPolicy::Follow
+    }
+
+    /// Return how the walk handles symbolic links.
+    #[inline]
+    pub fn get_symlinks(&self) -> SymlinkPolicy {
+        self.symlinks
+    }
+
+    /// Return whether the walk skips hidden files and directories.
+    #[inline]
+    pub fn get_skip_hidden(&self) -> bool {
+        self.skip_hidden
+    }
+
+    /// Return the least length of a file to add, or `None` for no limit.
+    #[inline]
+    pub fn get_min_len(&self) -> Option<u64> {
+        self.min_len
+    }
+
+    /// Return the greatest length of a file to add, or `None` for no limit.
+    #[inline]
+    pub fn get_max_len(&self) -> Option<u64> {
+        self.max_len
+    }
+
+    /// Return `true` if a file length is within the min length and the max length.
+    #[inline]
+    pub fn is_len_allowed(&self, len: u64) -> bool {
+        self.min_len.map_or(true, |min| len >= min) && self.max_len.map_or(true, |max| len <= max)
+    }
+
+    /// Return `true` if a path and its metadata pass every filter.
+    #[inline]
+    pub fn passes_filters(&self, path: &Path, metadata: &fs::Metadata) -> bool {
+        self.filters.iter().all(|predicate| predicate(path, metadata))
+    }
+
+    /// Return `true` if a regular file passes the include predicates.
+    #[inline]
+    pub fn is_included(&self, path: &Path) -> bool {
+        self.includes.is_empty() || self.includes.iter().any(|predicate| predicate(path))
+    }
+
+    /// Return `true` if a path matches any exclude predicate.
+    #[inline]
+    pub fn is_excluded(&self, path: &Path) -> bool {
+        self.excludes.iter().any(|predicate| predicate(path))
+    }
+
+}
+
+impl fmt::Debug for WalkOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WalkOptions")
+        .field("max_depth", &self.max_depth)
+        .field("symlinks", &self.symlinks)
+        .field("includes", &self.includes.len())
+        .field("excludes", &self.excludes.len())
+        .field("skip_hidden", &self.skip_hidden)
+        .field("filters", &self.filters.len())
+        .field("min_len", &self.min_len)
+        .field("max_len", &self.max_len)
+        .finish()
+    }
+}
+
+/// Read a directory, not recursively, and call a function with the
+/// path and metadata of each regular file, and of each symbolic link
+/// that the policy follows or records.
+///
+/// Return an error if the directory itself can't be read. Return the
+/// entries that can't be read as a report, and keep scanning past them.
+pub(crate) fn scan_dir<F>(dir: &Path, policy: SymlinkPolicy, mut f: F) -> io::Result<PathErrors>
+where
+    F: FnMut(PathBuf, &fs::Metadata),
+{
+    let mut errors = PathErrors::new();
+    for entry in fs::read_dir(dir)? {
+        let path = match entry {
+            Ok(entry) => entry.path(),
+            Err(err) => {
+                errors.push((dir.to_path_buf(), err));
+                continue;
+            },
+        };
+        match metadata(&path, policy) {
+            Ok(metadata) if is_file(&metadata, policy) => f(path, &metadata),
+            Ok(_) => {},
+            Err(err) => errors.push((path, err)),
+        }
+    }
+    Ok(errors)
+}
+
+/// Read the metadata of a path, following a symbolic link or not.
+fn metadata(path: &Path, policy: SymlinkPolicy) -> io::Result<fs::Metadata> {
+    if policy == SymlinkPolicy::Follow {
+        fs::metadata(path)
+    } else {
+        fs::symlink_metadata(path)
+    }
+}
+
+/// Read the metadata that a map keys a path by, for a lookup with
+/// options: a path's own metadata when the policy records links, which
+/// is how a walk or an insertion keyed a link, or else the target's.
+pub(crate) fn lookup_metadata(path: &Path, options: &WalkOptions) -> io::Result<fs::Metadata> {
+    if options.symlinks == SymlinkPolicy::Record {
+        fs::symlink_metadata(path)
+    } else {
+        fs::metadata(path)
+    }
+}
+
+/// Return `true` if metadata is of a file to add: a regular file, or a
+/// symbolic link that the policy records.
+fn is_file(metadata: &fs::Metadata, policy: SymlinkPolicy) -> bool {
+    metadata.is_file() || (policy == SymlinkPolicy::Record && metadata.file_type().is_symlink())
+}
+
+/// Check one path against the options, for an insertion.
+///
+/// Return the metadata if the path is a file that the options allow,
+/// or `None` if the options skip it. Return an error if the metadata
+/// can't be read.
+pub(crate) fn admit_path(path: &Path, options: &WalkOptions) -> io::Result<Option<fs::Metadata>> {
+    if options.is_excluded(path) || (options.skip_hidden && is_hidden(path)) {
+        return Ok(None);
+    }
+    let metadata = metadata(path, options.symlinks)?;
+    if is_file(&metadata, options.symlinks) && options.is_len_allowed(metadata.len()) && options.is_included(path) && options.passes_filters(path, &metadata) {
+        Ok(Some(metadata))
+    } else {
+        Ok(None)
+    }
+}
+
+/// One entry of a directory that a walk reads.
+enum DirEntry {
+    /// A file that the options allow, with its metadata.
+    File(PathBuf, fs::Metadata),
+    /// A subdirectory to walk, with its canonical path when the walk
+    /// follows symbolic links.
+    Dir(PathBuf, Option<PathBuf>),
+    /// An entry that can't be read.
+    Error(PathBuf, io::Error),
+}
+
+/// The entries of one directory that a walk reads.
+#[derive(Default)]
+struct DirEntries {
+    /// The files that the options allow, with their metadata.
+    files: Vec<(PathBuf, fs::Metadata)>,
+    /// The subdirectories to walk, each with its canonical path when
+    /// the walk follows symbolic links.
+    dirs: Vec<(PathBuf, Option<PathBuf>)>,
+    /// The entries that can't be read.
+    errors: PathErrors,
+}
+
+impl DirEntries {
+
+    /// Add one entry to its list.
+    fn push(&mut self, entry: DirEntry) {
+        match entry {
+            DirEntry::File(path, metadata) => self.files.push((path, metadata)),
+            DirEntry::Dir(path, canonical) => self.dirs.push((path, canonical)),
+            DirEntry::Error(path, err) => self.errors.push((path, err)),
+        }
+    }
+
+}
+
+/// List the paths in one directory, not recursively, without reading
+/// their metadata.
+///
+/// Return an error if the directory can't be read. Return the entries
+/// that can't be listed as a report.
+fn list_dir(dir: &Path) -> io::Result<(Vec<PathBuf>, PathErrors)> {
+    let mut paths = Vec::new();
+    let mut errors = PathErrors::new();
+    for entry in fs::read_dir(dir)? {
+        match entry {
+            Ok(entry) => paths.push(entry.path()),
+            Err(err) => errors.push((dir.to_path_buf(), err)),
+        }
+    }
+    Ok((paths, errors))
+}
+
+/// Read the metadata of one path in a directory for a walk.
+///
+/// Return `None` if the options skip the path.
+fn read_dir_entry(path: PathBuf, options: &WalkOptions) -> Option<DirEntry> {
+    if options.is_excluded(&path) || (options.skip_hidden && is_hidden(&path)) {
+        return None;
+    }
+    match metadata(&path, options.symlinks) {
+        Ok(metadata) if !options.passes_filters(&path, &metadata) => None,
+        Ok(metadata) if is_file(&metadata, options.symlinks) => {
+            if options.is_len_allowed(metadata.len()) && options.is_included(&path) {
+                Some(DirEntry::File(path, metadata))
+            } else {
+                None
+            }
+        },
+        Ok(metadata) if metadata.is_dir() => {
+            if options.symlinks == SymlinkPolicy::Follow {
+                match fs::canonicalize(&path) {
+                    Ok(canonical) => Some(DirEntry::Dir(path, Some(canonical))),
+                    Err(err) => Some(DirEntry::Error(path, err)),
+                }
+            } else {
+                Some(DirEntry::Dir(path, None))
+            }
+        },
+        Ok(_) => None,
+        Err(err) => Some(DirEntry::Error(path, err)),
+    }
+}
+
+/// Read the entries of one directory for a walk.
+///
+/// Return an error if the directory can't be read.
+fn read_dir_entries(dir: &Path, options: &WalkOptions) -> io::Result<DirEntries> {
+    let (paths, errors) = list_dir(dir)?;
+    let mut entries = DirEntries { errors, ..DirEntries::default() };
+    for entry in paths.into_iter().filter_map(|path| read_dir_entry(path, options)) {
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+/// Read the metadata of the root of a walk, which follows a symbolic
+/// link whatever the policy, except that a policy to record links keeps
+/// a link that isn't to a directory.
+fn root_metadata(root: &Path, policy: SymlinkPolicy) -> io::Result<fs::Metadata> {
+    let link = fs::symlink_metadata(root)?;
+    if !link.file_type().is_symlink() {
+        return Ok(link);
+    }
+    match fs::metadata(root) {
+        Ok(target) if target.is_dir() || policy != SymlinkPolicy::Record => Ok(target),
+        Err(err) if policy != SymlinkPolicy::Record => Err(err),
+        _ => Ok(link),
+    }
+}
+
+/// Check the root of a walk.
+///
+/// Return `true` if the root is a directory to walk. Otherwise, if the
+/// root is a regular file that the options allow, then call the
+/// function with it.
+fn walk_root<F>(root: &Path, options: &WalkOptions, f: &mut F) -> io::Result<bool>
+where
+    F: FnMut(PathBuf, &fs::Metadata),
+{
+    let root_metadata = root_metadata(root, options.symlinks)?;
+    if options.is_excluded(root) || !options.passes_filters(root, &root_metadata) {
+        return Ok(false);
+    }
+    if is_file(&root_metadata, options.symlinks) && options.is_len_allowed(root_metadata.len()) && options.is_included(root) {
+        f(root.to_path_buf(), &root_metadata);
+    }
+    Ok(root_metadata.is_dir())
+}
+
+/// Merge the entries of one directory into a walk.
+///
+/// Call the function with each file, add each subdirectory that the
+/// walk has not visited to the next directories, and keep the errors.
+fn merge_dir_entries<F>(
+    entries: DirEntries,
+    depth: usize,
+    visited: &mut HashSet<PathBuf>,
+    next: &mut Vec<(PathBuf, usize)>,
+    errors: &mut PathErrors,
+    f: &mut F,
+)
+where
+    F: FnMut(PathBuf, &fs::Metadata),
+{
+    for (path, metadata) in entries.files {
+        f(path, &metadata);
+    }
+    for (path, canonical) in entries.dirs {
+        if canonical.map_or(true, |canonical| visited.insert(canonical)) {
+            next.push((path, depth + 1));
+        }
+    }
+    errors.extend(entries.errors);
+}
+
+/// Walk a directory tree, and call a function with the path and metadata
+/// of each regular file that the options allow.
+///
+/// If the root is a regular file, then the walk calls the function with
+/// the root alone. Return an error if the root can't be read. Return the
+/// paths below the root that can't be read as a report, and keep walking
+/// past them.
+pub(crate) fn walk_tree<F>(root: &Path, options: &WalkOptions, mut f: F) -> io::Result<PathErrors>
+where
+    F: FnMut(PathBuf, &fs::Metadata),
+{
+    let mut errors = PathErrors::new();
+    let mut visited: HashSet<PathBuf> = HashSet::new();
+    if !walk_root(root, options, &mut f)? {
+        return Ok(errors);
+    }
+    if options.symlinks == SymlinkPolicy::Follow {
+        visited.insert(fs::canonicalize(root)?);
+    }
+    let mut stack: Vec<(PathBuf, usize)> = vec![(root.to_path_buf(), 0)];
+    while let Some((dir, depth)) = stack.pop() {
+        if options.max_depth.is_some_and(|max| depth >= max) {
+            continue;
+        }
+        match read_dir_entries(&dir, options) {
+            Ok(entries) => merge_dir_entries(entries, depth, &mut visited, &mut stack, &mut errors, &mut f),
+            Err(err) if depth == 0 => return Err(err),
+            Err(err) => errors.push((dir, err)),
+        }
+    }
+    Ok(errors)
+}
+
+/// Walk a directory tree like `walk_tree`, and read the directories of
+/// each depth in parallel, then the metadata of all their entries in
+/// parallel, so a large flat directory is spread across threads too.
+///
+/// The function runs on the calling thread, so it may mutate a collection.
+#[cfg(feature = "rayon")]
+pub(crate) fn par_walk_tree<F>(root: &Path, options: &WalkOptions, mut f: F) -> io::Result<PathErrors>
+where
+    F: FnMut(PathBuf, &fs::Metadata),
+{
+    use rayon::prelude::*;
+    let mut errors = PathErrors::new();
+    let mut visited: HashSet<PathBuf> = HashSet::new();
+    if !walk_root(root, options, &mut f)? {
+        return Ok(errors);
+    }
+    if options.symlinks == SymlinkPolicy::Follow {
+        visited.insert(fs::canonicalize(root)?);
+    }
+    let mut depth = 0;
+    let mut level: Vec<PathBuf> = vec![root.to_path_buf()];
+    while !level.is_empty() && options.max_depth.map_or(true, |max| depth < max) {
+        let listed: Vec<_> = level.into_par_iter()
+        .map(|dir| {
+            let listed = list_dir(&dir);
+            (dir, listed)
+        })
+        .collect();
+        let mut paths: Vec<PathBuf> = Vec::new();
+        for (dir, listed) in listed {
+            match listed {
+                Ok((dir_paths, dir_errors)) => {
+                    paths.extend(dir_paths);
+                    errors.extend(dir_errors);
+                },
+                Err(err) if depth == 0 => return Err(err),
+                Err(err) => errors.push((dir, err)),
+            }
+        }
+        let mut entries = DirEntries::default();
+        for entry in paths.into_par_iter().filter_map(|path| read_dir_entry(path, options)).collect::<Vec<DirEntry>>() {
+            entries.push(entry);
+        }
+        let mut next: Vec<(PathBuf, usize)> = Vec::new();
+        merge_dir_entries(entries, depth, &mut visited, &mut next, &mut errors, &mut f);
+        level = next.into_iter().map(|(dir, _)| dir).collect();
+        depth += 1;
+    }
+    Ok(errors)
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+
+    #[test]
+    /// Test `scan_dir` finds the regular files of a directory.
+    fn test_scan_dir() {
+        let dir: PathBuf = [env!("CARGO_MANIFEST_DIR"), "test", "hash_map_of_file_len_to_set_of_path_buf"].iter().collect();
+        let mut actual: Vec<(PathBuf, u64)> = Vec::new();
+        let errors = scan_dir(&dir, SymlinkPolicy::Follow, |path, metadata| actual.push((path, metadata.len()))).unwrap();
+        actual.sort();
+        assert_eq!(actual, vec![(dir.join("alpha.txt"), 5), (dir.join("bravo.txt"), 5)]);
+        assert_eq!(errors.is_empty(), true);
+    }
+
+    #[test]
+    /// Test `mtime_secs` after and before the epoch.
+    fn test_mtime_secs() {
+        use std::time::Duration;
+        let path = std::env::temp_dir().join(format!("path_scan_mtime_secs_{}", std::process::id()));
+        let file = fs::File::create(&path).unwrap();
+        file.set_modified(UNIX_EPOCH + Duration::from_millis(1_500)).unwrap();
+        let after = mtime_secs(&file.metadata().unwrap()).unwrap();
+        file.set_modified(UNIX_EPOCH - Duration::from_millis(1_500)).unwrap();
+        let before = mtime_secs(&file.metadata().unwrap()).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(after, 1);
+        assert_eq!(before, -2);
+    }
+
+    #[test]
+    /// Test `is_missing` with a file, a missing path, and a broken symbolic link.
+    fn test_is_missing() {
+        let dir = std::env::temp_dir().join(format!("path_scan_is_missing_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("alpha.txt"), "alpha").unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(dir.join("missing.txt"), dir.join("broken")).unwrap();
+        assert_eq!(is_missing(&dir.join("alpha.txt")), false);
+        assert_eq!(is_missing(&dir.join("missing.txt")), true);
+        #[cfg(unix)]
+        assert_eq!(is_missing(&dir.join("broken")), false);
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(is_missing(&dir.join("alpha.txt")), true);
+    }
+
+    #[test]
+    /// Test `scan_dir` with a missing directory.
+    fn test_scan_dir_x_missing() {
+        let dir: PathBuf = [env!("CARGO_MANIFEST_DIR"), "test", "missing"].iter().collect();
+        let err = scan_dir(&dir, SymlinkPolicy::Follow, |_, _| {}).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    }
+
+    /// Create a fresh directory tree for a test, and return its root.
+    ///
+    /// The tree is `alpha.txt`, `one/bravo.txt`, `one/two/charlie.md`.
+    fn make_tree(name: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("path_scan_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("one").join("two")).unwrap();
+        fs::write(root.join("alpha.txt"), "alpha").unwrap();
+        fs::write(root.join("one").join("bravo.txt"), "bravo").unwrap();
+        fs::write(root.join("one").join("two").join("charlie.md"), "charlie").unwrap();
+        root
+    }
+
+    /// Walk a tree and return the sorted file paths relative to the root.
+    fn walk_names(root: &Path, options: &WalkOptions) -> Vec<PathBuf> {
+        let mut actual: Vec<PathBuf> = Vec::new();
+        let errors = walk_tree(root, options, |path, _| actual.push(path.strip_prefix(root).unwrap().to_path_buf())).unwrap();
+        assert_eq!(errors.is_empty(), true);
+        actual.sort();
+        actual
+    }
+
+    #[test]
+    /// Test `walk_tree` with the default options, a max depth, and predicates.
+    fn test_walk_tree() {
+        let root = make_tree("walk_tree");
+        let all = walk_names(&root, &WalkOptions::new());
+        let depth_1 = walk_names(&root, &WalkOptions::new().max_depth(1));
+        let depth_0 = walk_names(&root, &WalkOptions::new().max_depth(0));
+        let txt = walk_names(&root, &WalkOptions::new().include(|path| path.extension().is_some_and(|ext| ext == "txt")));
+        let not_two = walk_names(&root, &WalkOptions::new().exclude(|path| path.ends_with("two")));
+        fs::remove_dir_all(&root).unwrap();
+        assert_eq!(all, vec![PathBuf::from("alpha.txt"), ["one", "bravo.txt"].iter().collect(), ["one", "two", "charlie.md"].iter().collect()]);
+        assert_eq!(depth_1, vec![PathBuf::from("alpha.txt")]);
+        assert_eq!(depth_0, Vec::<PathBuf>::new());
+        assert_eq!(txt, vec![PathBuf::from("alpha.txt"), ["one", "bravo.txt"].iter().collect()]);
+        assert_eq!(not_two, vec![PathBuf::from("alpha.txt"), ["one", "bravo.txt"].iter().collect()]);
+    }
+
+    #[test]
+    /// Test `walk_tree` with hidden paths and metadata filters.
+    fn test_walk_tree_x_filters() {
+        let root = make_tree("walk_tree_x_filters");
+        fs::create_dir_all(root.join(".hidden")).unwrap();
+        fs::write(root.join(".hidden").join("delta.txt"), "delta").unwrap();
+        fs::write(root.join(".echo.txt"), "echo").unwrap();
+        let all = walk_names(&root, &WalkOptions::new());
+        let not_hidden = walk_names(&root, &WalkOptions::new().skip_hidden(true));
+        let short = walk_names(&root, &WalkOptions::new().filter(|_, metadata| metadata.is_dir() || metadata.len() < 6));
+        let not_one = walk_names(&root, &WalkOptions::new().filter(|path, _| !path.ends_with("one")));
+        let rejected_root = walk_names(&root, &WalkOptions::new().filter(|_, metadata| !metadata.is_dir()));
+        let admitted = admit_path(&root.join(".echo.txt"), &WalkOptions::new()).unwrap().is_some();
+        let not_admitted = admit_path(&root.join(".echo.txt"), &WalkOptions::new().skip_hidden(true)).unwrap().is_some();
+        fs::remove_dir_all(&root).unwrap();
+        assert_eq!(all.len(), 5);
+        assert_eq!(not_hidden, vec![PathBuf::from("alpha.txt"), ["one", "bravo.txt"].iter().collect(), ["one", "two", "charlie.md"].iter().collect()]);
+        assert_eq!(short.len(), 4);
+        assert_eq!(short.contains(&["one", "two", "charlie.md"].iter().collect()), false);
+        assert_eq!(not_one, vec![PathBuf::from(".echo.txt"), [".hidden", "delta.txt"].iter().collect(), PathBuf::from("alpha.txt")]);
+        assert_eq!(rejected_root, Vec::<PathBuf>::new());
+        assert_eq!(admitted, true);
+        assert_eq!(not_admitted, false);
+    }
+
+    #[test]
+    /// Test `walk_tree` and `admit_path` with a min length and a max length.
+    fn test_walk_tree_x_len() {
+        let root = make_tree("walk_tree_x_len");
+        let at_least_6 = walk_names(&root, &WalkOptions::new().min_len(6));
+        let at_most_5 = walk_names(&root, &WalkOptions::new().max_len(5));
+        let none = walk_names(&root, &WalkOptions::new().min_len(6).max_len(6));
+        let admitted = admit_path(&root.join("alpha.txt"), &WalkOptions::new().min_len(5).max_len(5)).unwrap().is_some();
+        let not_admitted = admit_path(&root.join("alpha.txt"), &WalkOptions::new().min_len(4096)).unwrap().is_some();
+        fs::remove_dir_all(&root).unwrap();
+        assert_eq!(at_least_6, vec![["one", "two", "charlie.md"].iter().collect::<PathBuf>()]);
+        assert_eq!(at_most_5, vec![PathBuf::from("alpha.txt"), ["one", "bravo.txt"].iter().collect()]);
+        assert_eq!(none, Vec::<PathBuf>::new());
+        assert_eq!(admitted, true);
+        assert_eq!(not_admitted, false);
+    }
+
+    #[cfg(feature = "globset")]
+    #[test]
+    /// Test `walk_tree` with glob patterns.
+    fn test_walk_tree_x_globs() {
+        let root = make_tree("walk_tree_x_globs");
+        let txt = walk_names(&root, &WalkOptions::new().include_glob("*.txt").unwrap());
+        let not_two = walk_names(&root, &WalkOptions::new().exclude_glob("**/two").unwrap());
+        fs::remove_dir_all(&root).unwrap();
+        assert_eq!(txt, vec![PathBuf::from("alpha.txt"), ["one", "bravo.txt"].iter().collect()]);
+        assert_eq!(not_two, vec![PathBuf::from("alpha.txt"), ["one", "bravo.txt"].iter().collect()]);
+        assert_eq!(WalkOptions::new().include_glob("[").is_err(), true);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    /// Test `walk_tree` skips symbolic links by default, follows them without looping, and records them.
+    fn test_walk_tree_x_symlinks() {
+        let root = make_tree("walk_tree_x_symlinks");
+        std::os::unix::fs::symlink(root.join("alpha.txt"), root.join("link.txt")).unwrap();
+        std::os::unix::fs::symlink(&root, root.join("one").join("loop")).unwrap();
+        let skip = walk_names(&root, &WalkOptions::new());
+        let follow = walk_names(&root, &WalkOptions::new().follow_symlinks(true));
+        let record = walk_names(&root, &WalkOptions::new().symlinks(SymlinkPolicy::Record));
+        let mut record_lens: Vec<u64> = Vec::new();
+        walk_tree(&root.join("link.txt"), &WalkOptions::new().symlinks(SymlinkPolicy::Record), |_, metadata| record_lens.push(metadata.len())).unwrap();
+        fs::remove_dir_all(&root).unwrap();
+        assert_eq!(skip.len(), 3);
+        assert_eq!(follow.len(), 4);
+        assert_eq!(follow.contains(&PathBuf::from("link.txt")), true);
+        assert_eq!(record.len(), 5);
+        assert_eq!(record.contains(&PathBuf::from("link.txt")), true);
+        assert_eq!(record.contains(&["one", "loop"].iter().collect::<PathBuf>()), true);
+        assert_eq!(record_lens, vec![root.join("alpha.txt").as_os_str().len() as u64]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    /// Test `walk_tree` follows a root that is a symbolic link, whatever the policy.
+    fn test_walk_tree_x_symlink_root() {
+        let root = make_tree("walk_tree_x_symlink_root");
+        let link = std::env::temp_dir().join(format!("path_scan_walk_tree_x_symlink_root_link_{}", std::process::id()));
+        let _ = fs::remove_file(&link);
+        std::os::unix::fs::symlink(&root, &link).unwrap();
+        let skip = walk_names(&link, &WalkOptions::new());
+        let record = walk_names(&link, &WalkOptions::new().symlinks(SymlinkPolicy::Record));
+        let mut file_lens: Vec<u64> = Vec::new();
+        let file_link = root.join("link.txt");
+        std::os::unix::fs::symlink(root.join("alpha.txt"), &file_link).unwrap();
+        walk_tree(&file_link, &WalkOptions::new(), |_, metadata| file_lens.push(metadata.len())).unwrap();
+        fs::remove_file(&link).unwrap();
+        fs::remove_dir_all(&root).unwrap();
+        assert_eq!(skip, vec![PathBuf::from("alpha.txt"), ["one", "bravo.txt"].iter().collect(), ["one", "two", "charlie.md"].iter().collect()]);
+        assert_eq!(record, skip);
+        assert_eq!(file_lens, vec![5]);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    /// Test `par_walk_tree` finds the same files as `walk_tree`.
+    fn test_par_walk_tree() {
+        let root = make_tree("par_walk_tree");
+        let options = WalkOptions::new().exclude(|path| path.ends_with("charlie.md"));
+        let expect = walk_names(&root, &options);
+        let mut actual: Vec<PathBuf> = Vec::new();
+        let errors = par_walk_tree(&root, &options, |path, _| actual.push(path.strip_prefix(&root).unwrap().to_path_buf())).unwrap();
+        let depth_1 = par_walk_tree(&root, &WalkOptions::new().max_depth(1), |_, _| {}).unwrap();
+        let missing = par_walk_tree(&root.join("missing"), &options, |_, _| {});
+        fs::remove_dir_all(&root).unwrap();
+        actual.sort();
+        assert_eq!(errors.is_empty() && depth_1.is_empty(), true);
+        assert_eq!(actual, expect);
+        assert_eq!(actual.len(), 2);
+        assert_eq!(missing.is_err(), true);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    /// Test `par_walk_tree` spreads the entries of one flat directory across threads.
+    fn test_par_walk_tree_x_flat_dir() {
+        use std::sync::{Arc, Mutex};
+        use std::thread::ThreadId;
+        let root = std::env::temp_dir().join(format!("path_scan_par_walk_tree_x_flat_dir_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        for i in 0..64 {
+            fs::write(root.join(format!("{}.txt", i)), "alpha").unwrap();
+        }
+        let threads: Arc<Mutex<HashSet<ThreadId>>> = Arc::new(Mutex::new(HashSet::new()));
+        let sink = threads.clone();
+        let options = WalkOptions::new().filter(move |_, _| {
+            std::thread::sleep(std::time::Duration::from_millis(2));
+            sink.lock().unwrap().insert(std::thread::current().id());
+            true
+        });
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(4).build().unwrap();
+        let mut count = 0;
+        let errors = pool.install(|| par_walk_tree(&root, &options, |_, _| count += 1)).unwrap();
+        fs::remove_dir_all(&root).unwrap();
+        assert_eq!(errors.is_empty(), true);
+        assert_eq!(count, 64);
+        assert_eq!(threads.lock().unwrap().len() > 1, true);
+    }
+
+}
@@ -0,0 +1,462 @@
+use std::borrow::Borrow;
+use std::cmp::Eq;
+use std::fmt;
+use std::hash::Hash;
+use std::iter::FromIterator;
+use imbl::{HashMap, HashSet};
+
+/// A persistent map of keys to sets of values, where every change returns
+/// a new version that shares structure with the old one.
+///
+/// The map and each set are hash array mapped tries from the `imbl`
+/// crate, so a version costs O(log n) new nodes rather than a full clone,
+/// and `clone` is O(1). Old versions stay valid and unchanged, which
+/// suits keeping a snapshot per request.
+///
+/// This collection needs the `imbl` feature.
+///
+/// # Examples
+///
+/// ```
+/// use sixarm_collections::*;
+/// let v1: PersistentMapToSet<&str, u8> = PersistentMapToSet::new();
+/// let v2 = v1.sub_insert("a", 1);
+/// let v3 = v2.sub_insert("a", 2);
+/// assert_eq!(v1.sub_len(), 0);
+/// assert_eq!(v2.sub_len(), 1);
+/// assert_eq!(v3.sub_len(), 2);
+/// ```
+pub struct PersistentMapToSet<K, V> {
+    map: HashMap<K, HashSet<V>>,
+    sub_len: usize,
+}
+
+impl<K, V> PersistentMapToSet<K, V>
+where
+    K: Hash + Eq + Clone,
+    V: Hash + Eq + Clone,
+{
+
+    /// Create an empty map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: PersistentMapToSet<u8, u8> = PersistentMapToSet::new();
+    /// assert_eq!(a.is_empty(), true);
+    /// ```
+    #[inline]
+    pub fn new() -> Self {
+        PersistentMapToSet { map: HashMap::new(), sub_len: 0 }
+    }
+
+    /// Return the number of keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: PersistentMapToSet<u8, u8> = vec![(1, 2), (1, 3)].into_iter().collect();
+    /// assert_eq!(a.len(), 1);
+    /// ```
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Return `true` if the map has no keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: PersistentMapToSet<u8, u8> = PersistentMapToSet::new();
+    /// assert_eq!(a.is_empty(), true);
+    /// ```
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Return the total number of values across all keys.
+    ///
+    /// The total is kept up to date by each change, so this is O(1).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: PersistentMapToSet<u8, u8> = vec![(1, 2), (1, 3), (4, 5)].into_iter().collect();
+    /// assert_eq!(a.sub_len(), 3);
+    /// ```
+    #[inline]
+    pub fn sub_len(&self) -> usize {
+        self.sub_len
+    }
+
+    /// Return `true` if both maps are the same version, or share the same root.
+    ///
+    /// This is O(1), and `false` does not imply the maps differ.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: PersistentMapToSet<u8, u8> = vec![(1, 2)].into_iter().collect();
+    /// let b = a.clone();
+    /// assert_eq!(a.ptr_eq(&b), true);
+    /// assert_eq!(a.sub_insert(3, 4).ptr_eq(&b), false);
+    /// ```
+    #[inline]
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        self.map.ptr_eq(&other.map)
+    }
+
+    /// Return the set for a key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: PersistentMapToSet<u8, u8> = vec![(1, 2)].into_iter().collect();
+    /// assert_eq!(a.get(&1).map(|set| set.len()), Some(1));
+    /// assert_eq!(a.get(&9), None);
+    /// ```
+    #[inline]
+    pub fn get<Q>(&self, key: &Q) -> Option<&HashSet<V>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.get(key)
+    }
+
+    /// Return `true` if the map has a key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: PersistentMapToSet<u8, u8> = vec![(1, 2)].into_iter().collect();
+    /// assert_eq!(a.contains_key(&1), true);
+    /// ```
+    #[inline]
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.contains_key(key)
+    }
+
+    /// Return `true` if a key's set has a value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: PersistentMapToSet<u8, u8> = vec![(1, 2)].into_iter().collect();
+    /// assert_eq!(a.sub_contains(&1, &2), true);
+    /// assert_eq!(a.sub_contains(&1, &3), false);
+    /// ```
+    #[inline]
+    pub fn sub_contains<Q, R>(&self, key: &Q, value: &R) -> bool
+    where
+        K: Borrow<Q>,
+        V: Borrow<R>,
+        Q: Hash + Eq + ?Sized,
+        R: Hash + Eq + ?Sized,
+    {
+        match self.map.get(key) {
+            Some(set) => set.contains(value),
+            None => false,
+        }
+    }
+
+    /// Return the number of values in the set for a key, or 0 if the key is absent.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: PersistentMapToSet<u8, u8> = vec![(1, 2), (1, 3)].into_iter().collect();
+    /// assert_eq!(a.sub_len_of(&1), 2);
+    /// assert_eq!(a.sub_len_of(&9), 0);
+    /// ```
+    #[inline]
+    pub fn sub_len_of<Q>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.get(key).map_or(0, |set| set.len())
+    }
+
+    /// Return an iterator over each key and its set, in arbitrary order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: PersistentMapToSet<u8, u8> = vec![(1, 2)].into_iter().collect();
+    /// assert_eq!(a.iter().count(), 1);
+    /// ```
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &HashSet<V>)> {
+        self.map.iter()
+    }
+
+    /// Return an iterator over every (key, value) pair, in arbitrary order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: PersistentMapToSet<u8, u8> = vec![(1, 2), (1, 3)].into_iter().collect();
+    /// assert_eq!(a.sub_iter().count(), 2);
+    /// ```
+    #[inline]
+    pub fn sub_iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.map.iter()
+        .flat_map(|(key, set)| set.iter().map(move |value| (key, value)))
+    }
+
+    /// Return a new version with a value added to a key's set.
+    ///
+    /// If the value is already present, return a version that shares the
+    /// same root as this one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: PersistentMapToSet<u8, u8> = PersistentMapToSet::new();
+    /// let b = a.sub_insert(1, 2);
+    /// assert_eq!(a.sub_contains(&1, &2), false);
+    /// assert_eq!(b.sub_contains(&1, &2), true);
+    /// ```
+    #[must_use]
+    pub fn sub_insert(&self, key: K, value: V) -> Self {
+        let mut next = self.clone();
+        next.insert_mut(key, value);
+        next
+    }
+
+    /// Return a new version with a value removed from a key's set.
+    ///
+    /// The key stays in the map even when its set becomes empty;
+    /// use `sub_remove_prune` to also remove the key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: PersistentMapToSet<u8, u8> = vec![(1, 2)].into_iter().collect();
+    /// let b = a.sub_remove(&1, &2);
+    /// assert_eq!(a.sub_contains(&1, &2), true);
+    /// assert_eq!(b.sub_contains(&1, &2), false);
+    /// assert_eq!(b.contains_key(&1), true);
+    /// ```
+    #[must_use]
+    pub fn sub_remove<Q, R>(&self, key: &Q, value: &R) -> Self
+    where
+        K: Borrow<Q>,
+        V: Borrow<R>,
+        Q: Hash + Eq + ?Sized,
+        R: Hash + Eq + ?Sized,
+    {
+        let mut next = self.clone();
+        if let Some(set) = next.map.get_mut(key) {
+            if set.remove(value).is_some() {
+                next.sub_len -= 1;
+            }
+        }
+        next
+    }
+
+    /// Return a new version with a value removed from a key's set, and the
+    /// key removed if its set becomes empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: PersistentMapToSet<u8, u8> = vec![(1, 2)].into_iter().collect();
+    /// let b = a.sub_remove_prune(&1, &2);
+    /// assert_eq!(b.contains_key(&1), false);
+    /// ```
+    #[must_use]
+    pub fn sub_remove_prune<Q, R>(&self, key: &Q, value: &R) -> Self
+    where
+        K: Borrow<Q>,
+        V: Borrow<R>,
+        Q: Hash + Eq + ?Sized,
+        R: Hash + Eq + ?Sized,
+    {
+        let mut next = self.sub_remove(key, value);
+        if next.map.get(key).is_some_and(|set| set.is_empty()) {
+            next.map.remove(key);
+        }
+        next
+    }
+
+    /// Return a new version without a key and its set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: PersistentMapToSet<u8, u8> = vec![(1, 2), (1, 3)].into_iter().collect();
+    /// let b = a.remove(&1);
+    /// assert_eq!(a.sub_len(), 2);
+    /// assert_eq!(b.sub_len(), 0);
+    /// ```
+    #[must_use]
+    pub fn remove<Q>(&self, key: &Q) -> Self
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let mut next = self.clone();
+        if let Some(set) = next.map.remove(key) {
+            next.sub_len -= set.len();
+        }
+        next
+    }
+
+    /// Add a value to a key's set in place, and return `true` if it was newly added.
+    ///
+    /// Other versions are not affected, because shared nodes are copied on write.
+    fn insert_mut(&mut self, key: K, value: V) -> bool {
+        if self.sub_contains(&key, &value) {
+            return false;
+        }
+        self.map.entry(key)
+        .or_default()
+        .insert(value);
+        self.sub_len += 1;
+        true
+    }
+
+}
+
+impl<K, V> Clone for PersistentMapToSet<K, V>
+where
+    K: Hash + Eq + Clone,
+    V: Hash + Eq + Clone,
+{
+    /// Return another handle to the same version, in O(1).
+    fn clone(&self) -> Self {
+        PersistentMapToSet { map: self.map.clone(), sub_len: self.sub_len }
+    }
+}
+
+impl<K, V> fmt::Debug for PersistentMapToSet<K, V>
+where
+    K: Hash + Eq + Clone + fmt::Debug,
+    V: Hash + Eq + Clone + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.map.iter()).finish()
+    }
+}
+
+impl<K, V> Default for PersistentMapToSet<K, V>
+where
+    K: Hash + Eq + Clone,
+    V: Hash + Eq + Clone,
+{
+    fn default() -> Self {
+        PersistentMapToSet::new()
+    }
+}
+
+impl<K, V> PartialEq for PersistentMapToSet<K, V>
+where
+    K: Hash + Eq + Clone,
+    V: Hash + Eq + Clone,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.sub_len == other.sub_len && self.map == other.map
+    }
+}
+
+impl<K, V> Eq for PersistentMapToSet<K, V>
+where
+    K: Hash + Eq + Clone,
+    V: Hash + Eq + Clone,
+{}
+
+impl<K, V> FromIterator<(K, V)> for PersistentMapToSet<K, V>
+where
+    K: Hash + Eq + Clone,
+    V: Hash + Eq + Clone,
+{
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = PersistentMapToSet::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K, V> Extend<(K, V)> for PersistentMapToSet<K, V>
+where
+    K: Hash + Eq + Clone,
+    V: Hash + Eq + Clone,
+{
+    /// Add many pairs in place, without making a version per pair.
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert_mut(key, value);
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+
+    #[test]
+    /// Test each version keeps its own contents after later changes.
+    fn test_versions_x_independent() {
+        let v0: PersistentMapToSet<u8, char> = PersistentMapToSet::new();
+        let v1 = v0.sub_insert(1, 'a');
+        let v2 = v1.sub_insert(1, 'b').sub_insert(2, 'c');
+        let v3 = v2.sub_remove_prune(&2, &'c').sub_remove(&1, &'a');
+        assert_eq!(v0.sub_len(), 0);
+        assert_eq!(v1.sub_len(), 1);
+        assert_eq!(v2.sub_len(), 3);
+        assert_eq!(v3.sub_len(), 1);
+        assert_eq!(v2.sub_contains(&2, &'c'), true);
+        assert_eq!(v3.contains_key(&2), false);
+        assert_eq!(v3.sub_contains(&1, &'b'), true);
+        assert_eq!(v1.sub_contains(&1, &'b'), false);
+    }
+
+    #[test]
+    /// Test no-op changes keep the count and share the root.
+    fn test_no_op_changes() {
+        let subject: PersistentMapToSet<u8, char> = vec![(1, 'a')].into_iter().collect();
+        let same = subject.sub_insert(1, 'a');
+        assert_eq!(same.sub_len(), 1);
+        assert_eq!(same.ptr_eq(&subject), true);
+        assert_eq!(subject.sub_remove(&1, &'z').sub_len(), 1);
+        assert_eq!(subject.remove(&9).sub_len(), 1);
+    }
+
+    #[test]
+    /// Test `eq`, `sub_iter`, and `remove` with borrowed keys.
+    fn test_eq_and_sub_iter() {
+        let a: PersistentMapToSet<String, u8> = vec![(String::from("x"), 1), (String::from("x"), 2)].into_iter().collect();
+        let b = PersistentMapToSet::new().sub_insert(String::from("x"), 2).sub_insert(String::from("x"), 1);
+        assert_eq!(a == b, true);
+        let mut actual: Vec<u8> = a.sub_iter().map(|(_, v)| *v).collect();
+        actual.sort();
+        assert_eq!(actual, vec![1, 2]);
+        assert_eq!(a.remove("x").is_empty(), true);
+        assert_eq!(a.sub_len_of("x"), 2);
+    }
+
+}
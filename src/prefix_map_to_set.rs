@@ -0,0 +1,485 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::borrow::Borrow;
+use std::cmp::Ord;
+use std::iter::FromIterator;
+use std::ops::Bound;
+
+/// A map of string keys to sets of values, with fast prefix queries.
+///
+/// Keys are stored in a `BTreeMap<String, BTreeSet<V>>`. Every key with a
+/// given prefix sorts into one contiguous run that starts at the prefix
+/// itself, so a prefix query is one ordered seek plus a scan of only the
+/// matching keys.
+///
+/// # Examples
+///
+/// ```
+/// use sixarm_collections::*;
+/// let mut a: PrefixMapToSet<u8> = PrefixMapToSet::new();
+/// a.sub_insert("foo/a", 1);
+/// a.sub_insert("foo/b", 2);
+/// a.sub_insert("food", 3);
+/// let pairs: Vec<(&str, &u8)> = a.sub_iter_prefix("foo/").collect();
+/// assert_eq!(pairs, vec![("foo/a", &1), ("foo/b", &2)]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrefixMapToSet<V> {
+    map: BTreeMap<String, BTreeSet<V>>,
+}
+
+impl<V> PrefixMapToSet<V> {
+
+    /// Create an empty map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: PrefixMapToSet<u8> = PrefixMapToSet::new();
+    /// assert_eq!(a.is_empty(), true);
+    /// ```
+    #[inline]
+    pub fn new() -> Self {
+        PrefixMapToSet { map: BTreeMap::new() }
+    }
+
+    /// Return the number of keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: PrefixMapToSet<u8> = PrefixMapToSet::new();
+    /// a.sub_insert("a", 1);
+    /// a.sub_insert("a", 2);
+    /// assert_eq!(a.len(), 1);
+    /// ```
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Return `true` if the map has no keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: PrefixMapToSet<u8> = PrefixMapToSet::new();
+    /// assert_eq!(a.is_empty(), true);
+    /// ```
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Return the total number of values across all keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: PrefixMapToSet<u8> = PrefixMapToSet::new();
+    /// a.sub_insert("a", 1);
+    /// a.sub_insert("b", 2);
+    /// assert_eq!(a.sub_len(), 2);
+    /// ```
+    #[inline]
+    pub fn sub_len(&self) -> usize {
+        self.map.values().map(|set| set.len()).sum()
+    }
+
+    /// Return a reference to the underlying map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: PrefixMapToSet<u8> = PrefixMapToSet::new();
+    /// a.sub_insert("a", 1);
+    /// assert_eq!(a.as_map().contains_key("a"), true);
+    /// ```
+    #[inline]
+    pub fn as_map(&self) -> &BTreeMap<String, BTreeSet<V>> {
+        &self.map
+    }
+
+    /// Return the set for a key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: PrefixMapToSet<u8> = PrefixMapToSet::new();
+    /// a.sub_insert("a", 1);
+    /// assert_eq!(a.get("a").map(|set| set.len()), Some(1));
+    /// assert_eq!(a.get("b"), None);
+    /// ```
+    #[inline]
+    pub fn get(&self, key: &str) -> Option<&BTreeSet<V>> {
+        self.map.get(key)
+    }
+
+    /// Return `true` if the map has a key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: PrefixMapToSet<u8> = PrefixMapToSet::new();
+    /// a.sub_insert("a", 1);
+    /// assert_eq!(a.contains_key("a"), true);
+    /// ```
+    #[inline]
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.map.contains_key(key)
+    }
+
+    /// Remove a key and return its set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: PrefixMapToSet<u8> = PrefixMapToSet::new();
+    /// a.sub_insert("a", 1);
+    /// assert_eq!(a.remove("a").map(|set| set.len()), Some(1));
+    /// ```
+    #[inline]
+    pub fn remove(&mut self, key: &str) -> Option<BTreeSet<V>> {
+        self.map.remove(key)
+    }
+
+    /// Return an iterator over every (key, value) pair, in key order then value order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: PrefixMapToSet<u8> = PrefixMapToSet::new();
+    /// a.sub_insert("b", 1);
+    /// a.sub_insert("a", 2);
+    /// let pairs: Vec<(&str, &u8)> = a.sub_iter().collect();
+    /// assert_eq!(pairs, vec![("a", &2), ("b", &1)]);
+    /// ```
+    #[inline]
+    pub fn sub_iter(&self) -> impl Iterator<Item = (&str, &V)> {
+        self.map.iter()
+        .flat_map(|(key, set)| set.iter().map(move |value| (key.as_str(), value)))
+    }
+
+    /// Return an iterator over each key with a prefix and its set, in key order.
+    ///
+    /// The empty prefix matches every key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: PrefixMapToSet<u8> = PrefixMapToSet::new();
+    /// a.sub_insert("foo/a", 1);
+    /// a.sub_insert("bar/b", 2);
+    /// let keys: Vec<&str> = a.iter_prefix("foo/").map(|(key, _)| key).collect();
+    /// assert_eq!(keys, vec!["foo/a"]);
+    /// ```
+    #[inline]
+    pub fn iter_prefix<'a>(&'a self, prefix: &'a str) -> impl Iterator<Item = (&'a str, &'a BTreeSet<V>)> {
+        self.map.range::<str, _>((Bound::Included(prefix), Bound::Unbounded))
+        .take_while(move |(key, _)| key.starts_with(prefix))
+        .map(|(key, set)| (key.as_str(), set))
+    }
+
+    /// Return an iterator over every (key, value) pair whose key has a prefix.
+    ///
+    /// The iterator yields pairs in key order then value order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: PrefixMapToSet<u8> = PrefixMapToSet::new();
+    /// a.sub_insert("ns::x", 1);
+    /// a.sub_insert("ns::x", 2);
+    /// a.sub_insert("other", 3);
+    /// assert_eq!(a.sub_iter_prefix("ns::").count(), 2);
+    /// ```
+    #[inline]
+    pub fn sub_iter_prefix<'a>(&'a self, prefix: &'a str) -> impl Iterator<Item = (&'a str, &'a V)> {
+        self.iter_prefix(prefix)
+        .flat_map(|(key, set)| set.iter().map(move |value| (key, value)))
+    }
+
+    /// Return an iterator over the keys with a prefix, in key order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: PrefixMapToSet<u8> = PrefixMapToSet::new();
+    /// a.sub_insert("src/a.rs", 1);
+    /// a.sub_insert("src/b.rs", 2);
+    /// a.sub_insert("tests/c.rs", 3);
+    /// assert_eq!(a.keys_with_prefix("src/").collect::<Vec<&str>>(), vec!["src/a.rs", "src/b.rs"]);
+    /// ```
+    #[inline]
+    pub fn keys_with_prefix<'a>(&'a self, prefix: &'a str) -> impl Iterator<Item = &'a str> {
+        self.iter_prefix(prefix).map(|(key, _)| key)
+    }
+
+    /// Return the total number of values across the keys with a prefix.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: PrefixMapToSet<u8> = PrefixMapToSet::new();
+    /// a.sub_insert("a/x", 1);
+    /// a.sub_insert("a/y", 2);
+    /// a.sub_insert("b/z", 3);
+    /// assert_eq!(a.sub_len_prefix("a/"), 2);
+    /// ```
+    #[inline]
+    pub fn sub_len_prefix(&self, prefix: &str) -> usize {
+        self.iter_prefix(prefix).map(|(_, set)| set.len()).sum()
+    }
+
+    /// Return `true` if any key has a prefix.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: PrefixMapToSet<u8> = PrefixMapToSet::new();
+    /// a.sub_insert("a/x", 1);
+    /// assert_eq!(a.contains_prefix("a/"), true);
+    /// assert_eq!(a.contains_prefix("b/"), false);
+    /// ```
+    #[inline]
+    pub fn contains_prefix(&self, prefix: &str) -> bool {
+        self.iter_prefix(prefix).next().is_some()
+    }
+
+    /// Remove every key with a prefix, and return the number of keys removed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: PrefixMapToSet<u8> = PrefixMapToSet::new();
+    /// a.sub_insert("a/x", 1);
+    /// a.sub_insert("a/y", 2);
+    /// a.sub_insert("b/z", 3);
+    /// assert_eq!(a.remove_prefix("a/"), 2);
+    /// assert_eq!(a.len(), 1);
+    /// ```
+    #[inline]
+    pub fn remove_prefix(&mut self, prefix: &str) -> usize {
+        let mut tail = self.map.split_off(prefix);
+        let len = tail.len();
+        let mut rest = match tail.keys().find(|key| !key.starts_with(prefix)).cloned() {
+            Some(key) => tail.split_off(&key),
+            None => BTreeMap::new(),
+        };
+        let removed = len - rest.len();
+        self.map.append(&mut rest);
+        removed
+    }
+
+}
+
+impl<V> PrefixMapToSet<V>
+where
+    V: Ord,
+{
+
+    /// Add a value to a key's set, and return `true` if the value was newly added.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: PrefixMapToSet<u8> = PrefixMapToSet::new();
+    /// assert_eq!(a.sub_insert("a", 1), true);
+    /// assert_eq!(a.sub_insert("a", 1), false);
+    /// ```
+    #[inline]
+    pub fn sub_insert<S>(&mut self, key: S, value: V) -> bool
+    where
+        S: Into<String> + AsRef<str>,
+    {
+        match self.map.get_mut(key.as_ref()) {
+            Some(set) => set.insert(value),
+            None => {
+                self.map.insert(key.into(), BTreeSet::from([value]));
+                true
+            },
+        }
+    }
+
+    /// Return `true` if a key's set has a value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: PrefixMapToSet<u8> = PrefixMapToSet::new();
+    /// a.sub_insert("a", 1);
+    /// assert_eq!(a.sub_contains("a", &1), true);
+    /// assert_eq!(a.sub_contains("a", &2), false);
+    /// ```
+    #[inline]
+    pub fn sub_contains<R>(&self, key: &str, value: &R) -> bool
+    where
+        V: Borrow<R>,
+        R: Ord + ?Sized,
+    {
+        match self.map.get(key) {
+            Some(set) => set.contains(value),
+            None => false,
+        }
+    }
+
+    /// Remove a value from a key's set, and return `true` if it was present.
+    ///
+    /// The key stays in the map even when its set becomes empty;
+    /// use `sub_remove_prune` to also remove the key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: PrefixMapToSet<u8> = PrefixMapToSet::new();
+    /// a.sub_insert("a", 1);
+    /// assert_eq!(a.sub_remove("a", &1), true);
+    /// assert_eq!(a.contains_key("a"), true);
+    /// ```
+    #[inline]
+    pub fn sub_remove<R>(&mut self, key: &str, value: &R) -> bool
+    where
+        V: Borrow<R>,
+        R: Ord + ?Sized,
+    {
+        match self.map.get_mut(key) {
+            Some(set) => set.remove(value),
+            None => false,
+        }
+    }
+
+    /// Remove a value from a key's set, and prune the key if its set becomes empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: PrefixMapToSet<u8> = PrefixMapToSet::new();
+    /// a.sub_insert("a", 1);
+    /// assert_eq!(a.sub_remove_prune("a", &1), true);
+    /// assert_eq!(a.contains_key("a"), false);
+    /// ```
+    #[inline]
+    pub fn sub_remove_prune<R>(&mut self, key: &str, value: &R) -> bool
+    where
+        V: Borrow<R>,
+        R: Ord + ?Sized,
+    {
+        match self.map.get_mut(key) {
+            Some(set) => {
+                let removed = set.remove(value);
+                if set.is_empty() {
+                    self.map.remove(key);
+                }
+                removed
+            },
+            None => false,
+        }
+    }
+
+}
+
+impl<V> Default for PrefixMapToSet<V> {
+    fn default() -> Self {
+        PrefixMapToSet::new()
+    }
+}
+
+impl<S, V> FromIterator<(S, V)> for PrefixMapToSet<V>
+where
+    S: Into<String> + AsRef<str>,
+    V: Ord,
+{
+    fn from_iter<I: IntoIterator<Item = (S, V)>>(iter: I) -> Self {
+        let mut map = PrefixMapToSet::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<S, V> Extend<(S, V)> for PrefixMapToSet<V>
+where
+    S: Into<String> + AsRef<str>,
+    V: Ord,
+{
+    fn extend<I: IntoIterator<Item = (S, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.sub_insert(key, value);
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+
+    fn subject() -> PrefixMapToSet<u8> {
+        vec![("a", 1), ("a/b", 2), ("a/b", 3), ("a/c", 4), ("ab", 5), ("b", 6), ("a/", 7)].into_iter().collect()
+    }
+
+    #[test]
+    /// Test `sub_iter_prefix` yields only keys with the prefix, in order, and stops at the first non-match.
+    fn test_sub_iter_prefix() {
+        let subject = subject();
+        let actual: Vec<(&str, &u8)> = subject.sub_iter_prefix("a/").collect();
+        assert_eq!(actual, vec![("a/", &7), ("a/b", &2), ("a/b", &3), ("a/c", &4)]);
+        assert_eq!(subject.sub_iter_prefix("a").count(), 6);
+        assert_eq!(subject.sub_iter_prefix("").count(), 7);
+        assert_eq!(subject.sub_iter_prefix("z").count(), 0);
+    }
+
+    #[test]
+    /// Test `keys_with_prefix`, `sub_len_prefix`, and `contains_prefix`.
+    fn test_keys_with_prefix() {
+        let subject = subject();
+        assert_eq!(subject.keys_with_prefix("a/").collect::<Vec<&str>>(), vec!["a/", "a/b", "a/c"]);
+        assert_eq!(subject.sub_len_prefix("a/b"), 2);
+        assert_eq!(subject.contains_prefix("ab"), true);
+        assert_eq!(subject.contains_prefix("ac"), false);
+    }
+
+    #[test]
+    /// Test `remove_prefix` removes only matching keys and keeps the rest in order.
+    fn test_remove_prefix() {
+        let mut subject = subject();
+        assert_eq!(subject.remove_prefix("a/"), 3);
+        assert_eq!(subject.keys_with_prefix("").collect::<Vec<&str>>(), vec!["a", "ab", "b"]);
+        assert_eq!(subject.remove_prefix("a/"), 0);
+        assert_eq!(subject.remove_prefix(""), 3);
+        assert_eq!(subject.is_empty(), true);
+    }
+
+    #[test]
+    /// Test `sub_insert`, `sub_contains`, `sub_remove`, and `sub_remove_prune` with `String` and `&str` keys.
+    fn test_sub_insert_and_sub_remove() {
+        let mut subject: PrefixMapToSet<u8> = PrefixMapToSet::new();
+        assert_eq!(subject.sub_insert(String::from("k"), 1), true);
+        assert_eq!(subject.sub_insert("k", 1), false);
+        assert_eq!(subject.sub_contains("k", &1), true);
+        assert_eq!(subject.sub_remove("k", &1), true);
+        assert_eq!(subject.contains_key("k"), true);
+        subject.sub_insert("k", 2);
+        assert_eq!(subject.sub_remove_prune("k", &2), true);
+        assert_eq!(subject.contains_key("k"), false);
+        assert_eq!(subject.sub_len(), 0);
+    }
+
+}
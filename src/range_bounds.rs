@@ -0,0 +1,41 @@
+use std::ops::Bound;
+use std::ops::RangeBounds;
+
+/// Return `true` if a range is one that `BTreeMap::range` panics on.
+///
+/// That is a range whose start is after its end, or whose start equals
+/// its end when both bounds are excluded. Callers check this first, so
+/// they can treat the range as empty instead of panicking.
+pub(crate) fn is_inverted<T, R>(range: &R) -> bool
+where
+    T: Ord + ?Sized,
+    R: RangeBounds<T>,
+{
+    match (range.start_bound(), range.end_bound()) {
+        (Bound::Excluded(start), Bound::Excluded(end)) => start >= end,
+        (Bound::Included(start), Bound::Included(end))
+        | (Bound::Included(start), Bound::Excluded(end))
+        | (Bound::Excluded(start), Bound::Included(end)) => start > end,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    /// Test `is_inverted` with empty, inverted, and unbounded ranges.
+    fn test_is_inverted() {
+        let (high, low) = (6, 5);
+        assert!(!is_inverted(&(1..5)));
+        assert!(!is_inverted(&(5..5)));
+        assert!(!is_inverted(&(5..=5)));
+        assert!(!is_inverted(&(5..)));
+        assert!(!is_inverted::<u8, _>(&(..)));
+        assert!(is_inverted(&(high..low)));
+        assert!(is_inverted(&(high..=low)));
+        assert!(is_inverted(&(Bound::Excluded(5), Bound::Excluded(5))));
+        assert!(!is_inverted(&(Bound::Excluded(5), Bound::Included(5))));
+    }
+}
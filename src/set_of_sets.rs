@@ -0,0 +1,446 @@
+use std::collections::{BTreeSet, HashSet};
+use std::cmp::{Eq, Ord};
+use std::hash::Hash;
+use std::iter::FromIterator;
+
+/// A set of sets, stored as `HashSet<BTreeSet<T>>`.
+///
+/// Each inner set is a `BTreeSet`, so its items are always in sorted
+/// order, and two inner sets with the same items hash and compare the
+/// same no matter what order the items were inserted. This makes the
+/// outer set deduplicate identical groupings.
+///
+/// # Examples
+///
+/// ```
+/// use sixarm_collections::*;
+/// let mut a: SetOfSets<u8> = SetOfSets::new();
+/// a.insert_iter(vec![3, 1, 2]);
+/// a.insert_iter(vec![2, 3, 1, 1]);
+/// assert_eq!(a.len(), 1);
+/// assert_eq!(a.contains_iter(vec![1, 2, 3]), true);
+/// ```
+#[derive(Debug, Clone)]
+pub struct SetOfSets<T> {
+    sets: HashSet<BTreeSet<T>>,
+}
+
+impl<T> SetOfSets<T> {
+
+    /// Create an empty set of sets.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: SetOfSets<u8> = SetOfSets::new();
+    /// assert_eq!(a.is_empty(), true);
+    /// ```
+    #[inline]
+    pub fn new() -> Self {
+        SetOfSets { sets: HashSet::new() }
+    }
+
+    /// Return the number of distinct inner sets.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: SetOfSets<u8> = SetOfSets::new();
+    /// a.insert_iter(vec![1, 2]);
+    /// a.insert_iter(vec![3]);
+    /// assert_eq!(a.len(), 2);
+    /// ```
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.sets.len()
+    }
+
+    /// Return `true` if there are no inner sets.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: SetOfSets<u8> = SetOfSets::new();
+    /// assert_eq!(a.is_empty(), true);
+    /// ```
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.sets.is_empty()
+    }
+
+    /// Return the total number of items across all inner sets.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: SetOfSets<u8> = SetOfSets::new();
+    /// a.insert_iter(vec![1, 2]);
+    /// a.insert_iter(vec![2, 3, 4]);
+    /// assert_eq!(a.sub_len(), 5);
+    /// ```
+    #[inline]
+    pub fn sub_len(&self) -> usize {
+        self.sets.iter().map(|set| set.len()).sum()
+    }
+
+    /// Return an iterator over the inner sets, in arbitrary order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: SetOfSets<u8> = SetOfSets::new();
+    /// a.insert_iter(vec![2, 1]);
+    /// let sets: Vec<Vec<u8>> = a.iter().map(|set| set.iter().copied().collect()).collect();
+    /// assert_eq!(sets, vec![vec![1, 2]]);
+    /// ```
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = &BTreeSet<T>> {
+        self.sets.iter()
+    }
+
+    /// Remove all inner sets.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: SetOfSets<u8> = SetOfSets::new();
+    /// a.insert_iter(vec![1]);
+    /// a.clear();
+    /// assert_eq!(a.is_empty(), true);
+    /// ```
+    #[inline]
+    pub fn clear(&mut self) {
+        self.sets.clear()
+    }
+
+    /// Return the inner sets as a `HashSet`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: SetOfSets<u8> = SetOfSets::new();
+    /// assert_eq!(a.as_set().is_empty(), true);
+    /// ```
+    #[inline]
+    pub fn as_set(&self) -> &HashSet<BTreeSet<T>> {
+        &self.sets
+    }
+
+    /// Consume the set of sets and return the inner sets as a `HashSet`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: SetOfSets<u8> = SetOfSets::new();
+    /// a.insert_iter(vec![1]);
+    /// assert_eq!(a.into_set().len(), 1);
+    /// ```
+    #[inline]
+    pub fn into_set(self) -> HashSet<BTreeSet<T>> {
+        self.sets
+    }
+
+}
+
+impl<T> SetOfSets<T>
+where
+    T: Ord + Hash,
+{
+
+    /// Insert an inner set.
+    ///
+    /// Return `true` if the set was not already present.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// use std::collections::BTreeSet;
+    /// let mut a: SetOfSets<u8> = SetOfSets::new();
+    /// let set: BTreeSet<u8> = vec![1, 2].into_iter().collect();
+    /// assert_eq!(a.insert(set.clone()), true);
+    /// assert_eq!(a.insert(set), false);
+    /// ```
+    #[inline]
+    pub fn insert(&mut self, set: BTreeSet<T>) -> bool {
+        self.sets.insert(set)
+    }
+
+    /// Insert the items of an iterator as one inner set.
+    ///
+    /// The items are canonicalized: they are sorted and deduplicated,
+    /// so the iteration order and any repeats do not matter.
+    ///
+    /// Return `true` if the set was not already present.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: SetOfSets<u8> = SetOfSets::new();
+    /// assert_eq!(a.insert_iter(vec![2, 1]), true);
+    /// assert_eq!(a.insert_iter(vec![1, 2, 2]), false);
+    /// ```
+    #[inline]
+    pub fn insert_iter<I>(&mut self, items: I) -> bool
+    where
+        I: IntoIterator<Item = T>,
+    {
+        self.sets.insert(items.into_iter().collect())
+    }
+
+    /// Return `true` if the set of sets contains an inner set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// use std::collections::BTreeSet;
+    /// let mut a: SetOfSets<u8> = SetOfSets::new();
+    /// a.insert_iter(vec![1, 2]);
+    /// let set: BTreeSet<u8> = vec![2, 1].into_iter().collect();
+    /// assert_eq!(a.contains(&set), true);
+    /// ```
+    #[inline]
+    pub fn contains(&self, set: &BTreeSet<T>) -> bool {
+        self.sets.contains(set)
+    }
+
+    /// Return `true` if the set of sets contains the canonicalized items of an iterator.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: SetOfSets<u8> = SetOfSets::new();
+    /// a.insert_iter(vec![1, 2]);
+    /// assert_eq!(a.contains_iter(vec![2, 1, 1]), true);
+    /// assert_eq!(a.contains_iter(vec![1]), false);
+    /// ```
+    #[inline]
+    pub fn contains_iter<I>(&self, items: I) -> bool
+    where
+        I: IntoIterator<Item = T>,
+    {
+        self.sets.contains(&items.into_iter().collect::<BTreeSet<T>>())
+    }
+
+    /// Remove an inner set.
+    ///
+    /// Return `true` if the set was present.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// use std::collections::BTreeSet;
+    /// let mut a: SetOfSets<u8> = SetOfSets::new();
+    /// a.insert_iter(vec![1, 2]);
+    /// let set: BTreeSet<u8> = vec![1, 2].into_iter().collect();
+    /// assert_eq!(a.remove(&set), true);
+    /// assert_eq!(a.remove(&set), false);
+    /// ```
+    #[inline]
+    pub fn remove(&mut self, set: &BTreeSet<T>) -> bool {
+        self.sets.remove(set)
+    }
+
+    /// Remove the canonicalized items of an iterator as one inner set.
+    ///
+    /// Return `true` if the set was present.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: SetOfSets<u8> = SetOfSets::new();
+    /// a.insert_iter(vec![1, 2]);
+    /// assert_eq!(a.remove_iter(vec![2, 1]), true);
+    /// assert_eq!(a.is_empty(), true);
+    /// ```
+    #[inline]
+    pub fn remove_iter<I>(&mut self, items: I) -> bool
+    where
+        I: IntoIterator<Item = T>,
+    {
+        self.sets.remove(&items.into_iter().collect::<BTreeSet<T>>())
+    }
+
+    /// Return an iterator over the inner sets that are subsets of a set,
+    /// in arbitrary order.
+    ///
+    /// An inner set equal to the given set counts as a subset.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// use std::collections::BTreeSet;
+    /// let mut a: SetOfSets<u8> = SetOfSets::new();
+    /// a.insert_iter(vec![1]);
+    /// a.insert_iter(vec![1, 2]);
+    /// a.insert_iter(vec![3]);
+    /// let set: BTreeSet<u8> = vec![1, 2].into_iter().collect();
+    /// assert_eq!(a.subsets_of(&set).count(), 2);
+    /// ```
+    #[inline]
+    pub fn subsets_of<'a>(&'a self, set: &'a BTreeSet<T>) -> impl Iterator<Item = &'a BTreeSet<T>> {
+        self.sets.iter().filter(move |inner| inner.is_subset(set))
+    }
+
+    /// Return an iterator over the inner sets that are supersets of a set,
+    /// in arbitrary order.
+    ///
+    /// An inner set equal to the given set counts as a superset.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// use std::collections::BTreeSet;
+    /// let mut a: SetOfSets<u8> = SetOfSets::new();
+    /// a.insert_iter(vec![1]);
+    /// a.insert_iter(vec![1, 2]);
+    /// a.insert_iter(vec![3]);
+    /// let set: BTreeSet<u8> = vec![1].into_iter().collect();
+    /// assert_eq!(a.supersets_of(&set).count(), 2);
+    /// ```
+    #[inline]
+    pub fn supersets_of<'a>(&'a self, set: &'a BTreeSet<T>) -> impl Iterator<Item = &'a BTreeSet<T>> {
+        self.sets.iter().filter(move |inner| inner.is_superset(set))
+    }
+
+    /// Return an iterator over the inner sets that contain an item,
+    /// in arbitrary order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: SetOfSets<u8> = SetOfSets::new();
+    /// a.insert_iter(vec![1, 2]);
+    /// a.insert_iter(vec![2, 3]);
+    /// a.insert_iter(vec![4]);
+    /// assert_eq!(a.containing(&2).count(), 2);
+    /// ```
+    #[inline]
+    pub fn containing<'a>(&'a self, item: &'a T) -> impl Iterator<Item = &'a BTreeSet<T>> {
+        self.sets.iter().filter(move |inner| inner.contains(item))
+    }
+
+}
+
+impl<T> Default for SetOfSets<T> {
+    fn default() -> Self {
+        SetOfSets::new()
+    }
+}
+
+impl<T> PartialEq for SetOfSets<T>
+where
+    T: Eq + Hash,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.sets == other.sets
+    }
+}
+
+impl<T> Eq for SetOfSets<T>
+where
+    T: Eq + Hash,
+{
+}
+
+impl<T> From<HashSet<BTreeSet<T>>> for SetOfSets<T> {
+    fn from(sets: HashSet<BTreeSet<T>>) -> Self {
+        SetOfSets { sets }
+    }
+}
+
+impl<T> FromIterator<BTreeSet<T>> for SetOfSets<T>
+where
+    T: Ord + Hash,
+{
+    fn from_iter<I: IntoIterator<Item = BTreeSet<T>>>(iter: I) -> Self {
+        SetOfSets { sets: iter.into_iter().collect() }
+    }
+}
+
+impl<T> Extend<BTreeSet<T>> for SetOfSets<T>
+where
+    T: Ord + Hash,
+{
+    fn extend<I: IntoIterator<Item = BTreeSet<T>>>(&mut self, iter: I) {
+        self.sets.extend(iter)
+    }
+}
+
+impl<T> IntoIterator for SetOfSets<T> {
+    type Item = BTreeSet<T>;
+    type IntoIter = std::collections::hash_set::IntoIter<BTreeSet<T>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.sets.into_iter()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+
+    #[test]
+    /// Test that groupings with the same items deduplicate regardless of order and repeats.
+    fn test_insert_iter_canonicalizes() {
+        let mut subject: SetOfSets<char> = SetOfSets::new();
+        assert_eq!(subject.insert_iter(vec!['c', 'a', 'b']), true);
+        assert_eq!(subject.insert_iter(vec!['b', 'c', 'a', 'a']), false);
+        assert_eq!(subject.insert_iter(vec!['a', 'b']), true);
+        assert_eq!(subject.len(), 2);
+        assert_eq!(subject.sub_len(), 5);
+        assert_eq!(subject.contains_iter(vec!['a', 'c', 'b']), true);
+        assert_eq!(subject.remove_iter(vec!['b', 'a']), true);
+        assert_eq!(subject.len(), 1);
+    }
+
+    #[test]
+    /// Test `subsets_of`, `supersets_of`, and `containing`.
+    fn test_subsets_x_supersets_x_containing() {
+        let subject: SetOfSets<u8> = vec![
+            vec![1].into_iter().collect(),
+            vec![1, 2].into_iter().collect(),
+            vec![1, 2, 3].into_iter().collect(),
+            vec![4].into_iter().collect(),
+        ].into_iter().collect();
+        let probe: BTreeSet<u8> = vec![1, 2].into_iter().collect();
+        let mut subsets: Vec<usize> = subject.subsets_of(&probe).map(|set| set.len()).collect();
+        subsets.sort();
+        assert_eq!(subsets, vec![1, 2]);
+        let mut supersets: Vec<usize> = subject.supersets_of(&probe).map(|set| set.len()).collect();
+        supersets.sort();
+        assert_eq!(supersets, vec![2, 3]);
+        assert_eq!(subject.containing(&4).count(), 1);
+        assert_eq!(subject.containing(&9).count(), 0);
+    }
+
+    #[test]
+    /// Test that equality ignores the order inner sets were inserted.
+    fn test_eq() {
+        let mut a: SetOfSets<u8> = SetOfSets::new();
+        a.insert_iter(vec![1, 2]);
+        a.insert_iter(vec![3]);
+        let mut b: SetOfSets<u8> = SetOfSets::new();
+        b.insert_iter(vec![3]);
+        b.insert_iter(vec![2, 1]);
+        assert_eq!(a, b);
+    }
+
+}
@@ -0,0 +1,345 @@
+use std::collections::HashSet;
+use std::collections::hash_set;
+use std::borrow::Borrow;
+use std::cmp::Eq;
+use std::hash::Hash;
+use smallvec::SmallVec;
+
+/// A set that stores up to `N` values inline, and spills to a heap
+/// `HashSet` when it grows beyond that.
+///
+/// Most multimaps have a few values per key, so keeping those values
+/// inline avoids a hash table allocation for each key. Membership
+/// checks on the inline values are linear scans. Once the set spills
+/// it stays spilled.
+///
+/// This collection needs the `smallvec` feature.
+///
+/// # Examples
+///
+/// ```
+/// use sixarm_collections::*;
+/// let mut a: SmallSet<u8, 2> = SmallSet::new();
+/// a.insert(1);
+/// a.insert(2);
+/// assert_eq!(a.is_spilled(), false);
+/// a.insert(3);
+/// assert_eq!(a.is_spilled(), true);
+/// assert_eq!(a.len(), 3);
+/// ```
+#[derive(Debug, Clone)]
+pub struct SmallSet<V, const N: usize> {
+    repr: Repr<V, N>,
+}
+
+#[derive(Debug, Clone)]
+enum Repr<V, const N: usize> {
+    Inline(SmallVec<[V; N]>),
+    Heap(HashSet<V>),
+}
+
+impl<V, const N: usize> SmallSet<V, N> {
+
+    /// Create an empty set with inline storage.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: SmallSet<u8, 4> = SmallSet::new();
+    /// assert_eq!(a.is_empty(), true);
+    /// ```
+    #[inline]
+    pub fn new() -> Self {
+        SmallSet { repr: Repr::Inline(SmallVec::new()) }
+    }
+
+    /// Return the number of values in the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: SmallSet<u8, 4> = SmallSet::new();
+    /// a.insert(1);
+    /// assert_eq!(a.len(), 1);
+    /// ```
+    #[inline]
+    pub fn len(&self) -> usize {
+        match &self.repr {
+            Repr::Inline(vec) => vec.len(),
+            Repr::Heap(set) => set.len(),
+        }
+    }
+
+    /// Return `true` if the set has no values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: SmallSet<u8, 4> = SmallSet::new();
+    /// assert_eq!(a.is_empty(), true);
+    /// ```
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Return `true` if the set has spilled its values to a heap `HashSet`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: SmallSet<u8, 1> = SmallSet::new();
+    /// a.insert(1);
+    /// assert_eq!(a.is_spilled(), false);
+    /// a.insert(2);
+    /// assert_eq!(a.is_spilled(), true);
+    /// ```
+    #[inline]
+    pub fn is_spilled(&self) -> bool {
+        matches!(self.repr, Repr::Heap(_))
+    }
+
+    /// Return an iterator over the values in the set.
+    ///
+    /// Inline values iterate in insertion order; spilled values
+    /// iterate in arbitrary order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: SmallSet<u8, 4> = SmallSet::new();
+    /// a.insert(2);
+    /// a.insert(1);
+    /// let values: Vec<&u8> = a.iter().collect();
+    /// assert_eq!(values, vec![&2, &1]);
+    /// ```
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = &V> {
+        let (inline, heap) = match &self.repr {
+            Repr::Inline(vec) => (Some(vec.iter()), None),
+            Repr::Heap(set) => (None, Some(set.iter())),
+        };
+        inline.into_iter().flatten().chain(heap.into_iter().flatten())
+    }
+
+}
+
+impl<V, const N: usize> SmallSet<V, N>
+where
+    V: Hash + Eq,
+{
+
+    /// Return `true` if the set contains a value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: SmallSet<u8, 4> = SmallSet::new();
+    /// a.insert(1);
+    /// assert_eq!(a.contains(&1), true);
+    /// assert_eq!(a.contains(&2), false);
+    /// ```
+    #[inline]
+    pub fn contains<R>(&self, value: &R) -> bool
+    where
+        V: Borrow<R>,
+        R: Hash + Eq + ?Sized,
+    {
+        match &self.repr {
+            Repr::Inline(vec) => vec.iter().any(|x| x.borrow() == value),
+            Repr::Heap(set) => set.contains(value),
+        }
+    }
+
+    /// Add a value to the set.
+    ///
+    /// Return whether the value is newly added. Adding a value to a
+    /// full inline set spills every value to a heap `HashSet`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: SmallSet<u8, 4> = SmallSet::new();
+    /// assert_eq!(a.insert(1), true);
+    /// assert_eq!(a.insert(1), false);
+    /// ```
+    #[inline]
+    pub fn insert(&mut self, value: V) -> bool {
+        match &mut self.repr {
+            Repr::Inline(vec) => {
+                if vec.contains(&value) {
+                    return false;
+                }
+                if vec.len() < N {
+                    vec.push(value);
+                    return true;
+                }
+                let mut set: HashSet<V> = vec.drain(..).collect();
+                set.insert(value);
+                self.repr = Repr::Heap(set);
+                true
+            },
+            Repr::Heap(set) => set.insert(value),
+        }
+    }
+
+    /// Remove a value from the set.
+    ///
+    /// Return whether the value was present. Inline values keep their
+    /// order; a spilled set stays spilled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: SmallSet<u8, 4> = SmallSet::new();
+    /// a.insert(1);
+    /// assert_eq!(a.remove(&1), true);
+    /// assert_eq!(a.remove(&1), false);
+    /// ```
+    #[inline]
+    pub fn remove<R>(&mut self, value: &R) -> bool
+    where
+        V: Borrow<R>,
+        R: Hash + Eq + ?Sized,
+    {
+        match &mut self.repr {
+            Repr::Inline(vec) => match vec.iter().position(|x| x.borrow() == value) {
+                Some(index) => {
+                    vec.remove(index);
+                    true
+                },
+                None => false,
+            },
+            Repr::Heap(set) => set.remove(value),
+        }
+    }
+
+}
+
+impl<V, const N: usize> Default for SmallSet<V, N> {
+    fn default() -> Self {
+        SmallSet::new()
+    }
+}
+
+impl<V, const N: usize> PartialEq for SmallSet<V, N>
+where
+    V: Hash + Eq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().all(|value| other.contains(value))
+    }
+}
+
+impl<V, const N: usize> Eq for SmallSet<V, N>
+where
+    V: Hash + Eq,
+{}
+
+impl<V, const N: usize> Extend<V> for SmallSet<V, N>
+where
+    V: Hash + Eq,
+{
+    fn extend<I: IntoIterator<Item = V>>(&mut self, iter: I) {
+        for value in iter {
+            self.insert(value);
+        }
+    }
+}
+
+/// An owning iterator over the values of a `SmallSet`.
+pub struct SmallSetIntoIter<V, const N: usize> {
+    repr: IntoIterRepr<V, N>,
+}
+
+enum IntoIterRepr<V, const N: usize> {
+    Inline(smallvec::IntoIter<[V; N]>),
+    Heap(hash_set::IntoIter<V>),
+}
+
+impl<V, const N: usize> Iterator for SmallSetIntoIter<V, N> {
+    type Item = V;
+
+    fn next(&mut self) -> Option<V> {
+        match &mut self.repr {
+            IntoIterRepr::Inline(iter) => iter.next(),
+            IntoIterRepr::Heap(iter) => iter.next(),
+        }
+    }
+}
+
+impl<V, const N: usize> IntoIterator for SmallSet<V, N> {
+    type Item = V;
+    type IntoIter = SmallSetIntoIter<V, N>;
+
+    fn into_iter(self) -> SmallSetIntoIter<V, N> {
+        let repr = match self.repr {
+            Repr::Inline(vec) => IntoIterRepr::Inline(vec.into_iter()),
+            Repr::Heap(set) => IntoIterRepr::Heap(set.into_iter()),
+        };
+        SmallSetIntoIter { repr }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+
+    #[test]
+    /// Test `insert` keeps values inline up to `N`, then spills.
+    fn test_insert_x_spill() {
+        let mut subject: SmallSet<u8, 2> = SmallSet::new();
+        assert_eq!(subject.insert(1), true);
+        assert_eq!(subject.insert(2), true);
+        assert_eq!(subject.insert(2), false);
+        assert_eq!(subject.is_spilled(), false);
+        assert_eq!(subject.insert(3), true);
+        assert_eq!(subject.is_spilled(), true);
+        assert_eq!(subject.insert(3), false);
+        assert_eq!(subject.len(), 3);
+        assert_eq!(subject.contains(&1), true);
+        assert_eq!(subject.contains(&3), true);
+    }
+
+    #[test]
+    /// Test `remove` on inline and spilled sets.
+    fn test_remove() {
+        let mut subject: SmallSet<u8, 2> = SmallSet::new();
+        subject.extend(vec![1, 2]);
+        assert_eq!(subject.remove(&1), true);
+        assert_eq!(subject.remove(&1), false);
+        subject.extend(vec![3, 4]);
+        assert_eq!(subject.is_spilled(), true);
+        assert_eq!(subject.remove(&2), true);
+        assert_eq!(subject.remove(&2), false);
+        assert_eq!(subject.len(), 2);
+        assert_eq!(subject.is_spilled(), true);
+    }
+
+    #[test]
+    /// Test `iter`, `into_iter`, and `eq` agree for inline and spilled sets.
+    fn test_iter_and_eq() {
+        let mut inline: SmallSet<u8, 4> = SmallSet::new();
+        inline.extend(vec![3, 1, 2]);
+        let mut spilled: SmallSet<u8, 4> = SmallSet::new();
+        spilled.extend(vec![1, 2, 3, 4, 5]);
+        spilled.remove(&4);
+        spilled.remove(&5);
+        assert_eq!(inline, spilled);
+        let actual: Vec<&u8> = inline.iter().collect();
+        assert_eq!(actual, vec![&3, &1, &2]);
+        let mut actual: Vec<u8> = spilled.into_iter().collect();
+        actual.sort();
+        assert_eq!(actual, vec![1, 2, 3]);
+    }
+
+}
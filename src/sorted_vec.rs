@@ -0,0 +1,326 @@
+use std::borrow::Borrow;
+use std::cmp::{Ord, Ordering};
+use std::iter::FromIterator;
+
+/// A set stored as a sorted, deduplicated `Vec`.
+///
+/// Membership uses binary search, and iteration walks contiguous memory
+/// in sorted order, so read-heavy workloads use less memory and iterate
+/// faster than with a `BTreeSet`. Inserts and removes shift elements,
+/// so they cost O(n).
+///
+/// # Examples
+///
+/// ```
+/// use sixarm_collections::*;
+/// let a: SortedVec<u8> = vec![3, 1, 2, 1].into_iter().collect();
+/// assert_eq!(a.as_slice(), &[1, 2, 3]);
+/// assert_eq!(a.contains(&2), true);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SortedVec<V> {
+    vec: Vec<V>,
+}
+
+impl<V> SortedVec<V> {
+
+    /// Create an empty sorted vector.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: SortedVec<u8> = SortedVec::new();
+    /// assert_eq!(a.is_empty(), true);
+    /// ```
+    #[inline]
+    pub fn new() -> Self {
+        SortedVec { vec: Vec::new() }
+    }
+
+    /// Return the number of values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: SortedVec<u8> = vec![1, 2].into_iter().collect();
+    /// assert_eq!(a.len(), 2);
+    /// ```
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.vec.len()
+    }
+
+    /// Return `true` if there are no values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: SortedVec<u8> = SortedVec::new();
+    /// assert_eq!(a.is_empty(), true);
+    /// ```
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.vec.is_empty()
+    }
+
+    /// Return the values as a sorted slice.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: SortedVec<u8> = vec![2, 1].into_iter().collect();
+    /// assert_eq!(a.as_slice(), &[1, 2]);
+    /// ```
+    #[inline]
+    pub fn as_slice(&self) -> &[V] {
+        &self.vec
+    }
+
+    /// Return an iterator over the values in sorted order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: SortedVec<u8> = vec![2, 1].into_iter().collect();
+    /// let values: Vec<&u8> = a.iter().collect();
+    /// assert_eq!(values, vec![&1, &2]);
+    /// ```
+    #[inline]
+    pub fn iter(&self) -> std::slice::Iter<'_, V> {
+        self.vec.iter()
+    }
+
+    /// Consume the sorted vector and return the inner `Vec`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: SortedVec<u8> = vec![2, 1].into_iter().collect();
+    /// assert_eq!(a.into_vec(), vec![1, 2]);
+    /// ```
+    #[inline]
+    pub fn into_vec(self) -> Vec<V> {
+        self.vec
+    }
+
+}
+
+impl<V> SortedVec<V>
+where
+    V: Ord,
+{
+
+    /// Return `true` if the sorted vector contains a value, by binary search.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: SortedVec<u8> = vec![1, 2].into_iter().collect();
+    /// assert_eq!(a.contains(&2), true);
+    /// assert_eq!(a.contains(&3), false);
+    /// ```
+    #[inline]
+    pub fn contains<R>(&self, value: &R) -> bool
+    where
+        V: Borrow<R>,
+        R: Ord + ?Sized,
+    {
+        self.vec.binary_search_by(|x| x.borrow().cmp(value)).is_ok()
+    }
+
+    /// Add a value at its sorted position.
+    ///
+    /// Return whether the value is newly added.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: SortedVec<u8> = SortedVec::new();
+    /// assert_eq!(a.insert(2), true);
+    /// assert_eq!(a.insert(1), true);
+    /// assert_eq!(a.insert(2), false);
+    /// assert_eq!(a.as_slice(), &[1, 2]);
+    /// ```
+    #[inline]
+    pub fn insert(&mut self, value: V) -> bool {
+        match self.vec.binary_search(&value) {
+            Ok(_) => false,
+            Err(index) => {
+                self.vec.insert(index, value);
+                true
+            },
+        }
+    }
+
+    /// Remove a value.
+    ///
+    /// Return whether the value was present.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: SortedVec<u8> = vec![1, 2].into_iter().collect();
+    /// assert_eq!(a.remove(&1), true);
+    /// assert_eq!(a.remove(&1), false);
+    /// ```
+    #[inline]
+    pub fn remove<R>(&mut self, value: &R) -> bool
+    where
+        V: Borrow<R>,
+        R: Ord + ?Sized,
+    {
+        match self.vec.binary_search_by(|x| x.borrow().cmp(value)) {
+            Ok(index) => {
+                self.vec.remove(index);
+                true
+            },
+            Err(_) => false,
+        }
+    }
+
+    /// Merge another sorted vector into this one in linear time.
+    ///
+    /// Return the number of values that are newly added.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let mut a: SortedVec<u8> = vec![1, 3, 5].into_iter().collect();
+    /// let b: SortedVec<u8> = vec![2, 3, 4].into_iter().collect();
+    /// assert_eq!(a.merge(b), 2);
+    /// assert_eq!(a.as_slice(), &[1, 2, 3, 4, 5]);
+    /// ```
+    #[inline]
+    pub fn merge(&mut self, other: SortedVec<V>) -> usize {
+        if other.is_empty() {
+            return 0;
+        }
+        let len = self.vec.len();
+        let mut merged = Vec::with_capacity(len + other.len());
+        let mut left = std::mem::take(&mut self.vec).into_iter().peekable();
+        let mut right = other.vec.into_iter().peekable();
+        loop {
+            let ordering = match (left.peek(), right.peek()) {
+                (Some(x), Some(y)) => x.cmp(y),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => break,
+            };
+            match ordering {
+                Ordering::Less => merged.extend(left.next()),
+                Ordering::Greater => merged.extend(right.next()),
+                Ordering::Equal => {
+                    merged.extend(left.next());
+                    right.next();
+                },
+            }
+        }
+        self.vec = merged;
+        self.vec.len() - len
+    }
+
+}
+
+impl<V> Default for SortedVec<V> {
+    fn default() -> Self {
+        SortedVec::new()
+    }
+}
+
+impl<V> FromIterator<V> for SortedVec<V>
+where
+    V: Ord,
+{
+    fn from_iter<I: IntoIterator<Item = V>>(iter: I) -> Self {
+        let mut vec: Vec<V> = iter.into_iter().collect();
+        vec.sort();
+        vec.dedup();
+        SortedVec { vec }
+    }
+}
+
+impl<V> Extend<V> for SortedVec<V>
+where
+    V: Ord,
+{
+    fn extend<I: IntoIterator<Item = V>>(&mut self, iter: I) {
+        self.merge(iter.into_iter().collect());
+    }
+}
+
+impl<V> IntoIterator for SortedVec<V> {
+    type Item = V;
+    type IntoIter = std::vec::IntoIter<V>;
+
+    fn into_iter(self) -> std::vec::IntoIter<V> {
+        self.vec.into_iter()
+    }
+}
+
+impl<'a, V> IntoIterator for &'a SortedVec<V> {
+    type Item = &'a V;
+    type IntoIter = std::slice::Iter<'a, V>;
+
+    fn into_iter(self) -> std::slice::Iter<'a, V> {
+        self.vec.iter()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+
+    #[test]
+    /// Test `from_iter` sorts and deduplicates.
+    fn test_from_iter() {
+        let subject: SortedVec<u8> = vec![5, 1, 3, 1, 5].into_iter().collect();
+        assert_eq!(subject.as_slice(), &[1, 3, 5]);
+    }
+
+    #[test]
+    /// Test `insert`, `contains`, and `remove` keep the vector sorted.
+    fn test_insert_contains_remove() {
+        let mut subject: SortedVec<u8> = SortedVec::new();
+        for value in [4, 2, 8, 6, 2] {
+            subject.insert(value);
+        }
+        assert_eq!(subject.as_slice(), &[2, 4, 6, 8]);
+        assert_eq!(subject.contains(&6), true);
+        assert_eq!(subject.contains(&5), false);
+        assert_eq!(subject.remove(&4), true);
+        assert_eq!(subject.remove(&5), false);
+        assert_eq!(subject.as_slice(), &[2, 6, 8]);
+    }
+
+    #[test]
+    /// Test `merge` with disjoint, overlapping, and empty inputs.
+    fn test_merge() {
+        let mut subject: SortedVec<u8> = vec![1, 4, 7].into_iter().collect();
+        assert_eq!(subject.merge(vec![0, 4, 9].into_iter().collect()), 2);
+        assert_eq!(subject.as_slice(), &[0, 1, 4, 7, 9]);
+        assert_eq!(subject.merge(SortedVec::new()), 0);
+        let mut empty: SortedVec<u8> = SortedVec::new();
+        assert_eq!(empty.merge(subject.clone()), 5);
+        assert_eq!(empty, subject);
+    }
+
+    #[test]
+    /// Test `contains` and `remove` with borrowed forms of the value.
+    fn test_contains_x_borrowed_forms() {
+        let mut subject: SortedVec<String> = vec![String::from("alpha")].into_iter().collect();
+        assert_eq!(subject.contains("alpha"), true);
+        assert_eq!(subject.remove("alpha"), true);
+    }
+
+}
@@ -0,0 +1,604 @@
+use std::collections::{HashMap, HashSet};
+use std::collections::hash_map::RandomState;
+use std::borrow::Borrow;
+use std::cmp::Eq;
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+use std::sync::{PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use crate::hash_map_to_set::{HashMapToSet, HashMapToSetExt};
+
+/// The default number of shards for `SyncMapToSet::new`.
+pub const SYNC_MAP_TO_SET_DEFAULT_SHARDS: usize = 16;
+
+/// A thread-safe map of keys to sets, sharded across `RwLock`s.
+///
+/// The key space is split across a fixed number of shards, each a
+/// `HashMapToSet` behind its own `RwLock`. A call locks only the shard
+/// of its key, so readers never block each other and writers to
+/// different shards run in parallel. Methods take `&self`, so share the
+/// map across threads with `Arc`.
+///
+/// Whole-map methods such as `len`, `sub_len`, and `snapshot` lock the
+/// shards one at a time, so under concurrent writes their result is a
+/// merge of per-shard views rather than a single point in time.
+///
+/// A poisoned lock is recovered rather than propagated: a panic in
+/// another thread can at worst leave one set partly updated.
+///
+/// # Examples
+///
+/// ```
+/// use sixarm_collections::*;
+/// use std::sync::Arc;
+/// let map: Arc<SyncMapToSet<u8, u8>> = Arc::new(SyncMapToSet::new());
+/// let handles: Vec<_> = (0..4).map(|i| {
+///     let map = Arc::clone(&map);
+///     std::thread::spawn(move || { map.sub_insert(i % 2, i); })
+/// }).collect();
+/// for handle in handles { handle.join().unwrap(); }
+/// assert_eq!(map.sub_len(), 4);
+/// assert_eq!(map.sub_len_of(&0), 2);
+/// ```
+pub struct SyncMapToSet<K, V, S = RandomState> {
+    shards: Box<[RwLock<HashMapToSet<K, V>>]>,
+    hasher: S,
+}
+
+impl<K, V> SyncMapToSet<K, V> {
+
+    /// Create an empty map with the default number of shards.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: SyncMapToSet<u8, u8> = SyncMapToSet::new();
+    /// assert_eq!(a.shard_count(), SYNC_MAP_TO_SET_DEFAULT_SHARDS);
+    /// ```
+    #[inline]
+    pub fn new() -> Self {
+        SyncMapToSet::with_shards(SYNC_MAP_TO_SET_DEFAULT_SHARDS)
+    }
+
+    /// Create an empty map with a number of shards.
+    ///
+    /// A shard count of 0 is treated as 1.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: SyncMapToSet<u8, u8> = SyncMapToSet::with_shards(4);
+    /// assert_eq!(a.shard_count(), 4);
+    /// ```
+    #[inline]
+    pub fn with_shards(shards: usize) -> Self {
+        SyncMapToSet::with_shards_and_hasher(shards, RandomState::new())
+    }
+
+}
+
+impl<K, V, S> SyncMapToSet<K, V, S> {
+
+    /// Create an empty map with a number of shards and a hasher that picks each key's shard.
+    ///
+    /// A shard count of 0 is treated as 1.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// use std::collections::hash_map::RandomState;
+    /// let a: SyncMapToSet<u8, u8> = SyncMapToSet::with_shards_and_hasher(8, RandomState::new());
+    /// assert_eq!(a.shard_count(), 8);
+    /// ```
+    #[inline]
+    pub fn with_shards_and_hasher(shards: usize, hasher: S) -> Self {
+        SyncMapToSet {
+            shards: (0..shards.max(1)).map(|_| RwLock::new(HashMap::new())).collect(),
+            hasher,
+        }
+    }
+
+    /// Return the number of shards.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: SyncMapToSet<u8, u8> = SyncMapToSet::with_shards(4);
+    /// assert_eq!(a.shard_count(), 4);
+    /// ```
+    #[inline]
+    pub fn shard_count(&self) -> usize {
+        self.shards.len()
+    }
+
+    /// Return the number of keys across all shards.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: SyncMapToSet<u8, u8> = SyncMapToSet::new();
+    /// a.sub_insert(1, 2);
+    /// a.sub_insert(1, 3);
+    /// assert_eq!(a.len(), 1);
+    /// ```
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.shards.iter().map(|shard| read(shard).len()).sum()
+    }
+
+    /// Return `true` if every shard has no keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: SyncMapToSet<u8, u8> = SyncMapToSet::new();
+    /// assert_eq!(a.is_empty(), true);
+    /// ```
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.shards.iter().all(|shard| read(shard).is_empty())
+    }
+
+    /// Return the total number of values across all keys in all shards.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: SyncMapToSet<u8, u8> = SyncMapToSet::new();
+    /// a.sub_insert(1, 2);
+    /// a.sub_insert(4, 5);
+    /// assert_eq!(a.sub_len(), 2);
+    /// ```
+    #[inline]
+    pub fn sub_len(&self) -> usize {
+        self.shards.iter().map(|shard| read(shard).values().map(|set| set.len()).sum::<usize>()).sum()
+    }
+
+    /// Remove every key from every shard.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: SyncMapToSet<u8, u8> = SyncMapToSet::new();
+    /// a.sub_insert(1, 2);
+    /// a.clear();
+    /// assert_eq!(a.is_empty(), true);
+    /// ```
+    #[inline]
+    pub fn clear(&self) {
+        for shard in self.shards.iter() {
+            write(shard).clear();
+        }
+    }
+
+    /// Consume the map and merge its shards into one `HashMapToSet`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: SyncMapToSet<u8, u8> = SyncMapToSet::new();
+    /// a.sub_insert(1, 2);
+    /// let b: HashMapToSet<u8, u8> = a.into_inner();
+    /// assert_eq!(b.sub_contains(&1, &2), true);
+    /// ```
+    #[inline]
+    pub fn into_inner(self) -> HashMapToSet<K, V>
+    where
+        K: Hash + Eq,
+    {
+        let mut map = HashMap::new();
+        for shard in self.shards.into_vec() {
+            map.extend(shard.into_inner().unwrap_or_else(PoisonError::into_inner));
+        }
+        map
+    }
+
+}
+
+impl<K, V, S> SyncMapToSet<K, V, S>
+where
+    K: Hash + Eq,
+    V: Hash + Eq,
+    S: BuildHasher,
+{
+
+    /// Return the shard that holds a key.
+    fn shard<Q>(&self, key: &Q) -> &RwLock<HashMapToSet<K, V>>
+    where
+        Q: Hash + ?Sized,
+    {
+        let index = (self.hasher.hash_one(key) % self.shards.len() as u64) as usize;
+        &self.shards[index]
+    }
+
+    /// Return `true` if the collection contains a sub-key-value item.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: SyncMapToSet<u8, u8> = SyncMapToSet::new();
+    /// a.sub_insert(1, 2);
+    /// assert_eq!(a.sub_contains(&1, &2), true);
+    /// assert_eq!(a.sub_contains(&3, &4), false);
+    /// ```
+    #[inline]
+    pub fn sub_contains<Q, R>(&self, key: &Q, value: &R) -> bool
+    where
+        K: Borrow<Q>,
+        V: Borrow<R>,
+        Q: Hash + Eq + ?Sized,
+        R: Hash + Eq + ?Sized,
+    {
+        read(self.shard(key)).sub_contains(key, value)
+    }
+
+    /// Return `true` if the map has a key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: SyncMapToSet<u8, u8> = SyncMapToSet::new();
+    /// a.sub_insert(1, 2);
+    /// assert_eq!(a.contains_key(&1), true);
+    /// ```
+    #[inline]
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        read(self.shard(key)).contains_key(key)
+    }
+
+    /// Add a sub-key-value item to the collection.
+    ///
+    /// Return whether the item is added in the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: SyncMapToSet<u8, u8> = SyncMapToSet::new();
+    /// assert_eq!(a.sub_insert(1, 2), true);
+    /// assert_eq!(a.sub_insert(1, 2), false);
+    /// ```
+    #[inline]
+    pub fn sub_insert(&self, key: K, value: V) -> bool {
+        write(self.shard(&key)).sub_insert(key, value)
+    }
+
+    /// Add many values to the set for one key, under one lock.
+    ///
+    /// Return the number of values that are newly added in the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: SyncMapToSet<u8, u8> = SyncMapToSet::new();
+    /// assert_eq!(a.sub_insert_many(1, vec![2, 3, 2]), 2);
+    /// ```
+    #[inline]
+    pub fn sub_insert_many<I>(&self, key: K, values: I) -> usize
+    where
+        I: IntoIterator<Item = V>,
+    {
+        write(self.shard(&key)).sub_insert_many(key, values)
+    }
+
+    /// Remove a sub-key-value pair from the collection.
+    ///
+    /// Return whether the value was present in the set.
+    ///
+    /// The key stays in the collection even when its set becomes empty;
+    /// use `sub_remove_prune` to also remove the key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: SyncMapToSet<u8, u8> = SyncMapToSet::new();
+    /// a.sub_insert(1, 2);
+    /// assert_eq!(a.sub_remove(&1, &2), true);
+    /// assert_eq!(a.contains_key(&1), true);
+    /// ```
+    #[inline]
+    pub fn sub_remove<Q, R>(&self, key: &Q, value: &R) -> bool
+    where
+        K: Borrow<Q>,
+        V: Borrow<R>,
+        Q: Hash + Eq + ?Sized,
+        R: Hash + Eq + ?Sized,
+    {
+        write(self.shard(key)).sub_remove(key, value)
+    }
+
+    /// Remove a sub-key-value pair from the collection, and prune the key if its set becomes empty.
+    ///
+    /// Return whether the value was present in the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: SyncMapToSet<u8, u8> = SyncMapToSet::new();
+    /// a.sub_insert(1, 2);
+    /// assert_eq!(a.sub_remove_prune(&1, &2), true);
+    /// assert_eq!(a.contains_key(&1), false);
+    /// ```
+    #[inline]
+    pub fn sub_remove_prune<Q, R>(&self, key: &Q, value: &R) -> bool
+    where
+        K: Borrow<Q>,
+        V: Borrow<R>,
+        Q: Hash + Eq + ?Sized,
+        R: Hash + Eq + ?Sized,
+    {
+        write(self.shard(key)).sub_remove_prune(key, value)
+    }
+
+    /// Remove a key and return its set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: SyncMapToSet<u8, u8> = SyncMapToSet::new();
+    /// a.sub_insert(1, 2);
+    /// assert_eq!(a.remove(&1).map(|set| set.len()), Some(1));
+    /// assert_eq!(a.remove(&1), None);
+    /// ```
+    #[inline]
+    pub fn remove<Q>(&self, key: &Q) -> Option<HashSet<V>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        write(self.shard(key)).remove(key)
+    }
+
+    /// Return the number of values in the set for a key.
+    ///
+    /// Return 0 if the key is absent or if its set is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: SyncMapToSet<u8, u8> = SyncMapToSet::new();
+    /// a.sub_insert(1, 2);
+    /// assert_eq!(a.sub_len_of(&1), 1);
+    /// assert_eq!(a.sub_len_of(&4), 0);
+    /// ```
+    #[inline]
+    pub fn sub_len_of<Q>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        read(self.shard(key)).sub_len_of(key)
+    }
+
+    /// Return a clone of the set for a key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: SyncMapToSet<u8, u8> = SyncMapToSet::new();
+    /// a.sub_insert(1, 2);
+    /// assert_eq!(a.sub_get_cloned(&1).map(|set| set.len()), Some(1));
+    /// ```
+    #[inline]
+    pub fn sub_get_cloned<Q>(&self, key: &Q) -> Option<HashSet<V>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+        V: Clone,
+    {
+        read(self.shard(key)).get(key).cloned()
+    }
+
+    /// Call a function with a reference to the set for a key, under the shard's read lock.
+    ///
+    /// Return the function's result, or `None` if the key is absent.
+    ///
+    /// # Deadlocks
+    ///
+    /// The function runs while the shard's read lock is held, so it must
+    /// not write to the same map: a write to a key in that shard blocks
+    /// forever. Use `sub_get_cloned` to work on a copy instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: SyncMapToSet<u8, u8> = SyncMapToSet::new();
+    /// a.sub_insert_many(1, vec![2, 3]);
+    /// assert_eq!(a.sub_with(&1, |set| set.iter().sum::<u8>()), Some(5));
+    /// ```
+    #[inline]
+    pub fn sub_with<Q, F, T>(&self, key: &Q, f: F) -> Option<T>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+        F: FnOnce(&HashSet<V>) -> T,
+    {
+        read(self.shard(key)).get(key).map(f)
+    }
+
+    /// Return a merged copy of every shard as one `HashMapToSet`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: SyncMapToSet<u8, u8> = SyncMapToSet::new();
+    /// a.sub_insert(1, 2);
+    /// a.sub_insert(4, 5);
+    /// let b: HashMapToSet<u8, u8> = a.snapshot();
+    /// assert_eq!(b.sub_len(), 2);
+    /// ```
+    #[inline]
+    pub fn snapshot(&self) -> HashMapToSet<K, V>
+    where
+        K: Clone,
+        V: Clone,
+    {
+        let mut map = HashMap::new();
+        for shard in self.shards.iter() {
+            map.extend(read(shard).iter().map(|(key, set)| (key.clone(), set.clone())));
+        }
+        map
+    }
+
+    /// Call a function with every sub-key-value pair, locking one shard at a time.
+    ///
+    /// # Deadlocks
+    ///
+    /// The function runs while a shard's read lock is held, so it must
+    /// not write to the same map: a write to a key in that shard blocks
+    /// forever. Use `snapshot` to work on a copy instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sixarm_collections::*;
+    /// let a: SyncMapToSet<u8, u8> = SyncMapToSet::new();
+    /// a.sub_insert(1, 2);
+    /// a.sub_insert(4, 5);
+    /// let mut sum = 0;
+    /// a.sub_for_each(|key, value| sum += key + value);
+    /// assert_eq!(sum, 12);
+    /// ```
+    #[inline]
+    pub fn sub_for_each<F>(&self, mut f: F)
+    where
+        F: FnMut(&K, &V),
+    {
+        for shard in self.shards.iter() {
+            for (key, value) in read(shard).sub_iter() {
+                f(key, value);
+            }
+        }
+    }
+
+}
+
+/// Lock a shard for reading, recovering from poison.
+fn read<T>(lock: &RwLock<T>) -> RwLockReadGuard<'_, T> {
+    lock.read().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Lock a shard for writing, recovering from poison.
+fn write<T>(lock: &RwLock<T>) -> RwLockWriteGuard<'_, T> {
+    lock.write().unwrap_or_else(PoisonError::into_inner)
+}
+
+impl<K, V> Default for SyncMapToSet<K, V> {
+    fn default() -> Self {
+        SyncMapToSet::new()
+    }
+}
+
+impl<K, V, S> fmt::Debug for SyncMapToSet<K, V, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SyncMapToSet")
+        .field("shards", &self.shards.len())
+        .finish_non_exhaustive()
+    }
+}
+
+impl<K, V> From<HashMapToSet<K, V>> for SyncMapToSet<K, V>
+where
+    K: Hash + Eq,
+    V: Hash + Eq,
+{
+    fn from(map: HashMapToSet<K, V>) -> Self {
+        let sync = SyncMapToSet::new();
+        for (key, set) in map {
+            write(sync.shard(&key)).insert(key, set);
+        }
+        sync
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    /// Test the sub_* methods with some items across shards.
+    fn test_sub_methods() {
+        let subject: SyncMapToSet<u8, u8> = SyncMapToSet::with_shards(4);
+        for k in 0..20 {
+            assert_eq!(subject.sub_insert(k, k + 1), true);
+        }
+        assert_eq!(subject.sub_insert(3, 4), false);
+        assert_eq!(subject.len(), 20);
+        assert_eq!(subject.sub_len(), 20);
+        assert_eq!(subject.sub_contains(&3, &4), true);
+        assert_eq!(subject.sub_remove(&3, &4), true);
+        assert_eq!(subject.contains_key(&3), true);
+        assert_eq!(subject.sub_remove_prune(&5, &6), true);
+        assert_eq!(subject.contains_key(&5), false);
+        assert_eq!(subject.sub_len(), 18);
+    }
+
+    #[test]
+    /// Test `sub_contains` with borrowed forms of the key and value.
+    fn test_sub_contains_x_borrowed_forms() {
+        let subject: SyncMapToSet<String, String> = SyncMapToSet::new();
+        subject.sub_insert(String::from("alpha"), String::from("bravo"));
+        assert_eq!(subject.sub_contains("alpha", "bravo"), true);
+        assert_eq!(subject.sub_len_of("alpha"), 1);
+    }
+
+    #[test]
+    /// Test `snapshot`, `into_inner`, and `from` round trip the same contents.
+    fn test_snapshot_and_into_inner() {
+        let mut map: HashMapToSet<u8, u8> = HashMap::new();
+        map.sub_insert_many(1, vec![2, 3]);
+        map.sub_insert(4, 5);
+        let subject = SyncMapToSet::from(map.clone());
+        assert_eq!(subject.snapshot(), map);
+        let mut actual: Vec<(u8, u8)> = Vec::new();
+        subject.sub_for_each(|k, v| actual.push((*k, *v)));
+        actual.sort();
+        assert_eq!(actual, vec![(1, 2), (1, 3), (4, 5)]);
+        assert_eq!(subject.into_inner(), map);
+    }
+
+    #[test]
+    /// Test concurrent writers and readers from several threads.
+    fn test_threads() {
+        let subject: Arc<SyncMapToSet<u32, u32>> = Arc::new(SyncMapToSet::with_shards(4));
+        let handles: Vec<_> = (0..8).map(|t| {
+            let subject = Arc::clone(&subject);
+            std::thread::spawn(move || {
+                for i in 0..100 {
+                    subject.sub_insert(i % 10, t * 100 + i);
+                    subject.sub_len_of(&(i % 10));
+                }
+            })
+        }).collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert_eq!(subject.len(), 10);
+        assert_eq!(subject.sub_len(), 800);
+    }
+
+}
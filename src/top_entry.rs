@@ -0,0 +1,71 @@
+use std::cmp::Ordering;
+
+/// An entry for a bounded heap that keeps the top entries by count.
+///
+/// The ordering is reversed, so the top of a `BinaryHeap` is the worst
+/// entry, i.e. the smallest count, and for equal counts the latest entry.
+/// This lets a caller push each entry and pop the worst when the heap is
+/// over capacity, then call `into_sorted_vec` to get the best entry first.
+pub(crate) struct TopEntry<T> {
+    pub(crate) count: usize,
+    pub(crate) seq: usize,
+    pub(crate) item: T,
+}
+
+impl<T> PartialEq for TopEntry<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.count == other.count && self.seq == other.seq
+    }
+}
+
+impl<T> Eq for TopEntry<T> {}
+
+impl<T> PartialOrd for TopEntry<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for TopEntry<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.count.cmp(&self.count).then(self.seq.cmp(&other.seq))
+    }
+}
+
+/// Return the top `n` items by count, with the largest count first.
+///
+/// Items with equal counts keep their order from the iterator.
+/// This uses a bounded heap, so it takes O(len * log n) time.
+pub(crate) fn top_by_count<T, I>(iter: I, n: usize) -> Vec<(T, usize)>
+where
+    I: IntoIterator<Item = (T, usize)>,
+{
+    if n == 0 {
+        return Vec::new();
+    }
+    let mut heap = std::collections::BinaryHeap::with_capacity(n + 1);
+    for (seq, (item, count)) in iter.into_iter().enumerate() {
+        heap.push(TopEntry { count, seq, item });
+        if heap.len() > n {
+            heap.pop();
+        }
+    }
+    heap.into_sorted_vec()
+    .into_iter()
+    .map(|entry| (entry.item, entry.count))
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    /// Test `top_by_count` keeps iterator order for equal counts.
+    fn test_top_by_count_x_ties() {
+        let items = vec![('a', 1), ('b', 2), ('c', 2), ('d', 1), ('e', 2)];
+        assert_eq!(top_by_count(items.clone(), 2), vec![('b', 2), ('c', 2)]);
+        assert_eq!(top_by_count(items, 4), vec![('b', 2), ('c', 2), ('e', 2), ('a', 1)]);
+    }
+
+}